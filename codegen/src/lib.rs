@@ -0,0 +1,543 @@
+//! Generates the code wrapping an API with [api-request-utils-rs](https://crates.io/crates/api-request-utils-rs) from its
+//! OpenAPI 3 specification : a struct or enum per schema of `components/schemas`, an `Endpoint` per operation and a client
+//! implementing the request traits, instead of writing every endpoint by hand.
+//!
+//! Run it from a build script and include the generated file :
+//!
+//! ```rust ignore
+//! // build.rs
+//! fn main() {
+//!     println!("cargo:rerun-if-changed=openapi.json");
+//!     api_request_utils_codegen::Generator::from_path("openapi.json").unwrap().client_name("PetStore").write_to_out_dir("petstore.rs").unwrap();
+//! }
+//!
+//! // src/lib.rs
+//! include!(concat!(env!("OUT_DIR"),"/petstore.rs"));
+//!
+//! let pets = PetStore::default().request_endpoint(&ListPets { limit : Some(10) },|pets| pets).await?;
+//! ```
+//!
+//! Or generate it once from the command line, to commit and adjust it :
+//!
+//! ```text
+//! api-request-utils-codegen openapi.json --client PetStore > src/petstore.rs
+//! ```
+//!
+//! Only JSON specifications are read, YAML ones can be converted first, such as with `yq -o json`. The generated code
+//! only depends on `api-request-utils-rs`, whose re-exports of `serde`, `serde_json` and `reqwest` it uses.
+#![forbid(missing_docs)]
+
+use std::collections::BTreeSet;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+use serde_json::{Map, Value};
+
+/// The type of the schemas that cannot be expressed as a Rust type, such as the ones combining others with `oneOf`.
+const VALUE : &str = "::api_request_utils::serde_json::Value";
+
+/// The derives of the generated types, using the `serde` re-exported by `api-request-utils-rs`.
+const SERDE : &str = "#[serde(crate = \"::api_request_utils::serde\")]";
+
+/// The methods of the operations, in the order they are generated.
+const METHODS : [&str; 7] = ["get","put","post","delete","options","head","patch"];
+
+/// The keywords that cannot be used as field names, even as raw identifiers.
+const RESERVED : [&str; 5] = ["self","Self","super","crate","_"];
+
+/// The Rust keywords, which are turned into raw identifiers.
+const KEYWORDS : [&str; 47] = [
+    "as","async","await","break","const","continue","dyn","else","enum","extern","false","fn","for","if","impl","in","let",
+    "loop","match","mod","move","mut","pub","ref","return","static","struct","trait","true","type","unsafe","use","where",
+    "while","abstract","become","box","do","final","macro","override","priv","try","typeof","unsized","virtual","yield",
+];
+
+/// An error generating the code.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// The specification could not be read, or the code could not be written.
+    #[error("I/O error : {0}")]
+    Io(#[from] std::io::Error),
+    /// The specification is not valid JSON.
+    #[error("invalid JSON : {0}")]
+    Json(#[from] serde_json::Error),
+    /// The document is not an OpenAPI 3 specification.
+    #[error("unsupported specification : {0}")]
+    Unsupported(String),
+}
+
+/// Generates the code of an OpenAPI 3 specification.
+///
+/// ```
+/// use api_request_utils_codegen::Generator;
+///
+/// let spec = r##"{
+///     "openapi" : "3.0.3",
+///     "info" : { "title" : "Pet Store", "version" : "1.0.0" },
+///     "servers" : [{ "url" : "https://petstore.example.com/v1" }],
+///     "paths" : {
+///         "/pets/{petId}" : {
+///             "get" : {
+///                 "operationId" : "showPetById",
+///                 "parameters" : [{ "name" : "petId", "in" : "path", "required" : true, "schema" : { "type" : "integer" } }],
+///                 "responses" : { "200" : { "content" : { "application/json" : { "schema" : { "$ref" : "#/components/schemas/Pet" } } } } }
+///             }
+///         }
+///     },
+///     "components" : {
+///         "schemas" : {
+///             "Pet" : { "type" : "object", "required" : ["id"], "properties" : { "id" : { "type" : "integer" }, "tag" : { "type" : "string" } } }
+///         }
+///     }
+/// }"##;
+///
+/// let code = Generator::from_json(spec).unwrap().client_name("PetStore").generate();
+/// assert!(code.contains("pub struct ShowPetById {"));
+/// assert!(code.contains("const PATH : &'static str = \"pets/{petId}\";"));
+/// assert!(code.contains("pub tag : Option<String>,"));
+/// assert!(code.contains("const BASE_URL : &'static str = \"https://petstore.example.com/v1\";"));
+/// ```
+///
+/// Parameters are typed from their schema, optional ones being `Option`s, and request bodies are flattened into the
+/// endpoint, as [Endpoint](https://docs.rs/api-request-utils-rs/latest/api_request_utils/trait.Endpoint.html) sends the
+/// parameters of `POST`, `PUT` and `PATCH` operations as their body. The parameters it cannot send, such as headers or the
+/// query of these operations, are left out and listed in the documentation of the endpoint, and operations whose body is
+/// not an object are skipped. Schemas combining others with `oneOf`, `anyOf` or `allOf`, and inline objects, are generated
+/// as `serde_json::Value`.
+#[derive(Debug, Clone)]
+pub struct Generator {
+    spec : Value,
+    client_name : Option<String>,
+    error_type : String,
+}
+
+impl Generator {
+    /// Reads the given specification.
+    ///
+    /// # Returns
+    ///
+    /// The generator, or an error if the specification is not valid JSON or not an OpenAPI 3 document.
+    pub fn from_json(json : &str) -> Result<Self,Error> {
+        let spec : Value = serde_json::from_str(json)?;
+        match spec.get("openapi").and_then(Value::as_str) {
+            Some(version) if version.starts_with("3.") => Ok(Self { spec, client_name : Some("ApiClient".to_owned()), error_type : VALUE.to_owned() }),
+            Some(version) => Err(Error::Unsupported(format!("OpenAPI {version}, only 3.x is supported"))),
+            None => Err(Error::Unsupported("the `openapi` version is missing".to_owned()))
+        }
+    }
+
+    /// Reads the specification from the given file.
+    ///
+    /// # Returns
+    ///
+    /// The generator, or an error if the file cannot be read, is not valid JSON or is not an OpenAPI 3 document.
+    pub fn from_path(path : impl AsRef<Path>) -> Result<Self,Error> {
+        Self::from_json(&std::fs::read_to_string(path)?)
+    }
+
+    /// Sets the name of the generated client, `ApiClient` by default.
+    pub fn client_name(mut self,name : &str) -> Self {
+        self.client_name = Some(type_name(name));
+        self
+    }
+
+    /// Leaves the client out, to implement the request traits by hand, such as to override the defaults.
+    pub fn without_client(mut self) -> Self {
+        self.client_name = None;
+        self
+    }
+
+    /// Sets the type the error payloads are deserialized into, such as `crate::ApiError`, `serde_json::Value` by default.
+    pub fn error_type(mut self,error_type : &str) -> Self {
+        self.error_type = error_type.to_owned();
+        self
+    }
+
+    /// Generates the code.
+    pub fn generate(&self) -> String {
+        let mut out = String::new();
+        let info = self.spec.get("info");
+        let title = info.and_then(|info| info.get("title")).and_then(Value::as_str).unwrap_or("API");
+        let version = info.and_then(|info| info.get("version")).and_then(Value::as_str).unwrap_or_default();
+        let _ = writeln!(out,"// Generated by api-request-utils-codegen from the OpenAPI specification of {} {version}, do not edit.",title.trim());
+
+        let mut names = BTreeSet::new();
+        names.extend(self.client_name.clone());
+        let schemas = self.spec.pointer("/components/schemas").and_then(Value::as_object);
+        names.extend(schemas.into_iter().flatten().map(|(name,_)| type_name(name)));
+        for (name,schema) in schemas.into_iter().flatten() {
+            self.schema(&mut out,name,schema);
+        }
+
+        let paths = self.spec.get("paths").and_then(Value::as_object);
+        for (path,item) in paths.into_iter().flatten() {
+            for method in METHODS {
+                if let Some(operation) = item.get(method) {
+                    self.operation(&mut out,&mut names,path,method,operation,item.get("parameters"));
+                }
+            }
+        }
+
+        if let Some(client) = &self.client_name {
+            self.client(&mut out,client,title);
+        }
+        out
+    }
+
+    /// Generates the code into the `OUT_DIR` of the build script calling it.
+    ///
+    /// # Returns
+    ///
+    /// The path of the generated file, or an error if it is not called from a build script or cannot write the file.
+    pub fn write_to_out_dir(&self,file_name : &str) -> Result<PathBuf,Error> {
+        let dir = std::env::var_os("OUT_DIR").ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound,"`OUT_DIR` is only set for build scripts"))?;
+        let path = Path::new(&dir).join(file_name);
+        std::fs::write(&path,self.generate())?;
+        Ok(path)
+    }
+
+    /// Generates the struct, enum or type alias of a schema of `components/schemas`.
+    fn schema(&self,out : &mut String,name : &str,schema : &Value) {
+        let ty = type_name(name);
+        out.push('\n');
+        doc(out,"",schema.get("description").or_else(|| schema.get("title")));
+
+        if let Some(values) = schema.get("enum").and_then(Value::as_array).filter(|values| values.iter().all(Value::is_string)) {
+            let _ = writeln!(out,"#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ::api_request_utils::serde::Serialize, ::api_request_utils::serde::Deserialize)]");
+            let _ = writeln!(out,"{SERDE}\npub enum {ty} {{");
+            let mut variants = BTreeSet::new();
+            for value in values.iter().filter_map(Value::as_str) {
+                let mut variant = type_name(value);
+                while !variants.insert(variant.clone()) {
+                    variant.push('_');
+                }
+                let _ = writeln!(out,"    #[serde(rename = {value:?})]\n    {variant},");
+            }
+            out.push_str("}\n");
+            return;
+        }
+
+        match schema.get("properties").and_then(Value::as_object) {
+            Some(properties) => {
+                let required = required(schema);
+                let _ = writeln!(out,"#[derive(Debug, Clone, PartialEq, ::api_request_utils::serde::Serialize, ::api_request_utils::serde::Deserialize)]");
+                let _ = writeln!(out,"{SERDE}\npub struct {ty} {{");
+                for (name,property) in properties {
+                    let mut rust_type = self.rust_type(property);
+                    // Directly recursive schemas would have an infinite size
+                    if rust_type == ty || rust_type == format!("Option<{ty}>") {
+                        rust_type = rust_type.replace(&ty,&format!("Box<{ty}>"));
+                    }
+                    self.field(out,name,rust_type,required.contains(name.as_str()),property.get("description"));
+                }
+                out.push_str("}\n");
+            },
+            None => {
+                let _ = writeln!(out,"pub type {ty} = {};",self.rust_type(schema));
+            }
+        }
+    }
+
+    /// Generates the endpoint of an operation, `shared` being the parameters of all the operations of its path.
+    fn operation(&self,out : &mut String,names : &mut BTreeSet<String>,path : &str,method : &str,operation : &Value,shared : Option<&Value>) {
+        let mut name = match operation.get("operationId").and_then(Value::as_str) {
+            Some(id) => type_name(id),
+            None => type_name(&format!("{method} {}",path.replace(['{','}'],"")))
+        };
+        while !names.insert(name.clone()) {
+            name.push_str("Endpoint");
+        }
+
+        let query_method = ["get","head","delete","options"].contains(&method);
+        let mut left_out = Vec::new();
+        let mut fields = Vec::new();
+
+        // The parameters of the operation override the shared ones of the same name and location
+        let mut parameters : Vec<&Value> = Vec::new();
+        for parameter in [shared,operation.get("parameters")].into_iter().flatten().filter_map(Value::as_array).flatten() {
+            let parameter = self.resolve(parameter);
+            let key = |parameter : &Value| (parameter.get("name").cloned(),parameter.get("in").cloned());
+            parameters.retain(|existing| key(existing) != key(parameter));
+            parameters.push(parameter);
+        }
+        for parameter in parameters {
+            let Some(parameter_name) = parameter.get("name").and_then(Value::as_str) else { continue };
+            match parameter.get("in").and_then(Value::as_str) {
+                Some("path") => fields.push((parameter_name,parameter,true)),
+                Some("query") if query_method => fields.push((parameter_name,parameter,parameter.get("required") == Some(&Value::Bool(true)))),
+                Some(location) => left_out.push(format!("the `{parameter_name}` {location} parameter")),
+                None => {}
+            }
+        }
+
+        let body = operation.get("requestBody").map(|body| self.resolve(body));
+        let schema = body.and_then(|body| body.pointer("/content/application~1json/schema"));
+        let body = match (body,schema) {
+            (Some(_),_) if query_method => {
+                left_out.push("the request body".to_owned());
+                None
+            },
+            (Some(body),Some(schema)) => match self.is_object(schema) {
+                true => Some((self.rust_type(schema),body.get("required") == Some(&Value::Bool(true)))),
+                false => {
+                    let _ = writeln!(out,"\n// {} {path} is skipped, as its body is not an object.",method.to_uppercase());
+                    return;
+                }
+            },
+            (Some(_),None) => {
+                left_out.push("the request body, which is not JSON".to_owned());
+                None
+            },
+            (None,_) => None
+        };
+
+        out.push('\n');
+        let summary = operation.get("summary").and_then(Value::as_str).unwrap_or_default();
+        let description = operation.get("description").and_then(Value::as_str).unwrap_or_default();
+        let mut documentation = format!("`{} {path}`",method.to_uppercase());
+        for text in [summary,description].into_iter().filter(|text| !text.trim().is_empty()) {
+            documentation.push_str("\n\n");
+            documentation.push_str(text.trim());
+        }
+        if !left_out.is_empty() {
+            let _ = write!(documentation,"\n\nLeaves out {}, which cannot be sent by an `Endpoint`.",left_out.join(", "));
+        }
+        doc(out,"",Some(&Value::String(documentation)));
+        if operation.get("deprecated") == Some(&Value::Bool(true)) {
+            out.push_str("#[deprecated]\n");
+        }
+
+        let _ = writeln!(out,"#[derive(Debug, Clone, PartialEq, ::api_request_utils::serde::Serialize)]\n{SERDE}");
+        match fields.is_empty() && body.is_none() {
+            true => {
+                let _ = writeln!(out,"pub struct {name};");
+            },
+            false => {
+                let _ = writeln!(out,"pub struct {name} {{");
+                for (field,parameter,required) in fields {
+                    let schema = parameter.get("schema").unwrap_or(&Value::Null);
+                    self.field(out,field,self.rust_type(schema),required,parameter.get("description"));
+                }
+                if let Some((body,required)) = body {
+                    out.push_str("    /// The body of the request.\n    #[serde(flatten)]\n");
+                    let _ = writeln!(out,"    pub body : {},",if required { body } else { format!("Option<{body}>") });
+                }
+                out.push_str("}\n");
+            }
+        }
+
+        let _ = writeln!(out,"\nimpl ::api_request_utils::Endpoint for {name} {{");
+        let _ = writeln!(out,"    type Response = {};",self.response_type(operation));
+        let _ = writeln!(out,"    type Error = {};",self.error_type);
+        if method != "get" {
+            let _ = writeln!(out,"    const METHOD : ::api_request_utils::reqwest::Method = ::api_request_utils::reqwest::Method::{};",method.to_uppercase());
+        }
+        let _ = writeln!(out,"    const PATH : &'static str = {:?};\n}}",path.trim_start_matches('/'));
+    }
+
+    /// Generates the client sending the endpoints.
+    fn client(&self,out : &mut String,name : &str,title : &str) {
+        let server = self.spec.pointer("/servers/0");
+        let mut base_url = server.and_then(|server| server.get("url")).and_then(Value::as_str).unwrap_or_default().to_owned();
+        // Server variables are replaced with their default
+        for (variable,value) in server.and_then(|server| server.get("variables")).and_then(Value::as_object).into_iter().flatten() {
+            if let Some(default) = value.get("default").and_then(Value::as_str) {
+                base_url = base_url.replace(&format!("{{{variable}}}"),default);
+            }
+        }
+        // Relative server URLs cannot be resolved without knowing where the specification is served from
+        if !base_url.contains("://") {
+            base_url.clear();
+        }
+
+        let _ = write!(out,"
+/// A client of the {} API, sending the endpoints with `RequestHandler::request_endpoint`.
+#[derive(Debug, Clone)]
+pub struct {name} {{
+    /// The client the requests are sent with.
+    pub client : ::api_request_utils::reqwest::Client,
+}}
+
+impl Default for {name} {{
+    fn default() -> Self {{
+        Self {{ client : <Self as ::api_request_utils::RequestInfo>::default_client() }}
+    }}
+}}
+
+impl ::api_request_utils::RequestInfo for {name} {{
+    const BASE_URL : &'static str = {:?};
+
+    fn client(&self) -> &::api_request_utils::reqwest::Client {{
+        &self.client
+    }}
+}}
+
+impl ::api_request_utils::RequestModifiers for {name} {{}}
+
+impl ::api_request_utils::RequestDefaults for {name} {{}}
+
+impl<T : ::api_request_utils::serde::de::DeserializeOwned,O : ::api_request_utils::serde::de::DeserializeOwned,E : ::api_request_utils::serde::de::DeserializeOwned> ::api_request_utils::RequestHandler<T,O,E> for {name} {{}}
+",title.trim(),base_url.trim_end_matches('/'));
+    }
+
+    /// Generates a field of a struct, named after the property or parameter `name`.
+    fn field(&self,out : &mut String,name : &str,rust_type : String,required : bool,description : Option<&Value>) {
+        doc(out,"    ",description);
+        let field = field_name(name);
+        if field.trim_start_matches("r#") != name {
+            let _ = writeln!(out,"    #[serde(rename = {name:?})]");
+        }
+        match required || rust_type.starts_with("Option<") {
+            true => {
+                let _ = writeln!(out,"    pub {field} : {rust_type},");
+            },
+            false => {
+                let _ = writeln!(out,"    #[serde(default, skip_serializing_if = \"Option::is_none\")]\n    pub {field} : Option<{rust_type}>,");
+            }
+        }
+    }
+
+    /// Returns the Rust type of a schema.
+    fn rust_type(&self,schema : &Value) -> String {
+        if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+            return match reference.strip_prefix("#/components/schemas/") {
+                Some(name) => type_name(name),
+                None => VALUE.to_owned()
+            };
+        }
+
+        // `nullable` in OpenAPI 3.0, a `null` type in 3.1
+        let (ty,nullable) = match schema.get("type") {
+            Some(Value::String(ty)) => (ty.as_str(),schema.get("nullable") == Some(&Value::Bool(true))),
+            Some(Value::Array(types)) => {
+                let ty = types.iter().filter_map(Value::as_str).find(|ty| *ty != "null").unwrap_or_default();
+                (ty,types.iter().any(|ty| ty == "null"))
+            },
+            _ => ("",false)
+        };
+        let format = schema.get("format").and_then(Value::as_str).unwrap_or_default();
+        let rust_type = match ty {
+            "string" => "String".to_owned(),
+            "integer" => match format {
+                "int32" => "i32",
+                "uint32" => "u32",
+                "uint64" => "u64",
+                _ => "i64"
+            }.to_owned(),
+            "number" => match format {
+                "float" => "f32",
+                _ => "f64"
+            }.to_owned(),
+            "boolean" => "bool".to_owned(),
+            "array" => format!("Vec<{}>",self.rust_type(schema.get("items").unwrap_or(&Value::Null))),
+            "object" if schema.get("properties").is_none() => match schema.get("additionalProperties") {
+                Some(values @ Value::Object(_)) => format!("::std::collections::HashMap<String,{}>",self.rust_type(values)),
+                _ => format!("::std::collections::HashMap<String,{VALUE}>")
+            },
+            _ => VALUE.to_owned()
+        };
+        match nullable {
+            true => format!("Option<{rust_type}>"),
+            false => rust_type
+        }
+    }
+
+    /// Returns the type of the first successful response with a JSON body, `()` if it has no body.
+    fn response_type(&self,operation : &Value) -> String {
+        let responses = operation.get("responses").and_then(Value::as_object);
+        let Some(response) = responses.into_iter().flatten().find(|(status,_)| status.starts_with('2')).map(|(_,response)| self.resolve(response)) else {
+            return VALUE.to_owned()
+        };
+        let content = response.get("content").and_then(Value::as_object);
+        match content.into_iter().flatten().find(|(media_type,_)| media_type.contains("json")) {
+            Some((_,content)) => content.get("schema").map_or_else(|| VALUE.to_owned(),|schema| self.rust_type(schema)),
+            None if content.is_none_or(Map::is_empty) => "()".to_owned(),
+            None => VALUE.to_owned()
+        }
+    }
+
+    /// Returns whether the schema is an object, which can be flattened into an endpoint.
+    fn is_object(&self,schema : &Value) -> bool {
+        let schema = self.resolve(schema);
+        schema.get("properties").is_some() || schema.get("type").and_then(Value::as_str) == Some("object")
+    }
+
+    /// Follows the `$ref` of a parameter, request body, response or schema to the component it points to.
+    fn resolve<'a>(&'a self,value : &'a Value) -> &'a Value {
+        let mut value = value;
+        // Bounded, as references may form a cycle
+        for _ in 0..8 {
+            match value.get("$ref").and_then(Value::as_str).and_then(|reference| reference.strip_prefix('#')).and_then(|pointer| self.spec.pointer(pointer)) {
+                Some(target) => value = target,
+                None => break
+            }
+        }
+        value
+    }
+}
+
+/// Returns the names of the required properties of an object schema.
+fn required(schema : &Value) -> BTreeSet<&str> {
+    schema.get("required").and_then(Value::as_array).into_iter().flatten().filter_map(Value::as_str).collect()
+}
+
+/// Writes a description as doc comments, dropping the indentation and the code fences that would be run as doctests.
+fn doc(out : &mut String,indent : &str,description : Option<&Value>) {
+    let Some(description) = description.and_then(Value::as_str).map(str::trim).filter(|description| !description.is_empty()) else {
+        return
+    };
+    for line in description.lines().map(str::trim).filter(|line| !line.starts_with("```")) {
+        let _ = match line.is_empty() {
+            true => writeln!(out,"{indent}///"),
+            false => writeln!(out,"{indent}/// {line}")
+        };
+    }
+}
+
+/// Splits a name into its words, at separators and at the start of capitalized words, such as `petId` or `HTTPServer`.
+fn words(name : &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let chars : Vec<char> = name.chars().collect();
+    for (index,&c) in chars.iter().enumerate() {
+        if !c.is_ascii_alphanumeric() {
+            words.extend((!word.is_empty()).then(|| std::mem::take(&mut word)));
+            continue;
+        }
+        let previous = index.checked_sub(1).map(|index| chars[index]);
+        let next = chars.get(index + 1);
+        let boundary = c.is_ascii_uppercase() && previous.is_some_and(|previous| {
+            previous.is_ascii_lowercase() || previous.is_ascii_digit() || (previous.is_ascii_uppercase() && next.is_some_and(char::is_ascii_lowercase))
+        });
+        if boundary && !word.is_empty() {
+            words.push(std::mem::take(&mut word));
+        }
+        word.push(c);
+    }
+    words.extend((!word.is_empty()).then_some(word));
+    words
+}
+
+/// Turns a name into a type name, such as `pet_status` into `PetStatus`.
+fn type_name(name : &str) -> String {
+    let name : String = words(name).iter().map(|word| {
+        let mut chars = word.chars();
+        chars.next().map(|first| first.to_ascii_uppercase().to_string() + &chars.as_str().to_ascii_lowercase()).unwrap_or_default()
+    }).collect();
+    match name.chars().next() {
+        Some(first) if first.is_ascii_alphabetic() && name != "Self" => name,
+        Some(_) => format!("V{name}"),
+        None => "Unnamed".to_owned()
+    }
+}
+
+/// Turns a name into a field name, such as `petId` into `pet_id`.
+fn field_name(name : &str) -> String {
+    let name = words(name).join("_").to_ascii_lowercase();
+    match name.as_str() {
+        "" => "unnamed".to_owned(),
+        _ if name.starts_with(|c : char| c.is_ascii_digit()) || RESERVED.contains(&name.as_str()) => format!("_{name}"),
+        _ if KEYWORDS.contains(&name.as_str()) => format!("r#{name}"),
+        _ => name
+    }
+}
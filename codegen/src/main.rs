@@ -0,0 +1,57 @@
+//! Prints the code generated from an OpenAPI 3 specification :
+//!
+//! ```text
+//! api-request-utils-codegen <spec.json> [--client <name>] [--no-client] [--error <type>]
+//! ```
+
+use std::process::ExitCode;
+
+use api_request_utils_codegen::Generator;
+
+const USAGE : &str = "usage : api-request-utils-codegen <spec.json> [--client <name>] [--no-client] [--error <type>]";
+
+fn main() -> ExitCode {
+    let mut args = std::env::args().skip(1);
+    let (mut spec,mut client,mut error,mut no_client) = (None,None,None,false);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--client" => client = args.next(),
+            "--error" => error = args.next(),
+            "--no-client" => no_client = true,
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return ExitCode::SUCCESS;
+            },
+            _ if spec.is_none() && !arg.starts_with("--") => spec = Some(arg),
+            _ => {
+                eprintln!("{USAGE}");
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    let Some(spec) = spec else {
+        eprintln!("{USAGE}");
+        return ExitCode::FAILURE;
+    };
+
+    match Generator::from_path(&spec) {
+        Ok(mut generator) => {
+            if let Some(client) = client {
+                generator = generator.client_name(&client);
+            }
+            if no_client {
+                generator = generator.without_client();
+            }
+            if let Some(error) = error {
+                generator = generator.error_type(&error);
+            }
+            print!("{}",generator.generate());
+            ExitCode::SUCCESS
+        },
+        Err(error) => {
+            eprintln!("{spec} : {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,110 @@
+//! Derive macro implementing the traits of [api-request-utils-rs](https://crates.io/crates/api-request-utils-rs) for API clients.
+//!
+//! Use it through the `derive` feature of `api-request-utils-rs`, which re-exports [ApiClient].
+#![forbid(missing_docs)]
+
+use proc_macro::TokenStream;
+
+use quote::quote;
+use syn::{parse_macro_input, parse_quote, DeriveInput, Ident, LitStr};
+
+/// Implements `RequestInfo`, `RequestModifiers`, `RequestDefaults` and `RequestHandler` (for every response and error type) for an API client.
+///
+/// ```rust ignore
+/// use api_request_utils::{ApiClient, RequestHandler};
+///
+/// #[derive(ApiClient)]
+/// #[api(base_url = "https://api.example.com", client = "http")]
+/// struct MyAPIClient {
+///     http : reqwest::Client,
+/// }
+///
+/// let user = api_client.try_get_request_handler("users/1",&parameters,|user : User| user).await?;
+/// ```
+///
+/// # Attributes
+///
+/// * `base_url = "..."` - The `RequestInfo::BASE_URL`. Without it, `RequestInfo::base_url` must be overridden, which requires
+///   skipping `RequestInfo`.
+/// * `client = "..."` - The field holding the `reqwest::Client`, `client` by default.
+/// * `shared_client` - Sends the requests with the `shared_client` of the process rather than with a field of the client,
+///   which then needs none. Not available on `wasm32`.
+/// * `skip(...)` - The traits not to implement, among `RequestInfo`, `RequestDefaults` and `RequestHandler`, to implement them by hand
+///   when their defaults need to be overridden.
+#[proc_macro_derive(ApiClient,attributes(api))]
+pub fn derive_api_client(input : TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input).unwrap_or_else(syn::Error::into_compile_error).into()
+}
+
+fn expand(input : DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let mut base_url = None;
+    let mut client = Some(Ident::new("client",proc_macro2::Span::call_site()));
+    let mut skipped = Vec::new();
+
+    for attribute in input.attrs.iter().filter(|attribute| attribute.path().is_ident("api")) {
+        attribute.parse_nested_meta(|meta| {
+            if meta.path.is_ident("base_url") {
+                base_url = Some(meta.value()?.parse::<LitStr>()?);
+            } else if meta.path.is_ident("client") {
+                client = Some(meta.value()?.parse::<LitStr>()?.parse()?);
+            } else if meta.path.is_ident("shared_client") {
+                client = None;
+            } else if meta.path.is_ident("skip") {
+                meta.parse_nested_meta(|skip| {
+                    if !["RequestInfo","RequestDefaults","RequestHandler"].iter().any(|name| skip.path.is_ident(name)) {
+                        return Err(skip.error("expected `RequestInfo`, `RequestDefaults` or `RequestHandler`"));
+                    }
+                    skipped.push(skip.path.require_ident()?.to_string());
+                    Ok(())
+                })?;
+            } else {
+                return Err(meta.error("expected `base_url`, `client`, `shared_client` or `skip`"));
+            }
+            Ok(())
+        })?;
+    }
+
+    let name = &input.ident;
+    let (impl_generics,type_generics,where_clause) = input.generics.split_for_impl();
+    let implements = |name : &str| !skipped.iter().any(|skipped| skipped == name);
+
+    let info = implements("RequestInfo").then(|| {
+        let base_url = base_url.iter();
+        let client = client.iter();
+        quote! {
+            impl #impl_generics ::api_request_utils::RequestInfo for #name #type_generics #where_clause {
+                #(const BASE_URL : &'static str = #base_url;)*
+
+                #(fn client(&self) -> &::api_request_utils::reqwest::Client {
+                    &self.#client
+                })*
+            }
+        }
+    });
+
+    let defaults = implements("RequestDefaults").then(|| quote! {
+        impl #impl_generics ::api_request_utils::RequestDefaults for #name #type_generics #where_clause {}
+    });
+
+    let handler = implements("RequestHandler").then(|| {
+        let mut generics = input.generics.clone();
+        generics.params.push(parse_quote!(__T : ::api_request_utils::serde::de::DeserializeOwned));
+        generics.params.push(parse_quote!(__O : ::api_request_utils::serde::de::DeserializeOwned));
+        generics.params.push(parse_quote!(__E : ::api_request_utils::serde::de::DeserializeOwned));
+        let (handler_generics,_,_) = generics.split_for_impl();
+        quote! {
+            impl #handler_generics ::api_request_utils::RequestHandler<__T,__O,__E> for #name #type_generics #where_clause {}
+        }
+    });
+
+    Ok(quote! {
+        #info
+
+        impl #impl_generics ::api_request_utils::RequestModifiers for #name #type_generics #where_clause {}
+
+        #defaults
+
+        #handler
+    })
+}
@@ -0,0 +1,305 @@
+#[cfg(not(target_arch = "wasm32"))]
+use async_trait::async_trait;
+
+use reqwest::RequestBuilder;
+use reqwest::header::{HeaderName, HeaderValue, COOKIE};
+
+use crate::{MaybeSend, MaybeSync};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::{hash, RawResponse};
+
+/// Where an API expects its key, see [ApiKeyAuth::api_key_location].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApiKeyLocation {
+    /// In the given header, such as `X-Api-Key`.
+    Header(HeaderName),
+    /// In the given query parameter, such as `api_key`.
+    Query(String),
+    /// In the cookie of the given name.
+    Cookie(String),
+}
+
+/// Authenticates requests with an API key.
+///
+/// Return it from [crate::RequestDefaults::api_key_auth] to add the key to every request built by the default requestors.
+/// It is usually implemented by the client itself :
+///
+/// ```rust ignore
+/// impl ApiKeyAuth for MyAPIClient {
+///     fn api_key(&self) -> &str {
+///         &self.api_key
+///     }
+///
+///     fn api_key_location(&self) -> ApiKeyLocation {
+///         ApiKeyLocation::Query("apikey".to_owned())
+///     }
+/// }
+///
+/// impl RequestDefaults for MyAPIClient {
+///     fn api_key_auth(&self) -> Option<&dyn ApiKeyAuth> {
+///         Some(self)
+///     }
+/// }
+/// ```
+pub trait ApiKeyAuth : MaybeSend + MaybeSync {
+    /// Returns the API key.
+    fn api_key(&self) -> &str;
+
+    /// Returns where the key is sent.
+    ///
+    /// # Returns
+    ///
+    /// The `X-Api-Key` header by default.
+    fn api_key_location(&self) -> ApiKeyLocation {
+        ApiKeyLocation::Header(HeaderName::from_static("x-api-key"))
+    }
+}
+
+/// Adds the key of `auth` to the request where it belongs. Header values are marked as sensitive, so they are not logged.
+pub(crate) fn apply_api_key(auth : &dyn ApiKeyAuth,request_builder : RequestBuilder) -> RequestBuilder {
+    let key = auth.api_key();
+    let header = |request_builder : RequestBuilder,name : HeaderName,value : String| match HeaderValue::try_from(value) {
+        Ok(mut value) => {
+            value.set_sensitive(true);
+            request_builder.header(name,value)
+        }
+        // Left to reqwest, which reports the invalid value when the request is sent
+        Err(_) => request_builder.header(name,key)
+    };
+
+    match auth.api_key_location() {
+        ApiKeyLocation::Header(name) => header(request_builder,name,key.to_owned()),
+        ApiKeyLocation::Query(name) => request_builder.query(&[(name.as_str(),key)]),
+        ApiKeyLocation::Cookie(name) => header(request_builder,COOKIE,format!("{name}={key}"))
+    }
+}
+
+/// Refreshes expired credentials when a request is answered with `401 Unauthorized`.
+///
+/// Return it from [crate::RequestDefaults::auth_refresh] to apply it. Requests answered with `401 Unauthorized` are then
+/// retried once with the refreshed credentials, provided their body can be cloned (which excludes streamed bodies). The
+/// [crate::OAuth2TokenProvider] implements it by fetching a new token, and [DigestAuth] by answering the challenge of the
+/// server.
+///
+/// ```rust ignore
+/// struct SessionRefresh { session : RwLock<String>, /* ... */ }
+///
+/// #[async_trait]
+/// impl AuthRefresh for SessionRefresh {
+///     async fn refresh(&self,request : &mut reqwest::Request,response : &RawResponse) -> bool {
+///         let Ok(session) = self.login().await else {
+///             return false;
+///         };
+///         request.headers_mut().insert("X-Session",session.parse().unwrap());
+///         true
+///     }
+/// }
+/// ```
+///
+/// Not available on `wasm32`.
+#[cfg(not(target_arch = "wasm32"))]
+#[async_trait]
+pub trait AuthRefresh : Send + Sync {
+    /// Refreshes the credentials and updates the request to retry with them, such as by replacing its `Authorization` header.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The copy of the rejected request, which is sent again if this returns `true`.
+    /// * `response` - The `401 Unauthorized` response, whose `WWW-Authenticate` header may carry a challenge.
+    ///
+    /// # Returns
+    ///
+    /// Whether the request should be retried, `false` handing the `401 Unauthorized` response over as usual.
+    async fn refresh(&self,request : &mut reqwest::Request,response : &RawResponse) -> bool;
+}
+
+/// Authenticates requests with HTTP Digest authentication (RFC 7616), as still used by some enterprise APIs and devices.
+///
+/// Return it from [crate::RequestDefaults::auth_refresh] : requests are first sent without credentials, then the `401
+/// Unauthorized` challenge of the server is answered and the request retried. The `MD5`, `MD5-sess`, `SHA-256` and
+/// `SHA-256-sess` algorithms are supported, with the `auth` and `auth-int` qualities of protection.
+///
+/// ```rust ignore
+/// impl RequestDefaults for MyAPIClient {
+///     fn auth_refresh(&self) -> Option<&dyn AuthRefresh> {
+///         Some(&self.digest) // DigestAuth::new("user","password")
+///     }
+/// }
+/// ```
+///
+/// Not available on `wasm32`.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct DigestAuth {
+    username : String,
+    password : String,
+    nonce_count : std::sync::atomic::AtomicU32,
+}
+
+// The password is left out
+#[cfg(not(target_arch = "wasm32"))]
+impl std::fmt::Debug for DigestAuth {
+    fn fmt(&self,f : &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DigestAuth").field("username",&self.username).finish_non_exhaustive()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl DigestAuth {
+    /// Creates the authentication with the given credentials.
+    pub fn new(username : impl Into<String>,password : impl Into<String>) -> Self {
+        Self {
+            username : username.into(),
+            password : password.into(),
+            nonce_count : std::sync::atomic::AtomicU32::new(0),
+        }
+    }
+
+    /// Answers a `WWW-Authenticate` challenge for the request with the given client nonce, which [AuthRefresh::refresh] generates.
+    ///
+    /// Every answer counts as a use of the server nonce, sent in the `nc` parameter. The responses match the examples of
+    /// RFC 2617 and RFC 7616 :
+    ///
+    /// ```
+    /// use api_request_utils::DigestAuth;
+    /// use api_request_utils::reqwest::Client;
+    ///
+    /// let request = Client::new().get("https://example.org/dir/index.html").build().unwrap();
+    ///
+    /// let digest = DigestAuth::new("Mufasa","Circle Of Life");
+    /// let challenge = r#"Digest realm="testrealm@host.com", qop="auth,auth-int", nonce="dcd98b7102dd2f0e8b11d0f600bfb0c093", opaque="5ccc069c403ebaf9f0171e9517f40e41""#;
+    /// let authorization = digest.answer_with(challenge,&request,"0a4f113b").unwrap();
+    /// assert!(authorization.contains(r#"response="6629fae49393a05397450978507c4ef1", qop=auth, nc=00000001, cnonce="0a4f113b""#));
+    ///
+    /// let client_nonce = "f2/wE4q74E6zIJEtWaHKaf5wv/H5QzzpXusqGemxURZJ";
+    /// for (algorithm,response) in [("MD5","8ca523f5e9506fed4657c9700eebdbec"),("SHA-256","753927fa0e85d155564e2e272a28d1802ca10daf4496794697cf8db5856cb6c1")] {
+    ///     let digest = DigestAuth::new("Mufasa","Circle of Life");
+    ///     let challenge = format!(r#"Digest realm="http-auth@example.org", qop="auth, auth-int", algorithm={algorithm}, nonce="7ypf/xlj9XXwfDPEoM4URrv/xwf94BcCAzFZH4GiTo0v", opaque="FQhe/qaU925kfnzjCev0ciny7QMkPqMAFRtzCUYo5tdS""#);
+    ///     assert!(digest.answer_with(&challenge,&request,client_nonce).unwrap().contains(&format!(r#"response="{response}""#)));
+    /// }
+    /// ```
+    ///
+    /// # Returns
+    ///
+    /// The value of the `Authorization` header, `None` if the challenge is not a supported `Digest` one.
+    pub fn answer_with(&self,challenge : &str,request : &reqwest::Request,client_nonce : &str) -> Option<String> {
+        self.authorization(&parse_digest_challenge(challenge)?,request,client_nonce)
+    }
+
+    /// Computes the `Authorization` header answering the challenge for the request, `None` if the challenge is not supported.
+    fn authorization(&self,challenge : &[(String,String)],request : &reqwest::Request,client_nonce : &str) -> Option<String> {
+        let parameter = |name : &str| challenge.iter().find(|(key,_)| key.eq_ignore_ascii_case(name)).map(|(_,value)| value.as_str());
+        let realm = parameter("realm").unwrap_or_default();
+        let nonce = parameter("nonce")?;
+        let algorithm = parameter("algorithm").unwrap_or("MD5");
+        let digest : fn(&[u8]) -> String = match algorithm.trim_end_matches("-sess").to_ascii_uppercase().as_str() {
+            "MD5" => |data| hash::hex(&hash::md5(data)),
+            "SHA-256" => |data| hash::hex(&hash::sha256(data)),
+            _ => return None
+        };
+
+        let uri = match request.url().query() {
+            Some(query) => format!("{}?{query}",request.url().path()),
+            None => request.url().path().to_owned()
+        };
+        let qops : Vec<&str> = parameter("qop").map(|qop| qop.split(',').map(str::trim).collect()).unwrap_or_default();
+        let qop = match (qops.contains(&"auth"),qops.contains(&"auth-int"),qops.is_empty()) {
+            (true,_,_) => Some("auth"),
+            (false,true,_) => Some("auth-int"),
+            (false,false,true) => None,
+            (false,false,false) => return None
+        };
+
+        let nonce_count = format!("{:08x}",self.nonce_count.fetch_add(1,std::sync::atomic::Ordering::Relaxed) + 1);
+        let mut secret = digest(format!("{}:{realm}:{}",self.username,self.password).as_bytes());
+        if algorithm.to_ascii_lowercase().ends_with("-sess") {
+            secret = digest(format!("{secret}:{nonce}:{client_nonce}").as_bytes());
+        }
+        let target = match qop {
+            Some("auth-int") => {
+                let body = request.body().and_then(reqwest::Body::as_bytes).unwrap_or_default();
+                digest(format!("{}:{uri}:{}",request.method(),digest(body)).as_bytes())
+            }
+            _ => digest(format!("{}:{uri}",request.method()).as_bytes())
+        };
+        let response = match qop {
+            Some(qop) => digest(format!("{secret}:{nonce}:{nonce_count}:{client_nonce}:{qop}:{target}").as_bytes()),
+            None => digest(format!("{secret}:{nonce}:{target}").as_bytes())
+        };
+
+        let quote = |value : &str| value.replace('\\',"\\\\").replace('"',"\\\"");
+        let mut authorization = format!(
+            r#"Digest username="{}", realm="{}", nonce="{}", uri="{}", algorithm={algorithm}, response="{response}""#,
+            quote(&self.username),quote(realm),quote(nonce),quote(&uri)
+        );
+        if let Some(qop) = qop {
+            authorization.push_str(&format!(r#", qop={qop}, nc={nonce_count}, cnonce="{client_nonce}""#));
+        }
+        if let Some(opaque) = parameter("opaque") {
+            authorization.push_str(&format!(r#", opaque="{}""#,quote(opaque)));
+        }
+        Some(authorization)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[async_trait]
+impl AuthRefresh for DigestAuth {
+    async fn refresh(&self,request : &mut reqwest::Request,response : &RawResponse) -> bool {
+        let challenge = response.meta.headers.get_all(reqwest::header::WWW_AUTHENTICATE).iter()
+            .filter_map(|value| value.to_str().ok())
+            .find_map(parse_digest_challenge);
+        let Some(value) = challenge.zip(client_nonce()).and_then(|(challenge,client_nonce)| self.authorization(&challenge,request,&client_nonce)).and_then(|value| HeaderValue::try_from(value).ok()) else {
+            return false;
+        };
+        request.headers_mut().insert(reqwest::header::AUTHORIZATION,value);
+        true
+    }
+}
+
+/// Parses the parameters of a `Digest` challenge, `None` for the challenges of other schemes.
+#[cfg(not(target_arch = "wasm32"))]
+fn parse_digest_challenge(challenge : &str) -> Option<Vec<(String,String)>> {
+    let (scheme,mut rest) = challenge.trim().split_once(' ')?;
+    if !scheme.eq_ignore_ascii_case("digest") {
+        return None;
+    }
+
+    let mut parameters = Vec::new();
+    loop {
+        rest = rest.trim_start_matches([' ',',']);
+        let Some((name,value)) = rest.split_once('=') else {
+            return Some(parameters);
+        };
+        let value = value.trim_start();
+        let (value,remaining) = match value.strip_prefix('"') {
+            Some(quoted) => {
+                let mut unquoted = String::new();
+                let mut characters = quoted.char_indices();
+                let mut end = quoted.len();
+                while let Some((index,character)) = characters.next() {
+                    match character {
+                        '\\' => unquoted.extend(characters.next().map(|(_,escaped)| escaped)),
+                        '"' => {
+                            end = index + 1;
+                            break;
+                        }
+                        character => unquoted.push(character)
+                    }
+                }
+                (unquoted,&quoted[end..])
+            }
+            None => {
+                let end = value.find(',').unwrap_or(value.len());
+                (value[..end].trim().to_owned(),&value[end..])
+            }
+        };
+        parameters.push((name.trim().to_owned(),value));
+        rest = remaining;
+    }
+}
+
+/// Generates the client nonce of a digest response, `None` if no random bytes could be drawn.
+#[cfg(not(target_arch = "wasm32"))]
+fn client_nonce() -> Option<String> {
+    hash::random_bytes::<16>().ok().map(|bytes| hash::hex(&bytes))
+}
@@ -0,0 +1,281 @@
+//! Synchronous counterparts of the request traits, built on [reqwest::blocking], for applications without an async runtime.
+//!
+//! The traits mirror [crate::RequestInfo], [crate::RequestDefaults] and [crate::RequestHandler] : implement them the same way
+//! and call the handlers without `.await`. Only the core of the async API is mirrored; the hooks that need a runtime,
+//! such as the rate limiter, retries, middleware or mocks, are not available here. Requires the `blocking` feature.
+//!
+//! ```rust ignore
+//! use api_request_utils::blocking::*;
+//!
+//! struct MyAPIClient {
+//!     client : reqwest::blocking::Client,
+//! }
+//!
+//! impl BlockingRequestInfo for MyAPIClient {
+//!     const BASE_URL : &'static str = "https://api.example.com";
+//!     fn client(&self) -> &reqwest::blocking::Client {
+//!         &self.client
+//!     }
+//! }
+//!
+//! impl BlockingRequestDefaults for MyAPIClient {}
+//!
+//! impl BlockingRequestHandler<User,User,ApiError> for MyAPIClient {}
+//!
+//! fn main() -> Result<(),RequestError<ApiError>> {
+//!     let user = api_client.try_get_request_handler("users/1",&HashMap::new(),|user| user)?;
+//!     Ok(())
+//! }
+//! ```
+
+use std::collections::HashMap;
+
+use reqwest::Method;
+use reqwest::blocking::{Client, RequestBuilder, Response};
+use reqwest::header::{ACCEPT, CONTENT_LENGTH, USER_AGENT};
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+pub use crate::{RawResponse, RequestContext, RequestError, ResponseMeta};
+
+use crate::{merge_query, parse_response};
+
+/// Trait to provide some basic info about API, see [crate::RequestInfo].
+pub trait BlockingRequestInfo {
+    /// The base URL for the requests, used by [BlockingRequestInfo::base_url] unless it is overridden.
+    ///
+    /// Empty by default, in which case [BlockingRequestInfo::base_url] must be overridden.
+    const BASE_URL : &'static str = "";
+
+    /// Returns the base URL for the requests, which can be decided at runtime to switch between environments or regions.
+    ///
+    /// # Returns
+    ///
+    /// [BlockingRequestInfo::BASE_URL] by default.
+    fn base_url(&self) -> &str {
+        Self::BASE_URL
+    }
+
+    /// Returns the [reqwest::blocking::Client] instance associated with the API client.
+    fn client(&self) -> &Client;
+}
+
+/// Default methods for configuring HTTP requests, see [crate::RequestDefaults].
+pub trait BlockingRequestDefaults : BlockingRequestInfo {
+    /// Joins the given endpoint with the [BlockingRequestInfo::base_url], as done by the default requestors.
+    fn endpoint_url(&self,endpoint : &str) -> String {
+        format!("{}/{}",self.base_url(),endpoint)
+    }
+
+    /// Modifies the provided `RequestBuilder` with default headers.
+    fn default_headers(&self,request_builder : RequestBuilder) -> RequestBuilder {
+        request_builder
+    }
+
+    /// The `User-Agent` sent with every request built by the default requestors.
+    ///
+    /// # Returns
+    ///
+    /// `None` by default, leaving the `User-Agent` of the client in place.
+    fn user_agent(&self) -> Option<&str> {
+        None
+    }
+
+    /// The `Accept` header sent with every request built by the default requestors.
+    ///
+    /// # Returns
+    ///
+    /// `application/json` by default, or `None` to send no `Accept` header.
+    fn accept(&self) -> Option<&str> {
+        Some("application/json")
+    }
+
+    /// Modifies the provided `RequestBuilder` with default parameters.
+    fn default_parameters(&self,request_builder : RequestBuilder) -> RequestBuilder {
+        request_builder
+    }
+
+    /// Returns query parameters sent with every request, merged with the per-request parameters which take precedence.
+    fn default_query(&self) -> HashMap<&str,Value> {
+        HashMap::new()
+    }
+
+    /// Creates a `RequestBuilder` for the given method with default settings applied.
+    ///
+    /// # Arguments
+    ///
+    /// * `method` - The HTTP method of the request.
+    /// * `endpoint` - The endpoint for the request.
+    /// * `parameters` - The parameters for the request.
+    ///
+    /// # Returns
+    ///
+    /// The `RequestBuilder` with default settings applied.
+    fn default_requestor(&self,method : Method,endpoint : &str,parameters : &HashMap<&str,Value>) -> RequestBuilder {
+        let mut request_builder = self.client().request(method,self.endpoint_url(endpoint));
+        if let Some(user_agent) = self.user_agent() {
+            request_builder = request_builder.header(USER_AGENT,user_agent);
+        }
+        if let Some(accept) = self.accept() {
+            request_builder = request_builder.header(ACCEPT,accept);
+        }
+        self.default_parameters(self.default_headers(request_builder)).query(&merge_query(self.default_query(),parameters))
+    }
+
+    /// Creates a GET `RequestBuilder` with default settings applied.
+    fn default_get_requestor(&self,endpoint : &str,parameters : &HashMap<&str,Value>) -> RequestBuilder {
+        self.default_requestor(Method::GET,endpoint,parameters)
+    }
+
+    /// Creates a POST `RequestBuilder` carrying the JSON payload, with default settings applied.
+    fn default_post_requestor(&self,endpoint : &str,json : String) -> RequestBuilder {
+        with_body(self.default_requestor(Method::POST,endpoint,&HashMap::new()),json)
+    }
+
+    /// Creates a PUT `RequestBuilder` carrying the JSON payload, with default settings applied.
+    fn default_put_requestor(&self,endpoint : &str,json : String) -> RequestBuilder {
+        with_body(self.default_requestor(Method::PUT,endpoint,&HashMap::new()),json)
+    }
+
+    /// Creates a PATCH `RequestBuilder` carrying the JSON payload, with default settings applied.
+    fn default_patch_requestor(&self,endpoint : &str,json : String) -> RequestBuilder {
+        with_body(self.default_requestor(Method::PATCH,endpoint,&HashMap::new()),json)
+    }
+
+    /// Creates a DELETE `RequestBuilder` with default settings applied.
+    fn default_delete_requestor(&self,endpoint : &str,parameters : &HashMap<&str,Value>) -> RequestBuilder {
+        self.default_requestor(Method::DELETE,endpoint,parameters)
+    }
+}
+
+/// Attaches a buffered body together with an explicit `Content-Length` header.
+fn with_body(request_builder : RequestBuilder,body : String) -> RequestBuilder {
+    request_builder.header(CONTENT_LENGTH,body.len()).body(body)
+}
+
+/// A trait for handling HTTP requests synchronously, see [crate::RequestHandler].
+pub trait BlockingRequestHandler<T : DeserializeOwned,O : DeserializeOwned,E : DeserializeOwned> : BlockingRequestDefaults {
+    /// Sends an HTTP request with [BlockingRequestInfo::client] and reads the whole response without deserializing its body or checking its status.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the [RawResponse] or an `RequestError` variant.
+    fn request_raw(&self,request : RequestBuilder) -> Result<RawResponse,RequestError<E>> {
+        let request = request.build()?;
+        let method = request.method().clone();
+        let response = self.client().execute(request)?;
+        let meta = meta(method,&response);
+        Ok(RawResponse { meta, body : response.bytes()? })
+    }
+
+    /// Sends an HTTP request and maps the response like [crate::RequestHandler::request_map].
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The `RequestBuilder` representing the request to be sent.
+    /// * `map` - A closure that maps the successful response JSON into the desired output type.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the mapped output type or an `RequestError` variant.
+    fn request_map(&self,request : RequestBuilder,map : impl FnOnce(T) -> O) -> Result<O,RequestError<E>> {
+        parse_response(&self.request_raw(request)?).map(map)
+    }
+
+    /// Sends an HTTP request like [BlockingRequestHandler::request_map], also passing the metadata of the response to the map closure.
+    fn request_map_with_meta(&self,request : RequestBuilder,map : impl FnOnce(T,ResponseMeta) -> O) -> Result<O,RequestError<E>> {
+        let response = self.request_raw(request)?;
+        let body = parse_response(&response)?;
+        Ok(map(body,response.meta))
+    }
+
+    /// Resolves the error in the response and returns an option containing the value or `None`.
+    fn resolve_error(&self,response : Result<O,RequestError<E>>,error_handler : impl Fn(RequestError<E>)) -> Option<O> {
+        response.map_err(error_handler).ok()
+    }
+
+    /// Sends a GET request built with `default_get_requestor`, returning the mapped response or the error.
+    fn try_get_request_handler(&self,endpoint : &str,parameters : &HashMap<&str,Value>,map : impl FnOnce(T) -> O) -> Result<O,RequestError<E>> {
+        self.request_map(self.default_get_requestor(endpoint,parameters),map)
+    }
+
+    /// Sends a POST request built with `default_post_requestor`, returning the mapped response or the error.
+    fn try_post_request_handler(&self,endpoint : &str,json : String,map : impl FnOnce(T) -> O) -> Result<O,RequestError<E>> {
+        self.request_map(self.default_post_requestor(endpoint,json),map)
+    }
+
+    /// Sends a PUT request built with `default_put_requestor`, returning the mapped response or the error.
+    fn try_put_request_handler(&self,endpoint : &str,json : String,map : impl FnOnce(T) -> O) -> Result<O,RequestError<E>> {
+        self.request_map(self.default_put_requestor(endpoint,json),map)
+    }
+
+    /// Sends a PATCH request built with `default_patch_requestor`, returning the mapped response or the error.
+    fn try_patch_request_handler(&self,endpoint : &str,json : String,map : impl FnOnce(T) -> O) -> Result<O,RequestError<E>> {
+        self.request_map(self.default_patch_requestor(endpoint,json),map)
+    }
+
+    /// Sends a DELETE request built with `default_delete_requestor`, returning the mapped response or the error.
+    fn try_delete_request_handler(&self,endpoint : &str,parameters : &HashMap<&str,Value>,map : impl FnOnce(T) -> O) -> Result<O,RequestError<E>> {
+        self.request_map(self.default_delete_requestor(endpoint,parameters),map)
+    }
+
+    /// Sends a GET request like [BlockingRequestHandler::try_get_request_handler], passing the error to `error_handler`.
+    ///
+    /// # Returns
+    ///
+    /// An `Option<O>` representing the response data if successful, or `None` if an error occurred.
+    fn get_request_handler(&self,endpoint : &str,parameters : &HashMap<&str,Value>,map : impl FnOnce(T) -> O,error_handler : impl Fn(RequestError<E>)) -> Option<O> {
+        self.resolve_error(self.try_get_request_handler(endpoint,parameters,map),error_handler)
+    }
+
+    /// Sends a POST request like [BlockingRequestHandler::try_post_request_handler], passing the error to `error_handler`.
+    ///
+    /// # Returns
+    ///
+    /// An `Option<O>` representing the response data if successful, or `None` if an error occurred.
+    fn post_request_handler(&self,endpoint : &str,json : String,map : impl FnOnce(T) -> O,error_handler : impl Fn(RequestError<E>)) -> Option<O> {
+        self.resolve_error(self.try_post_request_handler(endpoint,json,map),error_handler)
+    }
+
+    /// Sends a PUT request like [BlockingRequestHandler::try_put_request_handler], passing the error to `error_handler`.
+    ///
+    /// # Returns
+    ///
+    /// An `Option<O>` representing the response data if successful, or `None` if an error occurred.
+    fn put_request_handler(&self,endpoint : &str,json : String,map : impl FnOnce(T) -> O,error_handler : impl Fn(RequestError<E>)) -> Option<O> {
+        self.resolve_error(self.try_put_request_handler(endpoint,json,map),error_handler)
+    }
+
+    /// Sends a PATCH request like [BlockingRequestHandler::try_patch_request_handler], passing the error to `error_handler`.
+    ///
+    /// # Returns
+    ///
+    /// An `Option<O>` representing the response data if successful, or `None` if an error occurred.
+    fn patch_request_handler(&self,endpoint : &str,json : String,map : impl FnOnce(T) -> O,error_handler : impl Fn(RequestError<E>)) -> Option<O> {
+        self.resolve_error(self.try_patch_request_handler(endpoint,json,map),error_handler)
+    }
+
+    /// Sends a DELETE request like [BlockingRequestHandler::try_delete_request_handler], passing the error to `error_handler`.
+    ///
+    /// # Returns
+    ///
+    /// An `Option<O>` representing the response data if successful, or `None` if an error occurred.
+    fn delete_request_handler(&self,endpoint : &str,parameters : &HashMap<&str,Value>,map : impl FnOnce(T) -> O,error_handler : impl Fn(RequestError<E>)) -> Option<O> {
+        self.resolve_error(self.try_delete_request_handler(endpoint,parameters,map),error_handler)
+    }
+}
+
+/// Captures the metadata of a blocking response, like [ResponseMeta::from_response].
+fn meta(method : Method,response : &Response) -> ResponseMeta {
+    ResponseMeta {
+        method,
+        status : response.status(),
+        headers : response.headers().clone(),
+        url : response.url().clone(),
+        version : response.version(),
+        peer_certificate : response.extensions().get::<reqwest::tls::TlsInfo>().and_then(|info| info.peer_certificate()).map(<[u8]>::to_vec),
+        correlation_id : None,
+        cache : None,
+    }
+}
@@ -0,0 +1,62 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// Limits how many requests are in flight at once to each host, so a burst of calls neither exhausts the connections to
+/// a host nor trips its abuse detection.
+///
+/// Requests beyond the limit wait for a slot, holding it until their response headers are received. A host is the
+/// scheme, host and port of the URL, so every mirror of a [crate::HostPool] has its own limit :
+///
+/// ```rust ignore
+/// let bulkhead = Bulkhead::new(8).host("https://search.example.com",2);
+/// ```
+///
+/// Unlike the [crate::RequestScheduler], which limits the requests of the client as a whole and orders them by
+/// priority, a slow host only holds up the requests sent to it. How long requests waited is reported as the
+/// [crate::RequestMetrics::queue_time].
+///
+/// Return it from [crate::RequestDefaults::bulkhead] to apply it. Not available on `wasm32`.
+#[derive(Debug)]
+pub struct Bulkhead {
+    max_in_flight : usize,
+    limits : HashMap<String,usize>,
+    semaphores : Mutex<HashMap<String,Arc<Semaphore>>>,
+}
+
+impl Bulkhead {
+    /// Creates a bulkhead letting `max_in_flight` requests be in flight at once to each host, at least one.
+    pub fn new(max_in_flight : usize) -> Self {
+        Self { max_in_flight : max_in_flight.max(1), limits : HashMap::new(), semaphores : Mutex::new(HashMap::new()) }
+    }
+
+    /// Sets a different limit for the given host, such as `https://api.example.com`.
+    pub fn host(mut self,host : &str,max_in_flight : usize) -> Self {
+        let host = reqwest::Url::parse(host).map_or_else(|_| host.trim_end_matches('/').to_owned(),|url| url.origin().ascii_serialization());
+        self.limits.insert(host,max_in_flight.max(1));
+        self
+    }
+
+    /// Returns how many requests may be in flight at once to the host of the given URL.
+    pub fn limit(&self,url : &reqwest::Url) -> usize {
+        self.limits.get(&url.origin().ascii_serialization()).copied().unwrap_or(self.max_in_flight)
+    }
+
+    /// Returns how many more requests may be sent to the host of the given URL right now.
+    pub fn available(&self,url : &reqwest::Url) -> usize {
+        self.semaphores.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+            .get(&url.origin().ascii_serialization())
+            .map_or_else(|| self.limit(url),|semaphore| semaphore.available_permits())
+    }
+
+    /// Waits for a slot for a request to the given URL, released when the returned permit is dropped.
+    pub(crate) async fn acquire(&self,url : &reqwest::Url) -> OwnedSemaphorePermit {
+        let limit = self.limit(url);
+        let semaphore = self.semaphores.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+            .entry(url.origin().ascii_serialization())
+            .or_insert_with(|| Arc::new(Semaphore::new(limit)))
+            .clone();
+        semaphore.acquire_owned().await.expect("the semaphores of a bulkhead are never closed")
+    }
+}
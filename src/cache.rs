@@ -0,0 +1,585 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use bytes::Bytes;
+
+use reqwest::{Method, StatusCode, Url, Version};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, CACHE_CONTROL, CONTENT_LENGTH, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{hash, CacheStatus, RawResponse, ResponseMeta};
+
+/// A cache of the responses to `GET` requests, so repeated lookups such as metadata endpoints do not hit the network.
+///
+/// Responses are keyed by their URL, the parameters of the query string being compared in any order, and kept for a TTL
+/// configurable per endpoint. Only successful responses are stored, unless they carry `Cache-Control: no-store`, and
+/// requests sent with `Cache-Control: no-cache` or their own `If-None-Match`/`If-Modified-Since` headers skip the lookup.
+/// The [ResponseMeta::cache](crate::ResponseMeta::cache) of the responses tells whether they were served from the cache.
+///
+/// Expired responses carrying an `ETag` or a `Last-Modified` header are kept to revalidate them : the next request is sent
+/// with `If-None-Match` or `If-Modified-Since`, and a `304 Not Modified` answer is replaced with the cached body. With a
+/// TTL of zero, such responses are revalidated on every request, which cuts the bandwidth of polling without ever
+/// serving outdated data.
+///
+/// Endpoints given a [ResponseCache::stale_while_revalidate] window rather answer with the expired response right away,
+/// while a background task refreshes it, trading freshness for latency.
+///
+/// ```rust ignore
+/// let cache = ResponseCache::new(Duration::from_secs(60))
+///     .endpoint_ttl("metadata",Duration::from_secs(3600))
+///     .stale_while_revalidate("feed",Duration::from_secs(300));
+/// // After updating a user
+/// api_client.cache.invalidate("users/1");
+/// ```
+///
+/// Responses are held in memory by default, or in any [CacheStore] given to [ResponseCache::with_store], such as a
+/// [DiskStore] keeping them across runs.
+///
+/// As the key does not include the headers, a client sending requests on behalf of several users must use a cache per user.
+/// Return it from [crate::RequestDefaults::response_cache] to apply it. Not available on `wasm32`.
+pub struct ResponseCache {
+    ttl : Duration,
+    endpoint_ttls : Vec<(String,Duration)>,
+    stale_windows : Vec<(String,Duration)>,
+    store : Arc<dyn CacheStore>,
+    refreshing : Arc<Mutex<HashSet<String>>>,
+}
+
+// The store is left out
+impl std::fmt::Debug for ResponseCache {
+    fn fmt(&self,f : &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ResponseCache")
+            .field("ttl",&self.ttl)
+            .field("endpoint_ttls",&self.endpoint_ttls)
+            .field("stale_windows",&self.stale_windows)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ResponseCache {
+    /// Creates a cache keeping responses for the given TTL in a [MemoryStore] holding up to 1000 of them.
+    pub fn new(ttl : Duration) -> Self {
+        Self::with_store(ttl,MemoryStore::new(1000))
+    }
+
+    /// Creates a cache keeping responses for the given TTL in the given store.
+    pub fn with_store(ttl : Duration,store : impl CacheStore + 'static) -> Self {
+        Self {
+            ttl,
+            endpoint_ttls : Vec::new(),
+            stale_windows : Vec::new(),
+            store : Arc::new(store),
+            refreshing : Arc::default(),
+        }
+    }
+
+    /// Sets the TTL of the responses of an endpoint and of the endpoints below it, `users` also covering `users/1`. The most
+    /// specific endpoint wins, and a TTL of zero only keeps the responses that can be revalidated.
+    pub fn endpoint_ttl(mut self,endpoint : &str,ttl : Duration) -> Self {
+        self.endpoint_ttls.push((endpoint.trim_matches('/').to_owned(),ttl));
+        self
+    }
+
+    /// Serves the expired responses of an endpoint and of the endpoints below it for up to `window` after they expired,
+    /// refreshing them in a background task. The most specific endpoint wins, and a window of zero disables it.
+    ///
+    /// The refresh is sent with the [crate::RequestInfo::client] as the request was built, so the hooks applied while
+    /// sending (token provider, request signer, rate limiter, retries, transport and mocks) are bypassed : it suits
+    /// endpoints authenticated by the default headers or query. It needs a Tokio runtime, the response being refreshed
+    /// in the foreground without one.
+    pub fn stale_while_revalidate(mut self,endpoint : &str,window : Duration) -> Self {
+        self.stale_windows.push((endpoint.trim_matches('/').to_owned(),window));
+        self
+    }
+
+    /// Returns the TTL of the responses of the given endpoint, see [ResponseCache::endpoint_ttl].
+    pub fn ttl(&self,endpoint : &str) -> Duration {
+        most_specific(&self.endpoint_ttls,endpoint).unwrap_or(self.ttl)
+    }
+
+    /// Returns how long after they expired the responses of the given endpoint are still served, see
+    /// [ResponseCache::stale_while_revalidate].
+    pub fn stale_window(&self,endpoint : &str) -> Duration {
+        most_specific(&self.stale_windows,endpoint).unwrap_or_default()
+    }
+
+    /// Removes the responses of the given endpoint, whatever their query.
+    pub fn invalidate(&self,endpoint : &str) {
+        let endpoint = endpoint.trim_matches('/');
+        self.store.retain(&mut |entry| entry.endpoint != endpoint);
+    }
+
+    /// Removes the response to the given URL.
+    pub fn invalidate_url(&self,url : &Url) {
+        self.store.remove(&key(url));
+    }
+
+    /// Removes every response.
+    pub fn clear(&self) {
+        self.store.clear();
+    }
+
+    /// Returns how many responses are held, including expired ones not evicted yet.
+    pub fn len(&self) -> usize {
+        self.store.len()
+    }
+
+    /// Whether no response is held.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Looks up the response to the given URL of the given endpoint.
+    ///
+    /// # Arguments
+    ///
+    /// * `can_refresh` - Whether a background refresh can be spawned, to serve an expired response.
+    pub(crate) fn lookup(&self,endpoint : &str,url : &Url,can_refresh : bool) -> Lookup {
+        let key = key(url);
+        let Some(mut entry) = self.store.get(&key) else {
+            return Lookup::Missing
+        };
+
+        if entry.is_fresh() {
+            entry.response.meta.cache = Some(CacheStatus::Hit);
+            return Lookup::Fresh(Box::new(entry.response));
+        }
+
+        let window = self.stale_window(endpoint);
+        let servable = SystemTime::now().duration_since(entry.expires_at).is_ok_and(|expired_for| expired_for < window);
+        match servable && can_refresh {
+            true => {
+                entry.response.meta.cache = Some(CacheStatus::Stale);
+                let refresh = self.refreshing().insert(key);
+                Lookup::Stale { response : Box::new(entry.response), refresh }
+            },
+            false => Lookup::Expired {
+                etag : entry.response.meta.headers.get(ETAG).cloned(),
+                last_modified : entry.response.meta.headers.get(LAST_MODIFIED).cloned(),
+            }
+        }
+    }
+
+    /// Sends the request in a background task and stores its response, after [ResponseCache::lookup] returned a stale
+    /// response to refresh. The request is sent with the validators of the stale response.
+    pub(crate) fn refresh_in_background(&self,endpoint : &str,url : &Url,request : reqwest::RequestBuilder) {
+        let key = key(url);
+        let (client,request) = request.build_split();
+        let Ok(mut request) = request else {
+            self.refreshing().remove(&key);
+            return
+        };
+
+        if let Some(stale) = self.store.get(&key) {
+            for (validator,conditional) in [(ETAG,IF_NONE_MATCH),(LAST_MODIFIED,IF_MODIFIED_SINCE)] {
+                if let Some(value) = stale.response.meta.headers.get(validator) {
+                    request.headers_mut().insert(conditional,value.clone());
+                }
+            }
+        }
+
+        let (store,refreshing) = (Arc::clone(&self.store),Arc::clone(&self.refreshing));
+        let (endpoint,ttl) = (endpoint.trim_matches('/').to_owned(),self.ttl(endpoint));
+        tokio::spawn(async move {
+            let method = request.method().clone();
+            let response = match client.execute(request).await {
+                Ok(response) => {
+                    let meta = ResponseMeta::from_response(method,&response);
+                    response.bytes().await.map(|body| RawResponse { meta, body })
+                },
+                Err(error) => Err(error)
+            };
+
+            match response {
+                Ok(response) if response.meta.status == StatusCode::NOT_MODIFIED => {
+                    renew(store.as_ref(),&key,ttl,&response);
+                },
+                Ok(response) => store_response(store.as_ref(),&key,&endpoint,ttl,&response),
+                Err(error) => log::warn!("Failed to refresh the cached response to {key} : {error}")
+            }
+            refreshing.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).remove(&key);
+        });
+    }
+
+    /// Renews the response to the given URL after the server answered its revalidation with `304 Not Modified`, updating
+    /// its headers with those of the answer.
+    ///
+    /// # Returns
+    ///
+    /// The cached response, or `None` if it was evicted in the meantime.
+    pub(crate) fn revalidated(&self,endpoint : &str,url : &Url,not_modified : &RawResponse) -> Option<RawResponse> {
+        let mut response = renew(self.store.as_ref(),&key(url),self.ttl(endpoint),not_modified)?;
+        response.meta.cache = Some(CacheStatus::Revalidated);
+        Some(response)
+    }
+
+    /// Stores the response to the given URL of the given endpoint, if it is cacheable.
+    pub(crate) fn insert(&self,endpoint : &str,url : &Url,response : &RawResponse) {
+        store_response(self.store.as_ref(),&key(url),endpoint,self.ttl(endpoint),response);
+    }
+
+    fn refreshing(&self) -> MutexGuard<'_,HashSet<String>> {
+        self.refreshing.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Returns the setting of the most specific endpoint the given endpoint is below, see [ResponseCache::endpoint_ttl].
+fn most_specific(settings : &[(String,Duration)],endpoint : &str) -> Option<Duration> {
+    let endpoint = endpoint.trim_matches('/');
+    settings.iter()
+        .filter(|(prefix,_)| endpoint.strip_prefix(prefix.as_str()).is_some_and(|rest| prefix.is_empty() || rest.is_empty() || rest.starts_with('/')))
+        .max_by_key(|(prefix,_)| prefix.len())
+        .map(|(_,setting)| *setting)
+}
+
+/// Stores the response under the given key with the given TTL, if it is cacheable.
+fn store_response(store : &dyn CacheStore,key : &str,endpoint : &str,ttl : Duration,response : &RawResponse) {
+    let cacheable = response.meta.status.is_success() && !has_directive(&response.meta.headers,"no-store");
+    if !cacheable || (ttl.is_zero() && !has_validator(&response.meta.headers)) {
+        return;
+    }
+
+    let entry = CacheEntry { endpoint : endpoint.trim_matches('/').to_owned(), response : response.clone(), expires_at : SystemTime::now() + ttl };
+    store.insert(key,entry);
+}
+
+/// Renews the response stored under the given key for the given TTL, updating its headers with those of the `304 Not Modified` answer.
+fn renew(store : &dyn CacheStore,key : &str,ttl : Duration,not_modified : &RawResponse) -> Option<RawResponse> {
+    let mut entry = store.get(key)?;
+    for (name,value) in not_modified.meta.headers.iter().filter(|(name,_)| **name != CONTENT_LENGTH) {
+        entry.response.meta.headers.insert(name,value.clone());
+    }
+    entry.expires_at = SystemTime::now() + ttl;
+
+    let response = entry.response.clone();
+    store.insert(key,entry);
+    Some(response)
+}
+
+/// Holds the responses of a [ResponseCache], see [ResponseCache::with_store].
+///
+/// [MemoryStore] and [DiskStore] are provided, other backends such as Redis implement the trait on top of their client.
+/// Stores are best-effort : failing to read or write an entry loses it rather than failing the request, and they are free
+/// to evict entries at any time, preferably those that are expired and cannot be revalidated.
+pub trait CacheStore : Send + Sync {
+    /// Returns the entry stored under the given key, fresh or not.
+    fn get(&self,key : &str) -> Option<CacheEntry>;
+
+    /// Stores the entry under the given key, replacing any previous one.
+    fn insert(&self,key : &str,entry : CacheEntry);
+
+    /// Removes the entry stored under the given key.
+    fn remove(&self,key : &str);
+
+    /// Removes the entries for which the predicate returns `false`.
+    fn retain(&self,keep : &mut dyn FnMut(&CacheEntry) -> bool);
+
+    /// Removes every entry.
+    fn clear(&self);
+
+    /// Returns how many entries are stored.
+    fn len(&self) -> usize;
+
+    /// Whether no entry is stored.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// A response held by a [CacheStore].
+#[derive(Debug, Clone)]
+pub struct CacheEntry {
+    /// The endpoint of the response, relative to the [crate::RequestInfo::base_url].
+    pub endpoint : String,
+    /// The response.
+    pub response : RawResponse,
+    /// When the response expires and has to be revalidated.
+    pub expires_at : SystemTime,
+}
+
+impl CacheEntry {
+    /// Whether the response has not expired yet.
+    pub fn is_fresh(&self) -> bool {
+        self.expires_at > SystemTime::now()
+    }
+
+    /// Whether the response carries an `ETag` or a `Last-Modified` date it can be revalidated with once expired.
+    pub fn can_revalidate(&self) -> bool {
+        has_validator(&self.response.meta.headers)
+    }
+}
+
+/// Holds the responses of a [ResponseCache] in memory, the default store.
+#[derive(Debug)]
+pub struct MemoryStore {
+    max_entries : usize,
+    entries : Mutex<HashMap<String,CacheEntry>>,
+}
+
+impl MemoryStore {
+    /// Creates a store holding up to `max_entries` responses, expired ones that cannot be revalidated being evicted first,
+    /// then those closest to expiring.
+    pub fn new(max_entries : usize) -> Self {
+        Self { max_entries, entries : Mutex::new(HashMap::new()) }
+    }
+
+    fn entries(&self) -> MutexGuard<'_,HashMap<String,CacheEntry>> {
+        self.entries.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl CacheStore for MemoryStore {
+    fn get(&self,key : &str) -> Option<CacheEntry> {
+        self.entries().get(key).cloned()
+    }
+
+    fn insert(&self,key : &str,entry : CacheEntry) {
+        if self.max_entries == 0 {
+            return;
+        }
+
+        let mut entries = self.entries();
+        entries.remove(key);
+        entries.retain(|_,entry| entry.is_fresh() || entry.can_revalidate());
+        while entries.len() >= self.max_entries {
+            let Some(key) = entries.iter().min_by_key(|(_,entry)| entry.expires_at).map(|(key,_)| key.clone()) else {
+                break
+            };
+            entries.remove(&key);
+        }
+        entries.insert(key.to_owned(),entry);
+    }
+
+    fn remove(&self,key : &str) {
+        self.entries().remove(key);
+    }
+
+    fn retain(&self,keep : &mut dyn FnMut(&CacheEntry) -> bool) {
+        self.entries().retain(|_,entry| keep(entry));
+    }
+
+    fn clear(&self) {
+        self.entries().clear();
+    }
+
+    fn len(&self) -> usize {
+        self.entries().len()
+    }
+}
+
+/// Holds the responses of a [ResponseCache] as files in a directory, so command line tools get warm caches across runs.
+///
+/// ```rust ignore
+/// let directory = dirs::cache_dir().unwrap().join("my-cli");
+/// let cache = ResponseCache::with_store(Duration::from_secs(300),DiskStore::new(directory)?.max_bytes(16 * 1024 * 1024));
+/// ```
+///
+/// Each response is a file named after the hash of its key, written atomically so several processes can share the
+/// directory. Once the files exceed the size limit, the least recently stored ones are deleted.
+#[derive(Debug)]
+pub struct DiskStore {
+    directory : PathBuf,
+    max_bytes : u64,
+}
+
+/// The metadata and key of a response stored by a [DiskStore], on the first line of its file, followed by its body.
+#[derive(Serialize, Deserialize)]
+struct StoredEntry {
+    key : String,
+    endpoint : String,
+    expires_at_ms : u64,
+    method : String,
+    status : u16,
+    url : String,
+    version : String,
+    headers : Vec<(String,String)>,
+    correlation_id : Option<String>,
+}
+
+impl DiskStore {
+    /// Creates a store in the given directory, creating it if needed, holding up to 64 MiB of responses.
+    pub fn new(directory : impl Into<PathBuf>) -> io::Result<Self> {
+        let directory = directory.into();
+        fs::create_dir_all(&directory)?;
+        Ok(Self { directory, max_bytes : 64 * 1024 * 1024 })
+    }
+
+    /// Sets how many bytes the files of the responses may take at most, 64 MiB by default.
+    pub fn max_bytes(mut self,max_bytes : u64) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    fn path(&self,key : &str) -> PathBuf {
+        self.directory.join(format!("{}.cache",hash::hex(&hash::sha256(key.as_bytes()))))
+    }
+
+    /// Lists the files of the stored responses.
+    fn files(&self) -> Vec<(PathBuf,fs::Metadata)> {
+        let Ok(directory) = fs::read_dir(&self.directory) else {
+            return Vec::new()
+        };
+        directory.filter_map(Result::ok)
+            .map(|file| file.path())
+            .filter(|path| path.extension().is_some_and(|extension| extension == "cache"))
+            .filter_map(|path| fs::metadata(&path).ok().map(|metadata| (path,metadata)))
+            .collect()
+    }
+
+    fn read(path : &PathBuf) -> Option<(String,CacheEntry)> {
+        let bytes = fs::read(path).ok()?;
+        let newline = bytes.iter().position(|byte| *byte == b'\n')?;
+        let stored : StoredEntry = serde_json::from_slice(&bytes[..newline]).ok()?;
+
+        let mut headers = HeaderMap::new();
+        for (name,value) in &stored.headers {
+            headers.append(HeaderName::from_bytes(name.as_bytes()).ok()?,HeaderValue::from_str(value).ok()?);
+        }
+        let version = match stored.version.as_str() {
+            "HTTP/0.9" => Version::HTTP_09,
+            "HTTP/1.0" => Version::HTTP_10,
+            "HTTP/2.0" => Version::HTTP_2,
+            "HTTP/3.0" => Version::HTTP_3,
+            _ => Version::HTTP_11
+        };
+        let meta = ResponseMeta {
+            method : Method::from_bytes(stored.method.as_bytes()).ok()?,
+            status : StatusCode::from_u16(stored.status).ok()?,
+            headers,
+            url : Url::parse(&stored.url).ok()?,
+            version,
+            peer_certificate : None,
+            correlation_id : stored.correlation_id,
+            cache : None,
+        };
+        let entry = CacheEntry {
+            endpoint : stored.endpoint,
+            response : RawResponse { meta, body : Bytes::copy_from_slice(&bytes[newline + 1..]) },
+            expires_at : UNIX_EPOCH + Duration::from_millis(stored.expires_at_ms),
+        };
+        Some((stored.key,entry))
+    }
+
+    fn write(&self,key : &str,entry : &CacheEntry) -> io::Result<()> {
+        let meta = &entry.response.meta;
+        let stored = StoredEntry {
+            key : key.to_owned(),
+            endpoint : entry.endpoint.clone(),
+            expires_at_ms : entry.expires_at.duration_since(UNIX_EPOCH).map_or(0,|expires_at| u64::try_from(expires_at.as_millis()).unwrap_or(u64::MAX)),
+            method : meta.method.to_string(),
+            status : meta.status.as_u16(),
+            url : meta.url.to_string(),
+            version : format!("{:?}",meta.version),
+            headers : meta.headers.iter().filter_map(|(name,value)| value.to_str().ok().map(|value| (name.to_string(),value.to_owned()))).collect(),
+            correlation_id : meta.correlation_id.clone(),
+        };
+
+        let mut bytes = serde_json::to_vec(&stored)?;
+        bytes.push(b'\n');
+        bytes.extend_from_slice(&entry.response.body);
+
+        let path = self.path(key);
+        let temporary = path.with_extension(format!("{}.tmp",std::process::id()));
+        fs::write(&temporary,bytes)?;
+        fs::rename(&temporary,&path)
+    }
+
+    /// Deletes the least recently stored files until they fit in [DiskStore::max_bytes].
+    fn evict(&self) {
+        let mut files = self.files();
+        let mut size : u64 = files.iter().map(|(_,metadata)| metadata.len()).sum();
+        files.sort_by_key(|(_,metadata)| metadata.modified().unwrap_or(UNIX_EPOCH));
+        for (path,metadata) in files {
+            if size <= self.max_bytes {
+                break;
+            }
+            if fs::remove_file(&path).is_ok() {
+                size = size.saturating_sub(metadata.len());
+            }
+        }
+    }
+}
+
+impl CacheStore for DiskStore {
+    fn get(&self,key : &str) -> Option<CacheEntry> {
+        Self::read(&self.path(key)).filter(|(stored,_)| stored == key).map(|(_,entry)| entry)
+    }
+
+    fn insert(&self,key : &str,entry : CacheEntry) {
+        match self.write(key,&entry) {
+            Ok(()) => self.evict(),
+            Err(error) => log::warn!("Failed to store the cached response to {key} : {error}")
+        }
+    }
+
+    fn remove(&self,key : &str) {
+        let _ = fs::remove_file(self.path(key));
+    }
+
+    fn retain(&self,keep : &mut dyn FnMut(&CacheEntry) -> bool) {
+        for (path,_) in self.files() {
+            if Self::read(&path).is_none_or(|(_,entry)| !keep(&entry)) {
+                let _ = fs::remove_file(path);
+            }
+        }
+    }
+
+    fn clear(&self) {
+        for (path,_) in self.files() {
+            let _ = fs::remove_file(path);
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.files().len()
+    }
+}
+
+/// The response to a URL held by a [ResponseCache].
+pub(crate) enum Lookup {
+    /// No response is held.
+    Missing,
+    /// The held response has not expired yet.
+    Fresh(Box<RawResponse>),
+    /// The held response expired but is served while it is refreshed in the background, which is only needed if `refresh`
+    /// is `true`, another request refreshing it otherwise.
+    Stale {
+        response : Box<RawResponse>,
+        refresh : bool,
+    },
+    /// The held response expired, and can be revalidated if it carries a validator.
+    Expired {
+        etag : Option<HeaderValue>,
+        last_modified : Option<HeaderValue>,
+    },
+}
+
+/// Whether the headers carry an `ETag` or a `Last-Modified` date the response can be revalidated with.
+fn has_validator(headers : &HeaderMap) -> bool {
+    headers.contains_key(ETAG) || headers.contains_key(LAST_MODIFIED)
+}
+
+/// Whether the `Cache-Control` headers carry the given directive.
+pub(crate) fn has_directive(headers : &HeaderMap,directive : &str) -> bool {
+    headers.get_all(CACHE_CONTROL).iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|value| value.trim().eq_ignore_ascii_case(directive))
+}
+
+/// The key of the response to the URL, whose query parameters are sorted so their order does not matter.
+pub(crate) fn key(url : &Url) -> String {
+    let mut url = url.clone();
+    url.set_fragment(None);
+    let mut query : Vec<(String,String)> = url.query_pairs().map(|(key,value)| (key.into_owned(),value.into_owned())).collect();
+    if !query.is_empty() {
+        query.sort();
+        url.query_pairs_mut().clear().extend_pairs(query);
+    }
+    url.into()
+}
@@ -0,0 +1,72 @@
+use std::future::Future;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use tokio_util::sync::CancellationToken;
+
+/// Tracks in-flight requests and allows all of them to be aborted at once.
+///
+/// Every request sent through [crate::RequestHandler::request_map] while a canceller is returned by
+/// [crate::RequestDefaults::canceller] is tied to a shared [CancellationToken]. Calling [RequestCanceller::cancel_all]
+/// cancels that token, which drops the pending futures (and with them the underlying connections) and makes the
+/// requests fail with [crate::RequestError::Cancelled]. A fresh token is installed afterwards so later requests are unaffected.
+#[derive(Debug, Default)]
+pub struct RequestCanceller {
+    token : Mutex<CancellationToken>,
+    in_flight : AtomicUsize,
+}
+
+impl RequestCanceller {
+    /// Creates a new `RequestCanceller` with no in-flight requests.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of requests currently running under this canceller.
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::SeqCst)
+    }
+
+    /// Cancels all outstanding requests.
+    ///
+    /// # Returns
+    ///
+    /// The number of requests that were in flight when the cancellation happened.
+    pub fn cancel_all(&self) -> usize {
+        let token = std::mem::take(&mut *self.token.lock().unwrap_or_else(|poisoned| poisoned.into_inner()));
+        let cancelled = self.in_flight();
+        token.cancel();
+        cancelled
+    }
+
+    /// Runs the given future until it completes or the requests are cancelled.
+    ///
+    /// # Arguments
+    ///
+    /// * `future` - The future to run, usually the sending of a request and the reading of its body.
+    ///
+    /// # Returns
+    ///
+    /// `Some` with the output of the future, or `None` if it was cancelled before finishing.
+    pub async fn run<F : Future>(&self,future : F) -> Option<F::Output> {
+        let token = self.token.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clone();
+        let _guard = InFlightGuard::new(&self.in_flight);
+        token.run_until_cancelled(future).await
+    }
+}
+
+/// Keeps the in-flight counter accurate even when the request future is dropped midway.
+struct InFlightGuard<'a>(&'a AtomicUsize);
+
+impl<'a> InFlightGuard<'a> {
+    fn new(counter : &'a AtomicUsize) -> Self {
+        counter.fetch_add(1, Ordering::SeqCst);
+        Self(counter)
+    }
+}
+
+impl Drop for InFlightGuard<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use reqwest::Url;
+
+/// A circuit breaker stopping requests to an upstream that keeps failing.
+///
+/// After `failure_threshold` consecutive failures (connection errors, timeouts and `5xx` statuses), the circuit opens
+/// and every request fails fast with [crate::RequestError::CircuitOpen] for the `cooldown` duration. A single trial
+/// request is then let through : the circuit closes again if it succeeds and reopens for another cooldown otherwise.
+///
+/// Failures are tracked globally by default, or per endpoint with [CircuitBreaker::per_endpoint]. Return it from
+/// [crate::RequestDefaults::circuit_breaker] to apply it.
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    failure_threshold : u32,
+    cooldown : Duration,
+    per_endpoint : bool,
+    routes : Vec<String>,
+    circuits : Mutex<HashMap<String,Circuit>>,
+}
+
+/// The state of a circuit, see [CircuitBreaker].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Requests are sent normally.
+    Closed,
+    /// Requests fail fast until the cooldown elapses.
+    Open,
+    /// A trial request is in flight, other requests fail fast until it completes.
+    HalfOpen,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Circuit {
+    Closed { failures : u32 },
+    Open { until : Instant },
+    HalfOpen { until : Instant },
+}
+
+impl CircuitBreaker {
+    /// Creates a circuit breaker opening after `failure_threshold` consecutive failures for `cooldown`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `failure_threshold` is `0`.
+    pub fn new(failure_threshold : u32,cooldown : Duration) -> Self {
+        assert!(failure_threshold > 0,"a circuit breaker must allow at least one failure");
+        Self {
+            failure_threshold,
+            cooldown,
+            per_endpoint : false,
+            routes : Vec::new(),
+            circuits : Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Tracks failures separately for every endpoint (URL without its query) rather than for the whole API. Disabled by default.
+    ///
+    /// Only the endpoints whose circuit is not closed are remembered.
+    pub fn per_endpoint(mut self,enabled : bool) -> Self {
+        self.per_endpoint = enabled;
+        self
+    }
+
+    /// Tracks the failures of the endpoints matching a path template, such as `users/{id}`, together when
+    /// [CircuitBreaker::per_endpoint] is enabled, instead of one circuit per user. The `{name}` placeholders match any
+    /// segment, and the template matches the paths ending with its segments, so it is relative to the base URL.
+    pub fn route(mut self,template : impl Into<String>) -> Self {
+        self.routes.push(template.into());
+        self
+    }
+
+    /// Returns the state of the circuit the given URL belongs to.
+    pub fn state(&self,url : &Url) -> CircuitState {
+        match self.circuits().get(&self.key(url)) {
+            None | Some(Circuit::Closed { .. }) => CircuitState::Closed,
+            Some(Circuit::Open { .. }) => CircuitState::Open,
+            Some(Circuit::HalfOpen { .. }) => CircuitState::HalfOpen,
+        }
+    }
+
+    /// Closes every circuit and forgets the failures recorded so far, for example once the upstream is known to be back.
+    pub fn reset(&self) {
+        self.circuits().clear();
+    }
+
+    /// Checks whether a request to the given URL may be sent, turning an open circuit half-open once its cooldown elapsed.
+    ///
+    /// # Returns
+    ///
+    /// `Ok` if the request may be sent, or how long until the next trial request otherwise.
+    pub(crate) fn acquire(&self,url : &Url) -> Result<(),Duration> {
+        let now = Instant::now();
+        let mut circuits = self.circuits();
+        let Some(circuit) = circuits.get_mut(&self.key(url)) else {
+            return Ok(())
+        };
+
+        match *circuit {
+            Circuit::Closed { .. } => Ok(()),
+            Circuit::Open { until } | Circuit::HalfOpen { until } if now < until => Err(until - now),
+            // The cooldown elapsed, or the previous trial never reported back
+            Circuit::Open { .. } | Circuit::HalfOpen { .. } => {
+                *circuit = Circuit::HalfOpen { until : now + self.cooldown };
+                Ok(())
+            }
+        }
+    }
+
+    /// Records the outcome of a request sent to the given URL.
+    pub(crate) fn record(&self,url : &Url,success : bool) {
+        let mut circuits = self.circuits();
+        let key = self.key(url);
+        // Healthy circuits are forgotten, so the endpoints seen do not pile up
+        if success {
+            circuits.remove(&key);
+            return
+        }
+        let circuit = circuits.entry(key).or_insert(Circuit::Closed { failures : 0 });
+
+        *circuit = match *circuit {
+            Circuit::Closed { failures } if failures + 1 < self.failure_threshold => Circuit::Closed { failures : failures + 1 },
+            _ => Circuit::Open { until : Instant::now() + self.cooldown },
+        };
+    }
+
+    fn key(&self,url : &Url) -> String {
+        match self.per_endpoint {
+            true => crate::endpoint::endpoint_key(url,&self.routes),
+            false => String::new()
+        }
+    }
+
+    fn circuits(&self) -> std::sync::MutexGuard<'_,HashMap<String,Circuit>> {
+        self.circuits.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
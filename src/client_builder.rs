@@ -0,0 +1,243 @@
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use reqwest::{Client, ClientBuilder};
+
+#[cfg(feature = "mtls")]
+use crate::{ClientBuilderExt, TlsConfig};
+use crate::{ProxyConfig, DEFAULT_TIMEOUT, DEFAULT_USER_AGENT};
+
+/// The client returned by [shared_client], built once per process.
+static SHARED_CLIENT : OnceLock<Client> = OnceLock::new();
+
+/// Returns the client shared by the whole process, which [crate::RequestInfo::client] returns by default, so API clients
+/// sending their requests with it need no field holding a [reqwest::Client].
+///
+/// It is built on first use with the settings given to [ApiClientBuilder::build_shared], or with the default ones of
+/// [ApiClientBuilder] if it was not called before.
+///
+/// ```
+/// use api_request_utils::{shared_client, ApiClientBuilder};
+///
+/// let client = ApiClientBuilder::new().application("weather-dashboard","2.1.0").build_shared().unwrap();
+/// assert!(std::ptr::eq(client,shared_client()));
+/// ```
+///
+/// # Panics
+///
+/// Panics if the client is built with the default settings and the TLS backend cannot be initialized, like `Client::new()`.
+pub fn shared_client() -> &'static Client {
+    SHARED_CLIENT.get_or_init(|| ApiClientBuilder::new().build().expect("the shared client should build"))
+}
+
+/// Builds the [reqwest::Client] of an API client with production settings, rather than the bare `Client::new()`.
+///
+/// By default, clients send a `User-Agent` made of the application, when given, and of [DEFAULT_USER_AGENT], time out
+/// after [DEFAULT_TIMEOUT], give up connecting after 10 seconds, and keep their TCP connections alive with probes every
+/// 60 seconds, so idle pooled connections are not silently dropped by load balancers :
+///
+/// ```
+/// use std::time::Duration;
+/// use api_request_utils::ApiClientBuilder;
+///
+/// let builder = ApiClientBuilder::new()
+///     .application("weather-dashboard","2.1.0")
+///     .pool_max_idle_per_host(16)
+///     .http2_keep_alive_interval(Duration::from_secs(20));
+/// assert_eq!(builder.user_agent_value(),concat!("weather-dashboard/2.1.0 api-request-utils-rs/",env!("CARGO_PKG_VERSION")));
+///
+/// let client = builder.build().unwrap();
+/// ```
+///
+/// Build the client once and share it, as [crate::RequestInfo::client] returns it for every request. Not available on
+/// `wasm32`, where the browser manages the connections, see [crate::RequestInfo::default_client_builder] instead.
+#[derive(Debug, Clone)]
+pub struct ApiClientBuilder {
+    application : Option<String>,
+    user_agent : Option<String>,
+    timeout : Option<Duration>,
+    connect_timeout : Option<Duration>,
+    tcp_keepalive : Option<Duration>,
+    pool_max_idle_per_host : Option<usize>,
+    pool_idle_timeout : Option<Duration>,
+    http2_prior_knowledge : bool,
+    http2_keep_alive_interval : Option<Duration>,
+    http2_adaptive_window : bool,
+    proxy : Option<ProxyConfig>,
+    #[cfg(feature = "mtls")]
+    tls : Option<TlsConfig>,
+}
+
+impl Default for ApiClientBuilder {
+    fn default() -> Self {
+        Self {
+            application : None,
+            user_agent : None,
+            timeout : Some(DEFAULT_TIMEOUT),
+            connect_timeout : Some(Duration::from_secs(10)),
+            tcp_keepalive : Some(Duration::from_secs(60)),
+            pool_max_idle_per_host : None,
+            pool_idle_timeout : None,
+            http2_prior_knowledge : false,
+            http2_keep_alive_interval : None,
+            http2_adaptive_window : false,
+            proxy : None,
+            #[cfg(feature = "mtls")]
+            tls : None,
+        }
+    }
+}
+
+impl ApiClientBuilder {
+    /// Creates a builder with the default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Prefixes the `User-Agent` with the name and version of the application, such as `my-app/1.0.0`, as many API
+    /// providers ask to identify who is calling.
+    pub fn application(mut self,name : &str,version : &str) -> Self {
+        self.application = Some(format!("{name}/{version}"));
+        self
+    }
+
+    /// Replaces the whole `User-Agent`, including the application and the crate version.
+    pub fn user_agent(mut self,user_agent : impl Into<String>) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// Sets the total timeout of every request, or disables it with `None`.
+    pub fn timeout(mut self,timeout : Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sets the timeout of establishing connections, or disables it with `None`.
+    pub fn connect_timeout(mut self,timeout : Option<Duration>) -> Self {
+        self.connect_timeout = timeout;
+        self
+    }
+
+    /// Sets the interval of the TCP keepalive probes of the connections, or disables them with `None`.
+    pub fn tcp_keepalive(mut self,interval : Option<Duration>) -> Self {
+        self.tcp_keepalive = interval;
+        self
+    }
+
+    /// Sets how many idle connections are kept open per host, unlimited by default.
+    pub fn pool_max_idle_per_host(mut self,max : usize) -> Self {
+        self.pool_max_idle_per_host = Some(max);
+        self
+    }
+
+    /// Sets how long idle connections are kept open, 90 seconds by default.
+    pub fn pool_idle_timeout(mut self,timeout : Duration) -> Self {
+        self.pool_idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Speaks HTTP/2 right away rather than negotiating it, for APIs known to support it, such as gRPC gateways.
+    pub fn http2_prior_knowledge(mut self) -> Self {
+        self.http2_prior_knowledge = true;
+        self
+    }
+
+    /// Sends HTTP/2 pings at the given interval, detecting dead connections even while requests are in flight.
+    pub fn http2_keep_alive_interval(mut self,interval : Duration) -> Self {
+        self.http2_keep_alive_interval = Some(interval);
+        self
+    }
+
+    /// Sizes the HTTP/2 flow control windows from the measured bandwidth, speeding up large downloads.
+    pub fn http2_adaptive_window(mut self,enabled : bool) -> Self {
+        self.http2_adaptive_window = enabled;
+        self
+    }
+
+    /// Sends the requests through the given proxy.
+    pub fn proxy(mut self,proxy : ProxyConfig) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Applies the given TLS settings, such as a client certificate.
+    ///
+    /// Requires the `mtls` feature.
+    #[cfg(feature = "mtls")]
+    pub fn tls(mut self,tls : TlsConfig) -> Self {
+        self.tls = Some(tls);
+        self
+    }
+
+    /// Returns the `User-Agent` the clients send.
+    pub fn user_agent_value(&self) -> String {
+        match (&self.user_agent,&self.application) {
+            (Some(user_agent),_) => user_agent.clone(),
+            (None,Some(application)) => format!("{application} {DEFAULT_USER_AGENT}"),
+            (None,None) => DEFAULT_USER_AGENT.to_owned()
+        }
+    }
+
+    /// Creates a `ClientBuilder` with the settings, to be customised further.
+    ///
+    /// # Returns
+    ///
+    /// The builder, or an error if the proxy or the TLS settings are invalid.
+    pub fn client_builder(&self) -> reqwest::Result<ClientBuilder> {
+        let mut builder = Client::builder()
+            .user_agent(self.user_agent_value())
+            .tcp_keepalive(self.tcp_keepalive)
+            .http2_adaptive_window(self.http2_adaptive_window);
+
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if let Some(max) = self.pool_max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max);
+        }
+        if let Some(timeout) = self.pool_idle_timeout {
+            builder = builder.pool_idle_timeout(timeout);
+        }
+        if self.http2_prior_knowledge {
+            builder = builder.http2_prior_knowledge();
+        }
+        if let Some(interval) = self.http2_keep_alive_interval {
+            builder = builder.http2_keep_alive_interval(interval).http2_keep_alive_while_idle(true);
+        }
+        if let Some(proxy) = &self.proxy {
+            builder = proxy.apply(builder)?;
+        }
+        #[cfg(feature = "mtls")]
+        if let Some(tls) = &self.tls {
+            builder = builder.tls_config(tls)?;
+        }
+        Ok(builder)
+    }
+
+    /// Builds the client.
+    ///
+    /// # Returns
+    ///
+    /// The client, or an error if the proxy or the TLS settings are invalid, or if the TLS backend cannot be initialized.
+    pub fn build(&self) -> reqwest::Result<Client> {
+        self.client_builder()?.build()
+    }
+
+    /// Builds the client and makes it the [shared_client], to be called once at startup, before any request is sent.
+    ///
+    /// # Returns
+    ///
+    /// The shared client, or an error if it cannot be built. If the shared client was already built, it is returned as it
+    /// is, without these settings.
+    pub fn build_shared(&self) -> reqwest::Result<&'static Client> {
+        if let Some(client) = SHARED_CLIENT.get() {
+            return Ok(client);
+        }
+        let client = self.build()?;
+        Ok(SHARED_CLIENT.get_or_init(|| client))
+    }
+}
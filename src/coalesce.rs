@@ -0,0 +1,95 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+
+use tokio::sync::watch;
+
+use crate::RawResponse;
+
+/// Coalesces identical `GET` and `HEAD` requests sent concurrently into a single exchange whose response is shared, so
+/// many tasks looking up the same resource at once only cost one network call.
+///
+/// Requests are identical when they have the same method and URL, the parameters of the query string being compared in
+/// any order. The first one is sent, and the others wait for its response instead of being sent. What is shared is the
+/// [RawResponse], before deserialization, so every caller deserializes and maps its own copy and the output type needs not
+/// be `Clone`. Errors cannot be cloned, so if the shared request fails or is cancelled, the waiting requests are sent
+/// on their own.
+///
+/// As the key does not include the headers, a client sending requests on behalf of several users must use a coalescer
+/// per user. Return it from [crate::RequestDefaults::request_coalescer] to apply it. Not available on `wasm32`.
+#[derive(Debug, Default)]
+pub struct RequestCoalescer {
+    in_flight : Mutex<HashMap<String,watch::Receiver<Option<Option<RawResponse>>>>>,
+}
+
+/// The part a request plays in a [RequestCoalescer].
+pub(crate) enum Flight<'a> {
+    /// The request is sent, and its response shared through [InFlight::finish].
+    Leader(InFlight<'a>),
+    /// An identical request is in flight, whose response is awaited with [RequestCoalescer::wait].
+    Follower(watch::Receiver<Option<Option<RawResponse>>>),
+}
+
+/// An exchange shared by a [RequestCoalescer]. Dropping it without [InFlight::finish] lets the waiting requests be sent on their own.
+pub(crate) struct InFlight<'a> {
+    coalescer : &'a RequestCoalescer,
+    key : Option<String>,
+    sender : watch::Sender<Option<Option<RawResponse>>>,
+}
+
+impl RequestCoalescer {
+    /// Creates a coalescer with no request in flight.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns how many distinct requests are in flight.
+    pub fn in_flight(&self) -> usize {
+        self.requests().len()
+    }
+
+    /// Joins the exchange of the request with the given key, leading it if none is in flight.
+    pub(crate) fn join(&self,key : String) -> Flight<'_> {
+        let mut requests = self.requests();
+        if let Some(receiver) = requests.get(&key) {
+            return Flight::Follower(receiver.clone());
+        }
+
+        let (sender,receiver) = watch::channel(None);
+        requests.insert(key.clone(),receiver);
+        Flight::Leader(InFlight { coalescer : self, key : Some(key), sender })
+    }
+
+    /// Waits for the response shared by the leader of an exchange.
+    ///
+    /// # Returns
+    ///
+    /// The response, or `None` if the leader failed or was cancelled.
+    pub(crate) async fn wait(mut receiver : watch::Receiver<Option<Option<RawResponse>>>) -> Option<RawResponse> {
+        receiver.wait_for(Option::is_some).await.ok()?.clone().flatten()
+    }
+
+    fn requests(&self) -> MutexGuard<'_,HashMap<String,watch::Receiver<Option<Option<RawResponse>>>>> {
+        self.in_flight.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl InFlight<'_> {
+    /// Shares the response of the exchange with the waiting requests, `None` meaning it failed.
+    pub(crate) fn finish(mut self,response : Option<&RawResponse>) {
+        self.leave();
+        self.sender.send_replace(Some(response.cloned()));
+    }
+
+    /// Removes the exchange from the requests in flight, so identical requests sent from now on lead a new one.
+    fn leave(&mut self) {
+        if let Some(key) = self.key.take() {
+            self.coalescer.requests().remove(&key);
+        }
+    }
+}
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        self.leave();
+    }
+}
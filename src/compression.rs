@@ -0,0 +1,115 @@
+#[cfg(any(feature = "gzip",feature = "deflate"))]
+use std::io::Write;
+
+#[cfg(any(feature = "gzip",feature = "deflate"))]
+use flate2::Compression;
+
+/// A content coding request bodies are compressed with, see [BodyCompression].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentCoding {
+    /// `gzip`, accepted by most APIs taking compressed bodies. Requires the `gzip` feature.
+    #[cfg(feature = "gzip")]
+    Gzip,
+    /// `deflate`, a zlib stream. Requires the `deflate` feature.
+    #[cfg(feature = "deflate")]
+    Deflate,
+    /// `zstd`, usually faster than `gzip` for a better ratio, accepted by fewer APIs. Requires the `zstd` feature and is not available on `wasm32`.
+    #[cfg(all(feature = "zstd",not(target_arch = "wasm32")))]
+    Zstd,
+}
+
+impl ContentCoding {
+    /// Returns the value of the `Content-Encoding` header of the coding.
+    pub fn name(self) -> &'static str {
+        match self {
+            #[cfg(feature = "gzip")]
+            Self::Gzip => "gzip",
+            #[cfg(feature = "deflate")]
+            Self::Deflate => "deflate",
+            #[cfg(all(feature = "zstd",not(target_arch = "wasm32")))]
+            Self::Zstd => "zstd",
+        }
+    }
+}
+
+/// Compression of the typed request bodies, such as the large JSON batches of bulk-ingest APIs.
+///
+/// Bodies of at least the threshold, 1 KiB by default, are compressed and sent with a `Content-Encoding` header, while
+/// smaller ones are sent as they are, compressing them rarely paying off. Only enable it for APIs documenting support
+/// for compressed bodies, as the others usually answer `415 Unsupported Media Type`.
+///
+/// ```rust ignore
+/// impl RequestDefaults for MyAPIClient {
+///     fn body_compression(&self) -> Option<BodyCompression> {
+///         Some(BodyCompression::new(ContentCoding::Gzip).threshold(16 * 1024))
+///     }
+/// }
+/// ```
+///
+/// Applies to the bodies of [crate::RequestHandler::default_body_requestor], [crate::RequestDefaults::default_post_requestor]
+/// [crate::RequestDefaults::default_put_requestor], [crate::RequestDefaults::default_patch_requestor] and
+/// [crate::RequestDefaults::default_graphql_requestor]. Requires the `gzip`, `deflate` or `zstd` feature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BodyCompression {
+    coding : ContentCoding,
+    threshold : usize,
+    level : u32,
+}
+
+impl BodyCompression {
+    /// Creates a compression with the given coding, the default level and a threshold of 1 KiB.
+    pub fn new(coding : ContentCoding) -> Self {
+        Self { coding, threshold : 1024, level : 6 }
+    }
+
+    /// Sets the size in bytes under which bodies are sent uncompressed.
+    pub fn threshold(mut self,threshold : usize) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// Sets the compression level, from 0 (none) to 9 (smallest), 6 by default.
+    ///
+    /// `zstd` uses it as its own level, 0 standing for its default one.
+    pub fn level(mut self,level : u32) -> Self {
+        self.level = level.min(9);
+        self
+    }
+
+    /// Returns the coding of the compression.
+    pub fn coding(&self) -> ContentCoding {
+        self.coding
+    }
+
+    /// Compresses the body if it reaches the threshold.
+    ///
+    /// # Returns
+    ///
+    /// The compressed body along with its content coding, or the body itself and `None` if it is under the threshold.
+    // Without any coding available, as with only `zstd` on `wasm32`, no compression can be created
+    #[cfg_attr(all(target_arch = "wasm32",not(any(feature = "gzip",feature = "deflate"))),allow(unreachable_code,unused_variables))]
+    pub fn compress(&self,body : Vec<u8>) -> (Vec<u8>,Option<ContentCoding>) {
+        if body.len() < self.threshold {
+            return (body,None);
+        }
+        // Writing to a `Vec` does not fail, the body would be sent uncompressed if it ever did
+        let compressed : std::io::Result<Vec<u8>> = match self.coding {
+            #[cfg(feature = "gzip")]
+            ContentCoding::Gzip => {
+                let mut encoder = flate2::write::GzEncoder::new(Vec::new(),Compression::new(self.level));
+                encoder.write_all(&body).and_then(|_| encoder.finish())
+            },
+            #[cfg(feature = "deflate")]
+            ContentCoding::Deflate => {
+                let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(),Compression::new(self.level));
+                encoder.write_all(&body).and_then(|_| encoder.finish())
+            },
+            #[cfg(all(feature = "zstd",not(target_arch = "wasm32")))]
+            ContentCoding::Zstd => zstd::encode_all(body.as_slice(),self.level as i32)
+        };
+        match compressed {
+            Ok(compressed) => (compressed,Some(self.coding)),
+            Err(_) => (body,None)
+        }
+    }
+}
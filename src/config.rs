@@ -0,0 +1,51 @@
+use std::time::Duration;
+
+use reqwest::RequestBuilder;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+
+/// Request defaults stored as data rather than trait overrides.
+///
+/// Return it from [crate::RequestDefaults::config] and it is applied to every request built by the default requestors,
+/// on top of [crate::RequestDefaults::default_headers]. Derived clients with different settings can then be created cheaply
+/// through [crate::RequestDefaults::with_overrides].
+#[derive(Debug, Clone, Default)]
+pub struct ClientConfig {
+    /// The timeout applied to every request, `None` to rely on the timeout of the [reqwest::Client]. Ignored on `wasm32`.
+    pub timeout : Option<Duration>,
+    /// Headers added to every request.
+    pub headers : HeaderMap,
+}
+
+impl ClientConfig {
+    /// Creates an empty configuration.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the timeout applied to every request.
+    pub fn timeout(mut self,timeout : Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Adds a header sent with every request, replacing any previous value for the same name.
+    pub fn header(mut self,name : HeaderName,value : HeaderValue) -> Self {
+        self.headers.insert(name,value);
+        self
+    }
+
+    /// Applies the configuration to the given `RequestBuilder`.
+    pub fn apply(&self,request_builder : RequestBuilder) -> RequestBuilder {
+        let request_builder = request_builder.headers(self.headers.clone());
+
+        // Browsers do not support per-request timeouts, see `reqwest::RequestBuilder::timeout`.
+        #[cfg(target_arch = "wasm32")]
+        return request_builder;
+
+        #[cfg(not(target_arch = "wasm32"))]
+        match self.timeout {
+            Some(timeout) => request_builder.timeout(timeout),
+            None => request_builder
+        }
+    }
+}
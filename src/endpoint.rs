@@ -0,0 +1,106 @@
+use reqwest::Method;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::RequestError;
+
+/// A route of an API, binding its method and path to the types of its parameters, response and error payload.
+///
+/// The endpoint value carries the parameters : the fields named in the path template, such as `{id}` in
+/// `users/{id}/repos`, are substituted, percent-encoded, into the path, and the others are sent as the query of `GET`,
+/// `HEAD`, `DELETE` and `OPTIONS` requests, or as the JSON body of the other methods. Fields set to `None` are left out.
+///
+/// ```rust ignore
+/// #[derive(Serialize)]
+/// struct ListRepositories<'a> { user : &'a str, page : Option<u32> }
+///
+/// impl Endpoint for ListRepositories<'_> {
+///     type Response = Vec<Repository>;
+///     type Error = ApiError;
+///     const PATH : &'static str = "users/{user}/repos";
+/// }
+///
+/// // GET users/o%27neil/repos?page=2
+/// let repositories = api_client.request_endpoint(&ListRepositories { user : "o'neil", page : Some(2) },|repositories| repositories).await?;
+/// ```
+///
+/// Sent with [crate::RequestHandler::request_endpoint], which only compiles for a client with a [crate::RequestHandler]
+/// for the response and error types of the endpoint.
+pub trait Endpoint : Serialize {
+    /// The type the successful responses are deserialized into.
+    type Response : DeserializeOwned;
+    /// The type the error payloads are deserialized into.
+    type Error : DeserializeOwned;
+
+    /// The method of the requests, `GET` by default.
+    const METHOD : Method = Method::GET;
+
+    /// The path of the endpoint relative to the [crate::RequestInfo::base_url], whose `{name}` placeholders are replaced
+    /// with the field of the same name.
+    const PATH : &'static str;
+}
+
+/// Returns whether the parameters of the endpoint left out of its path are sent in the query rather than in the body.
+pub(crate) fn is_query_method<P : Endpoint + ?Sized>() -> bool {
+    [Method::GET,Method::HEAD,Method::DELETE,Method::OPTIONS].contains(&P::METHOD)
+}
+
+/// Substitutes the parameters of the endpoint into its path template, returning the path and the remaining parameters.
+///
+/// Fails with [RequestError::InvalidPathParameter] if a placeholder has no matching parameter, or one that is not a
+/// non-empty string, a number or a boolean.
+pub(crate) fn resolve<P : Endpoint + ?Sized,E>(endpoint : &P) -> Result<(String,Map<String,Value>),RequestError<E>> {
+    let mut parameters = match serde_json::to_value(endpoint).map_err(|error| RequestError::InvalidRequestBody(Box::new(error)))? {
+        Value::Object(parameters) => parameters,
+        // Unit structs
+        Value::Null => Map::new(),
+        _ => return Err(RequestError::InvalidRequestBody("an endpoint must serialize to a map of its parameters".into()))
+    };
+
+    let mut path = String::with_capacity(P::PATH.len());
+    let mut template = P::PATH;
+    while let Some((start,end)) = template.find('{').and_then(|start| Some((start,start + template[start..].find('}')?))) {
+        path.push_str(&template[..start]);
+        let name = &template[start + 1..end];
+        let value = match parameters.remove(name) {
+            Some(Value::String(value)) if !value.is_empty() => value,
+            Some(value @ (Value::Number(_) | Value::Bool(_))) => value.to_string(),
+            _ => return Err(RequestError::InvalidPathParameter { name : name.to_owned() })
+        };
+        path.push_str(&uri_encode(&value));
+        template = &template[end + 1..];
+    }
+    path.push_str(template);
+
+    parameters.retain(|_,value| !value.is_null());
+    Ok((path,parameters))
+}
+
+/// Returns the key under which the per-endpoint state of [crate::RateLimiter] and [crate::CircuitBreaker] is kept : the
+/// origin of the URL followed by the first of `templates` matching its path, or by its whole path otherwise.
+///
+/// A template such as `users/{id}/repos` matches the paths ending with its segments, whose `{name}` placeholders match
+/// any segment, so the URLs of every user share one key.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn endpoint_key(url : &reqwest::Url,templates : &[String]) -> String {
+    let segments : Vec<&str> = url.path().split('/').filter(|segment| !segment.is_empty()).collect();
+    let matches = |template : &&String| {
+        let template : Vec<&str> = template.split('/').filter(|segment| !segment.is_empty()).collect();
+        template.len() <= segments.len() && template.iter().zip(&segments[segments.len() - template.len()..])
+            .all(|(expected,segment)| (expected.starts_with('{') && expected.ends_with('}')) || expected == segment)
+    };
+    match templates.iter().find(matches) {
+        Some(template) => format!("{}/{}",url.origin().ascii_serialization(),template.trim_start_matches('/')),
+        None => format!("{}{}",url.origin().ascii_serialization(),url.path())
+    }
+}
+
+/// Percent-encodes everything but the unreserved characters of RFC 3986, as path segments and signature schemes require.
+pub(crate) fn uri_encode(value : &str) -> String {
+    value.bytes().map(|byte| match byte {
+        b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => char::from(byte).to_string(),
+        byte => format!("%{byte:02X}")
+    }).collect()
+}
@@ -0,0 +1,87 @@
+use bytes::Bytes;
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::{RawResponse, RequestError};
+
+/// Describes the envelope an API wraps every payload in, such as `{ "data" : ..., "error" : ..., "meta" : ... }`.
+///
+/// With an envelope, the handlers deserializing the body into `T` deserialize the `data` field instead of the whole body,
+/// and a present `error` field fails with [crate::RequestError::ErrorPayload] whatever the status code, as such APIs often
+/// report errors with `200 OK`. Bodies which are not a JSON object are handled as usual.
+///
+/// These handlers are `request_map` and its variants (`request_map_with_timeout`, `request_map_with_limit`,
+/// `request_map_cancellable`, `request_map_both`, `request_map_with_meta`, `request_map_status`, `request_map_seeded`,
+/// `request_map_with` and the per-format ones such as `request_map_xml`), the handlers built on them such as `get_request_handler`,
+/// `request_scoped`, `poll_until` and the streams of [crate::PaginatedRequestHandler], as well as `graphql_request_map`,
+/// `jsonapi_request_map` and `request_envelope`, which read the envelope of their protocol inside the `data` field. The
+/// others leave the body untouched : `request_raw`, `request_bytes`, `request_text`, `request_map_no_content` and `login`
+/// return it as is, `request_csv` and `request_protobuf` read their own format, while [crate::JsonRpcHandler] reads the
+/// envelope of its own protocol. `BlockingRequestHandler` has no envelope.
+///
+/// Return it from [crate::RequestDefaults::envelope] to apply it.
+#[derive(Debug, Clone)]
+pub struct Envelope {
+    data_field : String,
+    error_field : String,
+}
+
+impl Default for Envelope {
+    fn default() -> Self {
+        Self {
+            data_field : "data".to_owned(),
+            error_field : "error".to_owned(),
+        }
+    }
+}
+
+impl Envelope {
+    /// Creates an envelope with a `data` and an `error` field.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the name of the field holding the payload of successful responses.
+    pub fn data_field(mut self,field : impl Into<String>) -> Self {
+        self.data_field = field.into();
+        self
+    }
+
+    /// Sets the name of the field holding the error payload, which is ignored when `null`.
+    pub fn error_field(mut self,field : impl Into<String>) -> Self {
+        self.error_field = field.into();
+        self
+    }
+
+    /// Replaces the body of the response by its `data` field, or fails with the error payload from its `error` field.
+    ///
+    /// A missing `data` field leaves an empty body, which deserializes as `null`.
+    pub(crate) fn unwrap<E : DeserializeOwned>(&self,response : RawResponse) -> Result<RawResponse,RequestError<E>> {
+        let Ok(Value::Object(mut body)) = serde_json::from_slice::<Value>(&response.body) else {
+            return Ok(response);
+        };
+
+        if let Some(error) = body.remove(&self.error_field).filter(|error| !error.is_null()) {
+            return Err(match serde_json::from_value(error) {
+                Ok(payload) => RequestError::ErrorPayload { payload, context : Box::new(response.meta.context()) },
+                Err(source) => RequestError::ErrorPayloadParseFailed {
+                    status : response.meta.status,
+                    source,
+                    body : String::from_utf8_lossy(&response.body).into_owned(),
+                }
+            });
+        }
+
+        match response.meta.status.is_success() {
+            true => {
+                let body = match body.remove(&self.data_field) {
+                    Some(data) => Bytes::from(serde_json::to_vec(&data)?),
+                    None => Bytes::new()
+                };
+                Ok(RawResponse { meta : response.meta, body })
+            }
+            false => Ok(response)
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use reqwest::header::{HeaderName, HeaderValue};
+
+use thiserror::Error as ErrorMacro;
+
+use crate::{ApiKeyAuth, ApiKeyLocation, ClientConfig};
+
+/// A named deployment of an API, such as `sandbox` or `production`, bundling its base URL, credentials and default headers.
+///
+/// Return it from [crate::RequestInfo::environment] to switch a client between deployments at runtime rather than by
+/// changing [crate::RequestInfo::BASE_URL] : unless they are overridden, [crate::RequestInfo::base_url],
+/// [crate::RequestDefaults::config] and [crate::RequestDefaults::api_key_auth] then return those of the environment.
+///
+/// ```
+/// use api_request_utils::{ApiEnvironment, ApiKeyLocation};
+///
+/// let environments = [
+///     ApiEnvironment::new("production","https://api.example.com/v1").api_key_from_env("EXAMPLE_API_KEY",ApiKeyLocation::Query("key".to_owned())),
+///     ApiEnvironment::new("sandbox","https://sandbox.example.com/v1").api_key("test-key",ApiKeyLocation::Query("key".to_owned())),
+/// ];
+/// // EXAMPLE_API_ENV=sandbox selects the sandbox, the first environment being the default
+/// let environment = ApiEnvironment::from_env_var("EXAMPLE_API_ENV",environments.clone()).unwrap();
+/// assert_eq!(environment.name(),"production");
+///
+/// let sandbox = ApiEnvironment::select("sandbox",environments.clone()).unwrap();
+/// assert_eq!(sandbox.base_url(),"https://sandbox.example.com/v1");
+/// assert!(ApiEnvironment::select("staging",environments).is_err());
+/// ```
+#[derive(Clone)]
+pub struct ApiEnvironment {
+    name : String,
+    base_url : String,
+    config : ClientConfig,
+    api_key : Option<EnvironmentKey>,
+}
+
+// The API key is left out
+impl std::fmt::Debug for ApiEnvironment {
+    fn fmt(&self,f : &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ApiEnvironment")
+            .field("name",&self.name)
+            .field("base_url",&self.base_url)
+            .field("config",&self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Clone)]
+struct EnvironmentKey {
+    key : String,
+    location : ApiKeyLocation,
+}
+
+impl ApiKeyAuth for EnvironmentKey {
+    fn api_key(&self) -> &str {
+        &self.key
+    }
+
+    fn api_key_location(&self) -> ApiKeyLocation {
+        self.location.clone()
+    }
+}
+
+/// Error indicating no [ApiEnvironment] has the requested name, see [ApiEnvironment::select].
+#[derive(Debug, Clone, PartialEq, Eq, ErrorMacro)]
+#[error("Unknown API environment {name}, expected one of {}",.available.join(", "))]
+pub struct UnknownEnvironment {
+    /// The requested name.
+    pub name : String,
+    /// The names of the environments to choose from.
+    pub available : Vec<String>,
+}
+
+impl ApiEnvironment {
+    /// Creates an environment without credentials or default headers.
+    pub fn new(name : impl Into<String>,base_url : impl Into<String>) -> Self {
+        Self { name : name.into(), base_url : base_url.into(), config : ClientConfig::new(), api_key : None }
+    }
+
+    /// Adds a header sent with every request, replacing any previous value for the same name.
+    pub fn header(mut self,name : HeaderName,value : HeaderValue) -> Self {
+        self.config = self.config.header(name,value);
+        self
+    }
+
+    /// Authenticates requests with the given API key, sent where `location` tells.
+    pub fn api_key(mut self,key : impl Into<String>,location : ApiKeyLocation) -> Self {
+        self.api_key = Some(EnvironmentKey { key : key.into(), location });
+        self
+    }
+
+    /// Authenticates requests with the API key read from the given environment variable, sent where `location` tells.
+    ///
+    /// The variable is read once, now. If it is not set, requests are sent without key and a warning is logged.
+    pub fn api_key_from_env(self,variable : &str,location : ApiKeyLocation) -> Self {
+        match std::env::var(variable) {
+            Ok(key) => self.api_key(key,location),
+            Err(_) => {
+                log::warn!("API key variable {variable} of environment {} is not set",self.name);
+                self
+            }
+        }
+    }
+
+    /// Returns the name of the environment.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the base URL of the environment.
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Returns the configuration carrying the default headers of the environment.
+    pub fn config(&self) -> &ClientConfig {
+        &self.config
+    }
+
+    /// Returns the API key authentication of the environment, `None` if it has no key.
+    pub fn api_key_auth(&self) -> Option<&dyn ApiKeyAuth> {
+        self.api_key.as_ref().map(|key| key as &dyn ApiKeyAuth)
+    }
+
+    /// Picks the environment of the given name, compared case-insensitively.
+    ///
+    /// # Returns
+    ///
+    /// The environment, or [UnknownEnvironment] if none has this name.
+    pub fn select(name : &str,environments : impl IntoIterator<Item = Self>) -> Result<Self,UnknownEnvironment> {
+        let mut available = Vec::new();
+        for environment in environments {
+            if environment.name.eq_ignore_ascii_case(name) {
+                return Ok(environment);
+            }
+            available.push(environment.name);
+        }
+        Err(UnknownEnvironment { name : name.to_owned(), available })
+    }
+
+    /// Picks the environment named by the given environment variable, such as `MY_API_ENV=sandbox`, or the first
+    /// environment if the variable is not set or empty.
+    ///
+    /// # Returns
+    ///
+    /// The environment, or [UnknownEnvironment] if none has the name of the variable, or if there is none to default to.
+    pub fn from_env_var(variable : &str,environments : impl IntoIterator<Item = Self>) -> Result<Self,UnknownEnvironment> {
+        match std::env::var(variable).ok().filter(|name| !name.trim().is_empty()) {
+            Some(name) => Self::select(name.trim(),environments),
+            None => environments.into_iter().next().ok_or_else(|| UnknownEnvironment { name : String::new(), available : Vec::new() })
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// Spreads requests over several hosts serving the same API, such as regional mirrors, failing over when one is down.
+///
+/// Requests are built against the [crate::RequestInfo::base_url] as usual, then sent to a host of the pool instead,
+/// keeping the rest of their URL : the hosts are given in the same form as the base URL, such as `https://eu.example.com/v1`.
+/// When a host fails to connect or answers with a `5xx` status, the request is sent again right away to the next host,
+/// and the failing host is marked unhealthy for the cooldown, during which the other hosts are tried before it.
+///
+/// ```rust ignore
+/// // The primary first, then the fallbacks
+/// let hosts = HostPool::new(["https://api.example.com","https://eu.api.example.com","https://us.api.example.com"]);
+/// // Or distributing the requests in turn
+/// let hosts = HostPool::new(mirrors).rotating(true).cooldown(Duration::from_secs(60));
+/// ```
+///
+/// Requests whose URL is not below the base URL, and requests with a streamed body, which cannot be sent twice, are sent
+/// to a single host. The [crate::RequestDefaults::retry_policy] applies on top of the failover, every retry trying the
+/// hosts again.
+///
+/// Return it from [crate::RequestInfo::host_pool] to apply it. Not available on `wasm32`.
+#[derive(Debug)]
+pub struct HostPool {
+    hosts : Vec<String>,
+    rotating : bool,
+    cooldown : Duration,
+    state : Mutex<State>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    next : usize,
+    unhealthy : HashMap<usize,Instant>,
+}
+
+impl HostPool {
+    /// Creates a pool sending every request to the first host, and to the following ones in order when it fails.
+    ///
+    /// Unhealthy hosts are marked so for 30 seconds by default.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `hosts` is empty.
+    pub fn new<I : IntoIterator<Item = S>,S : Into<String>>(hosts : I) -> Self {
+        let hosts : Vec<String> = hosts.into_iter().map(Into::into).collect();
+        assert!(!hosts.is_empty(),"a host pool must have at least one host");
+        Self { hosts, rotating : false, cooldown : Duration::from_secs(30), state : Mutex::new(State::default()) }
+    }
+
+    /// Sends every request to the host following the one the previous request started with, spreading the load evenly,
+    /// rather than to the first host. Disabled by default.
+    pub fn rotating(mut self,enabled : bool) -> Self {
+        self.rotating = enabled;
+        self
+    }
+
+    /// Sets how long a failing host is marked unhealthy, 30 seconds by default.
+    pub fn cooldown(mut self,cooldown : Duration) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+
+    /// Returns the hosts of the pool.
+    pub fn hosts(&self) -> &[String] {
+        &self.hosts
+    }
+
+    /// Returns the hosts not marked unhealthy, in the order of the pool.
+    pub fn healthy_hosts(&self) -> Vec<&str> {
+        let now = Instant::now();
+        let state = self.state();
+        self.hosts.iter().enumerate()
+            .filter(|(index,_)| state.unhealthy.get(index).is_none_or(|until| *until <= now))
+            .map(|(_,host)| host.as_str())
+            .collect()
+    }
+
+    /// Returns the indices of the hosts in the order a request tries them : the healthy ones first, starting from the
+    /// next one in turn when rotating, then the unhealthy ones, whose cooldown ends soonest first.
+    pub(crate) fn order(&self) -> Vec<usize> {
+        let now = Instant::now();
+        let mut state = self.state();
+        let start = match self.rotating {
+            true => {
+                let start = state.next % self.hosts.len();
+                state.next = start + 1;
+                start
+            },
+            false => 0
+        };
+        state.unhealthy.retain(|_,until| *until > now);
+
+        let mut order : Vec<usize> = (0..self.hosts.len()).map(|offset| (start + offset) % self.hosts.len()).collect();
+        // Stable, so the healthy hosts keep their order
+        order.sort_by_key(|index| state.unhealthy.get(index).copied());
+        order
+    }
+
+    /// Returns the URL of the request to the given host, replacing the base URL at the start of `url`.
+    pub(crate) fn url(&self,index : usize,base_url : &str,url : &reqwest::Url) -> Option<reqwest::Url> {
+        let rest = url.as_str().strip_prefix(base_url)?;
+        reqwest::Url::parse(&format!("{}{rest}",self.hosts[index])).ok()
+    }
+
+    /// Records whether a request sent to the given host reached it.
+    pub(crate) fn record(&self,index : usize,healthy : bool) {
+        let mut state = self.state();
+        match healthy {
+            true => state.unhealthy.remove(&index),
+            false => state.unhealthy.insert(index,Instant::now() + self.cooldown)
+        };
+    }
+
+    fn state(&self) -> MutexGuard<'_,State> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
@@ -0,0 +1,142 @@
+use reqwest::header::CONTENT_TYPE;
+
+use serde::Serialize;
+
+use crate::ResponseMeta;
+
+/// The format of the typed bodies exchanged with an API, see [crate::RequestDefaults::content_format].
+///
+/// It decides how [crate::RequestHandler::encode_body] serializes request bodies and the default `Accept` header, while
+/// responses are deserialized according to the `Content-Type` they declare, JSON being assumed when it is missing or
+/// unknown.
+///
+/// ```
+/// use api_request_utils::ContentFormat;
+///
+/// assert_eq!(ContentFormat::default(),ContentFormat::Json);
+/// assert_eq!(ContentFormat::Json.content_type(),"application/json");
+/// assert_eq!(ContentFormat::from_content_type("application/problem+json; charset=utf-8"),Some(ContentFormat::Json));
+/// assert_eq!(ContentFormat::from_content_type("text/html"),None);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ContentFormat {
+    /// JSON, with `serde_json`.
+    #[default]
+    Json,
+    /// MessagePack, with `rmp-serde`. Structs are encoded as maps, so fields are matched by name. Requires the `msgpack` feature.
+    #[cfg(feature = "msgpack")]
+    MessagePack,
+    /// CBOR, with `ciborium`. Requires the `cbor` feature.
+    #[cfg(feature = "cbor")]
+    Cbor,
+}
+
+impl ContentFormat {
+    /// Returns the content type of the format, sent as the `Content-Type` of request bodies and as the default `Accept` header.
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Json => "application/json",
+            #[cfg(feature = "msgpack")]
+            Self::MessagePack => "application/msgpack",
+            #[cfg(feature = "cbor")]
+            Self::Cbor => "application/cbor",
+        }
+    }
+
+    /// Returns the format of the given `Content-Type`, ignoring its parameters, if it is a known one.
+    pub fn from_content_type(content_type : &str) -> Option<Self> {
+        let essence = content_type.split(';').next().unwrap_or_default().trim().to_ascii_lowercase();
+        match essence.as_str() {
+            "application/json" | "text/json" => Some(Self::Json),
+            #[cfg(feature = "msgpack")]
+            "application/msgpack" | "application/x-msgpack" | "application/vnd.msgpack" => Some(Self::MessagePack),
+            #[cfg(feature = "cbor")]
+            "application/cbor" => Some(Self::Cbor),
+            _ if essence.ends_with("+json") => Some(Self::Json),
+            #[cfg(feature = "cbor")]
+            _ if essence.ends_with("+cbor") => Some(Self::Cbor),
+            _ => None
+        }
+    }
+
+    /// Returns the format declared by the `Content-Type` of a response, JSON if it declares none or an unknown one.
+    pub(crate) fn of(meta : &ResponseMeta) -> Self {
+        meta.headers.get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(Self::from_content_type)
+            .unwrap_or_default()
+    }
+
+    /// Serializes a value in the format.
+    ///
+    /// # Returns
+    ///
+    /// The encoded bytes, or the error raised while serializing the value.
+    pub fn encode<B : Serialize + ?Sized>(self,body : &B) -> Result<Vec<u8>,Box<dyn std::error::Error + Send + Sync>> {
+        match self {
+            Self::Json => Ok(serde_json::to_vec(body)?),
+            #[cfg(feature = "msgpack")]
+            Self::MessagePack => Ok(rmp_serde::to_vec_named(body)?),
+            #[cfg(feature = "cbor")]
+            Self::Cbor => {
+                let mut bytes = Vec::new();
+                ciborium::into_writer(body,&mut bytes)?;
+                Ok(bytes)
+            },
+        }
+    }
+}
+
+/// How [crate::RequestHandler::request_csv] reads `text/csv` bodies, such as the exports of reporting endpoints.
+///
+/// Records are comma-separated and start with a header row by default, whose names are matched against the fields of the
+/// deserialized type. Without a header row, fields are matched by position.
+///
+/// ```rust ignore
+/// let rows : Vec<(String,u64)> = api_client.request_csv(request,&CsvOptions::new().delimiter(b';').has_headers(false)).await?;
+/// ```
+///
+/// Requires the `csv` feature.
+#[cfg(feature = "csv")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CsvOptions {
+    delimiter : u8,
+    has_headers : bool,
+}
+
+#[cfg(feature = "csv")]
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self { delimiter : b',', has_headers : true }
+    }
+}
+
+#[cfg(feature = "csv")]
+impl CsvOptions {
+    /// Creates the options of comma-separated records starting with a header row.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the byte separating the fields of a record, such as `b';'` or `b'\t'`.
+    pub fn delimiter(mut self,delimiter : u8) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Sets whether the first record is a header row naming the fields.
+    pub fn has_headers(mut self,has_headers : bool) -> Self {
+        self.has_headers = has_headers;
+        self
+    }
+
+    /// Deserializes every record of a body.
+    pub(crate) fn parse<R : serde::de::DeserializeOwned>(&self,body : &[u8]) -> Result<Vec<R>,csv::Error> {
+        csv::ReaderBuilder::new()
+            .delimiter(self.delimiter)
+            .has_headers(self.has_headers)
+            .from_reader(body)
+            .deserialize()
+            .collect()
+    }
+}
@@ -0,0 +1,36 @@
+use serde::Deserialize;
+use serde_json::Value;
+
+/// The envelope of a GraphQL response.
+#[derive(Deserialize)]
+pub(crate) struct GraphQLEnvelope {
+    #[serde(default)]
+    pub(crate) data : Option<Value>,
+    #[serde(default)]
+    pub(crate) errors : Option<Value>,
+}
+
+/// An entry of the `errors` array of a GraphQL response, as described by the GraphQL specification.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct GraphQLError {
+    /// The description of the error.
+    pub message : String,
+    /// The locations in the query document the error relates to.
+    #[serde(default)]
+    pub locations : Vec<GraphQLLocation>,
+    /// The path of the response field the error happened in, made of field names and list indices.
+    #[serde(default)]
+    pub path : Vec<Value>,
+    /// The additional information of the server, such as an error code.
+    #[serde(default)]
+    pub extensions : Option<Value>,
+}
+
+/// A location in a GraphQL query document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct GraphQLLocation {
+    /// The line, starting from 1.
+    pub line : u32,
+    /// The column, starting from 1.
+    pub column : u32,
+}
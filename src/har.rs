@@ -0,0 +1,615 @@
+//! Support for recording requests into and replaying responses from [HAR](http://www.softwareishard.com/blog/har-12-spec/) (HTTP Archive) files.
+//!
+//! HAR is the interchange format used by browser devtools, which makes it a convenient way to share reproductions or to build
+//! tests out of captured traffic. A [HarRecorder] returned by [crate::RequestDefaults::har_recorder] captures every exchange made
+//! through `request_map`, while a [HarReplayer] returned by [crate::RequestDefaults::har_replayer] answers requests from a recorded
+//! archive without touching the network. A [Cassette] combines both, VCR style : it records the exchanges into a file the
+//! first time a test runs, and replays them from then on.
+//!
+//! Not available on `wasm32`, where responses cannot be built to replay them.
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+
+use reqwest::{Request, Response, StatusCode};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::logging::{self, REDACTED};
+
+/// The root of a HAR document.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Har {
+    /// The log containing all recorded entries.
+    pub log : HarLog,
+}
+
+/// The log of a HAR document.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HarLog {
+    /// The version of the HAR format.
+    pub version : String,
+    /// The application that created the log.
+    pub creator : HarCreator,
+    /// The recorded request/response pairs.
+    #[serde(default)]
+    pub entries : Vec<HarEntry>,
+}
+
+/// The application that created a HAR log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HarCreator {
+    /// The name of the application.
+    pub name : String,
+    /// The version of the application.
+    pub version : String,
+}
+
+/// A single recorded request/response pair.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarEntry {
+    /// The date and time the request was started, in ISO 8601 format.
+    pub started_date_time : String,
+    /// The total elapsed time of the request in milliseconds.
+    pub time : f64,
+    /// The recorded request.
+    pub request : HarRequest,
+    /// The recorded response.
+    pub response : HarResponse,
+    /// Information about the cache usage, kept as-is.
+    #[serde(default)]
+    pub cache : Value,
+    /// Timing information about the exchange.
+    #[serde(default)]
+    pub timings : HarTimings,
+}
+
+/// A header or query parameter recorded as a name/value pair.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HarNameValue {
+    /// The name of the pair.
+    pub name : String,
+    /// The value of the pair.
+    pub value : String,
+}
+
+/// A recorded request.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarRequest {
+    /// The request method.
+    pub method : String,
+    /// The absolute URL of the request, including the query string.
+    pub url : String,
+    /// The HTTP version of the request.
+    #[serde(default = "default_http_version")]
+    pub http_version : String,
+    /// The request headers.
+    #[serde(default)]
+    pub headers : Vec<HarNameValue>,
+    /// The query string parameters.
+    #[serde(default)]
+    pub query_string : Vec<HarNameValue>,
+    /// The posted data, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_data : Option<HarPostData>,
+    /// The size of the headers in bytes, `-1` if unknown.
+    #[serde(default = "unknown_size")]
+    pub headers_size : i64,
+    /// The size of the body in bytes, `-1` if unknown.
+    #[serde(default = "unknown_size")]
+    pub body_size : i64,
+}
+
+/// The body of a recorded request.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarPostData {
+    /// The mime type of the posted data.
+    #[serde(default)]
+    pub mime_type : String,
+    /// The posted data as text.
+    #[serde(default)]
+    pub text : String,
+}
+
+/// A recorded response.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarResponse {
+    /// The response status code.
+    pub status : u16,
+    /// The response status description.
+    #[serde(default)]
+    pub status_text : String,
+    /// The HTTP version of the response.
+    #[serde(default = "default_http_version")]
+    pub http_version : String,
+    /// The response headers.
+    #[serde(default)]
+    pub headers : Vec<HarNameValue>,
+    /// The response body.
+    pub content : HarContent,
+    /// The redirection target from the `Location` header.
+    #[serde(default, rename = "redirectURL")]
+    pub redirect_url : String,
+    /// The size of the headers in bytes, `-1` if unknown.
+    #[serde(default = "unknown_size")]
+    pub headers_size : i64,
+    /// The size of the body in bytes, `-1` if unknown.
+    #[serde(default = "unknown_size")]
+    pub body_size : i64,
+}
+
+/// The body of a recorded response.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarContent {
+    /// The length of the returned content in bytes.
+    #[serde(default)]
+    pub size : i64,
+    /// The mime type of the content.
+    #[serde(default)]
+    pub mime_type : String,
+    /// The content itself, either as text or encoded according to `encoding`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text : Option<String>,
+    /// The encoding used for `text`, such as `base64` for binary content.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encoding : Option<String>,
+}
+
+/// Timing information of a recorded exchange, in milliseconds.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HarTimings {
+    /// Time required to send the request.
+    #[serde(default)]
+    pub send : f64,
+    /// Time spent waiting for the response.
+    #[serde(default)]
+    pub wait : f64,
+    /// Time required to read the response.
+    #[serde(default)]
+    pub receive : f64,
+}
+
+fn default_http_version() -> String {
+    String::from("HTTP/1.1")
+}
+
+fn unknown_size() -> i64 {
+    -1
+}
+
+fn name_values(headers : &HeaderMap) -> Vec<HarNameValue> {
+    headers.iter().map(|(name,value)| HarNameValue {
+        name : name.to_string(),
+        value : String::from_utf8_lossy(value.as_bytes()).into_owned(),
+    }).collect()
+}
+
+fn content_type(headers : &HeaderMap) -> String {
+    headers.get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(String::from)
+        .unwrap_or_default()
+}
+
+/// Formats the given time as an ISO 8601 UTC timestamp with millisecond precision.
+fn iso8601(time : SystemTime) -> String {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let seconds = since_epoch.as_secs();
+    let (days, remainder) = (seconds / 86_400, seconds % 86_400);
+
+    // Civil-from-days conversion (H. Hinnant), valid for any date after the epoch.
+    let z = days as i64 + 719_468;
+    let era = z / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year, month, day,
+        remainder / 3_600, remainder % 3_600 / 60, remainder % 60,
+        since_epoch.subsec_millis()
+    )
+}
+
+impl HarRequest {
+    /// Captures the method, URL, headers, query string and (buffered) body of the given request.
+    pub fn from_request(request : &Request) -> Self {
+        let body = request.body().and_then(|body| body.as_bytes());
+        Self {
+            method : request.method().to_string(),
+            url : request.url().to_string(),
+            http_version : format!("{:?}",request.version()),
+            headers : name_values(request.headers()),
+            query_string : request.url().query_pairs().map(|(name,value)| HarNameValue {
+                name : name.into_owned(),
+                value : value.into_owned(),
+            }).collect(),
+            post_data : body.map(|body| HarPostData {
+                mime_type : content_type(request.headers()),
+                text : String::from_utf8_lossy(body).into_owned(),
+            }),
+            headers_size : -1,
+            body_size : body.map(|body| body.len() as i64).unwrap_or(0),
+        }
+    }
+}
+
+impl HarResponse {
+    /// Captures the status, headers and body of a response.
+    ///
+    /// Bodies that are not valid UTF-8 are stored base64 encoded.
+    pub fn new(status : StatusCode,headers : &HeaderMap,body : &[u8]) -> Self {
+        let (text, encoding) = match std::str::from_utf8(body) {
+            Ok(text) => (text.to_owned(), None),
+            Err(_) => (BASE64.encode(body), Some(String::from("base64"))),
+        };
+
+        Self {
+            status : status.as_u16(),
+            status_text : status.canonical_reason().unwrap_or_default().to_owned(),
+            http_version : default_http_version(),
+            headers : name_values(headers),
+            content : HarContent {
+                size : body.len() as i64,
+                mime_type : content_type(headers),
+                text : Some(text),
+                encoding,
+            },
+            redirect_url : headers.get(reqwest::header::LOCATION)
+                .and_then(|value| value.to_str().ok())
+                .map(String::from)
+                .unwrap_or_default(),
+            headers_size : -1,
+            body_size : body.len() as i64,
+        }
+    }
+
+    /// Returns the decoded body of the response.
+    pub fn body(&self) -> Vec<u8> {
+        let text = self.content.text.as_deref().unwrap_or_default();
+        match self.content.encoding.as_deref() {
+            Some("base64") => BASE64.decode(text).unwrap_or_else(|_| text.as_bytes().to_vec()),
+            _ => text.as_bytes().to_vec(),
+        }
+    }
+
+    /// Converts the recorded response into a [reqwest::Response] as if it had been received from the network.
+    ///
+    /// Recorded headers that are not valid are left out, the others are kept :
+    ///
+    /// ```rust
+    /// # use api_request_utils::har::HarResponse;
+    /// let response : HarResponse = serde_json::from_value(serde_json::json!({
+    ///     "status" : 200,
+    ///     "headers" : [{ "name" : "Content-Type", "value" : "text/plain" },{ "name" : "Bad Header", "value" : "x" }],
+    ///     "content" : { "text" : "hello" },
+    /// })).unwrap();
+    ///
+    /// let response = response.to_response();
+    /// assert_eq!(response.headers()["content-type"],"text/plain");
+    /// assert_eq!(response.headers().len(),1);
+    /// ```
+    pub fn to_response(&self) -> Response {
+        let mut response = http::Response::new(self.body());
+        *response.status_mut() = StatusCode::from_u16(self.status).unwrap_or(StatusCode::OK);
+        // Invalid recorded headers are skipped instead of failing the whole replay
+        for header in &self.headers {
+            if let (Ok(name),Ok(value)) = (HeaderName::from_bytes(header.name.as_bytes()),HeaderValue::from_str(&header.value)) {
+                response.headers_mut().append(name,value);
+            }
+        }
+        Response::from(response)
+    }
+}
+
+/// Records every exchange made through `request_map` so it can be exported as a HAR file.
+#[derive(Debug, Default)]
+pub struct HarRecorder {
+    entries : Mutex<Vec<HarEntry>>,
+}
+
+impl HarRecorder {
+    /// Creates an empty recorder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a single exchange.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The captured request.
+    /// * `response` - The captured response.
+    /// * `started` - The moment the request was sent.
+    /// * `elapsed` - How long the whole exchange took.
+    pub fn record(&self,request : HarRequest,response : HarResponse,started : SystemTime,elapsed : Duration) {
+        let time = elapsed.as_secs_f64() * 1_000.0;
+        let entry = HarEntry {
+            started_date_time : iso8601(started),
+            time,
+            request,
+            response,
+            cache : Value::Object(Default::default()),
+            timings : HarTimings { send : 0.0, wait : time, receive : 0.0 },
+        };
+        self.entries.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).push(entry);
+    }
+
+    /// Returns a copy of all entries recorded so far.
+    pub fn entries(&self) -> Vec<HarEntry> {
+        self.entries.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clone()
+    }
+
+    /// Builds a HAR document out of the recorded entries.
+    pub fn to_har(&self) -> Har {
+        Har {
+            log : HarLog {
+                version : String::from("1.2"),
+                creator : HarCreator {
+                    name : String::from(env!("CARGO_PKG_NAME")),
+                    version : String::from(env!("CARGO_PKG_VERSION")),
+                },
+                entries : self.entries(),
+            }
+        }
+    }
+
+    /// Writes the recorded entries as a HAR file to the given path.
+    pub fn save(&self,path : impl AsRef<Path>) -> std::io::Result<()> {
+        let json = serde_json::to_vec_pretty(&self.to_har())?;
+        std::fs::write(path,json)
+    }
+}
+
+/// Answers requests from the entries of a HAR document instead of the network.
+///
+/// A request matches an entry when both the method and the URL are equal, the parameters of the query string being
+/// compared in any order, and also the body with [HarReplayer::match_body]. If several entries match, the first one is used.
+#[derive(Debug, Clone)]
+pub struct HarReplayer {
+    har : Har,
+    match_body : bool,
+    // The query parameters redacted in the recorded URLs, see [Cassette::redact_query_parameter]
+    redacted_parameters : Vec<String>,
+}
+
+impl HarReplayer {
+    /// Creates a replayer from an already parsed HAR document.
+    pub fn new(har : Har) -> Self {
+        Self { har, match_body : false, redacted_parameters : Vec::new() }
+    }
+
+    /// Also requires the body of requests to equal the recorded one, so requests to the same URL with different payloads
+    /// are told apart. JSON bodies are compared as values, ignoring the order of their keys.
+    pub fn match_body(mut self) -> Self {
+        self.match_body = true;
+        self
+    }
+
+    /// Parses a HAR document from a JSON string.
+    pub fn from_json(json : &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json).map(Self::new)
+    }
+
+    /// Loads a HAR file from the given path.
+    pub fn from_file(path : impl AsRef<Path>) -> std::io::Result<Self> {
+        let json = std::fs::read(path)?;
+        Ok(Self::new(serde_json::from_slice(&json)?))
+    }
+
+    /// Returns the underlying HAR document.
+    pub fn har(&self) -> &Har {
+        &self.har
+    }
+
+    /// Finds the entry recorded for the given request.
+    pub fn find(&self,request : &Request) -> Option<&HarEntry> {
+        let method = request.method().as_str();
+        let url = normalized_url(request.url(),&self.redacted_parameters);
+        let body = request.body().and_then(reqwest::Body::as_bytes).map(String::from_utf8_lossy);
+        self.har.log.entries.iter().find(|entry| {
+            entry.request.method.eq_ignore_ascii_case(method)
+                && reqwest::Url::parse(&entry.request.url).is_ok_and(|recorded| normalized_url(&recorded,&self.redacted_parameters) == url)
+                && (!self.match_body || same_body(entry.request.post_data.as_ref().map(|data| data.text.as_str()),body.as_deref()))
+        })
+    }
+}
+
+/// Sorts the parameters of the query string of the URL, so URLs only differing by their order compare equal, and redacts
+/// the values of the given parameters.
+fn normalized_url(url : &reqwest::Url,redacted_parameters : &[String]) -> reqwest::Url {
+    let mut url = url.clone();
+    let mut query : Vec<(String,String)> = url.query_pairs().map(|(key,value)| match logging::is_redacted(redacted_parameters,&key) {
+        true => (key.into_owned(),REDACTED.to_owned()),
+        false => (key.into_owned(),value.into_owned())
+    }).collect();
+    if query.is_empty() {
+        return url;
+    }
+    query.sort();
+    url.query_pairs_mut().clear().extend_pairs(query);
+    url
+}
+
+/// Compares a recorded body with the body of a request, as JSON values when both are JSON.
+fn same_body(recorded : Option<&str>,body : Option<&str>) -> bool {
+    match (recorded.filter(|text| !text.is_empty()),body.filter(|text| !text.is_empty())) {
+        (Some(recorded),Some(body)) => match (serde_json::from_str::<Value>(recorded),serde_json::from_str::<Value>(body)) {
+            (Ok(recorded),Ok(body)) => recorded == body,
+            _ => recorded == body
+        },
+        (recorded,body) => recorded == body
+    }
+}
+
+/// Whether a [Cassette] records exchanges or replays them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CassetteMode {
+    /// Requests are sent over the network, and their exchanges written to the cassette when it is saved.
+    Record,
+    /// Requests are answered from the cassette, those that were not recorded failing with [crate::RequestError::ReplayMiss].
+    Replay,
+    /// Replays the cassette if its file exists, records it otherwise. Deleting the file records it again.
+    Auto,
+}
+
+/// A file of recorded exchanges, VCR style, making integration tests against third-party APIs deterministic.
+///
+/// Cassettes are HAR files. While recording, they are saved when dropped (or with [Cassette::save]), with the same headers
+/// and query parameters redacted as by [crate::LoggingMiddleware], such as `Authorization` or `api_key`. While replaying,
+/// requests are matched by method, URL and body, see [HarReplayer::match_body], the redacted parameters matching any
+/// value. Return both halves from the hooks :
+///
+/// ```rust ignore
+/// impl RequestDefaults for MyAPIClient {
+///     fn har_recorder(&self) -> Option<&HarRecorder> {
+///         self.cassette.recorder()
+///     }
+///
+///     fn har_replayer(&self) -> Option<&HarReplayer> {
+///         self.cassette.replayer()
+///     }
+/// }
+///
+/// let api_client = MyAPIClient { cassette : Cassette::open("tests/cassettes/users.har",CassetteMode::Auto)?, /* ... */ };
+/// ```
+#[derive(Debug)]
+pub struct Cassette {
+    path : PathBuf,
+    recorder : Option<HarRecorder>,
+    replayer : Option<HarReplayer>,
+    redacted_headers : Vec<String>,
+    redacted_parameters : Vec<String>,
+}
+
+impl Cassette {
+    /// Opens the cassette at the given path in the given mode.
+    ///
+    /// # Returns
+    ///
+    /// The cassette, or the error reading its file when replaying, such as when it does not exist in [CassetteMode::Replay].
+    pub fn open(path : impl Into<PathBuf>,mode : CassetteMode) -> std::io::Result<Self> {
+        let path = path.into();
+        let replay = match mode {
+            CassetteMode::Record => false,
+            CassetteMode::Replay => true,
+            CassetteMode::Auto => path.exists()
+        };
+
+        let redacted_parameters : Vec<String> = logging::REDACTED_PARAMETERS.map(str::to_owned).to_vec();
+        let (recorder,replayer) = match replay {
+            true => (None,Some(HarReplayer { redacted_parameters : redacted_parameters.clone(), ..HarReplayer::from_file(&path)?.match_body() })),
+            false => (Some(HarRecorder::new()),None)
+        };
+        Ok(Self {
+            path,
+            recorder,
+            replayer,
+            redacted_headers : logging::REDACTED_HEADERS.map(str::to_owned).to_vec(),
+            redacted_parameters,
+        })
+    }
+
+    /// Adds a header, compared case-insensitively, whose value is redacted when the cassette is saved.
+    pub fn redact_header(mut self,name : impl Into<String>) -> Self {
+        self.redacted_headers.push(name.into().to_ascii_lowercase());
+        self
+    }
+
+    /// Adds a query parameter, compared case-insensitively, whose value is redacted when the cassette is saved.
+    pub fn redact_query_parameter(mut self,name : impl Into<String>) -> Self {
+        let name = name.into().to_ascii_lowercase();
+        if let Some(replayer) = &mut self.replayer {
+            replayer.redacted_parameters.push(name.clone());
+        }
+        self.redacted_parameters.push(name);
+        self
+    }
+
+    /// Whether the cassette records exchanges rather than replaying them.
+    pub fn is_recording(&self) -> bool {
+        self.recorder.is_some()
+    }
+
+    /// Returns the recorder to return from [crate::RequestDefaults::har_recorder], `None` while replaying.
+    pub fn recorder(&self) -> Option<&HarRecorder> {
+        self.recorder.as_ref()
+    }
+
+    /// Returns the replayer to return from [crate::RequestDefaults::har_replayer], `None` while recording.
+    pub fn replayer(&self) -> Option<&HarReplayer> {
+        self.replayer.as_ref()
+    }
+
+    /// Writes the recorded exchanges to the file of the cassette, creating its directory if needed. Does nothing while replaying.
+    pub fn save(&self) -> std::io::Result<()> {
+        let Some(recorder) = &self.recorder else {
+            return Ok(())
+        };
+
+        let mut har = recorder.to_har();
+        let redact = |values : &mut Vec<HarNameValue>,names : &[String]| values.iter_mut()
+            .filter(|value| logging::is_redacted(names,&value.name))
+            .for_each(|value| value.value = REDACTED.to_owned());
+        for entry in &mut har.log.entries {
+            redact(&mut entry.request.headers,&self.redacted_headers);
+            redact(&mut entry.response.headers,&self.redacted_headers);
+            redact(&mut entry.request.query_string,&self.redacted_parameters);
+            if let Ok(url) = reqwest::Url::parse(&entry.request.url) {
+                entry.request.url = logging::redact_url_with(&url,&self.redacted_parameters);
+            }
+        }
+
+        if let Some(directory) = self.path.parent().filter(|directory| !directory.as_os_str().is_empty()) {
+            std::fs::create_dir_all(directory)?;
+        }
+        std::fs::write(&self.path,serde_json::to_vec_pretty(&har)?)
+    }
+}
+
+impl Drop for Cassette {
+    fn drop(&mut self) {
+        if let Err(error) = self.save() {
+            log::warn!("Failed to save cassette {} : {error}",self.path.display());
+        }
+    }
+}
+
+/// An exchange that is being captured by a [HarRecorder].
+pub(crate) struct Recording<'a> {
+    recorder : &'a HarRecorder,
+    request : HarRequest,
+    started : SystemTime,
+    instant : std::time::Instant,
+}
+
+impl<'a> Recording<'a> {
+    pub(crate) fn start(recorder : &'a HarRecorder,request : &Request) -> Self {
+        Self {
+            recorder,
+            request : HarRequest::from_request(request),
+            started : SystemTime::now(),
+            instant : std::time::Instant::now(),
+        }
+    }
+
+    pub(crate) fn finish(self,status : StatusCode,headers : &HeaderMap,body : &[u8]) {
+        self.recorder.record(self.request,HarResponse::new(status,headers,body),self.started,self.instant.elapsed())
+    }
+}
@@ -0,0 +1,49 @@
+// The digests and random values needed by the authentication schemes, on top of the RustCrypto crates and `getrandom`.
+
+use hmac::{Hmac, Mac};
+
+use sha2::{Digest, Sha256};
+
+/// Computes the MD5 digest of the data.
+pub(crate) fn md5(data : &[u8]) -> [u8; 16] {
+    md5::Md5::digest(data).into()
+}
+
+/// Computes the SHA-1 digest of the data.
+#[cfg(any(feature = "websocket",feature = "oauth1"))]
+pub(crate) fn sha1(data : &[u8]) -> [u8; 20] {
+    sha1::Sha1::digest(data).into()
+}
+
+/// Computes the SHA-256 digest of the data.
+pub(crate) fn sha256(data : &[u8]) -> [u8; 32] {
+    Sha256::digest(data).into()
+}
+
+/// Encodes the bytes as lowercase hexadecimal.
+pub(crate) fn hex(bytes : &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Computes the HMAC-SHA256 of the message with the given key.
+pub(crate) fn hmac_sha256(key : &[u8],message : &[u8]) -> [u8; 32] {
+    // HMAC takes keys of any length
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).unwrap_or_else(|_| unreachable!());
+    mac.update(message);
+    mac.finalize().into_bytes().into()
+}
+
+/// Computes the HMAC-SHA1 of the message with the given key.
+#[cfg(feature = "oauth1")]
+pub(crate) fn hmac_sha1(key : &[u8],message : &[u8]) -> [u8; 20] {
+    let mut mac = Hmac::<sha1::Sha1>::new_from_slice(key).unwrap_or_else(|_| unreachable!());
+    mac.update(message);
+    mac.finalize().into_bytes().into()
+}
+
+/// Returns bytes drawn from the randomness source of the operating system, as nonces and WebSocket masks require.
+pub(crate) fn random_bytes<const N : usize>() -> std::io::Result<[u8; N]> {
+    let mut bytes = [0; N];
+    getrandom::getrandom(&mut bytes)?;
+    Ok(bytes)
+}
@@ -0,0 +1,119 @@
+use std::collections::VecDeque;
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+
+/// How many latencies the percentile of a [HedgePolicy] is computed over.
+const WINDOW : usize = 100;
+
+/// How many latencies are needed before the percentile of a [HedgePolicy] replaces its initial delay.
+const MIN_SAMPLES : usize = 20;
+
+/// Hedging of idempotent requests, cutting the tail latency of APIs where a few requests are much slower than the others.
+///
+/// When a `GET` or `HEAD` request is not answered within the hedge delay, a copy is sent, rather than waiting for the slow
+/// one, and whichever request succeeds first is used, the others being cancelled. The delay is either fixed or follows a
+/// percentile of the latencies observed recently, so only the slowest requests are hedged :
+///
+/// ```
+/// use std::time::Duration;
+/// use api_request_utils::HedgePolicy;
+///
+/// // Hedge the 5% slowest requests, once
+/// let hedging = HedgePolicy::percentile(0.95).min_delay(Duration::from_millis(50));
+/// assert_eq!(hedging.delay(),Duration::from_secs(1)); // Until latencies are known
+///
+/// let hedging = HedgePolicy::fixed(Duration::from_millis(200)).max_hedges(2);
+/// assert_eq!(hedging.delay(),Duration::from_millis(200));
+/// ```
+///
+/// Every hedge is a request of its own for the server, counting towards its rate limits, so hedged APIs should be able to
+/// absorb the extra load. Requests with a streamed body, which cannot be sent twice, are not hedged.
+///
+/// Return it from [crate::RequestDefaults::hedge_policy] to apply it. Not available on `wasm32`.
+#[derive(Debug)]
+pub struct HedgePolicy {
+    delay : HedgeDelay,
+    max_hedges : usize,
+    min_delay : Duration,
+    latencies : Mutex<VecDeque<Duration>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum HedgeDelay {
+    Fixed(Duration),
+    Percentile { percentile : f64, initial : Duration },
+}
+
+impl HedgePolicy {
+    /// Creates a policy sending a copy of the requests not answered within `delay`.
+    pub fn fixed(delay : Duration) -> Self {
+        Self::new(HedgeDelay::Fixed(delay))
+    }
+
+    /// Creates a policy sending a copy of the requests slower than the given percentile of the recent latencies, such as
+    /// `0.95` to hedge the 5% slowest requests. Until enough latencies are known, the delay is 1 second.
+    pub fn percentile(percentile : f64) -> Self {
+        Self::new(HedgeDelay::Percentile { percentile : percentile.clamp(0.0,1.0), initial : Duration::from_secs(1) })
+    }
+
+    fn new(delay : HedgeDelay) -> Self {
+        Self { delay, max_hedges : 1, min_delay : Duration::ZERO, latencies : Mutex::new(VecDeque::with_capacity(WINDOW)) }
+    }
+
+    /// Sets how many copies of a request may be sent, one after each delay, 1 by default.
+    pub fn max_hedges(mut self,max_hedges : usize) -> Self {
+        self.max_hedges = max_hedges;
+        self
+    }
+
+    /// Sets the delay of a percentile policy until enough latencies are known, 1 second by default.
+    pub fn initial_delay(mut self,delay : Duration) -> Self {
+        if let HedgeDelay::Percentile { initial, .. } = &mut self.delay {
+            *initial = delay;
+        }
+        self
+    }
+
+    /// Sets the shortest delay, keeping a percentile policy from hedging every request of a fast API.
+    pub fn min_delay(mut self,delay : Duration) -> Self {
+        self.min_delay = delay;
+        self
+    }
+
+    /// Returns how many copies of a request may be sent.
+    pub fn hedges(&self) -> usize {
+        self.max_hedges
+    }
+
+    /// Returns how long a request is waited for before a copy is sent, from the latencies observed so far.
+    pub fn delay(&self) -> Duration {
+        let delay = match self.delay {
+            HedgeDelay::Fixed(delay) => delay,
+            HedgeDelay::Percentile { percentile, initial } => {
+                let mut latencies : Vec<Duration> = self.latencies().iter().copied().collect();
+                match latencies.len() >= MIN_SAMPLES {
+                    true => {
+                        latencies.sort_unstable();
+                        let index = ((latencies.len() - 1) as f64 * percentile).round() as usize;
+                        latencies[index]
+                    },
+                    false => initial
+                }
+            }
+        };
+        delay.max(self.min_delay)
+    }
+
+    /// Records the latency of a successful request.
+    pub(crate) fn record(&self,latency : Duration) {
+        let mut latencies = self.latencies();
+        if latencies.len() == WINDOW {
+            latencies.pop_front();
+        }
+        latencies.push_back(latency);
+    }
+
+    fn latencies(&self) -> MutexGuard<'_,VecDeque<Duration>> {
+        self.latencies.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
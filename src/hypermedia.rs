@@ -0,0 +1,193 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+
+use reqwest::{Method, Url};
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize};
+
+use serde_json::Value;
+
+use crate::{base_url_requestor, finish_request, RequestError, RequestHandler};
+
+/// A link of a hypermedia response, see [Links].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Link {
+    /// The target of the link, absolute or relative to the [crate::RequestInfo::base_url].
+    pub href : String,
+    /// The title of the link, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title : Option<String>,
+    /// Whether the target is an URI template (RFC 6570), such as `orders{?page}`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub templated : bool,
+}
+
+/// The links of a hypermedia response, by relation.
+///
+/// It deserializes the usual shapes of the `_links` object of HAL and the `links` object of JSON:API :
+///
+/// ```
+/// use api_request_utils::Links;
+///
+/// let hal : Links = serde_json::from_str(r#"{ "self" : { "href" : "/orders/1" }, "item" : [{ "href" : "/items/1" },{ "href" : "/items/2" }] }"#).unwrap();
+/// assert_eq!(hal.get("self").unwrap().href,"/orders/1");
+/// assert_eq!(hal.all("item").len(),2);
+///
+/// let json_api : Links = serde_json::from_str(r#"{ "next" : "https://api.example.com/orders?page=2", "prev" : null }"#).unwrap();
+/// assert_eq!(json_api.get("next").unwrap().href,"https://api.example.com/orders?page=2");
+/// assert!(json_api.get("prev").is_none());
+///
+/// let array : Links = serde_json::from_str(r#"[{ "rel" : "customer", "href" : "customers/7" }]"#).unwrap();
+/// assert_eq!(array.get("customer").unwrap().href,"customers/7");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Links(HashMap<String,Vec<Link>>);
+
+impl Links {
+    /// Returns the first link with the given relation, if any.
+    pub fn get(&self,rel : &str) -> Option<&Link> {
+        self.all(rel).first()
+    }
+
+    /// Returns every link with the given relation.
+    pub fn all(&self,rel : &str) -> &[Link] {
+        self.0.get(rel).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Returns whether a link has the given relation.
+    pub fn contains(&self,rel : &str) -> bool {
+        !self.all(rel).is_empty()
+    }
+
+    /// Returns the links with their relation.
+    pub fn iter(&self) -> impl Iterator<Item = (&str,&Link)> {
+        self.0.iter().flat_map(|(rel,links)| links.iter().map(move |link| (rel.as_str(),link)))
+    }
+}
+
+impl<'de> Deserialize<'de> for Links {
+    fn deserialize<D : Deserializer<'de>>(deserializer : D) -> Result<Self,D::Error> {
+        /// Reads a link given as a bare URL or as an object, `None` for `null` and other values.
+        fn link(value : Value) -> Option<Link> {
+            match value {
+                Value::String(href) => Some(Link { href, ..Link::default() }),
+                value @ Value::Object(_) => serde_json::from_value(value).ok(),
+                _ => None
+            }
+        }
+
+        let mut links : HashMap<String,Vec<Link>> = HashMap::new();
+        match Value::deserialize(deserializer)? {
+            Value::Object(relations) => for (rel,value) in relations {
+                let targets : Vec<Link> = match value {
+                    Value::Array(values) => values.into_iter().filter_map(link).collect(),
+                    value => link(value).into_iter().collect()
+                };
+                if !targets.is_empty() {
+                    links.entry(rel).or_default().extend(targets);
+                }
+            },
+            // Link objects carrying their relations, as in Siren
+            Value::Array(values) => for value in values {
+                let rels : Vec<String> = match value.get("rel") {
+                    Some(Value::String(rel)) => vec![rel.clone()],
+                    Some(Value::Array(rels)) => rels.iter().filter_map(Value::as_str).map(str::to_owned).collect(),
+                    _ => Vec::new()
+                };
+                if let Some(target) = link(value) {
+                    for rel in rels {
+                        links.entry(rel).or_default().push(target.clone());
+                    }
+                }
+            },
+            Value::Null => {},
+            _ => return Err(serde::de::Error::custom("expected an object or an array of links"))
+        }
+        Ok(Self(links))
+    }
+}
+
+/// A response body along with its links, read from its `_links` or `links` field.
+///
+/// ```rust ignore
+/// impl RequestHandler<Linked<Order>,Linked<Order>,ApiError> for MyAPIClient {}
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Linked<T> {
+    /// The links of the response.
+    #[serde(rename = "_links", alias = "links", default)]
+    pub links : Links,
+    /// The rest of the response.
+    #[serde(flatten)]
+    pub data : T,
+}
+
+/// Traverses hypermedia APIs by following the links of their responses rather than building URLs by hand.
+///
+/// Implemented like the other traits, it is available for every resource type `T` the client has a [RequestHandler] for :
+///
+/// ```rust ignore
+/// impl RequestHandler<Linked<Customer>,Linked<Customer>,ApiError> for MyAPIClient {}
+/// impl FollowLinks<Linked<Customer>,ApiError> for MyAPIClient {}
+///
+/// let order : Linked<Order> = api_client.request_map(request,|order| order).await?;
+/// let customer : Option<Linked<Customer>> = api_client.follow(&order.links,"customer").await?;
+/// ```
+#[cfg_attr(not(feature = "wasm"),async_trait)]
+#[cfg_attr(feature = "wasm",async_trait(?Send))]
+pub trait FollowLinks<T : DeserializeOwned,E : DeserializeOwned> : RequestHandler<T,T,E> {
+    /// Builds a `GET` request for the first link with the given relation, with the default headers, authentication and
+    /// query of the default requestors.
+    ///
+    /// Absolute hrefs are used as they are, which sends the credentials to whatever host the API links to. Hrefs starting
+    /// with `/` are resolved against the origin of the [crate::RequestInfo::base_url], and the others joined with it like
+    /// endpoints. The expressions of templated links are left out, so `orders{?page}` is requested as `orders`.
+    ///
+    /// # Returns
+    ///
+    /// The request, or `None` if there is no link with this relation.
+    fn link_requestor(&self,links : &Links,rel : &str) -> Option<reqwest::RequestBuilder> {
+        let link = links.get(rel)?;
+        let href = match link.templated {
+            true => strip_template(&link.href),
+            false => link.href.clone()
+        };
+
+        let url = match Url::parse(&href) {
+            Ok(url) => url.to_string(),
+            Err(_) if href.starts_with('/') => Url::parse(self.base_url()).and_then(|base| base.join(&href)).map_or_else(|_| self.endpoint_url(&href),|url| url.to_string()),
+            Err(_) => self.endpoint_url(&href)
+        };
+        Some(finish_request(self,base_url_requestor(self,Method::GET,url,&HashMap::new())))
+    }
+
+    /// Follows the first link with the given relation with [FollowLinks::link_requestor], and maps the response like
+    /// [RequestHandler::request_map].
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the resource, `None` if there is no link with this relation, or an `RequestError` variant.
+    async fn follow(&self,links : &Links,rel : &str) -> Result<Option<T>,RequestError<E>> {
+        match self.link_requestor(links,rel) {
+            Some(request) => self.request_map(request,|resource| resource).await.map(Some),
+            None => Ok(None)
+        }
+    }
+}
+
+/// Removes the expressions of an URI template, keeping its literal parts.
+fn strip_template(template : &str) -> String {
+    let mut literal = String::with_capacity(template.len());
+    let mut depth = 0usize;
+    for character in template.chars() {
+        match character {
+            '{' => depth += 1,
+            '}' => depth = depth.saturating_sub(1),
+            character if depth == 0 => literal.push(character),
+            _ => {}
+        }
+    }
+    literal
+}
@@ -0,0 +1,183 @@
+use std::collections::HashMap;
+
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Serialize, Serializer};
+use serde_json::{Map, Value};
+
+/// An entry of the `errors` array of a JSON:API document.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct JsonApiError {
+    /// The unique identifier of this occurrence of the problem.
+    #[serde(default)]
+    pub id : Option<String>,
+    /// The HTTP status code applicable to the problem, as a string.
+    #[serde(default)]
+    pub status : Option<String>,
+    /// The application-specific error code.
+    #[serde(default)]
+    pub code : Option<String>,
+    /// The short, human-readable summary of the problem.
+    #[serde(default)]
+    pub title : Option<String>,
+    /// The human-readable explanation specific to this occurrence of the problem.
+    #[serde(default)]
+    pub detail : Option<String>,
+    /// The part of the request document (`pointer`), or the query parameter (`parameter`), causing the problem.
+    #[serde(default)]
+    pub source : Option<Value>,
+    /// The non-standard meta-information about the error.
+    #[serde(default)]
+    pub meta : Option<Value>,
+}
+
+impl std::fmt::Display for JsonApiError {
+    fn fmt(&self,f : &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (&self.title,&self.detail) {
+            (Some(title),Some(detail)) => write!(f,"{title} : {detail}"),
+            (Some(message),None) | (None,Some(message)) => f.write_str(message),
+            (None,None) => f.write_str(self.code.as_deref().unwrap_or("unknown error"))
+        }
+    }
+}
+
+/// The query parameters of JSON:API requests : included relationships, sparse fieldsets, sorting, filtering and pagination.
+///
+/// It serializes to the query parameters the default query requestors expect :
+///
+/// ```
+/// use api_request_utils::JsonApiQuery;
+///
+/// let query = JsonApiQuery::new()
+///     .include("author")
+///     .include("comments.author")
+///     .fields("articles",["title","body","author"])
+///     .sort("-created")
+///     .filter("tag","rust")
+///     .page("size",10);
+/// let query = serde_json::to_value(&query).unwrap();
+/// assert_eq!(query["include"],"author,comments.author");
+/// assert_eq!(query["fields[articles]"],"title,body,author");
+/// assert_eq!(query["sort"],"-created");
+/// assert_eq!(query["filter[tag]"],"rust");
+/// assert_eq!(query["page[size]"],"10");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct JsonApiQuery {
+    include : Vec<String>,
+    fields : Vec<(String,Vec<String>)>,
+    sort : Vec<String>,
+    parameters : Vec<(String,String)>,
+}
+
+impl JsonApiQuery {
+    /// Creates a query without any parameter.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Includes the resources of the given relationship path, such as `comments.author`, in the `included` section.
+    pub fn include(mut self,path : impl Into<String>) -> Self {
+        self.include.push(path.into());
+        self
+    }
+
+    /// Only returns the given fields of the resources of the given type.
+    pub fn fields<I : IntoIterator<Item = F>,F : Into<String>>(mut self,resource_type : impl Into<String>,fields : I) -> Self {
+        self.fields.push((resource_type.into(),fields.into_iter().map(Into::into).collect()));
+        self
+    }
+
+    /// Sorts by the given field, descending when prefixed with `-`. Fields added first take precedence.
+    pub fn sort(mut self,field : impl Into<String>) -> Self {
+        self.sort.push(field.into());
+        self
+    }
+
+    /// Adds the `filter[name]` parameter, whose meaning is left to the server.
+    pub fn filter(mut self,name : &str,value : impl ToString) -> Self {
+        self.parameters.push((format!("filter[{name}]"),value.to_string()));
+        self
+    }
+
+    /// Adds the `page[name]` parameter, such as `page[number]`, `page[size]` or `page[cursor]`.
+    pub fn page(mut self,name : &str,value : impl ToString) -> Self {
+        self.parameters.push((format!("page[{name}]"),value.to_string()));
+        self
+    }
+}
+
+impl Serialize for JsonApiQuery {
+    fn serialize<S : Serializer>(&self,serializer : S) -> Result<S::Ok,S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+        if !self.include.is_empty() {
+            map.serialize_entry("include",&self.include.join(","))?;
+        }
+        for (resource_type,fields) in &self.fields {
+            map.serialize_entry(&format!("fields[{resource_type}]"),&fields.join(","))?;
+        }
+        if !self.sort.is_empty() {
+            map.serialize_entry("sort",&self.sort.join(","))?;
+        }
+        for (name,value) in &self.parameters {
+            map.serialize_entry(name,value)?;
+        }
+        map.end()
+    }
+}
+
+/// Flattens the primary data of a JSON:API document, a resource object or an array of them, into plain JSON objects.
+///
+/// The `id`, `type` and `attributes` of a resource become the fields of a single object, along with its relationships,
+/// which are replaced with the flattened resources of the `included` section, or with their `id` and `type` when not
+/// included, or when they are already being flattened, as resources may reference each other. To-many relationships
+/// become arrays, and empty ones `null` or `[]`.
+pub(crate) fn flatten(document : &Value) -> Value {
+    let included : HashMap<(&str,&str),&Value> = document.get("included").and_then(Value::as_array).into_iter().flatten()
+        .filter_map(|resource| Some((resource_key(resource)?,resource)))
+        .collect();
+
+    match document.get("data") {
+        Some(Value::Array(resources)) => Value::Array(resources.iter().map(|resource| flatten_resource(resource,&included,&mut Vec::new())).collect()),
+        Some(resource @ Value::Object(_)) => flatten_resource(resource,&included,&mut Vec::new()),
+        _ => Value::Null
+    }
+}
+
+/// Flattens a resource, `ancestors` being the keys of the resources it is a relationship of.
+fn flatten_resource<'a>(resource : &'a Value,included : &HashMap<(&'a str,&'a str),&'a Value>,ancestors : &mut Vec<(&'a str,&'a str)>) -> Value {
+    let mut flattened = Map::new();
+    for key in ["id","type"] {
+        if let Some(value) = resource.get(key) {
+            flattened.insert(key.to_owned(),value.clone());
+        }
+    }
+    if let Some(Value::Object(attributes)) = resource.get("attributes") {
+        flattened.extend(attributes.iter().map(|(name,value)| (name.clone(),value.clone())));
+    }
+
+    let Some(Value::Object(relationships)) = resource.get("relationships") else {
+        return Value::Object(flattened)
+    };
+    let key = resource_key(resource);
+    ancestors.extend(key);
+    let mut resolve = |identifier : &'a Value| match resource_key(identifier).filter(|key| !ancestors.contains(key)).and_then(|key| included.get(&key)) {
+        Some(resource) => flatten_resource(resource,included,ancestors),
+        None => Value::Object(["id","type"].into_iter().filter_map(|key| Some((key.to_owned(),identifier.get(key)?.clone()))).collect())
+    };
+    for (name,relationship) in relationships {
+        let value = match relationship.get("data") {
+            Some(Value::Array(identifiers)) => Value::Array(identifiers.iter().map(&mut resolve).collect()),
+            Some(identifier @ Value::Object(_)) => resolve(identifier),
+            _ => Value::Null
+        };
+        flattened.insert(name.clone(),value);
+    }
+    if key.is_some() {
+        ancestors.pop();
+    }
+    Value::Object(flattened)
+}
+
+fn resource_key(resource : &Value) -> Option<(&str,&str)> {
+    resource.get("type")?.as_str().zip(resource.get("id")?.as_str())
+}
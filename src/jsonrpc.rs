@@ -0,0 +1,264 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use async_trait::async_trait;
+
+use reqwest::Method;
+use reqwest::header::CONTENT_TYPE;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::{base_requestor, base_url_requestor, finish_request, parse_error_payload, MaybeSync, RequestError, RequestHandler};
+
+/// The id of the next JSON-RPC request, unique within the process.
+static NEXT_ID : AtomicU64 = AtomicU64::new(1);
+
+/// The `error` object of a JSON-RPC 2.0 response, see [RequestError::JsonRpc].
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct JsonRpcError {
+    /// The code of the error, such as `-32601` for a method that does not exist or `-32602` for invalid params.
+    pub code : i64,
+    /// The short description of the error.
+    pub message : String,
+    /// The additional information of the server, if any.
+    #[serde(default)]
+    pub data : Option<Value>,
+}
+
+impl std::fmt::Display for JsonRpcError {
+    fn fmt(&self,f : &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f,"{} ({})",self.message,self.code)
+    }
+}
+
+/// A call of a JSON-RPC batch, see [JsonRpcHandler::json_rpc_batch].
+///
+/// ```
+/// use api_request_utils::JsonRpcCall;
+///
+/// let calls = [
+///     JsonRpcCall::new("eth_getBalance",&("0x407d73d8a49eeb85d32cf465507dd71d507100c1","latest")).unwrap(),
+///     JsonRpcCall::without_params("eth_blockNumber"),
+///     JsonRpcCall::without_params("eth_syncing").notification(),
+/// ];
+/// assert_eq!(calls[1].method(),"eth_blockNumber");
+/// assert!(calls[2].is_notification());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonRpcCall {
+    method : String,
+    params : Value,
+    notification : bool,
+}
+
+impl JsonRpcCall {
+    /// Creates a call of the given method with its params, usually a tuple or a struct.
+    ///
+    /// # Returns
+    ///
+    /// The call, or the error raised while serializing the params.
+    pub fn new<P : Serialize + ?Sized>(method : impl Into<String>,params : &P) -> Result<Self,serde_json::Error> {
+        Ok(Self { method : method.into(), params : serde_json::to_value(params)?, notification : false })
+    }
+
+    /// Creates a call of the given method without params.
+    pub fn without_params(method : impl Into<String>) -> Self {
+        Self { method : method.into(), params : Value::Null, notification : false }
+    }
+
+    /// Makes the call a notification, which the server does not answer.
+    pub fn notification(mut self) -> Self {
+        self.notification = true;
+        self
+    }
+
+    /// Returns the method called.
+    pub fn method(&self) -> &str {
+        &self.method
+    }
+
+    /// Returns whether the call is a notification.
+    pub fn is_notification(&self) -> bool {
+        self.notification
+    }
+
+    /// Returns the envelope of the call, with the given id unless it is a notification.
+    fn envelope(&self,id : Value) -> Value {
+        let mut envelope = json!({ "jsonrpc" : "2.0", "method" : self.method });
+        // `params` may be left out, but not be `null`
+        if !self.params.is_null() {
+            envelope["params"] = self.params.clone();
+        }
+        if !self.notification {
+            envelope["id"] = id;
+        }
+        envelope
+    }
+}
+
+/// The envelope of a JSON-RPC response.
+#[derive(Deserialize)]
+struct JsonRpcResponse {
+    #[serde(default)]
+    id : Value,
+    #[serde(default)]
+    result : Option<Value>,
+    #[serde(default)]
+    error : Option<JsonRpcError>,
+}
+
+/// Calls the methods of [JSON-RPC 2.0](https://www.jsonrpc.org/specification) servers, such as Ethereum nodes, wrapping
+/// them in the envelope of the protocol and matching the responses to their calls.
+///
+/// Implemented like the other traits, on top of a [RequestHandler] for `serde_json::Value` :
+///
+/// ```rust ignore
+/// impl RequestHandler<Value,Value,ApiError> for MyAPIClient {}
+/// impl JsonRpcHandler<ApiError> for MyAPIClient {}
+///
+/// let block : String = api_client.json_rpc_call("eth_blockNumber",&()).await?;
+/// ```
+///
+/// An `error` object fails with [RequestError::JsonRpc], whatever the HTTP status, while unsuccessful responses without a
+/// JSON-RPC envelope are handled like in [RequestHandler::request_map].
+#[cfg_attr(not(feature = "wasm"),async_trait)]
+#[cfg_attr(feature = "wasm",async_trait(?Send))]
+pub trait JsonRpcHandler<E : DeserializeOwned> : RequestHandler<Value,Value,E> {
+    /// Returns the endpoint JSON-RPC requests are sent to, relative to the base URL.
+    ///
+    /// # Returns
+    ///
+    /// `""` by default, the base URL itself.
+    fn json_rpc_endpoint(&self) -> &str {
+        ""
+    }
+
+    /// Returns the id of the next request, matching its response.
+    ///
+    /// # Returns
+    ///
+    /// A number unique within the process by default.
+    fn next_json_rpc_id(&self) -> Value {
+        Value::from(NEXT_ID.fetch_add(1,Ordering::Relaxed))
+    }
+
+    /// Modifies the provided `RequestBuilder` with default settings for a JSON-RPC request.
+    ///
+    /// The request is a POST to [JsonRpcHandler::json_rpc_endpoint] carrying the given envelope, or array of envelopes.
+    ///
+    /// # Returns
+    ///
+    /// The modified `RequestBuilder` with default settings applied, or the error raised while serializing the envelope.
+    fn default_json_rpc_requestor<B : Serialize + ?Sized>(&self,body : &B) -> Result<reqwest::RequestBuilder,serde_json::Error> {
+        let body = serde_json::to_vec(body)?;
+        // The base URL is used as it is, as JSON-RPC servers such as `https://mainnet.infura.io/v3/{key}` may not accept a trailing slash
+        let request = match self.json_rpc_endpoint() {
+            "" => base_url_requestor(self,Method::POST,self.base_url().to_owned(),&HashMap::new()),
+            endpoint => base_requestor(self,Method::POST,endpoint,&HashMap::new())
+        };
+        let request = request.header(CONTENT_TYPE,"application/json");
+        Ok(finish_request(self,self.apply_body(request,body)))
+    }
+
+    /// Calls a method and deserializes its `result` into `R`.
+    ///
+    /// # Arguments
+    ///
+    /// * `method` - The method called.
+    /// * `params` - The params of the call, usually a tuple or a struct, left out when serialized to `null` such as `&()`.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the `result` of the call or an `RequestError` variant.
+    async fn json_rpc_call<P : Serialize + ?Sized + MaybeSync,R : DeserializeOwned>(&self,method : &str,params : &P) -> Result<R,RequestError<E>> {
+        let call = JsonRpcCall::new(method,params).map_err(|error| RequestError::InvalidRequestBody(Box::new(error)))?;
+        let request = self.default_json_rpc_requestor(&call.envelope(self.next_json_rpc_id())).map_err(|error| RequestError::InvalidRequestBody(Box::new(error)))?;
+        let response = self.request_raw(request).await?;
+
+        let context = || Box::new(response.meta.context());
+        match serde_json::from_slice::<JsonRpcResponse>(&response.body) {
+            Ok(JsonRpcResponse { error : Some(error), .. }) => Err(RequestError::JsonRpc { error, context : context() }),
+            Ok(JsonRpcResponse { result, .. }) if response.meta.status.is_success() => {
+                serde_json::from_value(result.unwrap_or(Value::Null)).map_err(|source| RequestError::InvalidJsonBody { source, context : Some(context()) })
+            },
+            _ => Err(parse_error_payload(&response))
+        }
+    }
+
+    /// Sends a notification, a call without id which the server does not answer.
+    ///
+    /// # Arguments
+    ///
+    /// * `method` - The method called.
+    /// * `params` - The params of the call, left out when serialized to `null`.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which is `Ok` once the server accepted the notification, or an `RequestError` variant.
+    async fn json_rpc_notify<P : Serialize + ?Sized + MaybeSync>(&self,method : &str,params : &P) -> Result<(),RequestError<E>> {
+        let call = JsonRpcCall::new(method,params).map_err(|error| RequestError::InvalidRequestBody(Box::new(error)))?.notification();
+        let request = self.default_json_rpc_requestor(&call.envelope(Value::Null)).map_err(|error| RequestError::InvalidRequestBody(Box::new(error)))?;
+        let response = self.request_raw(request).await?;
+        match response.meta.status.is_success() {
+            true => Ok(()),
+            false => Err(parse_error_payload(&response))
+        }
+    }
+
+    /// Sends several calls in a single batch request.
+    ///
+    /// The responses, which servers may send in any order, are matched to their calls through their id. Calls answered
+    /// with an `error` object yield a [RequestError::JsonRpc], and calls left unanswered an `InvalidJsonBody` error, while
+    /// notifications always yield `Ok(Value::Null)`. An empty batch is not sent.
+    ///
+    /// ```rust ignore
+    /// let calls = [JsonRpcCall::without_params("eth_blockNumber"),JsonRpcCall::without_params("eth_gasPrice")];
+    /// let results = api_client.json_rpc_batch(&calls).await?;
+    /// for result in results {
+    ///     let value : String = serde_json::from_value(result?)?;
+    /// }
+    /// ```
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the result of every call in the order of `calls`, or the `RequestError` the batch failed with.
+    async fn json_rpc_batch(&self,calls : &[JsonRpcCall]) -> Result<Vec<Result<Value,RequestError<E>>>,RequestError<E>> {
+        if calls.is_empty() {
+            return Ok(Vec::new());
+        }
+        let ids : Vec<Value> = calls.iter().map(|call| match call.notification {
+            true => Value::Null,
+            false => self.next_json_rpc_id()
+        }).collect();
+        let envelopes : Vec<Value> = calls.iter().zip(&ids).map(|(call,id)| call.envelope(id.clone())).collect();
+        let request = self.default_json_rpc_requestor(&envelopes).map_err(|error| RequestError::InvalidRequestBody(Box::new(error)))?;
+        let response = self.request_raw(request).await?;
+
+        let context = || Box::new(response.meta.context());
+        let mut responses : HashMap<String,JsonRpcResponse> = match serde_json::from_slice::<Vec<JsonRpcResponse>>(&response.body) {
+            Ok(responses) => responses.into_iter().map(|response| (response.id.to_string(),response)).collect(),
+            // Batches made only of notifications are not answered
+            Err(_) if response.body.iter().all(u8::is_ascii_whitespace) && response.meta.status.is_success() => HashMap::new(),
+            // Batches the server could not read are answered with a single error
+            Err(error) => return Err(match serde_json::from_slice::<JsonRpcResponse>(&response.body) {
+                Ok(JsonRpcResponse { error : Some(error), .. }) => RequestError::JsonRpc { error, context : context() },
+                _ if !response.meta.status.is_success() => parse_error_payload(&response),
+                _ => RequestError::InvalidJsonBody { source : error, context : Some(context()) }
+            })
+        };
+
+        Ok(ids.iter().map(|id| match id.is_null() {
+            true => Ok(Value::Null),
+            false => match responses.remove(&id.to_string()) {
+                Some(JsonRpcResponse { error : Some(error), .. }) => Err(RequestError::JsonRpc { error, context : context() }),
+                Some(JsonRpcResponse { result, .. }) => Ok(result.unwrap_or(Value::Null)),
+                None => Err(RequestError::InvalidJsonBody {
+                    source : serde::de::Error::custom(format!("no response to the call with id {id}")),
+                    context : Some(context())
+                })
+            }
+        }).collect())
+    }
+}
@@ -0,0 +1,151 @@
+use std::error::Error;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD as BASE64_URL;
+
+use serde_json::{Map, Value};
+
+use crate::hash;
+
+/// Signs the JSON Web Tokens built by [JwtAssertion].
+///
+/// [Hs256Signer] signs with a shared secret. Asymmetric algorithms such as `RS256` or `ES256`, used by Google service
+/// accounts and GitHub Apps, are implemented on top of a cryptography crate :
+///
+/// ```rust ignore
+/// struct Rs256Signer(rsa::pkcs1v15::SigningKey<sha2::Sha256>);
+///
+/// impl JwtSigner for Rs256Signer {
+///     fn algorithm(&self) -> &str {
+///         "RS256"
+///     }
+///
+///     fn sign(&self,message : &[u8]) -> Result<Vec<u8>,Box<dyn Error + Send + Sync>> {
+///         Ok(self.0.try_sign(message)?.to_vec())
+///     }
+/// }
+/// ```
+pub trait JwtSigner : Send + Sync {
+    /// Returns the `alg` of the header of the token, such as `RS256`.
+    fn algorithm(&self) -> &str;
+
+    /// Signs the encoded header and claims of the token.
+    ///
+    /// # Returns
+    ///
+    /// The raw signature, or the error preventing signing.
+    fn sign(&self,message : &[u8]) -> Result<Vec<u8>,Box<dyn Error + Send + Sync>>;
+}
+
+/// Signs tokens with HMAC-SHA256.
+pub struct Hs256Signer {
+    secret : Vec<u8>,
+}
+
+impl Hs256Signer {
+    /// Creates a signer with the given secret.
+    pub fn new(secret : impl Into<Vec<u8>>) -> Self {
+        Self { secret : secret.into() }
+    }
+}
+
+impl JwtSigner for Hs256Signer {
+    fn algorithm(&self) -> &str {
+        "HS256"
+    }
+
+    fn sign(&self,message : &[u8]) -> Result<Vec<u8>,Box<dyn Error + Send + Sync>> {
+        Ok(hash::hmac_sha256(&self.secret,message).to_vec())
+    }
+}
+
+/// Builds signed JSON Web Tokens (RFC 7519) to authenticate a client without a shared secret.
+///
+/// Give it to [crate::OAuth2TokenProvider::jwt_bearer] to exchange it for access tokens with the JWT bearer grant
+/// (RFC 7523), as Google service accounts do, or send [JwtAssertion::build] directly as a bearer token where the API
+/// accepts it, as GitHub Apps do.
+///
+/// ```rust ignore
+/// let assertion = JwtAssertion::new(Rs256Signer(key),service_account_email)
+///     .audience("https://oauth2.googleapis.com/token")
+///     .claim("scope","https://www.googleapis.com/auth/cloud-platform");
+/// ```
+pub struct JwtAssertion {
+    signer : Box<dyn JwtSigner>,
+    key_id : Option<String>,
+    claims : Map<String,Value>,
+    lifetime : Duration,
+}
+
+// The signer is left out
+impl std::fmt::Debug for JwtAssertion {
+    fn fmt(&self,f : &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JwtAssertion")
+            .field("algorithm",&self.signer.algorithm())
+            .field("key_id",&self.key_id)
+            .field("claims",&self.claims)
+            .field("lifetime",&self.lifetime)
+            .finish_non_exhaustive()
+    }
+}
+
+impl JwtAssertion {
+    /// Creates an assertion signed by the given signer, whose `iss` claim is the given issuer, valid for an hour.
+    pub fn new(signer : impl JwtSigner + 'static,issuer : impl Into<String>) -> Self {
+        let mut claims = Map::new();
+        claims.insert("iss".to_owned(),Value::String(issuer.into()));
+        Self { signer : Box::new(signer), key_id : None, claims, lifetime : Duration::from_secs(3600) }
+    }
+
+    /// Sets the `sub` claim, the principal the token is about.
+    pub fn subject(self,subject : impl Into<String>) -> Self {
+        self.claim("sub",subject.into())
+    }
+
+    /// Sets the `aud` claim, usually the token endpoint.
+    pub fn audience(self,audience : impl Into<String>) -> Self {
+        self.claim("aud",audience.into())
+    }
+
+    /// Sets a claim, such as the `scope` some providers expect in the token.
+    pub fn claim(mut self,name : impl Into<String>,value : impl Into<Value>) -> Self {
+        self.claims.insert(name.into(),value.into());
+        self
+    }
+
+    /// Sets the `kid` of the header, identifying the key the token is signed with.
+    pub fn key_id(mut self,key_id : impl Into<String>) -> Self {
+        self.key_id = Some(key_id.into());
+        self
+    }
+
+    /// Sets how long built tokens are valid, their `exp` claim being this long after their `iat` claim, an hour by default.
+    pub fn lifetime(mut self,lifetime : Duration) -> Self {
+        self.lifetime = lifetime;
+        self
+    }
+
+    /// Builds and signs a token issued now.
+    ///
+    /// # Returns
+    ///
+    /// The compact serialization of the token, or the error of the signer.
+    pub fn build(&self) -> Result<String,Box<dyn Error + Send + Sync>> {
+        let mut header = Map::new();
+        header.insert("alg".to_owned(),Value::from(self.signer.algorithm()));
+        header.insert("typ".to_owned(),Value::from("JWT"));
+        if let Some(key_id) = &self.key_id {
+            header.insert("kid".to_owned(),Value::from(key_id.as_str()));
+        }
+
+        let issued_at = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        let mut claims = self.claims.clone();
+        claims.insert("iat".to_owned(),Value::from(issued_at));
+        claims.insert("exp".to_owned(),Value::from(issued_at + self.lifetime.as_secs()));
+
+        let message = format!("{}.{}",BASE64_URL.encode(serde_json::to_vec(&header)?),BASE64_URL.encode(serde_json::to_vec(&claims)?));
+        let signature = self.signer.sign(message.as_bytes())?;
+        Ok(format!("{message}.{}",BASE64_URL.encode(signature)))
+    }
+}
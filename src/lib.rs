@@ -1,36 +1,368 @@
 #![doc = include_str!("../README.md")]
 #![forbid(missing_docs)]
 
+#[cfg(all(target_arch = "wasm32",not(feature = "wasm")))]
+compile_error!("the `wasm` feature must be enabled when targeting wasm32, as browser futures are not `Send`");
+
 use std::collections::HashMap;
 
 use reqwest::{
     Client,
+    Method,
     RequestBuilder,
+    StatusCode,
+    header::{HeaderMap, HeaderName, HeaderValue, ACCEPT, ACCESS_CONTROL_ALLOW_METHODS, ALLOW, CONTENT_TYPE, USER_AGENT},
 }; 
 
 use serde_json::Value;
-use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde::de::{DeserializeOwned, DeserializeSeed};
 
 use async_trait::async_trait;
 
+use bytes::Bytes;
+use futures_util::Stream;
+
 use thiserror::Error as ErrorMacro;
 
+mod auth;
+#[cfg(not(target_arch = "wasm32"))]
+mod bulkhead;
+#[cfg(not(target_arch = "wasm32"))]
+mod cache;
+mod cancellation;
+#[cfg(not(target_arch = "wasm32"))]
+mod coalesce;
+#[cfg(not(target_arch = "wasm32"))]
+mod circuit_breaker;
+#[cfg(not(target_arch = "wasm32"))]
+mod client_builder;
+#[cfg(any(feature = "gzip",feature = "deflate",feature = "zstd"))]
+mod compression;
+mod config;
+mod endpoint;
+mod envelope;
+mod environment;
+#[cfg(not(target_arch = "wasm32"))]
+mod failover;
+mod format;
+mod macros;
+mod graphql;
+#[cfg(not(target_arch = "wasm32"))]
+mod hash;
+#[cfg(not(target_arch = "wasm32"))]
+mod hedging;
+mod hypermedia;
+#[cfg(feature = "jsonapi")]
+mod jsonapi;
+mod jsonrpc;
+mod maintenance;
+pub mod logging;
+#[cfg(all(feature = "jwt",not(target_arch = "wasm32")))]
+mod jwt;
+mod middleware;
+#[cfg(not(target_arch = "wasm32"))]
+mod metrics;
+#[cfg(all(feature = "oauth1",not(target_arch = "wasm32")))]
+mod oauth1;
+#[cfg(not(target_arch = "wasm32"))]
+mod oauth2;
+#[cfg(not(target_arch = "wasm32"))]
+mod offline;
+mod odata;
+mod pagination;
+#[cfg(all(feature = "pinning",not(target_arch = "wasm32")))]
+mod pinning;
+mod parser;
+mod propagation;
+#[cfg(not(target_arch = "wasm32"))]
+mod proxy;
+#[cfg(not(target_arch = "wasm32"))]
+mod rate_limit;
+mod response;
+mod routes;
+#[cfg(not(target_arch = "wasm32"))]
+mod scheduler;
+#[cfg(not(target_arch = "wasm32"))]
+mod session;
+#[cfg(not(target_arch = "wasm32"))]
+mod signing;
+#[cfg(all(feature = "aws-sigv4",not(target_arch = "wasm32")))]
+mod sigv4;
+#[cfg(feature = "schema")]
+mod schema;
+#[cfg(not(target_arch = "wasm32"))]
+mod retry;
+#[cfg(not(target_arch = "wasm32"))]
+mod sse;
+#[cfg(feature = "tracing")]
+mod telemetry;
+#[cfg(all(feature = "mtls",not(target_arch = "wasm32")))]
+mod tls;
+#[cfg(not(target_arch = "wasm32"))]
+mod transport;
+mod versioning;
+#[cfg(not(target_arch = "wasm32"))]
+mod webhook;
+
+#[cfg(all(feature = "blocking",not(target_arch = "wasm32")))]
+pub mod blocking;
+
+#[cfg(all(feature = "har",not(target_arch = "wasm32")))]
+pub mod har;
+
+#[cfg(all(feature = "websocket",not(target_arch = "wasm32")))]
+pub mod websocket;
+
+#[cfg(feature = "testing")]
+pub mod testing;
+
+pub use auth::{ApiKeyAuth, ApiKeyLocation};
+#[cfg(not(target_arch = "wasm32"))]
+pub use auth::{AuthRefresh, DigestAuth};
+#[cfg(not(target_arch = "wasm32"))]
+pub use bulkhead::Bulkhead;
+#[cfg(not(target_arch = "wasm32"))]
+pub use cache::{ResponseCache, CacheStore, CacheEntry, MemoryStore, DiskStore};
+pub use cancellation::RequestCanceller;
+pub use tokio_util::sync::CancellationToken;
+#[cfg(not(target_arch = "wasm32"))]
+pub use coalesce::RequestCoalescer;
+#[cfg(not(target_arch = "wasm32"))]
+pub use circuit_breaker::{CircuitBreaker, CircuitState};
+#[cfg(not(target_arch = "wasm32"))]
+pub use client_builder::{ApiClientBuilder, shared_client};
+#[cfg(any(feature = "gzip",feature = "deflate",feature = "zstd"))]
+pub use compression::{BodyCompression, ContentCoding};
+pub use config::ClientConfig;
+pub use endpoint::Endpoint;
+pub use envelope::Envelope;
+pub use environment::{ApiEnvironment, UnknownEnvironment};
+#[cfg(not(target_arch = "wasm32"))]
+pub use failover::HostPool;
+pub use format::ContentFormat;
+#[cfg(feature = "csv")]
+pub use format::CsvOptions;
+pub use graphql::{GraphQLError, GraphQLLocation};
+#[cfg(not(target_arch = "wasm32"))]
+pub use hedging::HedgePolicy;
+pub use hypermedia::{FollowLinks, Links, Link, Linked};
+use graphql::GraphQLEnvelope;
+#[cfg(feature = "jsonapi")]
+pub use jsonapi::{JsonApiError, JsonApiQuery};
+pub use jsonrpc::{JsonRpcHandler, JsonRpcCall, JsonRpcError};
+pub use maintenance::MaintenanceDetector;
+#[cfg(all(feature = "jwt",not(target_arch = "wasm32")))]
+pub use jwt::{JwtAssertion, JwtSigner, Hs256Signer};
+pub use logging::LoggingMiddleware;
+pub use middleware::Middleware;
+#[cfg(not(target_arch = "wasm32"))]
+pub use metrics::{MetricsSink, RequestMetrics, Outcome};
+#[cfg(all(feature = "metrics",not(target_arch = "wasm32")))]
+pub use metrics::MetricsCrateSink;
+#[cfg(all(feature = "oauth1",not(target_arch = "wasm32")))]
+pub use oauth1::OAuth1Signer;
+#[cfg(not(target_arch = "wasm32"))]
+pub use oauth2::OAuth2TokenProvider;
+#[cfg(not(target_arch = "wasm32"))]
+pub use offline::{OfflineQueue, QueuedRequest, ConflictResolution};
+pub use odata::{ODataQuery, ODataPage};
+pub use pagination::{PaginatedRequestHandler, PageConfig, DEFAULT_MAX_PAGES};
+#[cfg(all(feature = "pinning",not(target_arch = "wasm32")))]
+pub use pinning::CertificatePins;
+#[cfg(all(feature = "pinning",not(target_arch = "wasm32")))]
+use pinning::PinMismatch;
+pub use parser::{ResponseParser, JsonParser, StatusErrorMapper};
+#[cfg(feature = "simd")]
+pub use parser::SIMD_THRESHOLD;
+pub use propagation::{TracePropagator, TraceContext};
+#[cfg(not(target_arch = "wasm32"))]
+pub use proxy::{ProxyConfig, ProxyScope};
+#[cfg(feature = "xml")]
+pub use parser::XmlParser;
+#[cfg(feature = "msgpack")]
+pub use parser::MessagePackParser;
+#[cfg(feature = "cbor")]
+pub use parser::CborParser;
+#[cfg(not(target_arch = "wasm32"))]
+pub use rate_limit::{RateLimiter, DeadlineExceeded};
+pub use routes::RouteDefaults;
+pub use response::{RawResponse, ResponseMeta, RequestContext, CacheStatus, RateLimitState};
+#[cfg(feature = "schema")]
+pub use schema::{ResponseValidator, SchemaValidator, SchemaViolation};
+#[cfg(not(target_arch = "wasm32"))]
+pub use retry::{RetryPolicy, RetryConfig, BackoffStrategy};
+#[cfg(not(target_arch = "wasm32"))]
+pub use scheduler::{RequestScheduler, Priority};
+#[cfg(not(target_arch = "wasm32"))]
+pub use session::{CookieJar, Cookie};
+#[cfg(not(target_arch = "wasm32"))]
+pub use signing::{RequestSigner, HmacSigner};
+#[cfg(all(feature = "aws-sigv4",not(target_arch = "wasm32")))]
+pub use sigv4::AwsSigV4Signer;
+#[cfg(not(target_arch = "wasm32"))]
+pub use sse::{EventStreamHandler, ServerSentEvent};
+#[cfg(all(feature = "mtls",not(target_arch = "wasm32")))]
+pub use tls::{TlsConfig, ClientBuilderExt};
+#[cfg(not(target_arch = "wasm32"))]
+pub use transport::HttpTransport;
+pub use versioning::{ApiVersion, VersionLocation};
+#[cfg(not(target_arch = "wasm32"))]
+pub use webhook::{WebhookVerifier, SignatureEncoding, WebhookError};
+
+#[cfg(feature = "derive")]
+pub use api_request_utils_derive::ApiClient;
+
 pub use reqwest;
+pub use http;
 pub use serde_json;
 pub use serde;
 pub use ::async_trait;
 
+/// Marker for values that must be `Send`, unless the `wasm` feature is enabled.
+///
+/// Browser futures are not `Send`, so under the `wasm` feature this bound (and the `Send` bound on the futures of
+/// [RequestHandler]) is relaxed and implemented by every type.
+#[cfg(not(feature = "wasm"))]
+pub trait MaybeSend : Send {}
+
+#[cfg(not(feature = "wasm"))]
+impl<T : Send + ?Sized> MaybeSend for T {}
+
+/// Marker for values that must be `Send`, unless the `wasm` feature is enabled.
+///
+/// Browser futures are not `Send`, so under the `wasm` feature this bound (and the `Send` bound on the futures of
+/// [RequestHandler]) is relaxed and implemented by every type.
+#[cfg(feature = "wasm")]
+pub trait MaybeSend {}
+
+#[cfg(feature = "wasm")]
+impl<T : ?Sized> MaybeSend for T {}
+
+/// Marker for values that must be `Sync`, unless the `wasm` feature is enabled.
+#[cfg(not(feature = "wasm"))]
+pub trait MaybeSync : Sync {}
+
+#[cfg(not(feature = "wasm"))]
+impl<T : Sync + ?Sized> MaybeSync for T {}
+
+/// Marker for values that must be `Sync`, unless the `wasm` feature is enabled.
+#[cfg(feature = "wasm")]
+pub trait MaybeSync {}
+
+#[cfg(feature = "wasm")]
+impl<T : ?Sized> MaybeSync for T {}
+
 /// Trait to provide some basic info about API
 pub trait RequestInfo {
-    /// The base URL for the requests.
-    const BASE_URL : &'static str;
+    /// The base URL for the requests, used by [RequestInfo::base_url] unless it is overridden.
+    ///
+    /// Empty by default, in which case [RequestInfo::base_url] must be overridden.
+    const BASE_URL : &'static str = "";
+
+    /// Returns the base URL for the requests, which can be decided at runtime to switch between environments or regions.
+    ///
+    /// # Returns
+    ///
+    /// The base URL of the [RequestInfo::environment] if any, otherwise [RequestInfo::BASE_URL], by default.
+    fn base_url(&self) -> &str {
+        self.environment().map_or(Self::BASE_URL,ApiEnvironment::base_url)
+    }
+
+    /// Returns the [ApiEnvironment] the client talks to, such as a sandbox or production deployment, if any.
+    ///
+    /// Its base URL, default headers and API key are then used by [RequestInfo::base_url], [RequestDefaults::config] and
+    /// [RequestDefaults::api_key_auth], unless they are overridden.
+    ///
+    /// # Returns
+    ///
+    /// `None` by default.
+    fn environment(&self) -> Option<&ApiEnvironment> {
+        None
+    }
+
+    /// Returns the [HostPool] of the mirrors the requests built against the [RequestInfo::base_url] are spread over and
+    /// fail over between, if any.
+    ///
+    /// Not available on `wasm32`.
+    ///
+    /// # Returns
+    ///
+    /// `None` by default, meaning requests are sent to the base URL only.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn host_pool(&self) -> Option<&HostPool> {
+        None
+    }
+
+    /// Returns the [CertificatePins] the certificates of the API hosts must match, if any, checked during the TLS handshake
+    /// by the clients built from [RequestInfo::default_client_builder].
+    ///
+    /// Requires the `pinning` feature. Not available on `wasm32`.
+    ///
+    /// # Returns
+    ///
+    /// `None` by default, meaning any certificate the TLS backend trusts is accepted.
+    #[cfg(all(feature = "pinning",not(target_arch = "wasm32")))]
+    fn certificate_pins() -> Option<CertificatePins> where Self : Sized {
+        None
+    }
+
+    /// Returns the [reqwest::Client] instance associated with the API client.
+    ///
+    /// The client is used to send HTTP requests to the API.
+    ///
+    /// # Returns
+    ///
+    /// The [shared_client] of the process by default, built lazily, except on `wasm32` where it must be implemented.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn client(&self) -> &Client {
+        shared_client()
+    }
 
     /// Returns the [reqwest::Client] instance associated with the API client.
     ///
     /// The client is used to send HTTP requests to the API.
+    #[cfg(target_arch = "wasm32")]
     fn client(&self) -> &Client;
+
+    /// Creates a `ClientBuilder` with the defaults used by [RequestInfo::default_client], to be customised further.
+    ///
+    /// It sets [DEFAULT_USER_AGENT] and a [DEFAULT_TIMEOUT] (ignored on `wasm32`), and checks the
+    /// [RequestInfo::certificate_pins] if any. Responses are decompressed according to the compression features compiled
+    /// in (`gzip`, `brotli` and `deflate`).
+    fn default_client_builder() -> reqwest::ClientBuilder where Self : Sized {
+        let builder = Client::builder().user_agent(DEFAULT_USER_AGENT);
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let builder = builder.timeout(DEFAULT_TIMEOUT);
+
+        #[cfg(all(feature = "pinning",not(target_arch = "wasm32")))]
+        let builder = match Self::certificate_pins() {
+            Some(pins) => pins.apply(builder),
+            None => builder
+        };
+
+        builder
+    }
+
+    /// Creates a [reqwest::Client] with sensible defaults, see [RequestInfo::default_client_builder].
+    ///
+    /// Implementors can call it in their constructor instead of `Client::new()`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the TLS backend cannot be initialized, like `Client::new()`.
+    fn default_client() -> Client where Self : Sized {
+        Self::default_client_builder().build().expect("the default client should build")
+    }
 }
 
+/// The `User-Agent` sent by clients built with [RequestInfo::default_client], made of the crate name and version.
+pub const DEFAULT_USER_AGENT : &str = concat!(env!("CARGO_PKG_NAME"),"/",env!("CARGO_PKG_VERSION"));
+
+/// The timeout of clients built with [RequestInfo::default_client].
+pub const DEFAULT_TIMEOUT : std::time::Duration = std::time::Duration::from_secs(30);
+
 /// This trait provides methods for modifying the struct in a specific way:
 pub trait RequestModifiers: RequestInfo  {
     /// Joins the given endpoint with the base URL.
@@ -42,10 +374,26 @@ pub trait RequestModifiers: RequestInfo  {
     /// # Returns
     ///
     /// The joined URL as a `String`.
+    #[deprecated(note = "use `endpoint_url`, which honours the runtime `base_url` instead of the `BASE_URL` constant, empty by default")]
     fn create_endpoint(endpoint : &str) -> String {
         format!("{}/{}",Self::BASE_URL,endpoint)
     }
 
+    /// Joins the given endpoint with the [RequestInfo::base_url], as done by the default requestors.
+    ///
+    /// Unlike [RequestModifiers::create_endpoint], which only knows about [RequestInfo::BASE_URL], this honours a base URL decided at runtime.
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint` - The endpoint to join with the base URL.
+    ///
+    /// # Returns
+    ///
+    /// The joined URL as a `String`.
+    fn endpoint_url(&self,endpoint : &str) -> String {
+        format!("{}/{}",self.base_url(),endpoint)
+    }
+
     /// Conditionally adds a header to the given `RequestBuilder` based on the result of a closure.
     ///
     /// If the closure returns `true`, the specified header with the provided `key` and `value`
@@ -58,7 +406,7 @@ pub trait RequestModifiers: RequestInfo  {
     /// * `key` - The key of the header to be added.
     /// * `value` - The value of the header to be added.
     /// * `closure` - A closure that determines whether the header should be added. It should
-    ///               take no arguments and return a boolean value.
+    ///   take no arguments and return a boolean value.
     ///
     /// # Returns
     ///
@@ -70,6 +418,103 @@ pub trait RequestModifiers: RequestInfo  {
             false => request_builder
         }
     }
+
+    /// Adds a header to the given `RequestBuilder` whose value is produced lazily by a closure.
+    ///
+    /// Unlike [RequestModifiers::add_header_if], the value does not need to be computed up front, which avoids
+    /// wasted work for expensive values such as request signatures or timestamps. If the closure returns `None`,
+    /// no changes will be made to the request.
+    ///
+    /// # Arguments
+    ///
+    /// * `request_builder` - The `RequestBuilder` to add the header to.
+    /// * `key` - The key of the header to be added.
+    /// * `closure` - A closure that produces the value of the header, or `None` if the header should not be added.
+    ///
+    /// # Returns
+    ///
+    /// The modified `RequestBuilder` with the header added if the closure returns `Some`,
+    /// otherwise the original `RequestBuilder` without any modifications.
+    fn add_header_with(request_builder: RequestBuilder,key: &str,closure : impl FnOnce() -> Option<String>) -> RequestBuilder {
+        match closure() {
+            Some(value) => request_builder.header(key, value),
+            None => request_builder
+        }
+    }
+
+    /// Conditionally adds a typed header to the given `RequestBuilder`, like [RequestModifiers::add_header_if].
+    ///
+    /// Taking a [HeaderName] and a [HeaderValue] means they are validated when created, and lets constants such as
+    /// `CONTENT_TYPE` or `AUTHORIZATION` be reused.
+    ///
+    /// # Arguments
+    ///
+    /// * `request_builder` - The `RequestBuilder` to add the header to.
+    /// * `key` - The name of the header to be added.
+    /// * `value` - The value of the header to be added.
+    /// * `closure` - A closure that determines whether the header should be added.
+    ///
+    /// # Returns
+    ///
+    /// The modified `RequestBuilder` with the header added if the closure returns `true`,
+    /// otherwise the original `RequestBuilder` without any modifications.
+    fn add_typed_header_if(request_builder: RequestBuilder,key : HeaderName,value : HeaderValue,closure : impl FnOnce() -> bool) -> RequestBuilder {
+        match closure() {
+            true => request_builder.header(key,value),
+            false => request_builder
+        }
+    }
+
+    /// Adds HTTP Basic authentication to the given `RequestBuilder`.
+    ///
+    /// # Arguments
+    ///
+    /// * `request_builder` - The `RequestBuilder` to authenticate.
+    /// * `username` - The name of the user.
+    /// * `password` - The password of the user.
+    ///
+    /// # Returns
+    ///
+    /// The `RequestBuilder` with the `Authorization` header added, marked as sensitive.
+    fn add_basic_auth(request_builder: RequestBuilder,username : &str,password : &str) -> RequestBuilder {
+        request_builder.basic_auth(username,Some(password))
+    }
+
+    /// Adds a header to the given `RequestBuilder` after validating its name and value.
+    ///
+    /// Headers added from strings with [RequestModifiers::add_header_if] are only validated when the request is sent, which
+    /// reports an opaque builder error. This method reports the offending header right away instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `request_builder` - The `RequestBuilder` to add the header to.
+    /// * `key` - The name of the header to be added.
+    /// * `value` - The value of the header to be added.
+    ///
+    /// # Returns
+    ///
+    /// The modified `RequestBuilder`, or `RequestError::InvalidHeader` if the name or the value is not a valid header.
+    fn try_add_header<E>(request_builder: RequestBuilder,key : &str,value : &str) -> Result<RequestBuilder,RequestError<E>> {
+        let invalid = || RequestError::InvalidHeader { name : key.to_owned() };
+        let name = HeaderName::from_bytes(key.as_bytes()).map_err(|_| invalid())?;
+        let value = HeaderValue::from_str(value).map_err(|_| invalid())?;
+        Ok(request_builder.header(name,value))
+    }
+
+    /// Attaches a buffered body to the given `RequestBuilder` together with an explicit `Content-Length` header.
+    ///
+    /// # Arguments
+    ///
+    /// * `request_builder` - The `RequestBuilder` to attach the body to.
+    /// * `body` - The body of the request.
+    ///
+    /// # Returns
+    ///
+    /// The modified `RequestBuilder` with the body and its `Content-Length` set.
+    fn add_sized_body(request_builder: RequestBuilder,body : impl Into<Vec<u8>>) -> RequestBuilder {
+        let body = body.into();
+        request_builder.header(reqwest::header::CONTENT_LENGTH,body.len()).body(body)
+    }
 }
 
 /// The RequestDefaults trait provides default methods for configuring and modifying HTTP requests.
@@ -87,6 +532,70 @@ pub trait RequestDefaults: RequestModifiers {
         request_builder
     }
 
+    /// The `User-Agent` sent with every request built by the default requestors, identifying the application.
+    ///
+    /// # Returns
+    ///
+    /// `None` by default, leaving the `User-Agent` of the [reqwest::Client] in place.
+    fn user_agent(&self) -> Option<&str> {
+        None
+    }
+
+    /// The [ContentFormat] of the typed bodies sent by [RequestHandler::encode_body], also requested with the default
+    /// [RequestDefaults::accept] header.
+    ///
+    /// # Returns
+    ///
+    /// [ContentFormat::Json] by default.
+    fn content_format(&self) -> ContentFormat {
+        ContentFormat::Json
+    }
+
+    /// The `Accept` header sent with every request built by the default requestors.
+    ///
+    /// # Returns
+    ///
+    /// The content type of the [RequestDefaults::content_format] by default, `application/json` unless overridden, or `None`
+    /// to send no `Accept` header.
+    fn accept(&self) -> Option<&str> {
+        Some(self.content_format().content_type())
+    }
+
+    /// The [ApiVersion] applied to every request built by the default requestors, as a path prefix, a header or a query
+    /// parameter.
+    ///
+    /// # Returns
+    ///
+    /// `None` by default, meaning the version, if any, is part of the endpoints.
+    fn api_version(&self) -> Option<ApiVersion> {
+        None
+    }
+
+    /// Sends a request built by a default requestor with another version than the [RequestDefaults::api_version],
+    /// replacing the one it was built with.
+    ///
+    /// ```rust ignore
+    /// let request = api_client.default_get_query_requestor("reports",&());
+    /// let reports = api_client.request_map(api_client.with_api_version(request,&ApiVersion::path("v3"))?,|reports| reports).await?;
+    /// ```
+    ///
+    /// Not available on `wasm32`.
+    ///
+    /// # Returns
+    ///
+    /// The request with the version replaced, or the error building it, such as an invalid header value.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn with_api_version(&self,request_builder : reqwest::RequestBuilder,version : &ApiVersion) -> reqwest::Result<reqwest::RequestBuilder> {
+        let (client,request) = request_builder.build_split();
+        let mut request = request?;
+        version.replace(self.api_version().as_ref(),self.base_url(),&mut request);
+        // Replacing a media type version removes the `Accept` header
+        if let (false,Some(accept)) = (request.headers().contains_key(ACCEPT),self.accept().and_then(|accept| HeaderValue::from_str(accept).ok())) {
+            request.headers_mut().insert(ACCEPT,accept);
+        }
+        Ok(reqwest::RequestBuilder::from_parts(client,request))
+    }
+
     /// Modifies the provided `RequestBuilder` with default parameters.
     ///
     /// # Arguments
@@ -100,6 +609,49 @@ pub trait RequestDefaults: RequestModifiers {
         request_builder
     }
 
+    /// Returns query parameters sent with every request, such as an `api_key` or `api_version`.
+    ///
+    /// These are merged with the per-request parameters in the default requestors, with the per-request
+    /// parameters taking precedence on conflicting keys.
+    ///
+    /// # Returns
+    ///
+    /// The default query parameters, empty by default.
+    fn default_query(&self) -> HashMap<&str,Value> {
+        HashMap::new()
+    }
+
+    /// Modifies the provided `RequestBuilder` with the defaults of the endpoint it is built for, such as the auth scope,
+    /// `Accept` header or API version of a route.
+    ///
+    /// Applied by the default requestors after [RequestDefaults::default_headers], [RequestDefaults::default_parameters]
+    /// and [RequestDefaults::config], so headers set with `RequestBuilder::headers` replace theirs. Requests to URLs not
+    /// below the [RequestInfo::base_url], such as absolute links, are left as they are.
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint` - The endpoint of the request, relative to the base URL.
+    /// * `request_builder` - The `RequestBuilder` to modify.
+    ///
+    /// # Returns
+    ///
+    /// The `RequestBuilder` with the [RequestDefaults::route_defaults] applied by default.
+    fn endpoint_defaults(&self,endpoint : &str,request_builder : reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match self.route_defaults() {
+            Some(routes) => routes.apply(endpoint,request_builder),
+            None => request_builder
+        }
+    }
+
+    /// Returns the [RouteDefaults] adding headers and query parameters to the endpoints below given path prefixes, if any.
+    ///
+    /// # Returns
+    ///
+    /// `None` by default, meaning every endpoint gets the same defaults.
+    fn route_defaults(&self) -> Option<&RouteDefaults> {
+        None
+    }
+
      /// Modifies the provided `RequestBuilder` with default settings for post request.
     ///
     /// # Arguments
@@ -111,133 +663,3458 @@ pub trait RequestDefaults: RequestModifiers {
     ///
     /// The modified `RequestBuilder` with default settings applied.
     fn default_post_requestor(&self,endpoint : &str, json : String) -> reqwest::RequestBuilder {
-        self.default_parameters(self.default_headers(self.client().post(Self::create_endpoint(endpoint)))).body(json)
+        let (request,body) = compress_body(self,base_requestor(self,Method::POST,endpoint,&HashMap::new()),json.into_bytes());
+        finish_request(self,Self::add_sized_body(request,body))
     }
 
-    /// Modifies the provided `RequestBuilder` with default settings for get request.
+    /// Modifies the provided `RequestBuilder` with default settings for a post request carrying an
+    /// `application/x-www-form-urlencoded` body, as expected by OAuth token endpoints and many older APIs.
     ///
     /// # Arguments
     ///
     /// * `endpoint` - The endpoint for the request.
-    /// * `parameters` - The Parameters for the request.
+    /// * `form` - The form fields, such as a struct or a slice of pairs, failing to serialize making the request fail once sent.
     ///
     /// # Returns
     ///
     /// The modified `RequestBuilder` with default settings applied.
-    fn default_get_requestor<'a>(&self,endpoint : &str,parameters : &HashMap<&'a str,Value>) -> reqwest::RequestBuilder {
-        self.default_parameters(self.default_headers(self.client().get(Self::create_endpoint(endpoint)))).query(&parameters)
+    fn default_form_post_requestor<F : Serialize + ?Sized>(&self,endpoint : &str,form : &F) -> reqwest::RequestBuilder {
+        finish_request(self,base_requestor(self,Method::POST,endpoint,&HashMap::new()).form(form))
     }
-}
 
+    /// Modifies the provided `RequestBuilder` with default settings for a request carrying a protobuf message, sent and
+    /// requested as `application/x-protobuf`, see [RequestHandler::request_protobuf].
+    ///
+    /// # Arguments
+    ///
+    /// * `method` - The HTTP method of the request.
+    /// * `endpoint` - The endpoint for the request.
+    /// * `body` - The message to send as the body of the request.
+    ///
+    /// # Returns
+    ///
+    /// The modified `RequestBuilder` with default settings applied.
+    #[cfg(feature = "protobuf")]
+    fn default_protobuf_requestor(&self,method : Method,endpoint : &str,body : &impl prost::Message) -> reqwest::RequestBuilder {
+        let headers = HeaderMap::from_iter([(ACCEPT,HeaderValue::from_static(PROTOBUF)),(CONTENT_TYPE,HeaderValue::from_static(PROTOBUF))]);
+        let request = base_requestor(self,method,endpoint,&HashMap::new()).headers(headers);
+        finish_request(self,self.apply_body(request,body.encode_to_vec()))
+    }
 
-/// A trait for handling HTTP requests.
-#[async_trait]
-pub trait RequestHandler<T : DeserializeOwned,O : DeserializeOwned,E : DeserializeOwned> : RequestDefaults {
-    /// Sends an HTTP request, processes the response, and maps it using the provided closure.
+    /// Modifies the provided `RequestBuilder` with default settings for a post request carrying a `multipart/form-data` body,
+    /// such as a file upload.
     ///
-    /// This asynchronous function sends an HTTP request using the given `reqwest::RequestBuilder`,
-    /// processes the response, and maps it using the provided closure. It returns the mapped
-    /// result if the request is successful, or an `RequestError::ErrorPayload` variant if the
-    /// request fails.
+    /// The form is streamed rather than buffered, so requests built this way are never retried (see [RequestDefaults::retry_policy]).
+    ///
+    /// ```rust ignore
+    /// let form = reqwest::multipart::Form::new()
+    ///     .text("channels","general")
+    ///     .part("file",reqwest::multipart::Part::bytes(report).file_name("report.csv").mime_str("text/csv")?);
+    /// let request = api_client.default_multipart_requestor("files.upload",form);
+    /// ```
     ///
     /// # Arguments
     ///
-    /// * `self` - A reference to the struct implementing this trait.
-    /// * `request` - The `reqwest::RequestBuilder` representing the request to be sent.
-    /// * `map` - A closure that maps the successful response JSON into the desired output type. Just write `|x| x` if the not mapping is required. 
+    /// * `endpoint` - The endpoint for the request.
+    /// * `form` - The multipart form to send, whose boundary sets the `Content-Type`.
     ///
     /// # Returns
     ///
-    /// A `Result` containing the mapped output type or an `RequestError` variant.
-    async fn request_map(request: reqwest::RequestBuilder,map : impl FnOnce(T) -> O + Send + Sync) -> Result<O,RequestError<E>> {
-        let response = request.send().await?;
-        let status = response.status();
-
-        let body = response.bytes().await?;
-        
-        match status.is_success() {
-            true => {
-                let json = serde_json::from_slice(&body)?;
-                Ok(map(json))
-            }
-            false => {
-                let json = serde_json::from_slice(&body)?;
-                Err(RequestError::ErrorPayload(json))
-            }
-        }
+    /// The modified `RequestBuilder` with default settings applied.
+    #[cfg(feature = "multipart")]
+    fn default_multipart_requestor(&self,endpoint : &str,form : reqwest::multipart::Form) -> reqwest::RequestBuilder {
+        finish_request(self,base_requestor(self,Method::POST,endpoint,&HashMap::new()).multipart(form))
     }
 
-    /// Resolves the error in the response and returns an option containing the value or `None`.
+    /// Creates a `RequestBuilder` for the given method and endpoint whose body is streamed from the given chunks, so large
+    /// uploads are never held in memory.
+    ///
+    /// Streamed bodies cannot be cloned, so requests built this way are never retried (see [RequestDefaults::retry_policy]).
+    /// Not available on `wasm32`.
     ///
     /// # Arguments
     ///
-    /// * `response` - The response as a `Result` type.
-    /// * `error_resolver` - The closure that handles the error and performs custom error handling.
+    /// * `method` - The HTTP method of the request.
+    /// * `endpoint` - The endpoint for the request.
+    /// * `body` - The stream of chunks making up the body.
+    /// * `content_length` - The size of the body, sent as the `Content-Length` header when known, otherwise the body is sent
+    ///   with chunked transfer encoding.
     ///
     /// # Returns
     ///
-    /// An option containing the value if the response is successful, otherwise `None`.
-    fn resolve_error(&self,response : Result<O,RequestError<E>>,error_handler : impl Fn(RequestError<E>) + Sync) -> Option<O> {
-        match response {
-            Ok(value) => Some(value),
-            Err(error) => {
-                error_handler(error);
-                None
-            }
-        }
+    /// The `RequestBuilder` with default settings applied.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn default_stream_requestor<S>(&self,method : Method,endpoint : &str,body : S,content_length : Option<u64>) -> reqwest::RequestBuilder
+    where S : futures_util::TryStream + Send + Sync + 'static, S::Error : Into<Box<dyn std::error::Error + Send + Sync>>, Bytes : From<S::Ok> {
+        let request = base_requestor(self,method,endpoint,&HashMap::new()).body(reqwest::Body::wrap_stream(body));
+        let request = match content_length {
+            Some(length) => request.header(reqwest::header::CONTENT_LENGTH,length),
+            None => request
+        };
+        finish_request(self,request)
     }
 
-    /// This asynchronous function constructs (by default) a GET request using the `default_get_requestor` method
-    /// with the given endpoint and parameters. It then sends the request using the request method, expecting
-    /// a response of type `T` or an error of type `E`. The error is resolved using the `resolve_error` method
-    /// and returns an `Option<T>` representing the response data if successful, or `None` if an error occurred.
+    /// Creates a `RequestBuilder` for the given method and endpoint whose body is streamed from the given reader, such as a
+    /// `tokio::fs::File`, like [RequestDefaults::default_stream_requestor].
+    ///
+    /// ```rust ignore
+    /// let file = tokio::fs::File::open("backup.tar").await?;
+    /// let length = file.metadata().await?.len();
+    /// let request = api_client.default_reader_requestor(Method::PUT,"backups/latest",file,Some(length));
+    /// ```
     ///
     /// # Arguments
     ///
-    /// * `endpoint` - The endpoint URL to send the GET request to.
-    /// * `parameters` - A hashmap containing any parameters to include in the request.
-    /// * `map` - A closure that maps the successful response JSON into the desired output type.
-    /// * `error_handler` - A closure that handles the error case if an `RequestError` occurs.
+    /// * `method` - The HTTP method of the request.
+    /// * `endpoint` - The endpoint for the request.
+    /// * `reader` - The reader the body is read from.
+    /// * `content_length` - The size of the body, sent as the `Content-Length` header when known.
     ///
     /// # Returns
     ///
-    /// An `Option<O>` representing the response data if successful, or `None` if an error occurred.
-    async fn get_request_handler<'a>(&self,endpoint : &str,parameters : &HashMap<&'a str,Value>,map : impl FnOnce(T) -> O + Send + Sync,error_handler : impl Fn(RequestError<E>) + Sync + Send) -> Option<O> { 
-        let request = self.default_get_requestor(endpoint,parameters);
-        let response = Self::request_map(request,map).await;
-        self.resolve_error(response,error_handler)
+    /// The `RequestBuilder` with default settings applied.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn default_reader_requestor<R : tokio::io::AsyncRead + Send + Sync + 'static>(&self,method : Method,endpoint : &str,reader : R,content_length : Option<u64>) -> reqwest::RequestBuilder {
+        self.default_stream_requestor(method,endpoint,tokio_util::io::ReaderStream::new(reader),content_length)
     }
 
-    /// Handles a POST request to the specified endpoint with the provided JSON payload and returns the response data of type T.
-    ///
-    /// This asynchronous function constructs a POST request using the `default_post_requestor` method with the given endpoint
-    /// and JSON payload. It then sends the request using the request method, expecting a response of type `T` or an error of type `E`.
-    /// The error is resolved using the `resolve_error` method and returns an `Option<T>` representing the response data if successful,
-    /// or `None` if an error occurred.
+    /// Modifies the provided `RequestBuilder` with default settings for put request.
     ///
     /// # Arguments
     ///
-    /// * `endpoint` - The endpoint URL to send the POST request to.
-    /// * `json` - A string containing the JSON payload to include in the request.
-    /// * `map` - A closure that maps the successful response JSON into the desired output type.
-    /// * `error_handler` - A closure that handles the error case if an `RequestError` occurs.
+    /// * `endpoint` - The endpoint for the request.
+    /// * `json` - The JSON payload for the request.
     ///
     /// # Returns
     ///
-    /// An `Option<O>` representing the response data if successful, or `None` if an error occurred.
-    async fn post_request_handler(&self,endpoint : &str,json : String,map : impl FnOnce(T) -> O + Send + Sync,error_handler : impl Fn(RequestError<E>) + Sync  + Send) -> Option<O> {
-        let request = self.default_post_requestor(endpoint,json);
-        let response = Self::request_map(request,map).await;
-        self.resolve_error(response,error_handler)
+    /// The modified `RequestBuilder` with default settings applied.
+    fn default_put_requestor(&self,endpoint : &str,json : String) -> reqwest::RequestBuilder {
+        let (request,body) = compress_body(self,base_requestor(self,Method::PUT,endpoint,&HashMap::new()),json.into_bytes());
+        finish_request(self,Self::add_sized_body(request,body))
     }
-}
+
+    /// Modifies the provided `RequestBuilder` with default settings for patch request.
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint` - The endpoint for the request.
+    /// * `json` - The JSON payload for the request.
+    ///
+    /// # Returns
+    ///
+    /// The modified `RequestBuilder` with default settings applied.
+    fn default_patch_requestor(&self,endpoint : &str,json : String) -> reqwest::RequestBuilder {
+        let (request,body) = compress_body(self,base_requestor(self,Method::PATCH,endpoint,&HashMap::new()),json.into_bytes());
+        finish_request(self,Self::add_sized_body(request,body))
+    }
+
+    /// Modifies the provided `RequestBuilder` with default settings for delete request.
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint` - The endpoint for the request.
+    /// * `parameters` - The Parameters for the request.
+    ///
+    /// # Returns
+    ///
+    /// The modified `RequestBuilder` with default settings applied.
+    #[deprecated(note = "use `default_delete_query_requestor`, which takes any `Serialize` query and supports repeated keys")]
+    fn default_delete_requestor(&self,endpoint : &str,parameters : &HashMap<&str,Value>) -> reqwest::RequestBuilder {
+        self.default_requestor(Method::DELETE,endpoint,parameters)
+    }
+
+    /// Creates a `RequestBuilder` for the given method and endpoint with the default headers, parameters and query applied.
+    ///
+    /// The default requestors without a body are built on top of this method. Like every default requestor, it ends by
+    /// passing the request to the [RequestDefaults::middleware] and [RequestDefaults::finalize_request].
+    ///
+    /// # Arguments
+    ///
+    /// * `method` - The HTTP method of the request.
+    /// * `endpoint` - The endpoint for the request.
+    /// * `parameters` - The Parameters for the request, merged with [RequestDefaults::default_query].
+    ///
+    /// # Returns
+    ///
+    /// The `RequestBuilder` with default settings applied.
+    fn default_requestor(&self,method : Method,endpoint : &str,parameters : &HashMap<&str,Value>) -> reqwest::RequestBuilder {
+        finish_request(self,base_requestor(self,method,endpoint,parameters))
+    }
+
+    /// Creates a `RequestBuilder` for the given method and endpoint like [RequestDefaults::default_requestor], with the query
+    /// parameters taken from any `Serialize` value instead of a `HashMap`.
+    ///
+    /// The value is passed as is to `RequestBuilder::query`, so a struct gives typed parameters (with `Option` fields left out
+    /// when `None` and `#[serde(skip_serializing_if)]` honoured) while a slice of pairs allows repeated keys such as
+    /// `tag=a&tag=b`. It is appended after the [RequestDefaults::default_query], which it therefore cannot override.
+    ///
+    /// ```rust ignore
+    /// #[derive(Serialize)]
+    /// struct SearchQuery<'a> {
+    ///     q : &'a str,
+    ///     #[serde(skip_serializing_if = "Option::is_none")]
+    ///     cursor : Option<String>,
+    /// }
+    ///
+    /// let request = api_client.default_query_requestor(Method::GET,"search",&SearchQuery { q : "rust", cursor : None });
+    /// let request = api_client.default_query_requestor(Method::GET,"search",&[("tag","rust"),("tag","http")]);
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `method` - The HTTP method of the request.
+    /// * `endpoint` - The endpoint for the request.
+    /// * `query` - The query parameters for the request, failing to serialize making the request fail once sent.
+    ///
+    /// # Returns
+    ///
+    /// The `RequestBuilder` with default settings applied.
+    fn default_query_requestor<Q : Serialize + ?Sized>(&self,method : Method,endpoint : &str,query : &Q) -> reqwest::RequestBuilder {
+        finish_request(self,base_requestor(self,method,endpoint,&HashMap::new()).query(query))
+    }
+
+    /// Modifies the provided `RequestBuilder` with default settings for get request, with typed query parameters.
+    ///
+    /// See [RequestDefaults::default_query_requestor].
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint` - The endpoint for the request.
+    /// * `query` - The query parameters for the request.
+    ///
+    /// # Returns
+    ///
+    /// The modified `RequestBuilder` with default settings applied.
+    fn default_get_query_requestor<Q : Serialize + ?Sized>(&self,endpoint : &str,query : &Q) -> reqwest::RequestBuilder {
+        self.default_query_requestor(Method::GET,endpoint,query)
+    }
+
+    /// Modifies the provided `RequestBuilder` with default settings for delete request, with typed query parameters.
+    ///
+    /// See [RequestDefaults::default_query_requestor].
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint` - The endpoint for the request.
+    /// * `query` - The query parameters for the request.
+    ///
+    /// # Returns
+    ///
+    /// The modified `RequestBuilder` with default settings applied.
+    fn default_delete_query_requestor<Q : Serialize + ?Sized>(&self,endpoint : &str,query : &Q) -> reqwest::RequestBuilder {
+        self.default_query_requestor(Method::DELETE,endpoint,query)
+    }
+
+    /// Returns the [Middleware] wrapping every request built by the default requestors and every response they receive, in order.
+    ///
+    /// # Returns
+    ///
+    /// No middleware by default.
+    fn middleware(&self) -> &[Box<dyn Middleware>] {
+        &[]
+    }
+
+    /// Called by every default requestor as its last step, giving a single place to apply cross-cutting changes such as
+    /// signing the request or adding a computed header.
+    ///
+    /// # Arguments
+    ///
+    /// * `request_builder` - The `RequestBuilder` built by the requestor, body included.
+    ///
+    /// # Returns
+    ///
+    /// The `RequestBuilder` to send, unchanged by default.
+    fn finalize_request(&self,request_builder : reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        request_builder
+    }
+
+    /// The name of the header carrying a unique ID for every request.
+    ///
+    /// The ID lets client logs be correlated with server logs. It is added by the default requestors, kept across retries,
+    /// and attached to the [RequestContext] of errors, see [RequestError::correlation_id].
+    ///
+    /// # Returns
+    ///
+    /// `X-Request-Id` by default, or `None` to never add the header.
+    #[cfg(feature = "uuid")]
+    fn correlation_header(&self) -> Option<&str> {
+        Some("X-Request-Id")
+    }
+
+    /// Returns the correlation ID of the surrounding context, which requests inherit instead of a generated one.
+    ///
+    /// Useful to propagate the ID of the incoming request a server is handling, such as one kept in a tracing span or in a
+    /// task-local :
+    ///
+    /// ```rust ignore
+    /// tokio::task_local! {
+    ///     static REQUEST_ID : String;
+    /// }
+    ///
+    /// impl RequestDefaults for MyAPIClient {
+    ///     fn ambient_correlation_id(&self) -> Option<String> {
+    ///         REQUEST_ID.try_with(Clone::clone).ok()
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// # Returns
+    ///
+    /// `None` by default, meaning a new ID is generated for every request.
+    #[cfg(feature = "uuid")]
+    fn ambient_correlation_id(&self) -> Option<String> {
+        None
+    }
+
+    /// Adds the [RequestDefaults::ambient_correlation_id], or a freshly generated one, to the request under the
+    /// [RequestDefaults::correlation_header], replacing any previous one.
+    ///
+    /// # Arguments
+    ///
+    /// * `request_builder` - The `RequestBuilder` to add the ID to.
+    ///
+    /// # Returns
+    ///
+    /// The modified `RequestBuilder` and the generated ID, or `None` if no correlation header is configured.
+    #[cfg(feature = "uuid")]
+    fn with_correlation_id(&self,request_builder : reqwest::RequestBuilder) -> (reqwest::RequestBuilder,Option<String>) {
+        let Some(name) = self.correlation_header().and_then(|name| reqwest::header::HeaderName::from_bytes(name.as_bytes()).ok()) else {
+            return (request_builder,None)
+        };
+
+        // An ambient ID that cannot be sent as a header is replaced rather than failing the request
+        let id = self.ambient_correlation_id().filter(|id| HeaderValue::from_str(id).is_ok()).unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+        let mut headers = HeaderMap::new();
+        headers.insert(name,id.parse().expect("the ID is a valid header value"));
+        (request_builder.headers(headers),Some(id))
+    }
+
+    /// The name of the header carrying the idempotency key of retried `POST` and `PATCH` requests.
+    ///
+    /// When a [RequestDefaults::retry_policy] applies (or [RequestDefaults::honor_retry_after] is enabled), such requests
+    /// without this header get a freshly generated key, sent unchanged with every attempt so the server can recognise the
+    /// retries of a mutation it already performed. Use [RequestDefaults::with_idempotency_key] to generate the key beforehand,
+    /// for instance to log it.
+    ///
+    /// # Returns
+    ///
+    /// `Idempotency-Key` by default, or `None` to never add the header.
+    #[cfg(feature = "uuid")]
+    fn idempotency_header(&self) -> Option<&str> {
+        Some("Idempotency-Key")
+    }
+
+    /// Adds a freshly generated idempotency key to the request under the [RequestDefaults::idempotency_header], replacing any previous one.
+    ///
+    /// # Arguments
+    ///
+    /// * `request_builder` - The `RequestBuilder` to add the key to.
+    ///
+    /// # Returns
+    ///
+    /// The modified `RequestBuilder` and the generated key, or `None` if no idempotency header is configured.
+    #[cfg(feature = "uuid")]
+    fn with_idempotency_key(&self,request_builder : reqwest::RequestBuilder) -> (reqwest::RequestBuilder,Option<String>) {
+        let Some(name) = self.idempotency_header().and_then(|name| HeaderName::from_bytes(name.as_bytes()).ok()) else {
+            return (request_builder,None)
+        };
+
+        let key = uuid::Uuid::new_v4().to_string();
+        let mut headers = HeaderMap::new();
+        headers.insert(name,key.parse().expect("a UUID is a valid header value"));
+        (request_builder.headers(headers),Some(key))
+    }
+
+    /// Returns the [ClientConfig] applied to every request built by the default requestors, if any.
+    ///
+    /// # Returns
+    ///
+    /// The configuration of the [RequestInfo::environment] if any, otherwise `None`, by default.
+    fn config(&self) -> Option<&ClientConfig> {
+        self.environment().map(ApiEnvironment::config)
+    }
+
+    /// Returns mutable access to the [ClientConfig] returned by [RequestDefaults::config], used by [RequestDefaults::with_overrides].
+    ///
+    /// # Returns
+    ///
+    /// `None` by default, meaning the client cannot be overridden.
+    fn config_mut(&mut self) -> Option<&mut ClientConfig> {
+        None
+    }
+
+    /// Creates a copy of this client whose [ClientConfig] is modified by the given closure.
+    ///
+    /// The copy shares the connection pool of the original, as cloning a [reqwest::Client] only clones a handle to it,
+    /// so specialized sub-clients (for example with a longer timeout or an extra header) are cheap to derive.
+    /// If [RequestDefaults::config_mut] returns `None`, the copy is identical to the original.
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - A closure modifying the configuration of the copy.
+    ///
+    /// # Returns
+    ///
+    /// The derived client.
+    fn with_overrides(&self,f : impl FnOnce(&mut ClientConfig)) -> Self where Self : Clone + Sized {
+        let mut client = self.clone();
+        if let Some(config) = client.config_mut() {
+            f(config);
+        }
+        client
+    }
+
+    /// Whether bodies attached through [RequestDefaults::apply_body] carry an explicit `Content-Length` header.
+    ///
+    /// reqwest only falls back to `Transfer-Encoding: chunked` when the length of a body is unknown, which is the
+    /// case for streamed bodies. Strict servers (or proxies in front of them) can still reject requests without an
+    /// explicit `Content-Length` with `411 Length Required`, so by default the header is always set for buffered bodies.
+    ///
+    /// # Returns
+    ///
+    /// `true` by default.
+    fn force_content_length(&self) -> bool {
+        true
+    }
+
+    /// Attaches an already serialized body to the provided `RequestBuilder`, honouring [RequestDefaults::force_content_length]
+    /// and compressing it with the [RequestDefaults::body_compression], if any.
+    ///
+    /// This is used by the typed-body helpers after serializing their payload.
+    ///
+    /// # Arguments
+    ///
+    /// * `request_builder` - The `RequestBuilder` to attach the body to.
+    /// * `body` - The serialized body.
+    ///
+    /// # Returns
+    ///
+    /// The modified `RequestBuilder` with the body attached.
+    fn apply_body(&self,request_builder : reqwest::RequestBuilder,body : impl Into<Vec<u8>>) -> reqwest::RequestBuilder {
+        let (request_builder,body) = compress_body(self,request_builder,body.into());
+        match self.force_content_length() {
+            true => Self::add_sized_body(request_builder,body),
+            false => request_builder.body(body)
+        }
+    }
+
+    /// Modifies the provided `RequestBuilder` with default settings for get request.
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint` - The endpoint for the request.
+    /// * `parameters` - The Parameters for the request.
+    ///
+    /// # Returns
+    ///
+    /// The modified `RequestBuilder` with default settings applied.
+    #[deprecated(note = "use `default_get_query_requestor`, which takes any `Serialize` query and supports repeated keys")]
+    fn default_get_requestor(&self,endpoint : &str,parameters : &HashMap<&str,Value>) -> reqwest::RequestBuilder {
+        self.default_requestor(Method::GET,endpoint,parameters)
+    }
+
+    /// Modifies the provided `RequestBuilder` with default settings for head request.
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint` - The endpoint for the request.
+    /// * `parameters` - The Parameters for the request.
+    ///
+    /// # Returns
+    ///
+    /// The modified `RequestBuilder` with default settings applied.
+    fn default_head_requestor(&self,endpoint : &str,parameters : &HashMap<&str,Value>) -> reqwest::RequestBuilder {
+        self.default_requestor(Method::HEAD,endpoint,parameters)
+    }
+
+    /// Modifies the provided `RequestBuilder` with default settings for options request.
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint` - The endpoint for the request.
+    ///
+    /// # Returns
+    ///
+    /// The modified `RequestBuilder` with default settings applied.
+    fn default_options_requestor(&self,endpoint : &str) -> reqwest::RequestBuilder {
+        self.default_requestor(Method::OPTIONS,endpoint,&HashMap::new())
+    }
+
+    /// Returns the [RequestCanceller] used to abort in-flight requests, if any.
+    ///
+    /// When `Some`, every request sent through `request_map` can be aborted with [RequestDefaults::cancel_all].
+    ///
+    /// # Returns
+    ///
+    /// `None` by default, meaning requests cannot be cancelled collectively.
+    fn canceller(&self) -> Option<&RequestCanceller> {
+        None
+    }
+
+    /// Cancels all outstanding requests tracked by the [RequestCanceller] returned from [RequestDefaults::canceller].
+    ///
+    /// Cancelled requests fail with [RequestError::Cancelled] and their connections are dropped immediately.
+    ///
+    /// # Returns
+    ///
+    /// The number of requests that were cancelled, which is always `0` if no canceller is configured.
+    fn cancel_all(&self) -> usize {
+        self.canceller().map(RequestCanceller::cancel_all).unwrap_or(0)
+    }
+
+    /// Returns the endpoint GraphQL requests are sent to, relative to the base URL.
+    ///
+    /// # Returns
+    ///
+    /// `"graphql"` by default.
+    fn graphql_endpoint(&self) -> &str {
+        "graphql"
+    }
+
+    /// Modifies the provided `RequestBuilder` with default settings for a GraphQL request.
+    ///
+    /// The request is a POST to [RequestDefaults::graphql_endpoint] carrying the standard `{ "query": ..., "variables": ... }` envelope.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The GraphQL query or mutation document.
+    /// * `variables` - The variables of the query.
+    ///
+    /// # Returns
+    ///
+    /// The modified `RequestBuilder` with default settings applied, or the error raised while serializing the variables.
+    fn default_graphql_requestor<V : Serialize + ?Sized>(&self,query : &str,variables : &V) -> Result<reqwest::RequestBuilder,serde_json::Error> {
+        let body = serde_json::to_vec(&serde_json::json!({
+            "query" : query,
+            "variables" : variables,
+        }))?;
+        let request = base_requestor(self,Method::POST,self.graphql_endpoint(),&HashMap::new()).header(CONTENT_TYPE,"application/json");
+        Ok(finish_request(self,self.apply_body(request,body)))
+    }
+
+    /// Returns the [RetryPolicy], usually a [RetryConfig], deciding whether requests failing with a transient error are retried.
+    ///
+    /// Not available on `wasm32`, where there is no timer to wait on.
+    ///
+    /// # Returns
+    ///
+    /// `None` by default, meaning requests are sent only once.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn retry_policy(&self) -> Option<&dyn RetryPolicy> {
+        None
+    }
+
+    /// Returns the [HedgePolicy] sending copies of the `GET` and `HEAD` requests that are slow to be answered, if any.
+    ///
+    /// Not available on `wasm32`.
+    ///
+    /// # Returns
+    ///
+    /// `None` by default, meaning requests are sent once, waiting for their response however long it takes.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn hedge_policy(&self) -> Option<&HedgePolicy> {
+        None
+    }
+
+    /// Whether `429 Too Many Requests` and `503 Service Unavailable` responses are retried after the delay of their `Retry-After` header.
+    ///
+    /// When enabled, these responses are retried even without a [RequestDefaults::retry_policy], up to 3 times, waiting as long
+    /// as the server asked (or as decided by the policy when the header is missing). Once the retries are exhausted, the request
+    /// fails with [RequestError::RateLimited] carrying the last delay instead of trying to parse the body as an error payload.
+    ///
+    /// Not available on `wasm32`, where there is no timer to wait on.
+    ///
+    /// # Returns
+    ///
+    /// `false` by default.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn honor_retry_after(&self) -> bool {
+        false
+    }
+
+    /// The longest `Retry-After` delay waited on before retrying, when [RequestDefaults::honor_retry_after] is enabled.
+    ///
+    /// Responses asking for a longer delay, such as until a daily quota resets, fail right away with
+    /// [RequestError::RateLimited] carrying the delay instead of parking the caller.
+    ///
+    /// Not available on `wasm32`.
+    ///
+    /// # Returns
+    ///
+    /// 60 seconds by default, `None` waiting as long as the server asks.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn max_retry_after(&self) -> Option<std::time::Duration> {
+        Some(std::time::Duration::from_secs(60))
+    }
+
+    /// Returns how long a request may take as a whole, including its retries and the delays between them, the waits for the
+    /// rate limiter and the reading of the response body, before failing with [RequestError::Timeout].
+    ///
+    /// Unlike the timeout of each attempt, set with [ClientConfig::timeout] or `reqwest::RequestBuilder::timeout`, this bounds
+    /// the time callers wait however many retries the [RequestDefaults::retry_policy] allows. It is overridden per call by
+    /// [RequestHandler::request_map_with_timeout].
+    ///
+    /// Not available on `wasm32`, where there is no timer to wait on.
+    ///
+    /// # Returns
+    ///
+    /// `None` by default, meaning requests take as long as their attempts and retries do.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn request_deadline(&self) -> Option<std::time::Duration> {
+        None
+    }
+
+    /// Returns the [ResponseCache] answering repeated `GET` requests without hitting the network, if any.
+    ///
+    /// Not available on `wasm32`, where there is no clock to measure the TTLs with.
+    ///
+    /// # Returns
+    ///
+    /// `None` by default, meaning responses are not cached.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn response_cache(&self) -> Option<&ResponseCache> {
+        None
+    }
+
+    /// Returns the [RequestScheduler] limiting how many requests are in flight at once and sending the waiting ones by
+    /// [Priority], if any.
+    ///
+    /// Not available on `wasm32`.
+    ///
+    /// # Returns
+    ///
+    /// `None` by default, meaning requests are sent as soon as they are made.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn request_scheduler(&self) -> Option<&RequestScheduler> {
+        None
+    }
+
+    /// Returns the [Bulkhead] limiting how many requests are in flight at once to each host, if any.
+    ///
+    /// Not available on `wasm32`.
+    ///
+    /// # Returns
+    ///
+    /// `None` by default, meaning any number of requests may be in flight to a host.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn bulkhead(&self) -> Option<&Bulkhead> {
+        None
+    }
+
+    /// Returns the [RequestCoalescer] sending identical `GET` and `HEAD` requests issued concurrently only once, if any.
+    ///
+    /// Not available on `wasm32`.
+    ///
+    /// # Returns
+    ///
+    /// `None` by default, meaning every request is sent.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn request_coalescer(&self) -> Option<&RequestCoalescer> {
+        None
+    }
+
+    /// Returns the [OfflineQueue] holding the mutations that failed to connect until they are replayed, if any.
+    ///
+    /// Not available on `wasm32`.
+    ///
+    /// # Returns
+    ///
+    /// `None` by default, meaning requests failing to connect fail with [RequestError::RequestError].
+    #[cfg(not(target_arch = "wasm32"))]
+    fn offline_queue(&self) -> Option<&OfflineQueue> {
+        None
+    }
+
+    /// Returns the [CookieJar] storing the cookies of the responses and sending them back with the following requests, if any.
+    ///
+    /// Not available on `wasm32`.
+    ///
+    /// # Returns
+    ///
+    /// `None` by default, meaning cookies are neither stored nor sent, unless the [reqwest::Client] has its own store.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn cookie_jar(&self) -> Option<&CookieJar> {
+        None
+    }
+
+    /// Returns the [ProxyConfig] of the proxy the requests are sent through, if any.
+    ///
+    /// It only applies to clients built with [RequestDefaults::proxied_client_builder], as the proxy of a
+    /// [reqwest::Client] cannot change once built. Not available on `wasm32`.
+    ///
+    /// # Returns
+    ///
+    /// `None` by default, meaning requests are sent directly, unless the proxy environment variables are set.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn proxy(&self) -> Option<ProxyConfig> {
+        None
+    }
+
+    /// Creates a `ClientBuilder` with the defaults of [RequestInfo::default_client_builder] and the
+    /// [RequestDefaults::proxy], if any, to build the client of the handler from.
+    ///
+    /// Not available on `wasm32`.
+    ///
+    /// # Returns
+    ///
+    /// The builder, or an error if the proxy is invalid, see [ProxyConfig::to_proxy].
+    #[cfg(not(target_arch = "wasm32"))]
+    fn proxied_client_builder(&self) -> reqwest::Result<reqwest::ClientBuilder> where Self : Sized {
+        let builder = Self::default_client_builder();
+        match self.proxy() {
+            Some(proxy) => proxy.apply(builder),
+            None => Ok(builder)
+        }
+    }
+
+    /// Returns the [CircuitBreaker] failing requests fast while the upstream keeps failing, if any.
+    ///
+    /// Not available on `wasm32`, where there is no clock to measure the cooldown with.
+    ///
+    /// # Returns
+    ///
+    /// `None` by default, meaning requests are always sent.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn circuit_breaker(&self) -> Option<&CircuitBreaker> {
+        None
+    }
+
+    /// Returns the [RateLimiter] every request has to acquire a permit from before being sent, if any.
+    ///
+    /// Not available on `wasm32`, where there is no timer to wait on.
+    ///
+    /// # Returns
+    ///
+    /// `None` by default, meaning requests are not throttled.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn rate_limiter(&self) -> Option<&RateLimiter> {
+        None
+    }
+
+    /// Returns the [MetricsSink] receiving the method, endpoint, status, outcome and duration of every request sent, if any.
+    ///
+    /// Not available on `wasm32`.
+    ///
+    /// # Returns
+    ///
+    /// `None` by default, meaning no metrics are recorded.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn metrics_sink(&self) -> Option<&dyn MetricsSink> {
+        None
+    }
+
+    /// Returns the [ApiKeyAuth] adding an API key to every request built by the default requestors, if any.
+    ///
+    /// # Returns
+    ///
+    /// The API key of the [RequestInfo::environment] if any by default, otherwise `None`, meaning requests only carry the
+    /// authentication added by the other hooks.
+    fn api_key_auth(&self) -> Option<&dyn ApiKeyAuth> {
+        self.environment().and_then(ApiEnvironment::api_key_auth)
+    }
+
+    /// Returns the [OAuth2TokenProvider] authorizing every request with a bearer token, if any.
+    ///
+    /// The `Authorization` header is added when the request is sent rather than in [RequestDefaults::default_headers],
+    /// as fetching the token is asynchronous. Not available on `wasm32`.
+    ///
+    /// # Returns
+    ///
+    /// `None` by default, meaning requests only carry the authentication added by the other hooks.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn token_provider(&self) -> Option<&OAuth2TokenProvider> {
+        None
+    }
+
+    /// Returns the [AuthRefresh] retrying requests answered with `401 Unauthorized` with refreshed credentials, if any.
+    ///
+    /// Returning the [RequestDefaults::token_provider] here retries such requests with a new token. Not available on `wasm32`.
+    ///
+    /// # Returns
+    ///
+    /// `None` by default, meaning `401 Unauthorized` responses are handled like any other unsuccessful response.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn auth_refresh(&self) -> Option<&dyn AuthRefresh> {
+        None
+    }
+
+    /// Returns the [RequestSigner] signing every request right before it is sent, if any.
+    ///
+    /// Not available on `wasm32`.
+    ///
+    /// # Returns
+    ///
+    /// `None` by default, meaning requests are not signed.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn request_signer(&self) -> Option<&dyn RequestSigner> {
+        None
+    }
+
+    /// Returns the [TracePropagator] injecting the current trace context into every request right before it is sent, if any.
+    ///
+    /// # Returns
+    ///
+    /// `None` by default, meaning no trace context is propagated.
+    fn trace_propagator(&self) -> Option<&dyn TracePropagator> {
+        None
+    }
+
+    /// The maximum size in bytes of a typed body once serialized, see [RequestHandler::default_body_requestor].
+    ///
+    /// Larger bodies are rejected with [RequestError::BodyTooLarge] before anything is sent, guarding against bugs such as an
+    /// unbounded collection being serialized and uploaded.
+    ///
+    /// # Returns
+    ///
+    /// `None` by default, accepting bodies of any size.
+    fn max_request_body_bytes(&self) -> Option<usize> {
+        None
+    }
+
+    /// Returns the [BodyCompression] the typed request bodies are compressed with, if any.
+    ///
+    /// Requires the `gzip`, `deflate` or `zstd` feature.
+    ///
+    /// # Returns
+    ///
+    /// `None` by default, meaning bodies are sent uncompressed.
+    #[cfg(any(feature = "gzip",feature = "deflate",feature = "zstd"))]
+    fn body_compression(&self) -> Option<BodyCompression> {
+        None
+    }
+
+    /// The maximum number of body bytes read by `request_map` before giving up with [RequestError::ResponseTooLarge].
+    /// It is overridden per call by [RequestHandler::request_map_with_limit].
+    ///
+    /// Useful when calling untrusted or third-party endpoints that cannot be assumed to return small bodies. The body is
+    /// streamed and the request aborted as soon as the limit is exceeded, so an oversized body is never buffered entirely.
+    ///
+    /// # Returns
+    ///
+    /// `None` by default, reading bodies of any size.
+    fn max_response_bytes(&self) -> Option<usize> {
+        None
+    }
+
+    /// Whether requests and responses are passed to [RequestDefaults::log_request] and [RequestDefaults::log_response].
+    ///
+    /// # Returns
+    ///
+    /// `false` by default.
+    fn log_exchanges(&self) -> bool {
+        false
+    }
+
+    /// The maximum number of body bytes written by the logging hooks before truncating with a `...[truncated N bytes]` marker.
+    ///
+    /// # Returns
+    ///
+    /// [logging::DEFAULT_MAX_LOG_BODY_SIZE] by default, or `None` to disable truncation.
+    fn max_log_body_size(&self) -> Option<usize> {
+        Some(logging::DEFAULT_MAX_LOG_BODY_SIZE)
+    }
+
+    /// Logs a request about to be sent, when [RequestDefaults::log_exchanges] is enabled.
+    ///
+    /// By default the method, URL and (truncated) body are written at debug level through the `log` crate, prefixed by the
+    /// correlation ID when the `uuid` feature is enabled and a [RequestDefaults::correlation_header] is configured. The
+    /// values of the query parameters usually carrying credentials are redacted, as by [logging::redact_url].
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The request about to be sent.
+    fn log_request(&self,request : &reqwest::Request) {
+        let body = request.body().and_then(|body| body.as_bytes()).unwrap_or_default();
+
+        #[cfg(feature = "uuid")]
+        if let Some(id) = self.correlation_header().and_then(|name| request.headers().get(name)).and_then(|id| id.to_str().ok()) {
+            log::debug!("--> [{}] {} {} {}",id,request.method(),logging::redact_url(request.url()),logging::truncate_body(body,self.max_log_body_size()));
+            return;
+        }
+
+        log::debug!("--> {} {} {}",request.method(),logging::redact_url(request.url()),logging::truncate_body(body,self.max_log_body_size()));
+    }
+
+    /// Logs a received response, when [RequestDefaults::log_exchanges] is enabled.
+    ///
+    /// By default the status, URL and (truncated) body are written at debug level through the `log` crate, redacting the
+    /// URL like [RequestDefaults::log_request].
+    ///
+    /// # Arguments
+    ///
+    /// * `response` - The received response.
+    fn log_response(&self,response : &RawResponse) {
+        log::debug!("<-- {} {} {}",response.meta.status,logging::redact_url(&response.meta.url),logging::truncate_body(&response.body,self.max_log_body_size()));
+    }
+
+    /// Returns the [MaintenanceDetector] used to recognise maintenance responses, if any.
+    ///
+    /// # Returns
+    ///
+    /// `None` by default, meaning maintenance responses are handled like any other unsuccessful response.
+    fn maintenance_detector(&self) -> Option<&MaintenanceDetector> {
+        None
+    }
+
+    /// Returns the [Envelope] wrapping the payloads of the API, if any.
+    ///
+    /// # Returns
+    ///
+    /// `None` by default, meaning bodies are deserialized as a whole.
+    fn envelope(&self) -> Option<&Envelope> {
+        None
+    }
+
+    /// Returns the [ResponseValidator] checking the bodies of successful responses before they are deserialized, if any.
+    ///
+    /// It is applied by the handlers deserializing the body into `T`, listed in [Envelope], and not by those returning it as is.
+    ///
+    /// # Returns
+    ///
+    /// `None` by default, meaning responses are not validated.
+    #[cfg(feature = "schema")]
+    fn response_validator(&self) -> Option<&dyn ResponseValidator> {
+        None
+    }
+
+    /// Returns the [har::HarRecorder] that captures every exchange made through `request_map`, if any.
+    ///
+    /// # Returns
+    ///
+    /// `None` by default, meaning nothing is recorded.
+    #[cfg(all(feature = "har",not(target_arch = "wasm32")))]
+    fn har_recorder(&self) -> Option<&har::HarRecorder> {
+        None
+    }
+
+    /// Returns the [har::HarReplayer] that answers requests from a recorded HAR file instead of the network, if any.
+    ///
+    /// When `Some`, requests without a matching entry fail with [RequestError::ReplayMiss] rather than being sent.
+    ///
+    /// # Returns
+    ///
+    /// `None` by default, meaning requests are sent over the network.
+    #[cfg(all(feature = "har",not(target_arch = "wasm32")))]
+    fn har_replayer(&self) -> Option<&har::HarReplayer> {
+        None
+    }
+
+    /// Returns the [HttpTransport] sending requests instead of [RequestInfo::client], if any.
+    ///
+    /// Not available on `wasm32`, where requests are always sent through the fetch API.
+    ///
+    /// # Returns
+    ///
+    /// `None` by default, meaning requests are sent with [RequestInfo::client].
+    #[cfg(not(target_arch = "wasm32"))]
+    fn transport(&self) -> Option<&dyn HttpTransport> {
+        None
+    }
+
+    /// Returns the [testing::MockResponses] that answer requests with canned responses instead of the network, if any.
+    ///
+    /// When `Some`, requests without a registered response fail with [RequestError::ReplayMiss] rather than being sent.
+    ///
+    /// # Returns
+    ///
+    /// `None` by default, meaning requests are sent over the network.
+    #[cfg(feature = "testing")]
+    fn mock_responses(&self) -> Option<&testing::MockResponses> {
+        None
+    }
+
+    /// Returns the [testing::FixtureHandler] that answers requests from JSON files on disk instead of the network, if any.
+    ///
+    /// When `Some`, requests without a fixture fail with [RequestError::ReplayMiss] rather than being sent.
+    ///
+    /// # Returns
+    ///
+    /// `None` by default, meaning requests are sent over the network.
+    #[cfg(feature = "testing")]
+    fn fixtures(&self) -> Option<&testing::FixtureHandler> {
+        None
+    }
+}
+
+/// Passes a request built by a default requestor through the middleware, then [RequestDefaults::finalize_request].
+fn finish_request<D : RequestDefaults + ?Sized>(defaults : &D,request_builder : reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+    let request_builder = defaults.middleware().iter().fold(request_builder,|request_builder,middleware| middleware.on_request(request_builder));
+    defaults.finalize_request(request_builder)
+}
+
+/// Builds a request with the `User-Agent` and `Accept` hooks, the default headers, configuration, parameters and query applied, before [finish_request].
+fn base_requestor<D : RequestDefaults + ?Sized>(defaults : &D,method : Method,endpoint : &str,parameters : &HashMap<&str,Value>) -> reqwest::RequestBuilder {
+    let url = match defaults.api_version() {
+        Some(version) => defaults.endpoint_url(&version.endpoint(endpoint)),
+        None => defaults.endpoint_url(endpoint)
+    };
+    base_url_requestor(defaults,method,url,parameters)
+}
+
+/// Builds a request to the given URL like [base_requestor].
+fn base_url_requestor<D : RequestDefaults + ?Sized>(defaults : &D,method : Method,url : String,parameters : &HashMap<&str,Value>) -> reqwest::RequestBuilder {
+    let endpoint = url.strip_prefix(defaults.base_url()).map(str::to_owned);
+    let request_builder = defaults.client().request(method,url);
+    let request_builder = D::add_header_with(request_builder,USER_AGENT.as_str(),|| defaults.user_agent().map(str::to_owned));
+    let version = defaults.api_version();
+    // A media type version replaces the `Accept` header
+    let request_builder = match version.as_ref().and_then(ApiVersion::accept) {
+        Some(accept) => request_builder.header(ACCEPT,accept),
+        None => D::add_header_with(request_builder,ACCEPT.as_str(),|| defaults.accept().map(str::to_owned))
+    };
+    let request_builder = defaults.default_headers(request_builder);
+    let request_builder = match version {
+        Some(version) => version.apply(request_builder),
+        None => request_builder
+    };
+    let request_builder = match defaults.config() {
+        Some(config) => config.apply(request_builder),
+        None => request_builder
+    };
+    let request_builder = match defaults.api_key_auth() {
+        Some(auth) => auth::apply_api_key(auth,request_builder),
+        None => request_builder
+    };
+    let request_builder = defaults.default_parameters(request_builder).query(&merge_query(defaults.default_query(),parameters));
+    let request_builder = match endpoint {
+        Some(endpoint) => defaults.endpoint_defaults(endpoint.trim_start_matches('/'),request_builder),
+        None => request_builder
+    };
+
+    #[cfg(feature = "uuid")]
+    let (request_builder,_) = defaults.with_correlation_id(request_builder);
+
+    request_builder
+}
+
+/// Reads the correlation ID the request is sent with, see [RequestDefaults::correlation_header].
+#[allow(unused_variables)]
+fn correlation_id<D : RequestDefaults + ?Sized>(defaults : &D,request : &reqwest::Request) -> Option<String> {
+    #[cfg(feature = "uuid")]
+    return defaults.correlation_header().and_then(|name| request.headers().get(name)).and_then(|id| id.to_str().ok()).map(str::to_owned);
+
+    #[cfg(not(feature = "uuid"))]
+    None
+}
+
+/// Returns the endpoint the URL points to, relative to the [RequestInfo::base_url] and without the query, or its path if it is
+/// not below the base URL.
+#[cfg(any(feature = "testing",not(target_arch = "wasm32")))]
+fn relative_endpoint<'a,D : RequestDefaults + ?Sized>(defaults : &D,url : &'a reqwest::Url) -> &'a str {
+    let full = url.as_str();
+    let full = &full[..full.find(['?','#']).unwrap_or(full.len())];
+    full.strip_prefix(defaults.base_url()).unwrap_or(url.path())
+}
+
+/// Answers the request from the configured mocks, fixtures or HAR replayer instead of the network, if any of them is set.
+#[allow(unused_variables)]
+fn intercepted_response<D : RequestDefaults + ?Sized,E>(defaults : &D,request : &reqwest::Request) -> Option<Result<reqwest::Response,RequestError<E>>> {
+    #[cfg(any(all(feature = "har",not(target_arch = "wasm32")),feature = "testing"))]
+    let miss = || RequestError::ReplayMiss { method : request.method().clone(), url : request.url().to_string() };
+
+    #[cfg(feature = "testing")]
+    let endpoint = relative_endpoint(defaults,request.url());
+
+    #[cfg(feature = "testing")]
+    if let Some(mocks) = defaults.mock_responses() {
+        return Some(mocks.find(request.method(),endpoint).map(|mock| mock.to_response()).ok_or_else(miss));
+    }
+
+    #[cfg(feature = "testing")]
+    if let Some(fixtures) = defaults.fixtures() {
+        return Some(match fixtures.find(request.method(),endpoint) {
+            Ok(fixture) => fixture.map(|fixture| fixture.to_response()).ok_or_else(miss),
+            Err(error) => Err(RequestError::Io(error))
+        });
+    }
+
+    #[cfg(all(feature = "har",not(target_arch = "wasm32")))]
+    if let Some(replayer) = defaults.har_replayer() {
+        return Some(replayer.find(request).map(|entry| entry.response.to_response()).ok_or_else(miss));
+    }
+
+    None
+}
+
+/// The metadata of the last response received during a call to [RequestHandler::request_raw], passed to
+/// [RequestHandler::after_response] when the call failed because of that response.
+type LastResponse = std::sync::Mutex<Option<ResponseMeta>>;
+
+/// Sends a request once through the hooks of `defaults`, reading the whole response, see [send_request].
+#[cfg(not(target_arch = "wasm32"))]
+async fn send<D : RequestDefaults + ?Sized,E>(defaults : &D,request : reqwest::RequestBuilder,attempt : u32) -> Result<RawResponse,RequestError<E>> {
+    let last_response = LAST_RESPONSE.try_with(std::sync::Arc::clone).unwrap_or_default();
+    let (client,request) = request.build_split();
+    send_request(defaults,client,request,attempt,&last_response).await
+}
+
+/// Sends a built request once through the hooks of `defaults` with the given client, reading the whole response, whose
+/// metadata is stored in `last_response`.
+///
+/// `attempt` is the number of the retry the request is, `0` for the first attempt.
+#[allow(unused_variables)]
+async fn send_request<D : RequestDefaults + ?Sized,E>(defaults : &D,client : reqwest::Client,request : reqwest::Result<reqwest::Request>,attempt : u32,last_response : &LastResponse) -> Result<RawResponse,RequestError<E>> {
+    let exchange = async {
+        *last_response.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = None;
+        let request = request?;
+        #[cfg(feature = "tracing")]
+        telemetry::record_request(&request);
+        if defaults.log_exchanges() {
+            defaults.log_request(&request);
+        }
+
+        #[cfg(all(feature = "har",not(target_arch = "wasm32")))]
+        let recording = defaults.har_recorder().map(|recorder| har::Recording::start(recorder,&request));
+
+        let method = request.method().clone();
+        let correlation_id = correlation_id(defaults,&request);
+        let response = dispatch(defaults,&client,request).await?;
+        let meta = ResponseMeta { correlation_id, ..ResponseMeta::from_response(method,&response) };
+        *last_response.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(meta.clone());
+        let body = read_body(response,response_limit(defaults)).await?;
+
+        #[cfg(all(feature = "har",not(target_arch = "wasm32")))]
+        if let Some(recording) = recording {
+            recording.finish(meta.status,&meta.headers,&body);
+        }
+
+        let response = RawResponse { meta, body };
+        if defaults.log_exchanges() {
+            defaults.log_response(&response);
+        }
+        defaults.middleware().iter().for_each(|middleware| middleware.on_response(&response));
+
+        Ok::<_,RequestError<E>>(response)
+    };
+    #[cfg(feature = "tracing")]
+    let exchange = telemetry::instrument(attempt,|response : &RawResponse| response.meta.status,exchange);
+
+    let response = match defaults.canceller() {
+        Some(canceller) => canceller.run(exchange).await.ok_or(RequestError::Cancelled)??,
+        None => exchange.await?
+    };
+
+    match defaults.maintenance_detector().is_some_and(|detector| detector.is_maintenance(&response)) {
+        true => Err(RequestError::MaintenanceMode { retry_after : response.meta.retry_after() }),
+        false => Ok(response)
+    }
+}
+
+/// Sends a request through the hooks of `defaults` without reading the body of a successful response, so it can be streamed.
+///
+/// The body of an unsuccessful response is read and turned into an error like in [RequestHandler::request_map].
+async fn open_response<D : RequestDefaults + ?Sized,E : DeserializeOwned>(defaults : &D,request : reqwest::RequestBuilder) -> Result<reqwest::Response,RequestError<E>> {
+    let exchange = async {
+        #[cfg(not(target_arch = "wasm32"))]
+        let (client,request) = request.build_split();
+        #[cfg(target_arch = "wasm32")]
+        let (client,request) = (defaults.client().clone(),request.build());
+
+        let request = request?;
+        #[cfg(feature = "tracing")]
+        telemetry::record_request(&request);
+        if defaults.log_exchanges() {
+            defaults.log_request(&request);
+        }
+
+        let method = request.method().clone();
+        let correlation_id = correlation_id(defaults,&request);
+        let response = dispatch(defaults,&client,request).await?;
+        if response.status().is_success() {
+            return Ok(response);
+        }
+
+        let meta = ResponseMeta { correlation_id, ..ResponseMeta::from_response(method,&response) };
+        let body = read_body(response,response_limit(defaults)).await?;
+        let response = RawResponse { meta, body };
+        if defaults.log_exchanges() {
+            defaults.log_response(&response);
+        }
+        defaults.middleware().iter().for_each(|middleware| middleware.on_response(&response));
+
+        Err(match defaults.maintenance_detector().is_some_and(|detector| detector.is_maintenance(&response)) {
+            true => RequestError::MaintenanceMode { retry_after : response.meta.retry_after() },
+            false => parse_error_payload(&response)
+        })
+    };
+
+    #[cfg(feature = "tracing")]
+    return telemetry::instrument(0,reqwest::Response::status,exchange).await;
+
+    #[cfg(not(feature = "tracing"))]
+    exchange.await
+}
+
+/// The content type of protobuf messages, see [RequestHandler::request_protobuf].
+#[cfg(feature = "protobuf")]
+const PROTOBUF : &str = "application/x-protobuf";
+
+/// The ASCII record separator starting every record of a JSON text sequence (RFC 7464).
+const RECORD_SEPARATOR : u8 = 0x1E;
+
+/// Splits a body into lines as chunks arrive, deserializing every non-empty line into `T`.
+///
+/// Lines split across chunks are buffered until complete, and the last line does not need to end with a newline. Record
+/// separators are treated like newlines, so JSON text sequences are split the same way. The stream ends after a transport error.
+fn ndjson_lines<T : DeserializeOwned,E>(body : impl Stream<Item = reqwest::Result<Bytes>>) -> impl Stream<Item = Result<T,RequestError<E>>> {
+    use futures_util::StreamExt;
+
+    let parse = |line : &[u8]| serde_json::from_slice(line).map_err(RequestError::from);
+    futures_util::stream::unfold(Some((Box::pin(body),Vec::new())),move |state| async move {
+        let (mut body,mut buffer) = state?;
+        loop {
+            if let Some(end) = buffer.iter().position(|byte| matches!(*byte,b'\n' | RECORD_SEPARATOR)) {
+                let line : Vec<u8> = buffer.drain(..=end).collect();
+                match line[..end].trim_ascii() {
+                    [] => continue,
+                    line => return Some((parse(line),Some((body,buffer))))
+                }
+            }
+
+            match body.next().await {
+                Some(Ok(chunk)) => buffer.extend_from_slice(&chunk),
+                Some(Err(error)) => return Some((Err(error.into()),None)),
+                None => return match buffer.trim_ascii().is_empty() {
+                    true => None,
+                    false => Some((parse(buffer.trim_ascii()),None))
+                }
+            }
+        }
+    })
+}
+
+/// Sends a built request, answering it from the mocks or the HAR replayer when configured and waiting for the rate limiter otherwise.
+#[cfg_attr(target_arch = "wasm32",allow(unused_mut))]
+async fn dispatch<D : RequestDefaults + ?Sized,E>(defaults : &D,client : &reqwest::Client,mut request : reqwest::Request) -> Result<reqwest::Response,RequestError<E>> {
+    #[cfg(not(target_arch = "wasm32"))]
+    let breaker = match defaults.circuit_breaker() {
+        Some(breaker) => {
+            breaker.acquire(request.url()).map_err(|retry_after| RequestError::CircuitOpen { retry_after })?;
+            Some((breaker,request.url().clone()))
+        }
+        None => None
+    };
+
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(jar) = defaults.cookie_jar() {
+        jar.apply(&mut request);
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(provider) = defaults.token_provider() {
+        provider.authorize(client,&mut request).await?;
+    }
+    if let Some(propagator) = defaults.trace_propagator() {
+        propagator.inject(request.headers_mut());
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(signer) = defaults.request_signer() {
+        signer.sign(&mut request);
+    }
+
+    let intercepted = intercepted_response(defaults,&request).transpose()?;
+    #[cfg(not(target_arch = "wasm32"))]
+    let queued_at = std::time::Instant::now();
+    #[cfg(not(target_arch = "wasm32"))]
+    let _permit = match defaults.bulkhead().filter(|_| intercepted.is_none()) {
+        Some(bulkhead) => Some(bulkhead.acquire(request.url()).await),
+        None => None
+    };
+    #[cfg(not(target_arch = "wasm32"))]
+    let _slot = match defaults.request_scheduler().filter(|_| intercepted.is_none()) {
+        Some(scheduler) => Some(scheduler.acquire(Priority::current()).await),
+        None => None
+    };
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(limiter) = defaults.rate_limiter().filter(|_| intercepted.is_none()) {
+        acquire_permit(limiter,&mut request).await?;
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    let measured = defaults.metrics_sink().map(|sink| (sink,request.method().clone(),relative_endpoint(defaults,request.url()).to_owned(),std::time::Instant::now()));
+
+    let response = match intercepted {
+        Some(response) => Ok(response),
+        None => execute(defaults,client,request).await
+    };
+
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some((sink,method,endpoint,start)) = measured {
+        let status = response.as_ref().ok().map(reqwest::Response::status);
+        sink.record(&RequestMetrics { method, endpoint, status, outcome : Outcome::from_status(status), duration : start.elapsed(), queue_time : start - queued_at });
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some((breaker,url)) = breaker {
+        breaker.record(&url,response.as_ref().is_ok_and(|response| !response.status().is_server_error()));
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    if let (Some(jar),Ok(response)) = (defaults.cookie_jar(),&response) {
+        jar.store(response.url(),response.headers());
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    if let (Some(limiter),Ok(response)) = (defaults.rate_limiter(),&response) {
+        if let Some(state) = RateLimitState::from_headers(response.headers()) {
+            limiter.observe(response.url(),state);
+        }
+    }
+    response
+}
+
+/// Sends a request with the [RequestDefaults::transport], or with `client` if there is none.
+#[allow(unused_variables)]
+async fn execute<D : RequestDefaults + ?Sized,E>(defaults : &D,client : &reqwest::Client,request : reqwest::Request) -> Result<reqwest::Response,RequestError<E>> {
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(transport) = defaults.transport() {
+        let (url,timeout) = (request.url().clone(),request.timeout().copied());
+        let sent = transport.execute(transport::into_http_request(request).map_err(RequestError::Transport)?);
+        let response = match timeout {
+            Some(timeout) => tokio::time::timeout(timeout,sent).await.map_err(|_| RequestError::Timeout)?,
+            None => sent.await
+        };
+        return response.map(|response| transport::into_response(response,url)).map_err(RequestError::Transport);
+    }
+
+    let response = client.execute(request).await;
+    // Connections refused by the pins are not retried like other connection errors
+    #[cfg(all(feature = "pinning",not(target_arch = "wasm32")))]
+    if let Some(PinMismatch { host, fingerprint }) = response.as_ref().err().and_then(PinMismatch::find) {
+        return Err(RequestError::PinMismatch { host : host.clone(), fingerprint : fingerprint.clone() });
+    }
+    Ok(response?)
+}
+
+/// Returns the limit given to [RequestHandler::request_map_with_limit] if any, otherwise the [RequestDefaults::max_response_bytes].
+fn response_limit<D : RequestDefaults + ?Sized>(defaults : &D) -> Option<usize> {
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Ok(limit) = MAX_RESPONSE_BYTES.try_with(|limit| *limit) {
+        return Some(limit);
+    }
+    defaults.max_response_bytes()
+}
+
+/// Reads the body of a response, aborting with [RequestError::ResponseTooLarge] once more than `limit` bytes are received.
+async fn read_body<E>(response : reqwest::Response,limit : Option<usize>) -> Result<bytes::Bytes,RequestError<E>> {
+    use futures_util::StreamExt;
+
+    let Some(limit) = limit else {
+        return Ok(response.bytes().await?);
+    };
+
+    if let Some(length) = response.content_length().filter(|length| *length > limit as u64) {
+        return Err(RequestError::ResponseTooLarge { limit, received : usize::try_from(length).unwrap_or(usize::MAX) });
+    }
+
+    let mut body = Vec::new();
+    let mut stream = response.bytes_stream();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk?;
+        if body.len() + chunk.len() > limit {
+            return Err(RequestError::ResponseTooLarge { limit, received : body.len() + chunk.len() });
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(body.into())
+}
+
+/// Returns the first byte position of the `Content-Range` header of a partial response, if any.
+#[cfg(not(target_arch = "wasm32"))]
+fn content_range_start(response : &reqwest::Response) -> Option<u64> {
+    response.headers().get(reqwest::header::CONTENT_RANGE)?
+        .to_str().ok()?
+        .strip_prefix("bytes ")?
+        .split_once('-')?.0
+        .parse().ok()
+}
+
+/// Streams the body of a response into the writer, calling `on_progress` with the bytes written so far and the expected total after every chunk.
+#[cfg(not(target_arch = "wasm32"))]
+async fn write_body<W : tokio::io::AsyncWrite + Unpin,E>(response : reqwest::Response,dest : &mut W,on_progress : impl Fn(u64,Option<u64>)) -> Result<u64,RequestError<E>> {
+    use futures_util::StreamExt;
+    use tokio::io::AsyncWriteExt;
+
+    let total = response.content_length();
+    let mut stream = response.bytes_stream();
+    let mut written = 0;
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk?;
+        dest.write_all(&chunk).await?;
+        written += chunk.len() as u64;
+        on_progress(written,total);
+    }
+    dest.flush().await?;
+
+    Ok(written)
+}
+
+/// Waits for a permit of the rate limiter, honouring the timeout of the request when the limiter is deadline aware.
+#[cfg(not(target_arch = "wasm32"))]
+async fn acquire_permit<E>(limiter : &RateLimiter,request : &mut reqwest::Request) -> Result<(),RequestError<E>> {
+    if !limiter.is_deadline_aware() {
+        // Without a deadline a permit is always acquired.
+        let _ = limiter.acquire_for(request.url(),None).await;
+        return Ok(());
+    }
+
+    let timeout = request.timeout().copied();
+    let waited = limiter.acquire_for(request.url(),timeout).await.map_err(|_| RequestError::PermitTimeout)?;
+    if let Some(timeout) = request.timeout_mut() {
+        *timeout = timeout.saturating_sub(waited);
+    }
+    Ok(())
+}
+
+/// Returns the `Retry-After` delay of a `429` or `503` response when [RequestDefaults::honor_retry_after] is enabled.
+#[cfg(not(target_arch = "wasm32"))]
+fn honoured_retry_after<D : RequestDefaults + ?Sized>(defaults : &D,response : &RawResponse) -> Option<std::time::Duration> {
+    let status = response.meta.status;
+    let honoured = defaults.honor_retry_after() && matches!(status,StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE);
+    honoured.then(|| response.meta.retry_after()).flatten()
+}
+
+/// Turns a final `429` or `503` response into [RequestError::RateLimited] when [RequestDefaults::honor_retry_after] is enabled.
+#[cfg(not(target_arch = "wasm32"))]
+fn rate_limited<D : RequestDefaults + ?Sized,E>(defaults : &D,response : RawResponse) -> Result<RawResponse,RequestError<E>> {
+    let status = response.meta.status;
+    match defaults.honor_retry_after() && matches!(status,StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE) {
+        true => Err(RequestError::RateLimited { status, retry_after : response.meta.retry_after() }),
+        false => Ok(response)
+    }
+}
+
+/// Validates the body of a response with the [RequestDefaults::response_validator] and unwraps it from the [RequestDefaults::envelope],
+/// ahead of its deserialization.
+fn prepare_response<D : RequestDefaults + ?Sized,E : DeserializeOwned>(defaults : &D,response : RawResponse) -> Result<RawResponse,RequestError<E>> {
+    #[cfg(feature = "schema")]
+    if let Some(validator) = defaults.response_validator().filter(|_| response.meta.status.is_success()) {
+        // Bodies which are not JSON are left to the deserialization to report
+        if let Ok(body) = serde_json::from_slice::<Value>(&response.body) {
+            let violations = validator.validate(&response.meta,&body);
+            if !violations.is_empty() {
+                return Err(RequestError::SchemaViolation { violations, context : Box::new(response.meta.context()) });
+            }
+        }
+    }
+
+    match defaults.envelope() {
+        Some(envelope) => envelope.unwrap(response),
+        None => Ok(response)
+    }
+}
+
+/// Deserializes the body of a response into `T` on success or into the error payload `E` otherwise, from XML or another [ContentFormat]
+/// when the response declares it with the matching feature enabled, or from JSON.
+fn parse_response<T : DeserializeOwned,E : DeserializeOwned>(response : &RawResponse) -> Result<T,RequestError<E>> {
+    #[cfg(feature = "xml")]
+    if is_xml(&response.meta) {
+        return parse_response_with::<XmlParser,T,E>(response);
+    }
+
+    match ContentFormat::of(&response.meta) {
+        ContentFormat::Json => parse_response_with::<JsonParser,T,E>(response),
+        #[cfg(feature = "msgpack")]
+        ContentFormat::MessagePack => parse_response_with::<MessagePackParser,T,E>(response),
+        #[cfg(feature = "cbor")]
+        ContentFormat::Cbor => parse_response_with::<CborParser,T,E>(response),
+    }
+}
+
+/// Returns whether the response declares an XML `Content-Type`.
+#[cfg(feature = "xml")]
+fn is_xml(meta : &ResponseMeta) -> bool {
+    let Some(content_type) = meta.headers.get(CONTENT_TYPE).and_then(|value| value.to_str().ok()) else {
+        return false;
+    };
+    let essence = content_type.split(';').next().unwrap_or_default().trim().to_ascii_lowercase();
+    essence == "application/xml" || essence == "text/xml" || essence.ends_with("+xml")
+}
+
+/// Deserializes the body of a response with the parser `P`, into `T` on success or into the error payload `E` otherwise.
+fn parse_response_with<P : ResponseParser,T : DeserializeOwned,E : DeserializeOwned>(response : &RawResponse) -> Result<T,RequestError<E>> {
+    match response.meta.status.is_success() {
+        true => P::parse_body(&response.body).map_err(|error| error.in_context(&response.meta)),
+        false => Err(P::parse_error_body(response))
+    }
+}
+
+/// Deserializes the body of an unsuccessful response into the error payload `E`, see [JsonParser::parse_error_body], or from XML or
+/// another [ContentFormat] when the response declares it with the matching feature enabled.
+fn parse_error_payload<E : DeserializeOwned>(response : &RawResponse) -> RequestError<E> {
+    #[cfg(feature = "xml")]
+    if is_xml(&response.meta) {
+        return XmlParser::parse_error_body(response);
+    }
+
+    match ContentFormat::of(&response.meta) {
+        ContentFormat::Json => JsonParser::parse_error_body(response),
+        #[cfg(feature = "msgpack")]
+        ContentFormat::MessagePack => MessagePackParser::parse_error_body(response),
+        #[cfg(feature = "cbor")]
+        ContentFormat::Cbor => CborParser::parse_error_body(response),
+    }
+}
+
+/// Sends an HTTP request and deserializes the response into the types given at the call site.
+///
+/// [RequestHandler] fixes its success and error types per implementation, so a client talking to many endpoints
+/// needs one implementation per response type. This function is a lightweight alternative for one-off calls,
+/// applying the same status check and deserialization as [RequestHandler::request_map]. As it is not tied to a client,
+/// hooks such as the rate limiter or the canceller are not applied.
+///
+/// ```rust ignore
+/// let user = request_as::<User,ApiError,_>(api_client.default_get_query_requestor("users/1",&query),|user| user).await?;
+/// ```
+///
+/// # Arguments
+///
+/// * `request` - The `reqwest::RequestBuilder` representing the request to be sent.
+/// * `map` - A closure that maps the successful response JSON into the desired output type.
+///
+/// # Returns
+///
+/// A `Result` containing the mapped output type or an `RequestError` variant.
+pub async fn request_as<T : DeserializeOwned,E : DeserializeOwned,O>(request : reqwest::RequestBuilder,map : impl FnOnce(T) -> O) -> Result<O,RequestError<E>> {
+    #[cfg(not(target_arch = "wasm32"))]
+    let (method,response) = {
+        let (client,request) = request.build_split();
+        let request = request?;
+        (request.method().clone(),client.execute(request).await?)
+    };
+    // The fetch client of a builder cannot be taken apart, so the method is read from a copy of the request
+    #[cfg(target_arch = "wasm32")]
+    let (method,response) = {
+        let method = request.try_clone().and_then(|request| request.build().ok()).map_or(Method::GET,|request| request.method().clone());
+        (method,request.send().await?)
+    };
+    let meta = ResponseMeta::from_response(method,&response);
+    let body = response.bytes().await?;
+    parse_response(&RawResponse { meta, body }).map(map)
+}
+
+/// Sends a request through the hooks of `defaults`, retrying it according to the [RequestDefaults::retry_policy] and calling
+/// `on_retry` before each retry.
+#[cfg(not(target_arch = "wasm32"))]
+async fn send_retrying<D : RequestDefaults + ?Sized,E : DeserializeOwned>(defaults : &D,request : reqwest::RequestBuilder,on_retry : impl Fn(u32,&RequestError<E>,std::time::Duration)) -> Result<RawResponse,RequestError<E>> {
+    let fallback;
+    let retry = match defaults.retry_policy() {
+        Some(retry) => Some(retry),
+        None if defaults.honor_retry_after() => {
+            fallback = RetryConfig::default().retry_statuses([StatusCode::TOO_MANY_REQUESTS,StatusCode::SERVICE_UNAVAILABLE]);
+            Some(&fallback as &dyn RetryPolicy)
+        },
+        None => None
+    };
+
+    if let Some(retry) = retry {
+        #[cfg(feature = "uuid")]
+        let request = with_missing_idempotency_key(defaults,request)?;
+
+        let mut request = request;
+        let mut attempt = 0;
+        loop {
+            let Some(next) = request.try_clone().filter(|_| attempt < retry.max_retries()) else {
+                return send_failover(defaults,request,attempt).await.and_then(|response| rate_limited(defaults,response))
+            };
+
+            let sent = send_failover(defaults,request,attempt);
+            attempt += 1;
+            let delay = match sent.await {
+                Ok(response) if retry.is_retryable_status(response.meta.status) => {
+                    let honoured = honoured_retry_after(defaults,&response);
+                    if let Some(retry_after) = honoured.filter(|delay| defaults.max_retry_after().is_some_and(|max| *delay > max)) {
+                        return Err(RequestError::RateLimited { status : response.meta.status, retry_after : Some(retry_after) });
+                    }
+                    let delay = honoured.unwrap_or_else(|| retry.delay(attempt));
+                    on_retry(attempt,&parse_error_payload(&response),delay);
+                    delay
+                },
+                Err(error) if error.is_retryable(retry) => {
+                    let delay = retry.delay(attempt);
+                    on_retry(attempt,&error,delay);
+                    delay
+                },
+                result => return result.and_then(|response| rate_limited(defaults,response))
+            };
+
+            #[cfg(feature = "tracing")]
+            tracing::info!(attempt,delay_ms = u64::try_from(delay.as_millis()).unwrap_or(u64::MAX),"retrying request");
+            tokio::time::sleep(delay).await;
+            request = next;
+        }
+    }
+
+    send_failover(defaults,request,0).await
+}
+
+/// Sends a request with [send_hedged], to the next host of the [RequestInfo::host_pool] whenever it fails to connect or is
+/// answered with a `5xx` status.
+#[cfg(not(target_arch = "wasm32"))]
+async fn send_failover<D : RequestDefaults + ?Sized,E>(defaults : &D,request : reqwest::RequestBuilder,attempt : u32) -> Result<RawResponse,RequestError<E>> {
+    let Some(pool) = defaults.host_pool() else {
+        return send_hedged(defaults,request,attempt).await;
+    };
+
+    let (client,request) = request.build_split();
+    let mut request = request?;
+    let order = pool.order();
+    for (position,&index) in order.iter().enumerate() {
+        let Some(url) = pool.url(index,defaults.base_url(),request.url()) else {
+            return send_hedged(defaults,reqwest::RequestBuilder::from_parts(client,request),attempt).await;
+        };
+        let next = order.get(position + 1).and_then(|_| request.try_clone());
+        *request.url_mut() = url;
+
+        let response = send_hedged(defaults,reqwest::RequestBuilder::from_parts(client.clone(),request),attempt).await;
+        let reached = match &response {
+            Ok(response) => !response.meta.status.is_server_error(),
+            Err(RequestError::RequestError(error)) => !error.is_connect(),
+            Err(_) => true
+        };
+        pool.record(index,reached);
+        match (reached,next) {
+            (false,Some(next)) => {
+                log::warn!("Host {} failed, failing over to {}",pool.hosts()[index],pool.hosts()[order[position + 1]]);
+                request = next;
+            },
+            _ => return response
+        }
+    }
+    unreachable!("every host of the pool was tried")
+}
+
+/// Sends a request with [send], sending a copy whenever the [RequestDefaults::hedge_policy] delay elapses without an answer,
+/// up to its number of hedges, and returning the first successful response, the slower requests being cancelled.
+#[cfg(not(target_arch = "wasm32"))]
+async fn send_hedged<D : RequestDefaults + ?Sized,E>(defaults : &D,request : reqwest::RequestBuilder,attempt : u32) -> Result<RawResponse,RequestError<E>> {
+    use futures_util::future::{select, Either};
+    use futures_util::stream::{FuturesUnordered, StreamExt};
+
+    // Copies of the request are only built for idempotent requests whose body can be sent again
+    let idempotent = request.try_clone().and_then(|request| request.build().ok())
+        .is_some_and(|request| request.method() == Method::GET || request.method() == Method::HEAD);
+    let Some(policy) = defaults.hedge_policy().filter(|_| idempotent) else {
+        return send(defaults,request,attempt).await;
+    };
+
+    let timed = |request| async move {
+        let start = std::time::Instant::now();
+        (start,send(defaults,request,attempt).await)
+    };
+    let mut in_flight = FuturesUnordered::new();
+    in_flight.push(timed(request.try_clone().expect("idempotent requests can be cloned")));
+    let mut hedges = 0;
+    loop {
+        let completed = match hedges < policy.hedges() {
+            true => match select(in_flight.next(),std::pin::pin!(tokio::time::sleep(policy.delay()))).await {
+                Either::Left((completed,_)) => completed,
+                Either::Right(_) => None
+            },
+            false => in_flight.next().await
+        };
+        match completed {
+            Some((start,Ok(response))) => {
+                policy.record(start.elapsed());
+                if hedges > 0 {
+                    log::debug!("Hedged request answered, cancelling {} slower requests",in_flight.len());
+                }
+                return Ok(response);
+            },
+            // Retrying is left to send_retrying once every request in flight failed
+            Some((_,Err(error))) if in_flight.is_empty() => return Err(error),
+            Some((_,Err(_))) => continue,
+            // Hedge when the delay elapsed without an answer
+            None => {}
+        }
+        hedges += 1;
+        in_flight.push(timed(request.try_clone().expect("idempotent requests can be cloned")));
+    }
+}
+
+/// Sends a request with [send_coalesced], adding it to the [RequestDefaults::offline_queue] if it is a mutation failing to connect.
+#[cfg(not(target_arch = "wasm32"))]
+async fn send_queueing<D : RequestDefaults + ?Sized,E : DeserializeOwned>(defaults : &D,request : reqwest::RequestBuilder,on_retry : impl Fn(u32,&RequestError<E>,std::time::Duration)) -> Result<RawResponse,RequestError<E>> {
+    let queued = defaults.offline_queue().zip(request.try_clone().and_then(|request| request.build().ok()))
+        .filter(|(_,request)| matches!(*request.method(),Method::POST | Method::PUT | Method::PATCH | Method::DELETE));
+    let Some((queue,mut queued)) = queued else {
+        return send_coalesced(defaults,request,on_retry).await;
+    };
+
+    match send_coalesced(defaults,request,on_retry).await {
+        Err(RequestError::RequestError(error)) if error.is_connect() => {
+            offline::strip_api_key(defaults,queued.url_mut());
+            match queue.enqueue(&queued) {
+                Ok(id) => Err(RequestError::QueuedOffline { id }),
+                // The connection error is more telling than why the request could not be queued
+                Err(queue_error) => {
+                    log::warn!("Failed to queue {} {} : {queue_error}",queued.method(),logging::redact_url(queued.url()));
+                    Err(RequestError::RequestError(error))
+                }
+            }
+        },
+        result => result
+    }
+}
+
+/// Sends a request with [send_refreshing], unless an identical one is in flight in the [RequestDefaults::request_coalescer],
+/// whose response is then shared.
+#[cfg(not(target_arch = "wasm32"))]
+async fn send_coalesced<D : RequestDefaults + ?Sized,E : DeserializeOwned>(defaults : &D,request : reqwest::RequestBuilder,on_retry : impl Fn(u32,&RequestError<E>,std::time::Duration)) -> Result<RawResponse,RequestError<E>> {
+    let key = request.try_clone().and_then(|request| request.build().ok())
+        .filter(|request| request.method() == Method::GET || request.method() == Method::HEAD)
+        .map(|request| format!("{} {}",request.method(),cache::key(request.url())));
+    let Some((coalescer,key)) = defaults.request_coalescer().zip(key) else {
+        return send_refreshing(defaults,request,on_retry).await;
+    };
+
+    match coalescer.join(key) {
+        coalesce::Flight::Leader(flight) => {
+            let response = send_refreshing(defaults,request,on_retry).await;
+            flight.finish(response.as_ref().ok());
+            response
+        },
+        coalesce::Flight::Follower(receiver) => match RequestCoalescer::wait(receiver).await {
+            Some(response) => Ok(response),
+            None => send_refreshing(defaults,request,on_retry).await
+        }
+    }
+}
+
+/// Sends a request with [send_retrying], sending it once more if the [RequestDefaults::auth_refresh] refreshes the
+/// credentials after a `401 Unauthorized` response.
+#[cfg(not(target_arch = "wasm32"))]
+async fn send_refreshing<D : RequestDefaults + ?Sized,E : DeserializeOwned>(defaults : &D,request : reqwest::RequestBuilder,on_retry : impl Fn(u32,&RequestError<E>,std::time::Duration)) -> Result<RawResponse,RequestError<E>> {
+    let Some(refresh) = defaults.auth_refresh() else {
+        return send_retrying(defaults,request,on_retry).await;
+    };
+
+    let retry = request.try_clone();
+    let response = send_retrying(defaults,request,&on_retry).await?;
+    let Some(retry) = retry.filter(|_| response.meta.status == StatusCode::UNAUTHORIZED) else {
+        return Ok(response);
+    };
+
+    let (client,retry) = retry.build_split();
+    let mut retry = retry?;
+    match refresh.refresh(&mut retry,&response).await {
+        true => send_retrying(defaults,reqwest::RequestBuilder::from_parts(client,retry),on_retry).await,
+        false => Ok(response)
+    }
+}
+
+/// Returns the URL of the request if it is a `GET` request whose response can be cached, see [RequestDefaults::response_cache],
+/// and whether it skips the lookup, being sent with `Cache-Control: no-cache` or its own conditional headers.
+#[cfg(not(target_arch = "wasm32"))]
+fn cacheable_url(request : &reqwest::RequestBuilder) -> Option<(reqwest::Url,bool)> {
+    let request = request.try_clone()?.build().ok()?;
+    let headers = request.headers();
+    let bypass = cache::has_directive(headers,"no-cache") || headers.contains_key(reqwest::header::IF_NONE_MATCH) || headers.contains_key(reqwest::header::IF_MODIFIED_SINCE);
+    (request.method() == Method::GET).then(|| (request.url().clone(),bypass))
+}
+
+/// Adds an idempotency key to `POST` and `PATCH` requests lacking one, see [RequestDefaults::idempotency_header].
+#[cfg(all(feature = "uuid",not(target_arch = "wasm32")))]
+fn with_missing_idempotency_key<D : RequestDefaults + ?Sized,E>(defaults : &D,request : reqwest::RequestBuilder) -> Result<reqwest::RequestBuilder,RequestError<E>> {
+    let Some(name) = defaults.idempotency_header() else {
+        return Ok(request)
+    };
+
+    let (client,request) = request.build_split();
+    let request = request?;
+    let is_missing = matches!(*request.method(),Method::POST | Method::PATCH) && !request.headers().contains_key(name);
+    let request = reqwest::RequestBuilder::from_parts(client,request);
+    match is_missing {
+        true => Ok(defaults.with_idempotency_key(request).0),
+        false => Ok(request)
+    }
+}
+
+/// Compresses a serialized body with the [RequestDefaults::body_compression], if any, setting its `Content-Encoding`.
+#[cfg_attr(not(any(feature = "gzip",feature = "deflate",feature = "zstd")),allow(unused_variables))]
+fn compress_body<D : RequestDefaults + ?Sized>(defaults : &D,request_builder : RequestBuilder,body : Vec<u8>) -> (RequestBuilder,Vec<u8>) {
+    #[cfg(any(feature = "gzip",feature = "deflate",feature = "zstd"))]
+    if let Some(compression) = defaults.body_compression() {
+        return match compression.compress(body) {
+            (body,Some(coding)) => (request_builder.header(reqwest::header::CONTENT_ENCODING,coding.name()),body),
+            (body,None) => (request_builder,body)
+        };
+    }
+    (request_builder,body)
+}
+
+/// Merges the default query parameters with the per-request ones, the latter winning on conflict.
+fn merge_query<'a>(mut defaults : HashMap<&'a str,Value>,parameters : &HashMap<&'a str,Value>) -> HashMap<&'a str,Value> {
+    defaults.extend(parameters.iter().map(|(key,value)| (*key,value.clone())));
+    defaults
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+tokio::task_local! {
+    /// The deadline given to [RequestHandler::request_map_with_timeout], overriding the [RequestDefaults::request_deadline].
+    static DEADLINE : std::time::Duration;
+
+    /// The limit given to [RequestHandler::request_map_with_limit], overriding the [RequestDefaults::max_response_bytes].
+    static MAX_RESPONSE_BYTES : usize;
+
+    /// The metadata of the last response received during the current call to [RequestHandler::request_raw].
+    static LAST_RESPONSE : std::sync::Arc<LastResponse>;
+}
+
+/// A trait for handling HTTP requests.
+#[cfg_attr(not(feature = "wasm"),async_trait)]
+#[cfg_attr(feature = "wasm",async_trait(?Send))]
+pub trait RequestHandler<T : DeserializeOwned,O : DeserializeOwned,E : DeserializeOwned> : RequestDefaults {
+    /// Sends an HTTP request and reads the whole response without deserializing its body.
+    ///
+    /// This is the part of [RequestHandler::request_map] dealing with the exchange itself : it honours the configured
+    /// canceller, rate limiter, response cache, mocks and HAR recorder/replayer. Apart from maintenance responses recognised by the
+    /// [RequestDefaults::maintenance_detector], the status code is not checked, making it the building block for handlers
+    /// that interpret responses differently, such as `request_protobuf` decoding protobuf bodies with `prost`.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The `reqwest::RequestBuilder` representing the request to be sent.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the [RawResponse] or an `RequestError` variant.
+    async fn request_raw(&self,request : reqwest::RequestBuilder) -> Result<RawResponse,RequestError<E>> {
+        let last_response = std::sync::Arc::new(LastResponse::default());
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let response = {
+            let exchange = async {
+                let (client,request) = request.build_split();
+                let request = reqwest::RequestBuilder::from_parts(client,self.before_send(request?).await);
+                let on_retry = |attempt,error : &RequestError<E>,delay| self.on_retry(attempt,error,delay);
+                let Some((cache,(url,bypass))) = self.response_cache().zip(cacheable_url(&request)) else {
+                    return send_queueing(self,request,on_retry).await;
+                };
+
+                let endpoint = relative_endpoint(self,&url);
+                let (request,revalidating) = match bypass {
+                    true => (request,false),
+                    false => match cache.lookup(endpoint,&url,tokio::runtime::Handle::try_current().is_ok()) {
+                        cache::Lookup::Fresh(response) => return Ok(*response),
+                        cache::Lookup::Stale { response, refresh } => {
+                            if refresh {
+                                cache.refresh_in_background(endpoint,&url,request);
+                            }
+                            return Ok(*response);
+                        },
+                        cache::Lookup::Expired { etag, last_modified } if etag.is_some() || last_modified.is_some() => {
+                            let request = match etag {
+                                Some(etag) => request.header(reqwest::header::IF_NONE_MATCH,etag),
+                                None => request
+                            };
+                            match last_modified {
+                                Some(date) => (request.header(reqwest::header::IF_MODIFIED_SINCE,date),true),
+                                None => (request,true)
+                            }
+                        },
+                        cache::Lookup::Expired { .. } | cache::Lookup::Missing => (request,false)
+                    }
+                };
+
+                let mut response = send_coalesced(self,request,on_retry).await?;
+                if revalidating && response.meta.status == StatusCode::NOT_MODIFIED {
+                    if let Some(response) = cache.revalidated(endpoint,&url,&response) {
+                        return Ok(response);
+                    }
+                }
+                cache.insert(endpoint,&url,&response);
+                response.meta.cache = Some(CacheStatus::Miss);
+                Ok(response)
+            };
+
+            let exchange = LAST_RESPONSE.scope(last_response.clone(),exchange);
+            match DEADLINE.try_with(|deadline| *deadline).ok().or_else(|| self.request_deadline()) {
+                Some(deadline) => tokio::time::timeout(deadline,exchange).await.unwrap_or(Err(RequestError::Timeout)),
+                None => exchange.await
+            }
+        };
+
+        #[cfg(target_arch = "wasm32")]
+        let response = {
+            let request = match request.build() {
+                Ok(request) => Ok(self.before_send(request).await),
+                Err(error) => Err(error)
+            };
+            send_request::<_,E>(self,self.client().clone(),request,0,&last_response).await
+        };
+
+        // Error payloads are only deserialized by the handlers, so the outcome can be held across the hook without `E` being `Send`
+        let response = response.map_err(|error| error.map_payload(|_| unreachable!("request_raw does not deserialize error payloads")));
+        let last_response = last_response.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).take();
+        let meta = match &response {
+            Ok(response) => Some(&response.meta),
+            Err(_) => last_response.as_ref()
+        };
+        self.after_response(meta,&response).await;
+        response.map_err(|error| error.map_payload(|never| match never {}))
+    }
+
+    /// Called with every request sent through [RequestHandler::request_raw], and so through the handlers reading whole
+    /// responses, returning the request to send, such as with per-call credentials. Returns the request as it is by default.
+    ///
+    /// It is called once per call, before the [RequestDefaults::response_cache] is looked up, so retries are sent with the
+    /// returned request. For changes that do not need to await anything, see [RequestDefaults::finalize_request] and
+    /// [RequestDefaults::middleware].
+    ///
+    /// ```rust ignore
+    /// async fn before_send(&self,mut request : reqwest::Request) -> reqwest::Request {
+    ///     let token = self.vault.token().await;
+    ///     request.headers_mut().insert(AUTHORIZATION,HeaderValue::from_str(&format!("Bearer {token}")).unwrap());
+    ///     request
+    /// }
+    /// ```
+    async fn before_send(&self,request : reqwest::Request) -> reqwest::Request {
+        request
+    }
+
+    /// Called with the outcome of every call to [RequestHandler::request_raw], and so of the handlers reading whole
+    /// responses, once retries are over, such as to keep an audit log or to store the quota reported in the headers. Does
+    /// nothing by default.
+    ///
+    /// Responses are passed whatever their status, which the handlers check afterwards, and responses served from the
+    /// [RequestDefaults::response_cache] are passed as well. The call is awaited before the handler returns, so work such
+    /// as writing to a database delays the response.
+    ///
+    /// ```rust ignore
+    /// async fn after_response(&self,meta : Option<&ResponseMeta>,result : &Result<RawResponse,RequestError<Infallible>>) {
+    ///     if let Some(meta) = meta {
+    ///         self.audit_log.insert(&meta.context(),result.is_ok()).await;
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `meta` - The metadata of the response, also given for calls failing because of the response received, such as
+    ///   [RequestError::MaintenanceMode], [RequestError::RateLimited] or [RequestError::ResponseTooLarge]. `None` if the
+    ///   last attempt received no response, such as after a connection failure.
+    /// * `result` - The response, or the error the call failed with. Error payloads are only deserialized by the handlers
+    ///   afterwards, so no error carries one, which lets the outcome be passed without requiring `E` to be `Send`.
+    async fn after_response(&self,meta : Option<&ResponseMeta>,result : &Result<RawResponse,RequestError<std::convert::Infallible>>) {
+        let _ = (meta,result);
+    }
+
+    /// Called before a request is retried, see [RequestDefaults::retry_policy].
+    ///
+    /// Useful for logging or collecting metrics about retries. Does nothing by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `attempt` - The number of the upcoming retry, starting at `1`.
+    /// * `error` - The error that caused the retry. Retried statuses are reported through their error payload.
+    /// * `next_delay` - How long is waited before the retry is sent.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn on_retry(&self,attempt : u32,error : &RequestError<E>,next_delay : std::time::Duration) {
+        let _ = (attempt,error,next_delay);
+    }
+
+    /// Sends an HTTP request, processes the response, and maps it using the provided closure.
+    ///
+    /// This asynchronous function sends an HTTP request using the given `reqwest::RequestBuilder`,
+    /// processes the response, and maps it using the provided closure. It returns the mapped
+    /// result if the request is successful, or an `RequestError::ErrorPayload` variant if the
+    /// request fails.
+    ///
+    /// Bodies are deserialized from JSON, or from XML when the `xml` feature is enabled and the response declares an XML
+    /// `Content-Type` (such as `application/xml`, `text/xml` or `application/atom+xml`). When [RequestDefaults::envelope]
+    /// returns an [Envelope], only its data field is deserialized.
+    ///
+    /// # Arguments
+    ///
+    /// * `self` - A reference to the struct implementing this trait.
+    /// * `request` - The `reqwest::RequestBuilder` representing the request to be sent.
+    /// * `map` - A closure that maps the successful response JSON into the desired output type. Just write `|x| x` if the not mapping is required. 
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the mapped output type or an `RequestError` variant.
+    async fn request_map(&self,request: reqwest::RequestBuilder,map : impl FnOnce(T) -> O + MaybeSend + MaybeSync) -> Result<O,RequestError<E>> {
+        self.request_map_both(request,map,|error| error).await
+    }
+
+    /// Sends an HTTP request like [RequestHandler::request_map], failing with [RequestError::Timeout] unless it completes
+    /// within `timeout`, retries included.
+    ///
+    /// ```rust ignore
+    /// let user = api_client.request_map_with_timeout(request,Duration::from_secs(2),|user| user).await?;
+    /// ```
+    ///
+    /// The timeout replaces the [RequestDefaults::request_deadline] for this call, so it may be longer as well as shorter,
+    /// such as for a slow export endpoint. The timeout of each attempt is still set with `reqwest::RequestBuilder::timeout`.
+    /// Not available on `wasm32`.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The `reqwest::RequestBuilder` representing the request to be sent.
+    /// * `timeout` - How long the request may take as a whole.
+    /// * `map` - A closure that maps the successful response JSON into the desired output type.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the mapped output type or an `RequestError` variant.
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn request_map_with_timeout(&self,request : reqwest::RequestBuilder,timeout : std::time::Duration,map : impl FnOnce(T) -> O + MaybeSend + MaybeSync) -> Result<O,RequestError<E>> {
+        DEADLINE.scope(timeout,self.request_map(request,map)).await
+    }
+
+    /// Sends an HTTP request like [RequestHandler::request_map], failing with [RequestError::ResponseTooLarge] as soon as
+    /// more than `limit` bytes of the response body are received.
+    ///
+    /// ```rust ignore
+    /// let export = api_client.request_map_with_limit(request,64 * 1024 * 1024,|export| export).await?;
+    /// ```
+    ///
+    /// The limit replaces the [RequestDefaults::max_response_bytes] for this call, so it may be larger as well as smaller.
+    /// Not available on `wasm32`.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The `reqwest::RequestBuilder` representing the request to be sent.
+    /// * `limit` - The maximum size of the response body, in bytes.
+    /// * `map` - A closure that maps the successful response JSON into the desired output type.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the mapped output type or an `RequestError` variant.
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn request_map_with_limit(&self,request : reqwest::RequestBuilder,limit : usize,map : impl FnOnce(T) -> O + MaybeSend + MaybeSync) -> Result<O,RequestError<E>> {
+        MAX_RESPONSE_BYTES.scope(limit,self.request_map(request,map)).await
+    }
+
+    /// Sends an HTTP request like [RequestHandler::request_map], aborting it as soon as `token` is cancelled, such as when
+    /// the user leaves the screen that needed the response.
+    ///
+    /// ```rust ignore
+    /// let token = CancellationToken::new();
+    /// let search = api_client.request_map_cancellable(request,&token,|results| results);
+    /// // From the UI, when the query changes
+    /// token.cancel();
+    /// ```
+    ///
+    /// Cancelling drops the request along with its connection, wherever it is : waiting for the rate limiter, between
+    /// retries or reading the body. Dropping the future of any handler cancels it the same way, the token only tells
+    /// cancelled requests apart by failing them with [RequestError::Cancelled]. Unlike [RequestDefaults::cancel_all], it
+    /// only aborts the requests given the token, which can be a child of another to cancel them by groups.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The `reqwest::RequestBuilder` representing the request to be sent.
+    /// * `token` - The token cancelling the request.
+    /// * `map` - A closure that maps the successful response JSON into the desired output type.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the mapped output type or an `RequestError` variant.
+    async fn request_map_cancellable(&self,request : reqwest::RequestBuilder,token : &CancellationToken,map : impl FnOnce(T) -> O + MaybeSend + MaybeSync) -> Result<O,RequestError<E>> {
+        token.run_until_cancelled(self.request_map(request,map)).await.unwrap_or(Err(RequestError::Cancelled))
+    }
+
+    /// Sends an already built `reqwest::Request` and maps the response like [RequestHandler::request_map].
+    ///
+    /// This is an escape hatch for requests the default requestors cannot build, such as unusual methods or signed URLs,
+    /// which still get the status check, deserialization and error payload handling of this trait. The request is sent
+    /// with [RequestInfo::client] and the sending hooks (rate limiter, retries, ...) apply. Not available on `wasm32`.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The `reqwest::Request` to be sent.
+    /// * `map` - A closure that maps the successful response JSON into the desired output type.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the mapped output type or an `RequestError` variant.
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn execute_map(&self,request : reqwest::Request,map : impl FnOnce(T) -> O + MaybeSend + MaybeSync) -> Result<O,RequestError<E>> {
+        self.request_map(RequestBuilder::from_parts(self.client().clone(),request),map).await
+    }
+
+    /// Sends an HTTP request like [RequestHandler::request_map], mapping both the successful response and the error payload.
+    ///
+    /// ```rust ignore
+    /// let user = api_client.request_map_both(request,|user| user,|error : ApiError| MyError::from(error)).await?;
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The `reqwest::RequestBuilder` representing the request to be sent.
+    /// * `map` - A closure that maps the successful response JSON into the desired output type.
+    /// * `map_error` - A closure that maps the error payload into the desired error type, see [RequestError::map_payload].
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the mapped output type or an `RequestError` variant carrying the mapped error payload.
+    async fn request_map_both<F>(&self,request: reqwest::RequestBuilder,map : impl FnOnce(T) -> O + MaybeSend + MaybeSync,map_error : impl FnOnce(E) -> F + MaybeSend + MaybeSync) -> Result<O,RequestError<F>> {
+        let response = self.request_raw(request).await.and_then(|response| prepare_response(self,response));
+        response.and_then(|response| parse_response(&response)).map(map).map_err(|error| error.map_payload(map_error))
+    }
+
+    /// Sends an HTTP request like [RequestHandler::request_map], also passing the metadata of the response to the map closure.
+    ///
+    /// Useful to read the status code, rate-limit headers or ETags alongside the deserialized body.
+    ///
+    /// ```rust ignore
+    /// let (user,etag) = api_client.request_map_with_meta(request,|user,meta| (user,meta.headers.get(ETAG).cloned())).await?;
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The `reqwest::RequestBuilder` representing the request to be sent.
+    /// * `map` - A closure that maps the successful response JSON and its [ResponseMeta] into the desired output type.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the mapped output type or an `RequestError` variant.
+    async fn request_map_with_meta(&self,request: reqwest::RequestBuilder,map : impl FnOnce(T,ResponseMeta) -> O + MaybeSend + MaybeSync) -> Result<O,RequestError<E>> {
+        let response = prepare_response(self,self.request_raw(request).await?)?;
+        let body = parse_response(&response)?;
+        Ok(map(body,response.meta))
+    }
+
+    /// Sends an HTTP request and lends the body of the successful response to `scope`, which can deserialize it into types
+    /// borrowing from it with [RawResponse::json], avoiding an allocation for every string of large responses.
+    ///
+    /// ```rust ignore
+    /// #[derive(Deserialize)]
+    /// struct Repository<'a> { name : &'a str, #[serde(borrow)] description : Option<Cow<'a,str>> }
+    ///
+    /// let starred = api_client.request_scoped(request,|response| {
+    ///     let repositories : Vec<Repository> = response.json()?;
+    ///     Ok(repositories.iter().filter(|repository| repository.name.starts_with("rust")).count())
+    /// }).await?;
+    /// ```
+    ///
+    /// The borrowed values cannot outlive `scope`, which returns what is needed from them. To keep them longer, keep the
+    /// [RawResponse] of [RequestHandler::request_raw] instead. Unsuccessful responses are turned into errors like in
+    /// [RequestHandler::request_map].
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The `reqwest::RequestBuilder` representing the request to be sent.
+    /// * `scope` - A closure deserializing the successful response and computing the output from it.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the output of `scope` or an `RequestError` variant, [RequestError::InvalidJsonBody] if `scope`
+    /// fails to deserialize the body.
+    async fn request_scoped<R>(&self,request : reqwest::RequestBuilder,scope : impl for<'r> FnOnce(&'r RawResponse) -> Result<R,serde_json::Error> + MaybeSend + MaybeSync) -> Result<R,RequestError<E>> {
+        let response = prepare_response(self,self.request_raw(request).await?)?;
+        match response.meta.status.is_success() {
+            true => scope(&response).map_err(|error| RequestError::from(error).in_context(&response.meta)),
+            false => Err(parse_error_payload(&response))
+        }
+    }
+
+    /// Sends an HTTP request and maps the response like [RequestHandler::request_map], deserializing the error payload according
+    /// to the status code of the response with [StatusErrorMapper::from_status].
+    ///
+    /// ```rust ignore
+    /// match api_client.request_map_status(request,|user| user).await {
+    ///     Err(RequestError::ErrorPayload { payload : ApiError::Auth(error), .. }) => { /* Ask for credentials again */ }
+    ///     Err(RequestError::ErrorPayload { payload : ApiError::Validation(error), .. }) => { /* Highlight the invalid fields */ }
+    ///     result => { /* ... */ }
+    /// }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The `reqwest::RequestBuilder` representing the request to be sent.
+    /// * `map` - A closure that maps the successful response, deserialized like in [RequestHandler::request_map], into the desired output type.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the mapped output type or an `RequestError` variant. A body that does not match the payload chosen
+    /// for its status yields [RequestError::ErrorPayloadParseFailed].
+    async fn request_map_status(&self,request: reqwest::RequestBuilder,map : impl FnOnce(T) -> O + MaybeSend + MaybeSync) -> Result<O,RequestError<E>> where E : StatusErrorMapper {
+        let response = prepare_response(self,self.request_raw(request).await?)?;
+        let status = response.meta.status;
+        match status.is_success() {
+            true => parse_response(&response).map(map),
+            false => Err(match E::from_status(status,&response.body) {
+                Ok(payload) => RequestError::ErrorPayload { payload, context : Box::new(response.meta.context()) },
+                Err(source) => RequestError::ErrorPayloadParseFailed {
+                    status,
+                    source,
+                    body : String::from_utf8_lossy(&response.body).into_owned(),
+                }
+            })
+        }
+    }
+
+    /// Sends an HTTP request and maps the response like [RequestHandler::request_map], deserializing the bodies with the parser `P`.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The `reqwest::RequestBuilder` representing the request to be sent.
+    /// * `map` - A closure that maps the successful response into the desired output type.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the mapped output type or an `RequestError` variant.
+    async fn request_map_with<P : ResponseParser>(&self,request: reqwest::RequestBuilder,map : impl FnOnce(T) -> O + MaybeSend + MaybeSync) -> Result<O,RequestError<E>> {
+        let response = prepare_response(self,self.request_raw(request).await?)?;
+        parse_response_with::<P,T,E>(&response).map(map)
+    }
+
+    /// Sends an HTTP request and maps the response like [RequestHandler::request_map], deserializing both success and error bodies as XML.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The `reqwest::RequestBuilder` representing the request to be sent.
+    /// * `map` - A closure that maps the successful response into the desired output type.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the mapped output type or an `RequestError` variant.
+    #[cfg(feature = "xml")]
+    async fn request_map_xml(&self,request: reqwest::RequestBuilder,map : impl FnOnce(T) -> O + MaybeSend + MaybeSync) -> Result<O,RequestError<E>> {
+        self.request_map_with::<XmlParser>(request,map).await
+    }
+
+    /// Sends an HTTP request and maps the response like [RequestHandler::request_map], deserializing both success and error bodies
+    /// as MessagePack whatever their `Content-Type`.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The `reqwest::RequestBuilder` representing the request to be sent.
+    /// * `map` - A closure that maps the successful response into the desired output type.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the mapped output type or an `RequestError` variant.
+    #[cfg(feature = "msgpack")]
+    async fn request_map_msgpack(&self,request: reqwest::RequestBuilder,map : impl FnOnce(T) -> O + MaybeSend + MaybeSync) -> Result<O,RequestError<E>> {
+        self.request_map_with::<MessagePackParser>(request,map).await
+    }
+
+    /// Sends an HTTP request and maps the response like [RequestHandler::request_map], deserializing both success and error bodies
+    /// as CBOR whatever their `Content-Type`.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The `reqwest::RequestBuilder` representing the request to be sent.
+    /// * `map` - A closure that maps the successful response into the desired output type.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the mapped output type or an `RequestError` variant.
+    #[cfg(feature = "cbor")]
+    async fn request_map_cbor(&self,request: reqwest::RequestBuilder,map : impl FnOnce(T) -> O + MaybeSend + MaybeSync) -> Result<O,RequestError<E>> {
+        self.request_map_with::<CborParser>(request,map).await
+    }
+
+    /// Sends an HTTP request expecting no content in return, such as most DELETE requests.
+    ///
+    /// The body of a successful response is ignored whatever it holds, while unsuccessful responses are handled exactly like
+    /// in [RequestHandler::request_map]. Note that `request_map` also accepts empty bodies when `T` is `()` or an `Option`.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The `reqwest::RequestBuilder` representing the request to be sent.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating whether the request succeeded.
+    async fn request_map_no_content(&self,request : reqwest::RequestBuilder) -> Result<(),RequestError<E>> {
+        self.request_bytes(request).await.map(|_| ())
+    }
+
+    /// Sends an HTTP request and returns the body of a successful response as text, without deserializing it.
+    ///
+    /// Invalid UTF-8 sequences are replaced with `U+FFFD`. Unsuccessful responses are handled exactly like in
+    /// [RequestHandler::request_map].
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The `reqwest::RequestBuilder` representing the request to be sent.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the body or an `RequestError` variant.
+    async fn request_text(&self,request : reqwest::RequestBuilder) -> Result<String,RequestError<E>> {
+        self.request_bytes(request).await.map(|body| String::from_utf8_lossy(&body).into_owned())
+    }
+
+    /// Sends an HTTP request and returns the body of a successful response as is.
+    ///
+    /// Unsuccessful responses are handled exactly like in [RequestHandler::request_map].
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The `reqwest::RequestBuilder` representing the request to be sent.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the body or an `RequestError` variant.
+    async fn request_bytes(&self,request : reqwest::RequestBuilder) -> Result<Bytes,RequestError<E>> {
+        let response = self.request_raw(request).await?;
+        match response.meta.status.is_success() {
+            true => Ok(response.body),
+            false => Err(parse_error_payload(&response))
+        }
+    }
+
+    /// Sends an HTTP request and deserializes every record of the `text/csv` body of a successful response, such as the
+    /// exports of reporting endpoints.
+    ///
+    /// The `Accept` header of the request is replaced with `text/csv`. Unsuccessful responses are handled exactly like in
+    /// [RequestHandler::request_map].
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The `reqwest::RequestBuilder` representing the request to be sent.
+    /// * `options` - The delimiter and header row of the records.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the records or an `RequestError` variant, [RequestError::InvalidCsvBody] if a record does not
+    /// match `R`.
+    #[cfg(feature = "csv")]
+    async fn request_csv<R : DeserializeOwned>(&self,request : reqwest::RequestBuilder,options : &CsvOptions) -> Result<Vec<R>,RequestError<E>> {
+        let request = request.headers(HeaderMap::from_iter([(ACCEPT,HeaderValue::from_static("text/csv"))]));
+        let response = self.request_raw(request).await?;
+        match response.meta.status.is_success() {
+            true => options.parse(&response.body).map_err(RequestError::InvalidCsvBody),
+            false => Err(parse_error_payload(&response))
+        }
+    }
+
+    /// Sends an HTTP request and decodes the protobuf body of the response, for the REST gateways of gRPC services.
+    ///
+    /// The message `M` is decoded from successful responses and the error message `F` from the others, both being `prost`
+    /// messages rather than types of this handler. The `Accept` header of the request is replaced with `application/x-protobuf`,
+    /// so any requestor can be used, such as [RequestDefaults::default_protobuf_requestor] for requests carrying a message :
+    ///
+    /// ```rust ignore
+    /// let request = api_client.default_protobuf_requestor(Method::POST,"v1/users",&CreateUserRequest { name : "ferris".into() });
+    /// let user = api_client.request_protobuf::<User,google::rpc::Status>(request).await?;
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The `reqwest::RequestBuilder` representing the request to be sent.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the decoded message or an `RequestError` variant, [RequestError::InvalidProtobufBody] if the body
+    /// of a successful response is not an `M`, and [RequestError::UnexpectedBody] if the body of an unsuccessful one is not an `F`.
+    #[cfg(feature = "protobuf")]
+    async fn request_protobuf<M : prost::Message + Default,F : prost::Message + Default>(&self,request : reqwest::RequestBuilder) -> Result<M,RequestError<F>> {
+        let request = request.headers(HeaderMap::from_iter([(ACCEPT,HeaderValue::from_static(PROTOBUF))]));
+        // `request_raw` does not deserialize error payloads, so its errors never carry an `E`
+        let response = self.request_raw(request).await.map_err(|error| error.map_payload(|_| unreachable!()))?;
+        match response.meta.status.is_success() {
+            true => M::decode(response.body).map_err(RequestError::InvalidProtobufBody),
+            false => Err(match F::decode(response.body.clone()) {
+                Ok(payload) => RequestError::ErrorPayload { payload, context : Box::new(response.meta.context()) },
+                Err(_) => RequestError::UnexpectedBody {
+                    status : response.meta.status,
+                    body : String::from_utf8_lossy(&response.body).into_owned(),
+                }
+            })
+        }
+    }
+
+    /// Sends an HTTP request and streams a successful response body into a file, without buffering it in memory.
+    ///
+    /// The file is created (or truncated) only once a successful status has been received. Unsuccessful responses are read
+    /// and deserialized into the error payload exactly like in [RequestHandler::request_map], leaving the file untouched.
+    /// Downloads are not captured by the [RequestDefaults::har_recorder]. Not available on `wasm32`.
+    ///
+    /// ```rust ignore
+    /// let written = api_client.download_to_file(api_client.default_get_query_requestor("exports/latest",&query),Path::new("export.zip")).await?;
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The `reqwest::RequestBuilder` representing the request to be sent.
+    /// * `path` - The path of the file to write the body to.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the number of bytes written or an `RequestError` variant.
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn download_to_file(&self,request : reqwest::RequestBuilder,path : &std::path::Path) -> Result<u64,RequestError<E>> {
+        let download = async {
+            let response = open_response(self,request).await?;
+            let mut file = tokio::fs::File::create(path).await?;
+            write_body(response,&mut file,|_,_| ()).await
+        };
+
+        match self.canceller() {
+            Some(canceller) => canceller.run(download).await.ok_or(RequestError::Cancelled)?,
+            None => download.await
+        }
+    }
+
+    /// Sends an HTTP request and streams a successful response body into the given writer chunk by chunk, reporting the
+    /// progress along the way.
+    ///
+    /// Like [RequestHandler::download_to_file], nothing is written for unsuccessful responses, which are deserialized into the
+    /// error payload instead. Not available on `wasm32`.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The `reqwest::RequestBuilder` representing the request to be sent.
+    /// * `dest` - The writer receiving the body, flushed once the whole body is written.
+    /// * `on_progress` - A closure called after every chunk with the number of bytes written so far and the total size of the
+    ///   body, if announced by the `Content-Length` header.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the number of bytes written or an `RequestError` variant.
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn download_to_writer<W : tokio::io::AsyncWrite + Unpin + MaybeSend>(&self,request : reqwest::RequestBuilder,mut dest : W,on_progress : impl Fn(u64,Option<u64>) + MaybeSend + MaybeSync) -> Result<u64,RequestError<E>> {
+        let download = async {
+            let response = open_response(self,request).await?;
+            write_body(response,&mut dest,on_progress).await
+        };
+
+        match self.canceller() {
+            Some(canceller) => canceller.run(download).await.ok_or(RequestError::Cancelled)?,
+            None => download.await
+        }
+    }
+
+    /// Sends an HTTP request resuming the download of a partially written file, appending the rest of the body to it.
+    ///
+    /// When the file already holds some bytes, the request asks for the remaining ones with a `Range: bytes=N-` header,
+    /// along with an `If-Range` header when a validator (usually the `ETag` of the first response) is given. A
+    /// `206 Partial Content` response is then appended to the file, while a `200 OK` response to a request carrying a
+    /// validator means the resource changed, so the file is rewritten from scratch. A server ignoring the range otherwise
+    /// fails with [RequestError::RangeNotSupported], leaving the file untouched. A missing or empty file is downloaded
+    /// like with [RequestHandler::download_to_file]. Note that requesting the rest of an already complete file usually
+    /// fails with a `416 Range Not Satisfiable` error payload. Not available on `wasm32`.
+    ///
+    /// ```rust ignore
+    /// let size = api_client.resume_download_to_file(api_client.default_get_query_requestor("exports/latest",&query),Path::new("export.zip"),Some(&etag),|written,total| {
+    ///     println!("{written} / {total:?} bytes");
+    /// }).await?;
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The `reqwest::RequestBuilder` representing the request to be sent.
+    /// * `path` - The path of the file to resume writing the body to.
+    /// * `if_range` - The `ETag` or `Last-Modified` date the partial file was downloaded with, if known.
+    /// * `on_progress` - A closure called after every chunk with the size of the file so far and its expected total size.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the size of the complete file or an `RequestError` variant.
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn resume_download_to_file(&self,request : reqwest::RequestBuilder,path : &std::path::Path,if_range : Option<&str>,on_progress : impl Fn(u64,Option<u64>) + MaybeSend + MaybeSync) -> Result<u64,RequestError<E>> {
+        use reqwest::header::{IF_RANGE, RANGE};
+
+        let download = async {
+            let offset = match tokio::fs::metadata(path).await {
+                Ok(metadata) => metadata.len(),
+                Err(error) if error.kind() == std::io::ErrorKind::NotFound => 0,
+                Err(error) => return Err(error.into())
+            };
+            let request = match (offset,if_range) {
+                (0,_) => request,
+                (_,Some(validator)) => request.header(RANGE,format!("bytes={offset}-")).header(IF_RANGE,validator),
+                (_,None) => request.header(RANGE,format!("bytes={offset}-"))
+            };
+
+            let response = open_response(self,request).await?;
+            let resumed = match response.status() {
+                StatusCode::PARTIAL_CONTENT if content_range_start(&response) == Some(offset) => true,
+                status if status != StatusCode::PARTIAL_CONTENT && (offset == 0 || if_range.is_some()) => false,
+                status => return Err(RequestError::RangeNotSupported { status })
+            };
+
+            let (mut file,offset) = match resumed {
+                true => (tokio::fs::OpenOptions::new().append(true).open(path).await?,offset),
+                false => (tokio::fs::File::create(path).await?,0)
+            };
+            let written = write_body(response,&mut file,|written,total| on_progress(offset + written,total.map(|total| offset + total))).await?;
+
+            Ok(offset + written)
+        };
+
+        match self.canceller() {
+            Some(canceller) => canceller.run(download).await.ok_or(RequestError::Cancelled)?,
+            None => download.await
+        }
+    }
+
+    /// Sends an HTTP request and deserializes a newline-delimited JSON (NDJSON) response line by line as it arrives.
+    ///
+    /// Unlike [RequestHandler::request_map], the body is never buffered entirely, which makes it suitable for long-lived
+    /// streaming endpoints and bulk exports. Empty lines are skipped. JSON text sequences (`application/json-seq`), whose
+    /// records start with an ASCII record separator, are supported as well. An unsuccessful response yields a single error,
+    /// handled exactly like in `request_map`. Retries and the [RequestDefaults::canceller] do not apply to streams.
+    ///
+    /// ```rust ignore
+    /// let events = api_client.request_stream_ndjson(api_client.default_get_query_requestor("events",&query));
+    /// futures_util::pin_mut!(events);
+    /// while let Some(event) = events.next().await {
+    ///     println!("{:?}",event?);
+    /// }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The `reqwest::RequestBuilder` representing the request to be sent.
+    ///
+    /// # Returns
+    ///
+    /// A `Stream` of the deserialized lines or `RequestError` variants.
+    fn request_stream_ndjson<'a>(&'a self,request : reqwest::RequestBuilder) -> impl Stream<Item = Result<T,RequestError<E>>> + 'a where T : 'a, E : 'a {
+        use futures_util::StreamExt;
+
+        futures_util::stream::once(open_response(self,request)).flat_map(|response| match response {
+            Ok(response) => ndjson_lines(response.bytes_stream()).left_stream(),
+            Err(error) => futures_util::stream::once(std::future::ready(Err(error))).right_stream()
+        })
+    }
+
+    /// Sends an HTTP request and deserializes a successful response with a [serde::de::DeserializeSeed].
+    ///
+    /// This is the advanced counterpart of [RequestHandler::request_map] for types that need runtime context while
+    /// being deserialized, such as interning strings or resolving references against a shared registry. Unsuccessful
+    /// responses are handled exactly like in `request_map`.
+    ///
+    /// ```rust ignore
+    /// struct Interned<'a>(&'a mut HashSet<Arc<str>>);
+    ///
+    /// impl<'de> DeserializeSeed<'de> for Interned<'_> {
+    ///     type Value = Vec<Arc<str>>;
+    ///     fn deserialize<D : Deserializer<'de>>(self,deserializer : D) -> Result<Self::Value,D::Error> {
+    ///         let names = Vec::<String>::deserialize(deserializer)?;
+    ///         Ok(names.into_iter().map(|name| self.0.get_or_insert_with(name.as_str(),Arc::from).clone()).collect())
+    ///     }
+    /// }
+    ///
+    /// let names = api_client.request_map_seeded(api_client.default_get_query_requestor("names",&query),Interned(&mut registry)).await?;
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The `reqwest::RequestBuilder` representing the request to be sent.
+    /// * `seed` - The seed used to deserialize the successful response JSON.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the value produced by the seed or an `RequestError` variant.
+    async fn request_map_seeded<S,V>(&self,request : reqwest::RequestBuilder,seed : S) -> Result<V,RequestError<E>> where S : for<'de> DeserializeSeed<'de,Value = V> + MaybeSend {
+        let response = prepare_response(self,self.request_raw(request).await?)?;
+
+        match response.meta.status.is_success() {
+            true => {
+                let mut deserializer = serde_json::Deserializer::from_slice(&response.body);
+                seed.deserialize(&mut deserializer)
+                    .and_then(|value| deserializer.end().map(|_| value))
+                    .map_err(|error| RequestError::from(error).in_context(&response.meta))
+            }
+            false => Err(parse_error_payload(&response))
+        }
+    }
+
+    /// Sends a GraphQL request and processes the GraphQL response envelope.
+    ///
+    /// Unlike [RequestHandler::request_map], a populated top-level `errors` array fails with [RequestError::GraphQLErrors]
+    /// even when the HTTP status is `200 OK`, as GraphQL servers usually report errors that way. Otherwise the `data` field
+    /// is deserialized into `T` and mapped using the provided closure.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The `reqwest::RequestBuilder` representing the GraphQL request, usually from [RequestDefaults::default_graphql_requestor].
+    /// * `map` - A closure that maps the deserialized `data` into the desired output type.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the mapped output type or an `RequestError` variant.
+    async fn graphql_request_map(&self,request : reqwest::RequestBuilder,map : impl FnOnce(T) -> O + MaybeSend + MaybeSync) -> Result<O,RequestError<E>> {
+        let response = prepare_response(self,self.request_raw(request).await?)?;
+
+        let context = || Box::new(response.meta.context());
+        match serde_json::from_slice::<GraphQLEnvelope>(&response.body) {
+            Ok(GraphQLEnvelope { errors : Some(errors), data }) if errors.as_array().map_or(!errors.is_null(),|errors| !errors.is_empty()) => {
+                Err(match serde_json::from_value(errors) {
+                    Ok(errors) => RequestError::GraphQLErrors { errors, data : data.filter(|data| !data.is_null()), context : context() },
+                    Err(source) => RequestError::InvalidJsonBody { source, context : Some(context()) }
+                })
+            }
+            Ok(GraphQLEnvelope { data, .. }) if response.meta.status.is_success() => {
+                let json = serde_json::from_value(data.unwrap_or(Value::Null)).map_err(|source| RequestError::InvalidJsonBody { source, context : Some(context()) })?;
+                Ok(map(json))
+            }
+            _ => Err(parse_error_payload(&response))
+        }
+    }
+
+    /// Sends a request to a [JSON:API](https://jsonapi.org) server and flattens the resources of the response document.
+    ///
+    /// The `id`, `type`, `attributes` and `relationships` of the primary `data`, a resource or an array of them, are merged
+    /// into plain objects before being deserialized into `T` and mapped using the provided closure. Relationships are
+    /// replaced with the matching resources of the `included` section, themselves flattened, or with their `id` and `type`
+    /// when not included. A populated `errors` array fails with [RequestError::JsonApiErrors].
+    ///
+    /// ```rust ignore
+    /// #[derive(Deserialize)]
+    /// struct Article { id : String, title : String, author : Person }
+    ///
+    /// let query = JsonApiQuery::new().include("author").fields("articles",["title","author"]);
+    /// let request = api_client.default_get_query_requestor("articles/1",&query);
+    /// let article : Article = api_client.jsonapi_request_map(request,|article| article).await?;
+    /// ```
+    ///
+    /// JSON:API servers expect the `application/vnd.api+json` media type, which [RequestDefaults::accept] can return.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The `reqwest::RequestBuilder` representing the JSON:API request.
+    /// * `map` - A closure that maps the flattened `data` into the desired output type.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the mapped output type or an `RequestError` variant.
+    #[cfg(feature = "jsonapi")]
+    async fn jsonapi_request_map(&self,request : reqwest::RequestBuilder,map : impl FnOnce(T) -> O + MaybeSend + MaybeSync) -> Result<O,RequestError<E>> {
+        let response = prepare_response(self,self.request_raw(request).await?)?;
+
+        let context = || Box::new(response.meta.context());
+        let document = serde_json::from_slice::<Value>(&response.body).ok();
+        match document.as_ref().and_then(|document| document.get("errors")) {
+            Some(errors) if errors.as_array().is_some_and(|errors| !errors.is_empty()) => {
+                return Err(match serde_json::from_value(errors.clone()) {
+                    Ok(errors) => RequestError::JsonApiErrors { errors, context : context() },
+                    Err(source) => RequestError::InvalidJsonBody { source, context : Some(context()) }
+                })
+            }
+            _ => {}
+        }
+        match document {
+            Some(document) if response.meta.status.is_success() => {
+                let json = serde_json::from_value(jsonapi::flatten(&document)).map_err(|source| RequestError::InvalidJsonBody { source, context : Some(context()) })?;
+                Ok(map(json))
+            }
+            _ => Err(parse_error_payload(&response))
+        }
+    }
+
+    /// Sends a login request, whose session cookies the [RequestDefaults::cookie_jar] stores and sends with the following
+    /// requests of every handler sharing the jar.
+    ///
+    /// ```rust ignore
+    /// api_client.login(api_client.default_form_post_requestor("session",&[("username",username),("password",password)])).await?;
+    /// ```
+    ///
+    /// Not available on `wasm32`.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The `reqwest::RequestBuilder` representing the login request.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the response of a successful login, whose body may describe the session, or an `RequestError` variant.
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn login(&self,request : reqwest::RequestBuilder) -> Result<RawResponse,RequestError<E>> {
+        let response = self.request_raw(request).await?;
+        match response.meta.status.is_success() {
+            true => Ok(response),
+            false => Err(parse_error_payload(&response))
+        }
+    }
+
+    /// Sends the request an [Endpoint] describes and maps its response like [RequestHandler::request_map].
+    ///
+    /// The endpoint must declare the response and error types of this handler, so a route cannot be sent to a handler
+    /// deserializing another type.
+    ///
+    /// ```rust ignore
+    /// let user = api_client.request_endpoint(&GetUser { id : 42 },|user| user).await?;
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint` - The endpoint carrying the parameters of the request, see [RequestHandler::endpoint_requestor].
+    /// * `map` - A closure that maps the deserialized response into the desired output type.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the mapped output type or an `RequestError` variant.
+    async fn request_endpoint<P : Endpoint<Response = T,Error = E> + MaybeSync + ?Sized>(&self,endpoint : &P,map : impl FnOnce(T) -> O + MaybeSend + MaybeSync) -> Result<O,RequestError<E>> {
+        let request = self.endpoint_requestor(endpoint)?;
+        self.request_map(request,map).await
+    }
+
+    /// Sends an HTTP request to an API reporting failures in the body rather than through the HTTP status.
+    ///
+    /// Some APIs always answer `200 OK` and tell success from failure in the JSON itself, such as `{"ok" : false, "error" : ...}`.
+    /// For successful statuses, the body is handed to `is_error` and deserialized into the error payload `E` if it returns
+    /// `true`, or into `T` and mapped using the provided closure otherwise. Unsuccessful statuses are handled exactly like
+    /// in [RequestHandler::request_map].
+    ///
+    /// ```rust ignore
+    /// let user = api_client.request_envelope(request,|body| body["ok"] == false,|user| user).await?;
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The `reqwest::RequestBuilder` representing the request to be sent.
+    /// * `is_error` - A closure deciding from the parsed body whether the response reports a failure.
+    /// * `map` - A closure that maps the successful response JSON into the desired output type.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the mapped output type or an `RequestError` variant.
+    async fn request_envelope(&self,request : reqwest::RequestBuilder,is_error : impl for<'a> FnOnce(&'a Value) -> bool + MaybeSend + MaybeSync,map : impl FnOnce(T) -> O + MaybeSend + MaybeSync) -> Result<O,RequestError<E>> {
+        let response = prepare_response(self,self.request_raw(request).await?)?;
+        if !response.meta.status.is_success() {
+            return Err(parse_error_payload(&response));
+        }
+
+        let invalid = |error| RequestError::from(error).in_context(&response.meta);
+        let body : Value = serde_json::from_slice(&response.body).map_err(invalid)?;
+        match is_error(&body) {
+            true => Err(match serde_json::from_value(body) {
+                Ok(payload) => RequestError::ErrorPayload { payload, context : Box::new(response.meta.context()) },
+                Err(source) => RequestError::ErrorPayloadParseFailed {
+                    status : response.meta.status,
+                    source,
+                    body : String::from_utf8_lossy(&response.body).into_owned(),
+                }
+            }),
+            false => Ok(map(serde_json::from_value(body).map_err(invalid)?))
+        }
+    }
+
+    /// Serializes an outgoing body, returning the encoded bytes together with their content type.
+    ///
+    /// This is the single place deciding the format of every typed body sent by this trait, so overriding it switches
+    /// all of them at once, for formats which are not a [ContentFormat] (see [ResponseParser] for the responses).
+    ///
+    /// # Arguments
+    ///
+    /// * `body` - The value to serialize.
+    ///
+    /// # Returns
+    ///
+    /// The encoded body and its content type, in the [RequestDefaults::content_format] by default, or `RequestError::InvalidRequestBody`
+    /// if serialization fails.
+    fn encode_body<B : Serialize + ?Sized>(&self,body : &B) -> Result<(Vec<u8>,&'static str),RequestError<E>> {
+        let format = self.content_format();
+        format.encode(body)
+            .map(|bytes| (bytes,format.content_type()))
+            .map_err(RequestError::InvalidRequestBody)
+    }
+
+    /// Creates a `RequestBuilder` carrying a typed body encoded with [RequestHandler::encode_body].
+    ///
+    /// # Arguments
+    ///
+    /// * `method` - The HTTP method of the request.
+    /// * `endpoint` - The endpoint for the request.
+    /// * `body` - The value to send as the body of the request.
+    ///
+    /// # Returns
+    ///
+    /// The `RequestBuilder` with default settings, the encoded body and its `Content-Type` applied, or the serialization error.
+    /// A body larger than [RequestDefaults::max_request_body_bytes] yields `RequestError::BodyTooLarge`.
+    fn default_body_requestor<B : Serialize + ?Sized>(&self,method : Method,endpoint : &str,body : &B) -> Result<reqwest::RequestBuilder,RequestError<E>> {
+        let (body,content_type) = self.encode_body(body)?;
+        if let Some(limit) = self.max_request_body_bytes().filter(|limit| body.len() > *limit) {
+            return Err(RequestError::BodyTooLarge { limit, size : body.len() });
+        }
+        let request = base_requestor(self,method,endpoint,&HashMap::new()).header(CONTENT_TYPE,content_type);
+        Ok(finish_request(self,self.apply_body(request,body)))
+    }
+
+    /// Creates a `RequestBuilder` for an [Endpoint], with its parameters substituted into its path and the others sent as
+    /// the query, like [RequestDefaults::default_query_requestor], or as the body, like [RequestHandler::default_body_requestor].
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint` - The endpoint carrying the parameters of the request.
+    ///
+    /// # Returns
+    ///
+    /// The `RequestBuilder`, or `RequestError::InvalidPathParameter` if a path parameter is missing or invalid.
+    fn endpoint_requestor<P : Endpoint + ?Sized>(&self,endpoint : &P) -> Result<reqwest::RequestBuilder,RequestError<E>> {
+        let (path,parameters) = endpoint::resolve(endpoint)?;
+        match endpoint::is_query_method::<P>() {
+            true => Ok(self.default_query_requestor(P::METHOD,&path,&parameters)),
+            false => self.default_body_requestor(P::METHOD,&path,&parameters)
+        }
+    }
+
+    /// Resolves the error in the response and returns an option containing the value or `None`.
+    ///
+    /// # Arguments
+    ///
+    /// * `response` - The response as a `Result` type.
+    /// * `error_resolver` - The closure that handles the error and performs custom error handling.
+    ///
+    /// # Returns
+    ///
+    /// An option containing the value if the response is successful, otherwise `None`.
+    /// [RequestError::Cancelled] is not passed to the closure, as cancelling is something the caller asked for rather than a failure.
+    fn resolve_error(&self,response : Result<O,RequestError<E>>,error_handler : impl Fn(RequestError<E>) + MaybeSync) -> Option<O> {
+        match response {
+            Ok(value) => Some(value),
+            Err(RequestError::Cancelled) => None,
+            Err(error) => {
+                error_handler(error);
+                None
+            }
+        }
+    }
+
+    /// Sends a GET request built with `default_get_requestor` like [RequestHandler::get_request_handler], returning the error
+    /// instead of passing it to an error handler so it can be propagated with `?`.
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint` - The endpoint URL to send the GET request to.
+    /// * `parameters` - A hashmap containing any parameters to include in the request.
+    /// * `map` - A closure that maps the successful response JSON into the desired output type.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the mapped output type or an `RequestError` variant.
+    async fn try_get_request_handler(&self,endpoint : &str,parameters : &HashMap<&str,Value>,map : impl FnOnce(T) -> O + MaybeSend + MaybeSync) -> Result<O,RequestError<E>> {
+        #[allow(deprecated)]
+        let request = self.default_get_requestor(endpoint,parameters);
+        self.request_map(request,map).await
+    }
+
+    /// Sends many GET requests like [RequestHandler::try_get_request_handler], at most `max_concurrency` of them at once.
+    ///
+    /// Requests are started in order as soon as a slot is free; a slow response does not hold back the following ones.
+    ///
+    /// ```rust ignore
+    /// let requests = ids.iter().map(|id| (id.as_str(),HashMap::new())).collect();
+    /// let users = api_client.batch_get_request_handler(requests,8,|user| user).await;
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `requests` - The endpoints to send the GET requests to, with the parameters to include in each of them.
+    /// * `max_concurrency` - How many requests may be in flight at once, at least `1`.
+    /// * `map` - A closure that maps every successful response JSON into the desired output type.
+    ///
+    /// # Returns
+    ///
+    /// The result of every request, in the order of `requests`.
+    async fn batch_get_request_handler(&self,requests : Vec<(&str,HashMap<&str,Value>)>,max_concurrency : usize,map : impl Fn(T) -> O + MaybeSend + MaybeSync) -> Vec<Result<O,RequestError<E>>> where O : MaybeSend,E : MaybeSend {
+        use futures_util::StreamExt;
+
+        let map = &map;
+        let exchanges : Vec<_> = requests.iter().enumerate()
+            .map(|(index,(endpoint,parameters))| async move { (index,self.try_get_request_handler(endpoint,parameters,map).await) })
+            .collect();
+        let mut results : Vec<(usize,Result<O,RequestError<E>>)> = futures_util::stream::iter(exchanges).buffer_unordered(max_concurrency.max(1)).collect().await;
+        results.sort_by_key(|(index,_)| *index);
+        results.into_iter().map(|(_,result)| result).collect()
+    }
+
+    /// Polls the status of a long-running operation, such as the status URL of a `202 Accepted` response, until `predicate`
+    /// says it is complete.
+    ///
+    /// The endpoint is requested with `default_get_query_requestor`, bypassing the [RequestDefaults::response_cache], and
+    /// its body deserialized into `T`. Requests are spaced by the delays of `backoff`, or by the `Retry-After` header of the
+    /// previous response when it has one, and each of them goes through the usual hooks, retries included.
+    ///
+    /// ```rust ignore
+    /// let backoff = BackoffStrategy::Exponential { base : Duration::from_millis(500), max : Duration::from_secs(10) };
+    /// let export = api_client.poll_until("exports/42",|export| export.state != "running",backoff,Duration::from_secs(300)).await?;
+    /// ```
+    ///
+    /// Not available on `wasm32`, where there is no timer to wait on.
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint` - The endpoint describing the state of the operation.
+    /// * `predicate` - Returns whether the operation is complete, given its state.
+    /// * `backoff` - How long to wait between two requests, the first delay being waited after the first request.
+    /// * `max_wait` - How long to poll at most, including the time taken by the requests.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the state of the complete operation, [RequestError::Timeout] if it is not complete after
+    /// `max_wait`, or the first error a request failed with.
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn poll_until(&self,endpoint : &str,predicate : impl for<'a> Fn(&'a T) -> bool + MaybeSend + MaybeSync,backoff : BackoffStrategy,max_wait : std::time::Duration) -> Result<T,RequestError<E>> where T : MaybeSend {
+        let polling = async {
+            let mut attempt = 0;
+            loop {
+                let request = self.default_get_query_requestor(endpoint,&()).header(reqwest::header::CACHE_CONTROL,"no-cache");
+                let response = prepare_response(self,self.request_raw(request).await?)?;
+                let state : T = parse_response(&response)?;
+                if predicate(&state) {
+                    return Ok(state);
+                }
+
+                attempt += 1;
+                tokio::time::sleep(response.meta.retry_after().unwrap_or_else(|| backoff.delay(attempt))).await;
+            }
+        };
+        tokio::time::timeout(max_wait,polling).await.unwrap_or(Err(RequestError::Timeout))
+    }
+
+    /// Sends a POST request built with `default_post_requestor` like [RequestHandler::post_request_handler], returning the error
+    /// instead of passing it to an error handler so it can be propagated with `?`.
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint` - The endpoint URL to send the POST request to.
+    /// * `json` - A string containing the JSON payload to include in the request.
+    /// * `map` - A closure that maps the successful response JSON into the desired output type.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the mapped output type or an `RequestError` variant.
+    async fn try_post_request_handler(&self,endpoint : &str,json : String,map : impl FnOnce(T) -> O + MaybeSend + MaybeSync) -> Result<O,RequestError<E>> {
+        let request = self.default_post_requestor(endpoint,json);
+        self.request_map(request,map).await
+    }
+
+    /// Sends a PUT request built with `default_put_requestor` like [RequestHandler::put_request_handler], returning the error
+    /// instead of passing it to an error handler so it can be propagated with `?`.
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint` - The endpoint URL to send the PUT request to.
+    /// * `json` - A string containing the JSON payload to include in the request.
+    /// * `map` - A closure that maps the successful response JSON into the desired output type.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the mapped output type or an `RequestError` variant.
+    async fn try_put_request_handler(&self,endpoint : &str,json : String,map : impl FnOnce(T) -> O + MaybeSend + MaybeSync) -> Result<O,RequestError<E>> {
+        let request = self.default_put_requestor(endpoint,json);
+        self.request_map(request,map).await
+    }
+
+    /// Sends a PATCH request built with `default_patch_requestor` like [RequestHandler::patch_request_handler], returning the error
+    /// instead of passing it to an error handler so it can be propagated with `?`.
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint` - The endpoint URL to send the PATCH request to.
+    /// * `json` - A string containing the JSON payload to include in the request.
+    /// * `map` - A closure that maps the successful response JSON into the desired output type.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the mapped output type or an `RequestError` variant.
+    async fn try_patch_request_handler(&self,endpoint : &str,json : String,map : impl FnOnce(T) -> O + MaybeSend + MaybeSync) -> Result<O,RequestError<E>> {
+        let request = self.default_patch_requestor(endpoint,json);
+        self.request_map(request,map).await
+    }
+
+    /// Sends a DELETE request built with `default_delete_requestor` like [RequestHandler::delete_request_handler], returning the error
+    /// instead of passing it to an error handler so it can be propagated with `?`.
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint` - The endpoint URL to send the DELETE request to.
+    /// * `parameters` - A hashmap containing any parameters to include in the request.
+    /// * `map` - A closure that maps the successful response JSON into the desired output type.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the mapped output type or an `RequestError` variant.
+    async fn try_delete_request_handler(&self,endpoint : &str,parameters : &HashMap<&str,Value>,map : impl FnOnce(T) -> O + MaybeSend + MaybeSync) -> Result<O,RequestError<E>> {
+        #[allow(deprecated)]
+        let request = self.default_delete_requestor(endpoint,parameters);
+        self.request_map(request,map).await
+    }
+
+    /// This asynchronous function constructs (by default) a GET request using the `default_get_requestor` method
+    /// with the given endpoint and parameters. It then sends the request using the request method, expecting
+    /// a response of type `T` or an error of type `E`. The error is resolved using the `resolve_error` method
+    /// and returns an `Option<T>` representing the response data if successful, or `None` if an error occurred.
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint` - The endpoint URL to send the GET request to.
+    /// * `parameters` - A hashmap containing any parameters to include in the request.
+    /// * `map` - A closure that maps the successful response JSON into the desired output type.
+    /// * `error_handler` - A closure that handles the error case if an `RequestError` occurs.
+    ///
+    /// # Returns
+    ///
+    /// An `Option<O>` representing the response data if successful, or `None` if an error occurred.
+    async fn get_request_handler(&self,endpoint : &str,parameters : &HashMap<&str,Value>,map : impl FnOnce(T) -> O + MaybeSend + MaybeSync,error_handler : impl Fn(RequestError<E>) + MaybeSync + MaybeSend) -> Option<O> { 
+        let response = self.try_get_request_handler(endpoint,parameters,map).await;
+        self.resolve_error(response,error_handler)
+    }
+
+    /// Handles a POST request to the specified endpoint with the provided JSON payload and returns the response data of type T.
+    ///
+    /// This asynchronous function constructs a POST request using the `default_post_requestor` method with the given endpoint
+    /// and JSON payload. It then sends the request using the request method, expecting a response of type `T` or an error of type `E`.
+    /// The error is resolved using the `resolve_error` method and returns an `Option<T>` representing the response data if successful,
+    /// or `None` if an error occurred.
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint` - The endpoint URL to send the POST request to.
+    /// * `json` - A string containing the JSON payload to include in the request.
+    /// * `map` - A closure that maps the successful response JSON into the desired output type.
+    /// * `error_handler` - A closure that handles the error case if an `RequestError` occurs.
+    ///
+    /// # Returns
+    ///
+    /// An `Option<O>` representing the response data if successful, or `None` if an error occurred.
+    async fn post_request_handler(&self,endpoint : &str,json : String,map : impl FnOnce(T) -> O + MaybeSend + MaybeSync,error_handler : impl Fn(RequestError<E>) + MaybeSync + MaybeSend) -> Option<O> {
+        let response = self.try_post_request_handler(endpoint,json,map).await;
+        self.resolve_error(response,error_handler)
+    }
+
+    /// Handles a PUT request to the specified endpoint with the provided JSON payload and returns the response data of type T.
+    ///
+    /// This asynchronous function constructs a PUT request using the `default_put_requestor` method with the given endpoint
+    /// and JSON payload. It then sends the request using the request method, expecting a response of type `T` or an error of type `E`.
+    /// The error is resolved using the `resolve_error` method and returns an `Option<T>` representing the response data if successful,
+    /// or `None` if an error occurred.
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint` - The endpoint URL to send the PUT request to.
+    /// * `json` - A string containing the JSON payload to include in the request.
+    /// * `map` - A closure that maps the successful response JSON into the desired output type.
+    /// * `error_handler` - A closure that handles the error case if an `RequestError` occurs.
+    ///
+    /// # Returns
+    ///
+    /// An `Option<O>` representing the response data if successful, or `None` if an error occurred.
+    async fn put_request_handler(&self,endpoint : &str,json : String,map : impl FnOnce(T) -> O + MaybeSend + MaybeSync,error_handler : impl Fn(RequestError<E>) + MaybeSync + MaybeSend) -> Option<O> {
+        let response = self.try_put_request_handler(endpoint,json,map).await;
+        self.resolve_error(response,error_handler)
+    }
+
+    /// Handles a PATCH request to the specified endpoint with the provided JSON payload and returns the response data of type T.
+    ///
+    /// This asynchronous function constructs a PATCH request using the `default_patch_requestor` method with the given endpoint
+    /// and JSON payload. It then sends the request using the request method, expecting a response of type `T` or an error of type `E`.
+    /// The error is resolved using the `resolve_error` method and returns an `Option<T>` representing the response data if successful,
+    /// or `None` if an error occurred.
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint` - The endpoint URL to send the PATCH request to.
+    /// * `json` - A string containing the JSON payload to include in the request.
+    /// * `map` - A closure that maps the successful response JSON into the desired output type.
+    /// * `error_handler` - A closure that handles the error case if an `RequestError` occurs.
+    ///
+    /// # Returns
+    ///
+    /// An `Option<O>` representing the response data if successful, or `None` if an error occurred.
+    async fn patch_request_handler(&self,endpoint : &str,json : String,map : impl FnOnce(T) -> O + MaybeSend + MaybeSync,error_handler : impl Fn(RequestError<E>) + MaybeSync + MaybeSend) -> Option<O> {
+        let response = self.try_patch_request_handler(endpoint,json,map).await;
+        self.resolve_error(response,error_handler)
+    }
+
+    /// Handles a DELETE request to the specified endpoint and returns the response data of type T.
+    ///
+    /// This asynchronous function constructs a DELETE request using the `default_delete_requestor` method
+    /// with the given endpoint and parameters. It then sends the request using the request method, expecting
+    /// a response of type `T` or an error of type `E`. The error is resolved using the `resolve_error` method
+    /// and returns an `Option<T>` representing the response data if successful, or `None` if an error occurred.
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint` - The endpoint URL to send the DELETE request to.
+    /// * `parameters` - A hashmap containing any parameters to include in the request.
+    /// * `map` - A closure that maps the successful response JSON into the desired output type.
+    /// * `error_handler` - A closure that handles the error case if an `RequestError` occurs.
+    ///
+    /// # Returns
+    ///
+    /// An `Option<O>` representing the response data if successful, or `None` if an error occurred.
+    async fn delete_request_handler(&self,endpoint : &str,parameters : &HashMap<&str,Value>,map : impl FnOnce(T) -> O + MaybeSend + MaybeSync,error_handler : impl Fn(RequestError<E>) + MaybeSync + MaybeSend) -> Option<O> {
+        let response = self.try_delete_request_handler(endpoint,parameters,map).await;
+        self.resolve_error(response,error_handler)
+    }
+
+    /// Handles a POST request to the specified endpoint with a typed body and returns the response data of type T.
+    ///
+    /// The body is serialized with [RequestHandler::encode_body], so its format follows whatever encoding is configured there.
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint` - The endpoint URL to send the POST request to.
+    /// * `body` - The value to send as the body of the request.
+    /// * `map` - A closure that maps the successful response JSON into the desired output type.
+    /// * `error_handler` - A closure that handles the error case if an `RequestError` occurs.
+    ///
+    /// # Returns
+    ///
+    /// An `Option<O>` representing the response data if successful, or `None` if an error occurred.
+    async fn post_body_request_handler<B : Serialize + ?Sized + MaybeSync>(&self,endpoint : &str,body : &B,map : impl FnOnce(T) -> O + MaybeSend + MaybeSync,error_handler : impl Fn(RequestError<E>) + MaybeSync + MaybeSend) -> Option<O> {
+        let request = match self.default_body_requestor(Method::POST,endpoint,body) {
+            Ok(request) => request,
+            Err(error) => {
+                error_handler(error);
+                return None;
+            }
+        };
+        let response = self.request_map(request,map).await;
+        self.resolve_error(response,error_handler)
+    }
+
+    /// Handles a GET request and returns the body as text, for endpoints not answering with JSON.
+    ///
+    /// This asynchronous function constructs a GET request using the `default_get_requestor` method and processes the
+    /// response using the `request_text` method, so unsuccessful statuses are still passed to the error handler.
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint` - The endpoint URL to send the GET request to.
+    /// * `parameters` - A hashmap containing any parameters to include in the request.
+    /// * `error_handler` - A closure that handles the error case if an `RequestError` occurs.
+    ///
+    /// # Returns
+    ///
+    /// An `Option<String>` representing the body if successful, or `None` if an error occurred.
+    async fn get_text_request_handler(&self,endpoint : &str,parameters : &HashMap<&str,Value>,error_handler : impl Fn(RequestError<E>) + MaybeSync + MaybeSend) -> Option<String> {
+        #[allow(deprecated)]
+        let request = self.default_get_requestor(endpoint,parameters);
+        match self.request_text(request).await {
+            Ok(body) => Some(body),
+            Err(error) => {
+                error_handler(error);
+                None
+            }
+        }
+    }
+
+    /// Handles a GET request and returns the records of its `text/csv` body, for reporting and export endpoints.
+    ///
+    /// This asynchronous function constructs a GET request using the `default_get_requestor` method and processes the
+    /// response using the `request_csv` method, so unsuccessful statuses are still passed to the error handler.
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint` - The endpoint URL to send the GET request to.
+    /// * `parameters` - A hashmap containing any parameters to include in the request.
+    /// * `options` - The delimiter and header row of the records.
+    /// * `error_handler` - A closure that handles the error case if an `RequestError` occurs.
+    ///
+    /// # Returns
+    ///
+    /// An `Option<Vec<R>>` representing the records if successful, or `None` if an error occurred.
+    #[cfg(feature = "csv")]
+    async fn csv_request_handler<R : DeserializeOwned>(&self,endpoint : &str,parameters : &HashMap<&str,Value>,options : &CsvOptions,error_handler : impl Fn(RequestError<E>) + MaybeSync + MaybeSend) -> Option<Vec<R>> {
+        #[allow(deprecated)]
+        let request = self.default_get_requestor(endpoint,parameters);
+        match self.request_csv(request,options).await {
+            Ok(records) => Some(records),
+            Err(error) => {
+                error_handler(error);
+                None
+            }
+        }
+    }
+
+    /// Handles a POST request and returns the body as text, for endpoints not answering with JSON.
+    ///
+    /// This asynchronous function constructs a POST request using the `default_post_requestor` method and processes the
+    /// response using the `request_text` method, so unsuccessful statuses are still passed to the error handler.
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint` - The endpoint URL to send the POST request to.
+    /// * `json` - The JSON payload to send as the body of the request.
+    /// * `error_handler` - A closure that handles the error case if an `RequestError` occurs.
+    ///
+    /// # Returns
+    ///
+    /// An `Option<String>` representing the body if successful, or `None` if an error occurred.
+    async fn post_text_request_handler(&self,endpoint : &str,json : String,error_handler : impl Fn(RequestError<E>) + MaybeSync + MaybeSend) -> Option<String> {
+        let request = self.default_post_requestor(endpoint,json);
+        match self.request_text(request).await {
+            Ok(body) => Some(body),
+            Err(error) => {
+                error_handler(error);
+                None
+            }
+        }
+    }
+
+    /// Handles a GET request and returns the raw body, for endpoints not answering with JSON.
+    ///
+    /// This asynchronous function constructs a GET request using the `default_get_requestor` method and processes the
+    /// response using the `request_bytes` method, so unsuccessful statuses are still passed to the error handler.
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint` - The endpoint URL to send the GET request to.
+    /// * `parameters` - A hashmap containing any parameters to include in the request.
+    /// * `error_handler` - A closure that handles the error case if an `RequestError` occurs.
+    ///
+    /// # Returns
+    ///
+    /// An `Option<Bytes>` representing the body if successful, or `None` if an error occurred.
+    async fn get_bytes_request_handler(&self,endpoint : &str,parameters : &HashMap<&str,Value>,error_handler : impl Fn(RequestError<E>) + MaybeSync + MaybeSend) -> Option<Bytes> {
+        #[allow(deprecated)]
+        let request = self.default_get_requestor(endpoint,parameters);
+        match self.request_bytes(request).await {
+            Ok(body) => Some(body),
+            Err(error) => {
+                error_handler(error);
+                None
+            }
+        }
+    }
+
+    /// Handles a POST request and returns the raw body, for endpoints not answering with JSON.
+    ///
+    /// This asynchronous function constructs a POST request using the `default_post_requestor` method and processes the
+    /// response using the `request_bytes` method, so unsuccessful statuses are still passed to the error handler.
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint` - The endpoint URL to send the POST request to.
+    /// * `json` - The JSON payload to send as the body of the request.
+    /// * `error_handler` - A closure that handles the error case if an `RequestError` occurs.
+    ///
+    /// # Returns
+    ///
+    /// An `Option<Bytes>` representing the body if successful, or `None` if an error occurred.
+    async fn post_bytes_request_handler(&self,endpoint : &str,json : String,error_handler : impl Fn(RequestError<E>) + MaybeSync + MaybeSend) -> Option<Bytes> {
+        let request = self.default_post_requestor(endpoint,json);
+        match self.request_bytes(request).await {
+            Ok(body) => Some(body),
+            Err(error) => {
+                error_handler(error);
+                None
+            }
+        }
+    }
+
+    /// Handles a POST request with a form body and returns the response data of type T.
+    ///
+    /// This asynchronous function constructs a POST request using the `default_form_post_requestor` method and processes
+    /// the response using the `request_map` method, like [RequestHandler::post_request_handler].
+    ///
+    /// ```rust ignore
+    /// let token = api_client.post_form_request_handler("oauth/token",&[("grant_type","client_credentials"),("scope","read")],|token| token,|error| {
+    ///     // Handle error cases
+    /// }).await;
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint` - The endpoint URL to send the POST request to.
+    /// * `form` - The form fields to send as the body of the request.
+    /// * `map` - A closure that maps the successful response JSON into the desired output type.
+    /// * `error_handler` - A closure that handles the error case if an `RequestError` occurs.
+    ///
+    /// # Returns
+    ///
+    /// An `Option<O>` representing the response data if successful, or `None` if an error occurred.
+    async fn post_form_request_handler<F : Serialize + ?Sized + MaybeSync>(&self,endpoint : &str,form : &F,map : impl FnOnce(T) -> O + MaybeSend + MaybeSync,error_handler : impl Fn(RequestError<E>) + MaybeSync + MaybeSend) -> Option<O> {
+        let request = self.default_form_post_requestor(endpoint,form);
+        let response = self.request_map(request,map).await;
+        self.resolve_error(response,error_handler)
+    }
+
+    /// Handles a POST request with a multipart body and returns the response data of type T.
+    ///
+    /// This asynchronous function constructs a POST request using the `default_multipart_requestor` method and processes
+    /// the response using the `request_map` method, like [RequestHandler::post_request_handler].
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint` - The endpoint URL to send the POST request to.
+    /// * `form` - The multipart form to send as the body of the request.
+    /// * `map` - A closure that maps the successful response JSON into the desired output type.
+    /// * `error_handler` - A closure that handles the error case if an `RequestError` occurs.
+    ///
+    /// # Returns
+    ///
+    /// An `Option<O>` representing the response data if successful, or `None` if an error occurred.
+    #[cfg(feature = "multipart")]
+    async fn multipart_request_handler(&self,endpoint : &str,form : reqwest::multipart::Form,map : impl FnOnce(T) -> O + MaybeSend + MaybeSync,error_handler : impl Fn(RequestError<E>) + MaybeSync + MaybeSend) -> Option<O> {
+        let request = self.default_multipart_requestor(endpoint,form);
+        let response = self.request_map(request,map).await;
+        self.resolve_error(response,error_handler)
+    }
+
+    /// Handles a GET request downloading a potentially large body, streamed into the given writer instead of being buffered.
+    ///
+    /// This asynchronous function constructs a GET request using the `default_get_requestor` method and sends it using the
+    /// `download_to_writer` method. Not available on `wasm32`.
+    ///
+    /// ```rust ignore
+    /// let file = tokio::fs::File::create("artifact.tar.gz").await?;
+    /// let written = api_client.download_request_handler("artifacts/latest",&parameters,file,|written,total| {
+    ///     println!("{written} / {total:?} bytes");
+    /// },|error| {
+    ///     // Handle error cases
+    /// }).await;
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint` - The endpoint URL to send the GET request to.
+    /// * `parameters` - A hashmap containing any parameters to include in the request.
+    /// * `dest` - The writer receiving the body.
+    /// * `on_progress` - A closure called with the bytes written so far and the expected total after every chunk.
+    /// * `error_handler` - A closure that handles the error case if an `RequestError` occurs.
+    ///
+    /// # Returns
+    ///
+    /// An `Option<u64>` holding the number of bytes written if successful, or `None` if an error occurred.
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn download_request_handler<W : tokio::io::AsyncWrite + Unpin + MaybeSend>(&self,endpoint : &str,parameters : &HashMap<&str,Value>,dest : W,on_progress : impl Fn(u64,Option<u64>) + MaybeSend + MaybeSync,error_handler : impl Fn(RequestError<E>) + MaybeSync + MaybeSend) -> Option<u64> {
+        #[allow(deprecated)]
+        let request = self.default_get_requestor(endpoint,parameters);
+        match self.download_to_writer(request,dest,on_progress).await {
+            Ok(written) => Some(written),
+            Err(error) => {
+                error_handler(error);
+                None
+            }
+        }
+    }
+
+    /// Handles a GET request resuming the download of a partially written file, see [RequestHandler::resume_download_to_file].
+    ///
+    /// This asynchronous function constructs a GET request using the `default_get_requestor` method and sends it using the
+    /// `resume_download_to_file` method. Not available on `wasm32`.
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint` - The endpoint URL to send the GET request to.
+    /// * `parameters` - A hashmap containing any parameters to include in the request.
+    /// * `path` - The path of the file to resume writing the body to.
+    /// * `if_range` - The `ETag` or `Last-Modified` date the partial file was downloaded with, if known.
+    /// * `on_progress` - A closure called after every chunk with the size of the file so far and its expected total size.
+    /// * `error_handler` - A closure that handles the error case if an `RequestError` occurs.
+    ///
+    /// # Returns
+    ///
+    /// An `Option<u64>` holding the size of the complete file if successful, or `None` if an error occurred.
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn resume_download_handler(&self,endpoint : &str,parameters : &HashMap<&str,Value>,path : &std::path::Path,if_range : Option<&str>,on_progress : impl Fn(u64,Option<u64>) + MaybeSend + MaybeSync,error_handler : impl Fn(RequestError<E>) + MaybeSync + MaybeSend) -> Option<u64> {
+        #[allow(deprecated)]
+        let request = self.default_get_requestor(endpoint,parameters);
+        match self.resume_download_to_file(request,path,if_range,on_progress).await {
+            Ok(size) => Some(size),
+            Err(error) => {
+                error_handler(error);
+                None
+            }
+        }
+    }
+
+    /// Handles a GraphQL query or mutation and returns the response data of type T.
+    ///
+    /// This asynchronous function constructs a request using the `default_graphql_requestor` method and processes the
+    /// response envelope using the `graphql_request_map` method, so GraphQL `errors` reported with a `200 OK` status are
+    /// passed to the error handler as well.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The GraphQL query or mutation document.
+    /// * `variables` - The variables of the query.
+    /// * `map` - A closure that maps the deserialized `data` into the desired output type.
+    /// * `error_handler` - A closure that handles the error case if an `RequestError` occurs.
+    ///
+    /// # Returns
+    ///
+    /// An `Option<O>` representing the response data if successful, or `None` if an error occurred.
+    async fn graphql_request_handler<V : Serialize + ?Sized + MaybeSync>(&self,query : &str,variables : &V,map : impl FnOnce(T) -> O + MaybeSend + MaybeSync,error_handler : impl Fn(RequestError<E>) + MaybeSync + MaybeSend) -> Option<O> {
+        let request = match self.default_graphql_requestor(query,variables) {
+            Ok(request) => request,
+            Err(error) => {
+                error_handler(RequestError::InvalidRequestBody(Box::new(error)));
+                return None;
+            }
+        };
+        let response = self.graphql_request_map(request,map).await;
+        self.resolve_error(response,error_handler)
+    }
+
+    /// Handles a HEAD request to the specified endpoint and returns the status code and headers of the response.
+    ///
+    /// This asynchronous function constructs a HEAD request using the `default_head_requestor` method with the given endpoint
+    /// and parameters. No body is downloaded or deserialized, which makes it useful for checking whether a resource exists
+    /// or reading metadata such as `Content-Length` and `Last-Modified` before deciding to download it. A non-success status
+    /// is not treated as an error, as the status code itself is usually the information of interest. The request is sent
+    /// through [RequestHandler::request_raw], so the rate limiter, retries, mocks and other hooks apply to it.
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint` - The endpoint URL to send the HEAD request to.
+    /// * `parameters` - A hashmap containing any parameters to include in the request.
+    /// * `error_handler` - A closure that handles the error case if an `RequestError` occurs.
+    ///
+    /// # Returns
+    ///
+    /// An `Option<(StatusCode,HeaderMap)>` containing the response status and headers if the request could be sent, or `None` if an error occurred.
+    async fn head_request_handler(&self,endpoint : &str,parameters : &HashMap<&str,Value>,error_handler : impl Fn(RequestError<E>) + MaybeSync + MaybeSend) -> Option<(StatusCode,HeaderMap)> {
+        let request = self.default_head_requestor(endpoint,parameters);
+        match self.request_raw(request).await {
+            Ok(response) => Some((response.meta.status,response.meta.headers)),
+            Err(error) => {
+                error_handler(error);
+                None
+            }
+        }
+    }
+
+    /// Handles an OPTIONS request to the specified endpoint and returns the methods it supports along with the headers of the response.
+    ///
+    /// This asynchronous function constructs an OPTIONS request using the `default_options_requestor` method and parses the
+    /// `Allow` header of the response, which lets the verbs supported by an endpoint be discovered at runtime. Without it, the
+    /// `Access-Control-Allow-Methods` header of CORS preflight responses is used, the rest of the CORS information, such as
+    /// `Access-Control-Allow-Headers`, being left in the returned headers. Extension methods such as `PROPFIND` are returned
+    /// too, only the values which are not valid method tokens being skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint` - The endpoint URL to send the OPTIONS request to.
+    /// * `error_handler` - A closure that handles the error case if an `RequestError` occurs.
+    ///
+    /// # Returns
+    ///
+    /// An `Option<(Vec<Method>,HeaderMap)>` containing the allowed methods and the response headers if the request was successful, or `None` if an error occurred.
+    async fn options_request_handler(&self,endpoint : &str,error_handler : impl Fn(RequestError<E>) + MaybeSync + MaybeSend) -> Option<(Vec<Method>,HeaderMap)> {
+        let request = self.default_options_requestor(endpoint);
+        let response = match self.request_raw(request).await {
+            Ok(response) => response,
+            Err(error) => {
+                error_handler(error);
+                return None;
+            }
+        };
+
+        if !response.meta.status.is_success() {
+            error_handler(parse_error_payload(&response));
+            return None;
+        }
+
+        let allow = match response.meta.headers.contains_key(ALLOW) {
+            true => ALLOW,
+            false => ACCESS_CONTROL_ALLOW_METHODS
+        };
+        let methods = response.meta.headers.get_all(allow).iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .filter_map(|method| Method::from_bytes(method.trim().as_bytes()).ok())
+            .collect();
+        Some((methods,response.meta.headers))
+    }
+}
 
 
 /// Enum representing different types of HTTPS errors.
 #[derive(ErrorMacro)]
 pub enum RequestError<E> {
     /// Error that occurs when sending a request.
-    #[error(
+    #[cfg_attr(not(target_arch = "wasm32"),error(
 r#"Failed operation relating to request to ({}) with status code of {}. 
 Is request: {}, 
 Is connect: {}, 
@@ -247,14 +4124,471 @@ Is body: {}"#,
 .0.is_request(),
 .0.is_connect(),
 .0.is_body()
-)]
-    RequestError(#[from] reqwest::Error),
+))]
+    #[cfg_attr(target_arch = "wasm32",error(
+r#"Failed operation relating to request to ({}) with status code of {}. 
+Is request: {}, 
+Is body: {}"#,
+.0.url().map(|x|x.to_string()).unwrap_or(String::from("Not Found")),
+.0.status().map(|x|x.to_string()).unwrap_or(String::from("Not Found")),
+.0.is_request(),
+.0.is_body()
+))]
+    RequestError(reqwest::Error),
 
-    #[error("Failed to parse json due to {}",.0)]
     /// Error indicating invalid JSON body during deserialization.
-    InvalidJsonBody(#[from] serde_json::Error),
+    #[error("Failed to parse json{} due to {source}",.context.as_ref().map(|context| format!(" of {context}")).unwrap_or_default())]
+    InvalidJsonBody {
+        /// The deserialization error.
+        source : serde_json::Error,
+        /// The exchange the body was received in, `None` if the JSON did not come from a response read in full.
+        context : Option<Box<RequestContext>>,
+    },
+
+    /// Error indicating invalid XML body during deserialization, see [RequestHandler::request_map_xml].
+    #[cfg(feature = "xml")]
+    #[error("Failed to parse xml due to {0}")]
+    InvalidXmlBody(#[source] quick_xml::DeError),
+
+    /// Error indicating invalid MessagePack body during deserialization, see [RequestHandler::request_map_msgpack].
+    #[cfg(feature = "msgpack")]
+    #[error("Failed to parse msgpack due to {0}")]
+    InvalidMessagePackBody(#[source] rmp_serde::decode::Error),
+
+    /// Error indicating invalid CBOR body during deserialization, see [RequestHandler::request_map_cbor].
+    #[cfg(feature = "cbor")]
+    #[error("Failed to parse cbor due to {0}")]
+    InvalidCborBody(#[source] ciborium::de::Error<std::io::Error>),
+
+    /// Error indicating invalid protobuf body during decoding, see [RequestHandler::request_protobuf].
+    #[cfg(feature = "protobuf")]
+    #[error("Failed to decode protobuf due to {0}")]
+    InvalidProtobufBody(#[source] prost::DecodeError),
+
+    /// Error indicating invalid CSV body during deserialization, see [RequestHandler::request_csv].
+    #[cfg(feature = "csv")]
+    #[error("Failed to parse csv due to {0}")]
+    InvalidCsvBody(#[source] csv::Error),
 
     /// Error payload (json) when request is not successful
-    #[error("Request error playload : {0}")]
-    ErrorPayload(#[source] E),
+    #[error("Request error payload from {context} : {payload}")]
+    ErrorPayload {
+        /// The deserialized error payload.
+        #[source]
+        payload : E,
+        /// The exchange the payload was received in.
+        context : Box<RequestContext>,
+    },
+
+    /// Error indicating the server returned a non-success status whose body could not be deserialized into the error payload.
+    #[error("Failed to parse error payload of response with status code {status} due to {source}")]
+    ErrorPayloadParseFailed {
+        /// The status code of the response.
+        status : StatusCode,
+        /// The deserialization error.
+        source : serde_json::Error,
+        /// The raw body of the response, lossily converted to UTF-8.
+        body : String,
+    },
+
+    /// Error indicating the server returned a non-success status whose body could not be deserialized into the error payload
+    /// by a non-JSON [ResponseParser], such as [XmlParser]. JSON bodies yield [RequestError::ErrorPayloadParseFailed] instead.
+    #[error("Unexpected body in response with status code {status}")]
+    UnexpectedBody {
+        /// The status code of the response.
+        status : StatusCode,
+        /// The raw body of the response, lossily converted to UTF-8.
+        body : String,
+    },
+
+    /// Error indicating a GraphQL response carried a populated `errors` array, see [RequestHandler::graphql_request_map].
+    #[error("GraphQL errors from {context} : {}",.errors.iter().map(|error| error.message.as_str()).collect::<Vec<_>>().join("; "))]
+    GraphQLErrors {
+        /// The errors reported by the server.
+        errors : Vec<GraphQLError>,
+        /// The partial `data` returned along with the errors, if any.
+        data : Option<Value>,
+        /// The method, URL and status of the response.
+        context : Box<RequestContext>,
+    },
+
+    /// Error indicating a JSON-RPC response carried an `error` object, see [JsonRpcHandler].
+    #[error("JSON-RPC error from {context} : {error}")]
+    JsonRpc {
+        /// The error reported by the server.
+        error : JsonRpcError,
+        /// The method, URL and status of the response.
+        context : Box<RequestContext>,
+    },
+
+    /// Error indicating a JSON:API response carried a populated `errors` array, see [RequestHandler::jsonapi_request_map].
+    #[cfg(feature = "jsonapi")]
+    #[error("JSON:API errors from {context} : {}",.errors.iter().map(ToString::to_string).collect::<Vec<_>>().join("; "))]
+    JsonApiErrors {
+        /// The errors reported by the server.
+        errors : Vec<JsonApiError>,
+        /// The method, URL and status of the response.
+        context : Box<RequestContext>,
+    },
+
+    /// Error indicating the body of a successful response did not pass the [RequestDefaults::response_validator].
+    #[cfg(feature = "schema")]
+    #[error("Response from {context} violates its schema : {}",.violations.iter().map(ToString::to_string).collect::<Vec<_>>().join("; "))]
+    SchemaViolation {
+        /// The violations found in the body.
+        violations : Vec<SchemaViolation>,
+        /// The method, URL and status of the response.
+        context : Box<RequestContext>,
+    },
+
+    /// Error indicating an outgoing body could not be serialized.
+    #[error("Failed to serialize request body due to {0}")]
+    InvalidRequestBody(#[source] Box<dyn std::error::Error + Send + Sync>),
+
+    /// Error indicating a path parameter of an [Endpoint] is missing, empty, or neither a string, a number nor a boolean.
+    #[error("Invalid path parameter {name}")]
+    InvalidPathParameter {
+        /// The name of the parameter in the path template.
+        name : String,
+    },
+
+    /// Error indicating a header name or value is invalid, see [RequestModifiers::try_add_header].
+    #[error("Invalid header {name}")]
+    InvalidHeader {
+        /// The name of the invalid header. Its value is left out as it may be a secret.
+        name : String,
+    },
+
+    /// Error indicating the request could not be completed before its deadline, be it the timeout of an attempt or the
+    /// [RequestDefaults::request_deadline].
+    #[error("Request timed out")]
+    Timeout,
+
+    /// Error indicating the service is under maintenance, as recognised by the [RequestDefaults::maintenance_detector].
+    #[error("Service is under maintenance")]
+    MaintenanceMode {
+        /// How long the server asked to wait before retrying, from the `Retry-After` header.
+        retry_after : Option<std::time::Duration>,
+    },
+
+    /// Error indicating a serialized request body exceeded [RequestDefaults::max_request_body_bytes], so it was not sent.
+    #[error("Request body of {size} bytes exceeded the limit of {limit} bytes")]
+    BodyTooLarge {
+        /// The configured limit, in bytes.
+        limit : usize,
+        /// The size of the serialized body, in bytes.
+        size : usize,
+    },
+
+    /// Error indicating the response body exceeded [RequestDefaults::max_response_bytes], or the limit given to
+    /// [RequestHandler::request_map_with_limit].
+    #[error("Response body exceeded the limit of {limit} bytes after {received} bytes")]
+    ResponseTooLarge {
+        /// The configured limit, in bytes.
+        limit : usize,
+        /// How many bytes were received when the body was given up, or the length the response declared if it was
+        /// given up before reading it.
+        received : usize,
+    },
+
+    /// Error indicating the [RequestDefaults::transport] failed to send the request or receive its response.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[error("Transport failed due to {0}")]
+    Transport(#[source] Box<dyn std::error::Error + Send + Sync>),
+
+    /// Error indicating a file could not be written, see [RequestHandler::download_to_file].
+    #[error("I/O error due to {0}")]
+    Io(#[from] std::io::Error),
+
+    /// Error indicating the server did not answer the range request of [RequestHandler::resume_download_to_file] with the
+    /// requested part of the body, so the download could not be resumed.
+    #[error("Download could not be resumed, the range request was answered with status code {status}")]
+    RangeNotSupported {
+        /// The status of the response.
+        status : reqwest::StatusCode,
+    },
+
+    /// Error indicating the request was not sent because the [RequestDefaults::circuit_breaker] is open.
+    #[error("Circuit breaker is open, retry in {retry_after:?}")]
+    CircuitOpen {
+        /// How long until the circuit lets a trial request through.
+        retry_after : std::time::Duration,
+    },
+
+    /// Error indicating the request was not sent because the [RequestDefaults::rate_limiter] could not grant it a permit
+    /// within its timeout, see [RateLimiter::fail_fast_on_deadline]. It is not retried, as it would only queue again behind
+    /// the limiter.
+    #[error("Rate limiter could not grant a permit before the timeout of the request")]
+    PermitTimeout,
+
+    /// Error indicating the certificate of a host of the API did not match the [RequestInfo::certificate_pins], so the
+    /// connection was closed during the TLS handshake, before the request was sent.
+    #[cfg(all(feature = "pinning",not(target_arch = "wasm32")))]
+    #[error("Certificate of {host} does not match the pinned public keys")]
+    PinMismatch {
+        /// The host that presented the certificate.
+        host : String,
+        /// The pin of the leaf certificate presented, `None` if it cannot be parsed.
+        fingerprint : Option<String>,
+    },
+
+    /// Error indicating the server kept answering `429 Too Many Requests` or `503 Service Unavailable` after the retries
+    /// allowed by [RequestDefaults::honor_retry_after] were exhausted, or asked to wait longer than
+    /// [RequestDefaults::max_retry_after].
+    #[error("Rate limited with status code {status}, retry in {retry_after:?}")]
+    RateLimited {
+        /// The status of the last response.
+        status : reqwest::StatusCode,
+        /// How long the server asked to wait before retrying, from the `Retry-After` header of the last response.
+        retry_after : Option<std::time::Duration>,
+    },
+
+    /// Error indicating the token endpoint of the [RequestDefaults::token_provider] answered with an unsuccessful status.
+    #[error("Token request failed with status code {status}")]
+    TokenRequestFailed {
+        /// The status of the response.
+        status : StatusCode,
+        /// The raw body of the response, lossily converted to UTF-8, usually an OAuth2 error such as `invalid_client`.
+        body : String,
+    },
+
+    /// Error indicating the [OAuth2TokenProvider] could not sign the assertion of its token request.
+    #[cfg(all(feature = "jwt",not(target_arch = "wasm32")))]
+    #[error("Failed to sign token assertion due to {0}")]
+    TokenSigningFailed(#[source] Box<dyn std::error::Error + Send + Sync>),
+
+    /// Error indicating the request failed to connect and was added to the [RequestDefaults::offline_queue], to be sent by
+    /// [OfflineQueue::flush].
+    #[error("Request failed to connect and was queued offline with ID {id}")]
+    QueuedOffline {
+        /// The ID of the request in the queue.
+        id : u64,
+    },
+
+    /// Error indicating the request was aborted through [RequestDefaults::cancel_all] or the token of
+    /// [RequestHandler::request_map_cancellable]. The handlers taking an error handler do not pass it to the handler.
+    #[error("Request was cancelled before it completed")]
+    Cancelled,
+
+    /// Error indicating no recorded HAR entry or mocked response matched the request.
+    #[cfg(any(all(feature = "har",not(target_arch = "wasm32")),feature = "testing"))]
+    #[error("No recorded response found for {method} {url}")]
+    ReplayMiss {
+        /// The method of the unmatched request.
+        method : reqwest::Method,
+        /// The URL of the unmatched request.
+        url : String,
+    },
+}
+
+/// Timeouts are converted to [RequestError::Timeout], so they are told apart from other failures without inspecting the `reqwest::Error`.
+impl<E> From<reqwest::Error> for RequestError<E> {
+    fn from(error : reqwest::Error) -> Self {
+        match error.is_timeout() {
+            true => Self::Timeout,
+            false => Self::RequestError(error)
+        }
+    }
+}
+
+impl<E> From<serde_json::Error> for RequestError<E> {
+    fn from(source : serde_json::Error) -> Self {
+        Self::InvalidJsonBody { source, context : None }
+    }
+}
+
+/// Only requires `E : Display`, which is needed for [std::fmt::Display] anyway, so error payloads do not have to implement `Debug`.
+/// The payload of [RequestError::ErrorPayload] is written with its `Display` implementation.
+impl<E : std::fmt::Display> std::fmt::Debug for RequestError<E> {
+    fn fmt(&self,f : &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        /// Writes a value with its `Display` implementation where `Debug` is expected.
+        struct Displayed<'a,T>(&'a T);
+
+        impl<T : std::fmt::Display> std::fmt::Debug for Displayed<'_,T> {
+            fn fmt(&self,f : &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                std::fmt::Display::fmt(self.0,f)
+            }
+        }
+
+        match self {
+            Self::RequestError(error) => f.debug_tuple("RequestError").field(error).finish(),
+            Self::InvalidJsonBody { source, context } => f.debug_struct("InvalidJsonBody").field("source",source).field("context",context).finish(),
+            #[cfg(feature = "xml")]
+            Self::InvalidXmlBody(error) => f.debug_tuple("InvalidXmlBody").field(error).finish(),
+            #[cfg(feature = "msgpack")]
+            Self::InvalidMessagePackBody(error) => f.debug_tuple("InvalidMessagePackBody").field(error).finish(),
+            #[cfg(feature = "cbor")]
+            Self::InvalidCborBody(error) => f.debug_tuple("InvalidCborBody").field(error).finish(),
+            #[cfg(feature = "protobuf")]
+            Self::InvalidProtobufBody(error) => f.debug_tuple("InvalidProtobufBody").field(error).finish(),
+            #[cfg(feature = "csv")]
+            Self::InvalidCsvBody(error) => f.debug_tuple("InvalidCsvBody").field(error).finish(),
+            Self::ErrorPayload { payload, context } => f.debug_struct("ErrorPayload").field("payload",&Displayed(payload)).field("context",context).finish(),
+            Self::ErrorPayloadParseFailed { status, source, body } => f.debug_struct("ErrorPayloadParseFailed").field("status",status).field("source",source).field("body",body).finish(),
+            Self::UnexpectedBody { status, body } => f.debug_struct("UnexpectedBody").field("status",status).field("body",body).finish(),
+            Self::GraphQLErrors { errors, data, context } => f.debug_struct("GraphQLErrors").field("errors",errors).field("data",data).field("context",context).finish(),
+            Self::JsonRpc { error, context } => f.debug_struct("JsonRpc").field("error",error).field("context",context).finish(),
+            #[cfg(feature = "jsonapi")]
+            Self::JsonApiErrors { errors, context } => f.debug_struct("JsonApiErrors").field("errors",errors).field("context",context).finish(),
+            #[cfg(feature = "schema")]
+            Self::SchemaViolation { violations, context } => f.debug_struct("SchemaViolation").field("violations",violations).field("context",context).finish(),
+            Self::InvalidRequestBody(error) => f.debug_tuple("InvalidRequestBody").field(error).finish(),
+            Self::InvalidPathParameter { name } => f.debug_struct("InvalidPathParameter").field("name",name).finish(),
+            Self::InvalidHeader { name } => f.debug_struct("InvalidHeader").field("name",name).finish(),
+            Self::Timeout => f.write_str("Timeout"),
+            Self::MaintenanceMode { retry_after } => f.debug_struct("MaintenanceMode").field("retry_after",retry_after).finish(),
+            Self::BodyTooLarge { limit, size } => f.debug_struct("BodyTooLarge").field("limit",limit).field("size",size).finish(),
+            Self::ResponseTooLarge { limit, received } => f.debug_struct("ResponseTooLarge").field("limit",limit).field("received",received).finish(),
+            #[cfg(not(target_arch = "wasm32"))]
+            Self::Transport(error) => f.debug_tuple("Transport").field(error).finish(),
+            Self::Io(error) => f.debug_tuple("Io").field(error).finish(),
+            Self::RangeNotSupported { status } => f.debug_struct("RangeNotSupported").field("status",status).finish(),
+            Self::CircuitOpen { retry_after } => f.debug_struct("CircuitOpen").field("retry_after",retry_after).finish(),
+            Self::PermitTimeout => f.write_str("PermitTimeout"),
+            #[cfg(all(feature = "pinning",not(target_arch = "wasm32")))]
+            Self::PinMismatch { host, fingerprint } => f.debug_struct("PinMismatch").field("host",host).field("fingerprint",fingerprint).finish(),
+            Self::RateLimited { status, retry_after } => f.debug_struct("RateLimited").field("status",status).field("retry_after",retry_after).finish(),
+            Self::TokenRequestFailed { status, body } => f.debug_struct("TokenRequestFailed").field("status",status).field("body",body).finish(),
+            #[cfg(all(feature = "jwt",not(target_arch = "wasm32")))]
+            Self::TokenSigningFailed(error) => f.debug_tuple("TokenSigningFailed").field(error).finish(),
+            Self::QueuedOffline { id } => f.debug_struct("QueuedOffline").field("id",id).finish(),
+            Self::Cancelled => f.write_str("Cancelled"),
+            #[cfg(any(all(feature = "har",not(target_arch = "wasm32")),feature = "testing"))]
+            Self::ReplayMiss { method, url } => f.debug_struct("ReplayMiss").field("method",method).field("url",url).finish(),
+        }
+    }
+}
+
+impl<E> RequestError<E> {
+    /// Transforms the error payload with the given closure, leaving every other variant untouched.
+    ///
+    /// This makes it easy to fit the payload into a domain specific error type without matching all the variants by hand.
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - The closure mapping the error payload.
+    ///
+    /// # Returns
+    ///
+    /// The same error with its payload (if any) mapped.
+    #[must_use]
+    pub fn map_payload<F>(self,f : impl FnOnce(E) -> F) -> RequestError<F> {
+        match self {
+            Self::RequestError(error) => RequestError::RequestError(error),
+            Self::InvalidJsonBody { source, context } => RequestError::InvalidJsonBody { source, context },
+            #[cfg(feature = "xml")]
+            Self::InvalidXmlBody(error) => RequestError::InvalidXmlBody(error),
+            #[cfg(feature = "msgpack")]
+            Self::InvalidMessagePackBody(error) => RequestError::InvalidMessagePackBody(error),
+            #[cfg(feature = "cbor")]
+            Self::InvalidCborBody(error) => RequestError::InvalidCborBody(error),
+            #[cfg(feature = "protobuf")]
+            Self::InvalidProtobufBody(error) => RequestError::InvalidProtobufBody(error),
+            #[cfg(feature = "csv")]
+            Self::InvalidCsvBody(error) => RequestError::InvalidCsvBody(error),
+            Self::ErrorPayload { payload, context } => RequestError::ErrorPayload { payload : f(payload), context },
+            Self::ErrorPayloadParseFailed { status, source, body } => RequestError::ErrorPayloadParseFailed { status, source, body },
+            Self::UnexpectedBody { status, body } => RequestError::UnexpectedBody { status, body },
+            Self::GraphQLErrors { errors, data, context } => RequestError::GraphQLErrors { errors, data, context },
+            Self::JsonRpc { error, context } => RequestError::JsonRpc { error, context },
+            #[cfg(feature = "jsonapi")]
+            Self::JsonApiErrors { errors, context } => RequestError::JsonApiErrors { errors, context },
+            #[cfg(feature = "schema")]
+            Self::SchemaViolation { violations, context } => RequestError::SchemaViolation { violations, context },
+            Self::InvalidRequestBody(error) => RequestError::InvalidRequestBody(error),
+            Self::InvalidPathParameter { name } => RequestError::InvalidPathParameter { name },
+            Self::InvalidHeader { name } => RequestError::InvalidHeader { name },
+            Self::Timeout => RequestError::Timeout,
+            Self::MaintenanceMode { retry_after } => RequestError::MaintenanceMode { retry_after },
+            Self::BodyTooLarge { limit, size } => RequestError::BodyTooLarge { limit, size },
+            Self::ResponseTooLarge { limit, received } => RequestError::ResponseTooLarge { limit, received },
+            #[cfg(not(target_arch = "wasm32"))]
+            Self::Transport(error) => RequestError::Transport(error),
+            Self::Io(error) => RequestError::Io(error),
+            Self::RangeNotSupported { status } => RequestError::RangeNotSupported { status },
+            Self::CircuitOpen { retry_after } => RequestError::CircuitOpen { retry_after },
+            Self::PermitTimeout => RequestError::PermitTimeout,
+            #[cfg(all(feature = "pinning",not(target_arch = "wasm32")))]
+            Self::PinMismatch { host, fingerprint } => RequestError::PinMismatch { host, fingerprint },
+            Self::RateLimited { status, retry_after } => RequestError::RateLimited { status, retry_after },
+            Self::TokenRequestFailed { status, body } => RequestError::TokenRequestFailed { status, body },
+            #[cfg(all(feature = "jwt",not(target_arch = "wasm32")))]
+            Self::TokenSigningFailed(error) => RequestError::TokenSigningFailed(error),
+            Self::QueuedOffline { id } => RequestError::QueuedOffline { id },
+            Self::Cancelled => RequestError::Cancelled,
+            #[cfg(any(all(feature = "har",not(target_arch = "wasm32")),feature = "testing"))]
+            Self::ReplayMiss { method, url } => RequestError::ReplayMiss { method, url },
+        }
+    }
+
+    /// Returns whether the error is likely temporary according to the given policy, such as a connection failure or a timeout.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn is_retryable(&self,policy : &dyn RetryPolicy) -> bool {
+        match self {
+            Self::RequestError(error) => policy.is_retryable_error(error),
+            Self::Timeout => true,
+            _ => false
+        }
+    }
+
+    /// Returns the error payload if this is a [RequestError::ErrorPayload].
+    ///
+    /// # Returns
+    ///
+    /// `Some` with the payload, otherwise `None`.
+    #[must_use]
+    pub fn into_inner_payload(self) -> Option<E> {
+        match self {
+            Self::ErrorPayload { payload, .. } => Some(payload),
+            _ => None
+        }
+    }
+
+    /// Returns the method, URL and status of the exchange the error happened in, when known.
+    ///
+    /// # Returns
+    ///
+    /// `Some` for [RequestError::ErrorPayload], [RequestError::GraphQLErrors], [RequestError::JsonRpc], [RequestError::JsonApiErrors], [RequestError::SchemaViolation] and for [RequestError::InvalidJsonBody] errors of fully read responses, otherwise `None`.
+    pub fn context(&self) -> Option<&RequestContext> {
+        match self {
+            Self::ErrorPayload { context, .. } | Self::GraphQLErrors { context, .. } | Self::JsonRpc { context, .. } => Some(context),
+            #[cfg(feature = "jsonapi")]
+            Self::JsonApiErrors { context, .. } => Some(context),
+            #[cfg(feature = "schema")]
+            Self::SchemaViolation { context, .. } => Some(context),
+            Self::InvalidJsonBody { context, .. } => context.as_deref(),
+            _ => None
+        }
+    }
+
+    /// Returns the correlation ID of the request the error happened in, see [RequestDefaults::correlation_header].
+    ///
+    /// # Returns
+    ///
+    /// `Some` for the errors carrying a [RequestError::context] whose request was sent with a correlation ID, otherwise `None`.
+    /// Errors raised before a response was received, such as connection failures, carry no context : their ID is found in
+    /// the log written by [RequestDefaults::log_request] instead.
+    pub fn correlation_id(&self) -> Option<&str> {
+        self.context().and_then(|context| context.correlation_id.as_deref())
+    }
+
+    /// Attaches the context of the response the error happened in to JSON errors lacking one.
+    fn in_context(self,meta : &ResponseMeta) -> Self {
+        match self {
+            Self::InvalidJsonBody { source, context : None } => Self::InvalidJsonBody { source, context : Some(Box::new(meta.context())) },
+            error => error
+        }
+    }
+
+    /// Returns the raw body of an unsuccessful response whose error payload could not be deserialized.
+    ///
+    /// # Returns
+    ///
+    /// `Some` with the body for [RequestError::ErrorPayloadParseFailed], [RequestError::UnexpectedBody] and [RequestError::TokenRequestFailed], otherwise `None`.
+    pub fn raw_body(&self) -> Option<&str> {
+        match self {
+            Self::ErrorPayloadParseFailed { body, .. } | Self::UnexpectedBody { body, .. } | Self::TokenRequestFailed { body, .. } => Some(body),
+            _ => None
+        }
+    }
 }
@@ -0,0 +1,186 @@
+//! Helpers for logging requests and responses, see [crate::RequestDefaults::log_exchanges].
+
+use std::borrow::Cow;
+
+/// The default maximum number of body bytes written to the logs, see [crate::RequestDefaults::max_log_body_size].
+pub const DEFAULT_MAX_LOG_BODY_SIZE : usize = 4 * 1024;
+
+/// Prepares a body for logging, truncating it to `limit` bytes.
+///
+/// Truncated bodies end with a `...[truncated N bytes]` marker telling how many bytes were left out. Invalid UTF-8 is
+/// replaced, and truncation never splits a character.
+///
+/// # Arguments
+///
+/// * `body` - The body to log.
+/// * `limit` - The maximum number of bytes to keep, `None` to disable truncation.
+///
+/// # Returns
+///
+/// The (possibly truncated) body as text.
+///
+/// # Examples
+///
+/// ```
+/// use api_request_utils::logging::truncate_body;
+///
+/// assert_eq!(truncate_body(b"hello world",Some(5)),"hello...[truncated 6 bytes]");
+/// assert_eq!(truncate_body(b"hello world",None),"hello world");
+/// ```
+pub fn truncate_body(body : &[u8],limit : Option<usize>) -> Cow<'_,str> {
+    let text = String::from_utf8_lossy(body);
+    let Some(limit) = limit.filter(|limit| *limit < text.len()) else {
+        return text
+    };
+
+    let mut end = limit;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+
+    Cow::Owned(format!("{}...[truncated {} bytes]",&text[..end],text.len() - end))
+}
+
+/// The headers redacted by default by [LoggingMiddleware] and the HAR cassettes.
+pub(crate) const REDACTED_HEADERS : [&str; 6] = ["authorization","proxy-authorization","cookie","set-cookie","x-api-key","x-auth-token"];
+
+/// The query parameters redacted by default by [LoggingMiddleware], [redact_url] and the HAR cassettes.
+pub(crate) const REDACTED_PARAMETERS : [&str; 9] = ["api_key","apikey","key","token","access_token","refresh_token","client_secret","password","signature"];
+
+/// The text replacing redacted values.
+pub(crate) const REDACTED : &str = "[REDACTED]";
+
+/// Formats a URL for logging, redacting the values of the query parameters usually carrying credentials, such as
+/// `api_key` or `access_token`.
+///
+/// # Examples
+///
+/// ```
+/// use api_request_utils::logging::redact_url;
+/// use api_request_utils::reqwest::Url;
+///
+/// let url = Url::parse("https://api.example.com/users?page=2&api_key=secret").unwrap();
+/// assert_eq!(redact_url(&url),"https://api.example.com/users?page=2&api_key=[REDACTED]");
+/// ```
+pub fn redact_url(url : &reqwest::Url) -> String {
+    redact_url_with(url,&REDACTED_PARAMETERS)
+}
+
+/// Formats a URL, redacting the values of the given query parameters, compared case-insensitively.
+pub(crate) fn redact_url_with<S : AsRef<str>>(url : &reqwest::Url,parameters : &[S]) -> String {
+    let Some(query) = url.query() else {
+        return url.to_string()
+    };
+
+    let query : Vec<String> = query.split('&').map(|pair| match pair.split_once('=') {
+        Some((key,_)) if is_redacted(parameters,key) => format!("{key}={REDACTED}"),
+        _ => pair.to_owned()
+    }).collect();
+    let mut url = url.clone();
+    url.set_query(None);
+    url.set_fragment(None);
+    format!("{url}?{}",query.join("&"))
+}
+
+/// Whether the name is one of the given ones, compared case-insensitively.
+pub(crate) fn is_redacted<S : AsRef<str>>(names : &[S],name : &str) -> bool {
+    names.iter().any(|redacted| redacted.as_ref().eq_ignore_ascii_case(name))
+}
+
+/// A [crate::Middleware] logging the method, URL, headers and (truncated) body of every request built by the default
+/// requestors and of every response they receive, through the `log` crate.
+///
+/// Secrets are redacted, so the logs are safe to keep : the values of the `Authorization`, `Proxy-Authorization`, `Cookie`,
+/// `Set-Cookie`, `X-Api-Key` and `X-Auth-Token` headers, of headers marked as sensitive (such as the key added by
+/// [crate::RequestDefaults::api_key_auth]) and of query parameters usually carrying credentials (such as `api_key` or
+/// `access_token`). Headers added right before sending, such as those of signers and token providers, are not seen.
+///
+/// ```rust ignore
+/// impl RequestDefaults for MyAPIClient {
+///     fn middleware(&self) -> &[Box<dyn Middleware>] {
+///         &self.middleware // vec![Box::new(LoggingMiddleware::new().redact_header("x-session"))]
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct LoggingMiddleware {
+    level : log::Level,
+    max_body_size : Option<usize>,
+    redacted_headers : Vec<String>,
+    redacted_parameters : Vec<String>,
+}
+
+impl Default for LoggingMiddleware {
+    fn default() -> Self {
+        Self {
+            level : log::Level::Debug,
+            max_body_size : Some(DEFAULT_MAX_LOG_BODY_SIZE),
+            redacted_headers : REDACTED_HEADERS.map(str::to_owned).to_vec(),
+            redacted_parameters : REDACTED_PARAMETERS.map(str::to_owned).to_vec(),
+        }
+    }
+}
+
+impl LoggingMiddleware {
+    /// Creates a middleware logging at debug level, truncating bodies to [DEFAULT_MAX_LOG_BODY_SIZE] bytes.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the level exchanges are logged at.
+    pub fn level(mut self,level : log::Level) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Sets the maximum number of body bytes logged, `None` to disable truncation.
+    pub fn max_body_size(mut self,max_body_size : Option<usize>) -> Self {
+        self.max_body_size = max_body_size;
+        self
+    }
+
+    /// Adds a header, compared case-insensitively, whose value is redacted.
+    pub fn redact_header(mut self,name : impl Into<String>) -> Self {
+        self.redacted_headers.push(name.into().to_ascii_lowercase());
+        self
+    }
+
+    /// Adds a query parameter, compared case-insensitively, whose value is redacted.
+    pub fn redact_query_parameter(mut self,name : impl Into<String>) -> Self {
+        self.redacted_parameters.push(name.into().to_ascii_lowercase());
+        self
+    }
+
+    /// Formats the headers as `{name: value, ...}`, redacting the values of secret headers.
+    fn headers(&self,headers : &reqwest::header::HeaderMap) -> String {
+        let headers : Vec<String> = headers.iter().map(|(name,value)| {
+            let value = match value.is_sensitive() || is_redacted(&self.redacted_headers,name.as_str()) {
+                true => REDACTED.into(),
+                false => String::from_utf8_lossy(value.as_bytes())
+            };
+            format!("{name}: {value}")
+        }).collect();
+        format!("{{{}}}",headers.join(", "))
+    }
+
+    /// Formats the URL, redacting the values of secret query parameters.
+    fn url(&self,url : &reqwest::Url) -> String {
+        redact_url_with(url,&self.redacted_parameters)
+    }
+}
+
+impl crate::Middleware for LoggingMiddleware {
+    fn on_request(&self,request_builder : reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        // Requests with streamed bodies cannot be copied to be inspected, so they are not logged
+        if let Some(request) = request_builder.try_clone().and_then(|request_builder| request_builder.build().ok()) {
+            let body = request.body().and_then(reqwest::Body::as_bytes).unwrap_or_default();
+            log::log!(self.level,"--> {} {} headers={} body={}",request.method(),self.url(request.url()),self.headers(request.headers()),truncate_body(body,self.max_body_size));
+        }
+        request_builder
+    }
+
+    fn on_response(&self,response : &crate::RawResponse) {
+        let meta = &response.meta;
+        log::log!(self.level,"<-- {} {} {} headers={} body={}",meta.status,meta.method,self.url(&meta.url),self.headers(&meta.headers),truncate_body(&response.body,self.max_body_size));
+    }
+}
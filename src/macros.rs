@@ -0,0 +1,94 @@
+/// Builds the `HashMap<&str,serde_json::Value>` of parameters taken by the default requestors and handlers.
+///
+/// Every value is converted with `serde_json::Value::from`. Entries written with `=>?` take an `Option` and are left out
+/// when it is `None`, which suits optional parameters such as cursors.
+///
+/// ```
+/// use api_request_utils::params;
+///
+/// let cursor : Option<&str> = None;
+/// let parameters = params! {
+///     "limit" => 50,
+///     "q" => "rust",
+///     "cursor" =>? cursor,
+/// };
+///
+/// assert_eq!(parameters.len(),2);
+/// assert_eq!(parameters["limit"],50);
+/// ```
+#[macro_export]
+macro_rules! params {
+    ($($entries:tt)*) => {{
+        #[allow(unused_mut)]
+        let mut parameters = ::std::collections::HashMap::<&str,$crate::serde_json::Value>::new();
+        $crate::__params_entries!(parameters,$($entries)*);
+        parameters
+    }};
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __params_entries {
+    ($parameters:ident $(,)?) => {};
+    ($parameters:ident,$key:expr =>? $value:expr $(,$($rest:tt)*)?) => {
+        if let Some(value) = $value {
+            $parameters.insert($key,$crate::serde_json::Value::from(value));
+        }
+        $crate::__params_entries!($parameters $(,$($rest)*)?);
+    };
+    ($parameters:ident,$key:expr => $value:expr $(,$($rest:tt)*)?) => {
+        $parameters.insert($key,$crate::serde_json::Value::from($value));
+        $crate::__params_entries!($parameters $(,$($rest)*)?);
+    };
+}
+
+/// Builds a `reqwest::header::HeaderMap`, to be passed to `RequestBuilder::headers` (e.g. in [crate::RequestDefaults::default_headers]).
+///
+/// Entries written with `=>?` take an `Option` and are left out when it is `None`.
+///
+/// ```
+/// use api_request_utils::headers;
+///
+/// let api_key = String::from("secret");
+/// let tenant : Option<&str> = Some("acme");
+/// let headers = headers! {
+///     "X-Api-Key" => api_key,
+///     "X-Tenant" =>? tenant,
+/// };
+///
+/// assert_eq!(headers["x-api-key"],"secret");
+/// assert_eq!(headers["x-tenant"],"acme");
+/// ```
+///
+/// # Panics
+///
+/// Panics if a name is not a valid header name or a value is not a valid header value, use
+/// [crate::RequestModifiers::try_add_header] for values which may be invalid.
+#[macro_export]
+macro_rules! headers {
+    ($($entries:tt)*) => {{
+        #[allow(unused_mut)]
+        let mut headers = $crate::reqwest::header::HeaderMap::new();
+        $crate::__headers_entries!(headers,$($entries)*);
+        headers
+    }};
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __headers_entries {
+    ($headers:ident $(,)?) => {};
+    ($headers:ident,$key:expr =>? $value:expr $(,$($rest:tt)*)?) => {
+        if let Some(value) = $value {
+            $crate::__headers_entries!($headers,$key => value);
+        }
+        $crate::__headers_entries!($headers $(,$($rest)*)?);
+    };
+    ($headers:ident,$key:expr => $value:expr $(,$($rest:tt)*)?) => {
+        $headers.insert(
+            $crate::reqwest::header::HeaderName::try_from($key).expect("invalid header name"),
+            $crate::reqwest::header::HeaderValue::try_from($value).expect("invalid header value"),
+        );
+        $crate::__headers_entries!($headers $(,$($rest)*)?);
+    };
+}
@@ -0,0 +1,75 @@
+use reqwest::StatusCode;
+use reqwest::header::HeaderName;
+
+use crate::RawResponse;
+
+/// Detects responses telling that the service is under maintenance, such as during deploys.
+///
+/// A response is considered a maintenance response when its status matches and, if configured, it carries the given
+/// header (optionally with a given value) or its body contains the given marker. Such responses fail with
+/// [crate::RequestError::MaintenanceMode] carrying the parsed `Retry-After` delay, so clients can show a dedicated
+/// message and back off appropriately instead of handling a generic error.
+///
+/// Return it from [crate::RequestDefaults::maintenance_detector] to apply it.
+#[derive(Debug, Clone)]
+pub struct MaintenanceDetector {
+    status : StatusCode,
+    header : Option<(HeaderName,Option<String>)>,
+    body_marker : Option<String>,
+}
+
+impl Default for MaintenanceDetector {
+    fn default() -> Self {
+        Self {
+            status : StatusCode::SERVICE_UNAVAILABLE,
+            header : None,
+            body_marker : None,
+        }
+    }
+}
+
+impl MaintenanceDetector {
+    /// Creates a detector treating every `503 Service Unavailable` response as a maintenance response.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the status code of maintenance responses.
+    pub fn status(mut self,status : StatusCode) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// Requires maintenance responses to carry the given header, with the given value if `Some`.
+    pub fn header(mut self,name : HeaderName,value : Option<&str>) -> Self {
+        self.header = Some((name,value.map(String::from)));
+        self
+    }
+
+    /// Requires the body of maintenance responses to contain the given marker.
+    pub fn body_marker(mut self,marker : impl Into<String>) -> Self {
+        self.body_marker = Some(marker.into());
+        self
+    }
+
+    /// Returns whether the given response is a maintenance response.
+    pub fn is_maintenance(&self,response : &RawResponse) -> bool {
+        if response.meta.status != self.status {
+            return false;
+        }
+
+        let header_matches = self.header.as_ref().is_none_or(|(name,expected)| {
+            match (response.meta.headers.get(name),expected) {
+                (Some(value),Some(expected)) => value.as_bytes() == expected.as_bytes(),
+                (Some(_),None) => true,
+                (None,_) => false,
+            }
+        });
+
+        let body_matches = self.body_marker.as_ref().is_none_or(|marker| {
+            String::from_utf8_lossy(&response.body).contains(marker.as_str())
+        });
+
+        header_matches && body_matches
+    }
+}
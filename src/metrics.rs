@@ -0,0 +1,152 @@
+use std::time::Duration;
+
+use reqwest::{Method, StatusCode};
+
+/// Receives the measurements of every request sent through the handlers, to export them as metrics.
+///
+/// Return it from [crate::RequestDefaults::metrics_sink] to apply it. Every attempt is reported once its response headers
+/// are received, or once it failed without a response. With the `metrics` feature, [MetricsCrateSink] exports them through
+/// the `metrics` crate, while other backends only take a few lines :
+///
+/// ```rust ignore
+/// struct StatsdMetrics(StatsdClient);
+///
+/// impl MetricsSink for StatsdMetrics {
+///     fn record(&self,metrics : &RequestMetrics) {
+///         self.0.time(&format!("api.{}.{}",metrics.method,metrics.outcome.as_str()),metrics.duration);
+///     }
+/// }
+/// ```
+///
+/// Not available on `wasm32`.
+pub trait MetricsSink : Send + Sync {
+    /// Records the measurements of a request.
+    fn record(&self,metrics : &RequestMetrics);
+}
+
+/// The measurements of a request, see [MetricsSink].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestMetrics {
+    /// The method of the request.
+    pub method : Method,
+    /// The path of the request relative to the [crate::RequestInfo::base_url], without the query. Paths carrying IDs may
+    /// need to be normalized before being used as a label, to keep its cardinality low.
+    pub endpoint : String,
+    /// The status code of the response, `None` if none was received.
+    pub status : Option<StatusCode>,
+    /// The outcome of the request, derived from its status.
+    pub outcome : Outcome,
+    /// How long it took to receive the response headers, or to fail.
+    pub duration : Duration,
+    /// How long the request waited for the [crate::Bulkhead], the [crate::RequestScheduler] and the [crate::RateLimiter]
+    /// before being sent.
+    pub queue_time : Duration,
+}
+
+/// The outcome of a request, see [RequestMetrics::outcome].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Outcome {
+    /// The response has a `1xx`, `2xx` or `3xx` status.
+    Success,
+    /// The response has a `4xx` status.
+    ClientError,
+    /// The response has a `5xx` status.
+    ServerError,
+    /// No response was received, such as after a connection failure or a timeout.
+    Failed,
+}
+
+impl Outcome {
+    /// Classifies the given status, `None` meaning no response was received.
+    pub fn from_status(status : Option<StatusCode>) -> Self {
+        match status {
+            Some(status) if status.is_client_error() => Self::ClientError,
+            Some(status) if status.is_server_error() => Self::ServerError,
+            Some(_) => Self::Success,
+            None => Self::Failed
+        }
+    }
+
+    /// Returns the outcome in snake case, as used for metric labels.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Success => "success",
+            Self::ClientError => "client_error",
+            Self::ServerError => "server_error",
+            Self::Failed => "failed"
+        }
+    }
+}
+
+/// A [MetricsSink] recording every request through the [`metrics`](https://docs.rs/metrics) crate, to be exported by
+/// whichever recorder is installed, such as `metrics-exporter-prometheus`.
+///
+/// Each request increments the `{prefix}_requests_total` counter and records its duration and queue time in seconds in the
+/// `{prefix}_request_duration_seconds` and `{prefix}_request_queue_seconds` histograms, all labelled with its `method`,
+/// `endpoint`, `status` and `outcome`. The status is empty when no response was received.
+///
+/// ```rust ignore
+/// struct MyAPIClient {
+///     metrics : MetricsCrateSink,
+/// }
+///
+/// impl RequestDefaults for MyAPIClient {
+///     fn metrics_sink(&self) -> Option<&dyn MetricsSink> {
+///         Some(&self.metrics)
+///     }
+/// }
+///
+/// let api_client = MyAPIClient { metrics : MetricsCrateSink::new().prefix("github") };
+/// ```
+///
+/// Requires the `metrics` feature.
+#[cfg(feature = "metrics")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetricsCrateSink {
+    requests : String,
+    duration : String,
+    queue_time : String,
+}
+
+#[cfg(feature = "metrics")]
+impl Default for MetricsCrateSink {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "metrics")]
+impl MetricsCrateSink {
+    /// Creates a sink recording metrics prefixed with `api`, such as `api_requests_total`.
+    pub fn new() -> Self {
+        Self::with_prefix("api")
+    }
+
+    /// Sets the prefix of the metric names, such as `github` for `github_requests_total`.
+    pub fn prefix(self,prefix : &str) -> Self {
+        Self::with_prefix(prefix)
+    }
+
+    fn with_prefix(prefix : &str) -> Self {
+        Self {
+            requests : format!("{prefix}_requests_total"),
+            duration : format!("{prefix}_request_duration_seconds"),
+            queue_time : format!("{prefix}_request_queue_seconds"),
+        }
+    }
+}
+
+#[cfg(feature = "metrics")]
+impl MetricsSink for MetricsCrateSink {
+    fn record(&self,metrics : &RequestMetrics) {
+        let labels = [
+            ("method",metrics.method.to_string()),
+            ("endpoint",metrics.endpoint.clone()),
+            ("status",metrics.status.map(|status| status.as_str().to_owned()).unwrap_or_default()),
+            ("outcome",metrics.outcome.as_str().to_owned()),
+        ];
+        ::metrics::counter!(self.requests.clone(),&labels).increment(1);
+        ::metrics::histogram!(self.duration.clone(),&labels).record(metrics.duration.as_secs_f64());
+        ::metrics::histogram!(self.queue_time.clone(),&labels).record(metrics.queue_time.as_secs_f64());
+    }
+}
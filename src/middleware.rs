@@ -0,0 +1,20 @@
+use reqwest::RequestBuilder;
+
+use crate::{MaybeSend, MaybeSync, RawResponse};
+
+/// A reusable layer wrapping every request built by the default requestors and every response they receive.
+///
+/// Cross-cutting concerns such as logging, authentication or metrics can be written once as a middleware and attached
+/// to any client through [crate::RequestDefaults::middleware]. Middleware run in order : the first one sees requests
+/// first, before [crate::RequestDefaults::finalize_request], and responses first as well.
+pub trait Middleware : MaybeSend + MaybeSync {
+    /// Modifies a request built by a default requestor, body included. Does nothing by default.
+    fn on_request(&self,request_builder : RequestBuilder) -> RequestBuilder {
+        request_builder
+    }
+
+    /// Inspects a received response before it is deserialized. Does nothing by default.
+    fn on_response(&self,response : &RawResponse) {
+        let _ = response;
+    }
+}
@@ -0,0 +1,123 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+
+use reqwest::header::{HeaderValue, AUTHORIZATION, CONTENT_TYPE};
+
+use crate::{hash, RequestSigner};
+use crate::endpoint::uri_encode;
+use crate::signing::percent_decode;
+
+/// Signs requests with OAuth 1.0a (RFC 5849) and the `HMAC-SHA1` method, as required by Twitter/X v1.1 and other legacy APIs.
+///
+/// The query parameters, the parameters of `application/x-www-form-urlencoded` bodies and the `oauth_*` parameters are
+/// signed, and the latter are sent in the `Authorization` header.
+///
+/// ```rust ignore
+/// let signer = OAuth1Signer::new(consumer_key,consumer_secret).token(access_token,access_token_secret);
+/// ```
+///
+/// Return it from [crate::RequestDefaults::request_signer] to apply it. Not available on `wasm32`.
+pub struct OAuth1Signer {
+    consumer_key : String,
+    consumer_secret : String,
+    token : Option<(String,String)>,
+}
+
+// The secrets are left out
+impl std::fmt::Debug for OAuth1Signer {
+    fn fmt(&self,f : &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OAuth1Signer")
+            .field("consumer_key",&self.consumer_key)
+            .field("token",&self.token.as_ref().map(|(token,_)| token))
+            .finish_non_exhaustive()
+    }
+}
+
+impl OAuth1Signer {
+    /// Creates a signer with the given consumer credentials, for requests made on behalf of the application only.
+    pub fn new(consumer_key : impl Into<String>,consumer_secret : impl Into<String>) -> Self {
+        Self {
+            consumer_key : consumer_key.into(),
+            consumer_secret : consumer_secret.into(),
+            token : None,
+        }
+    }
+
+    /// Sets the token and token secret, for requests made on behalf of a user.
+    pub fn token(mut self,token : impl Into<String>,token_secret : impl Into<String>) -> Self {
+        self.token = Some((token.into(),token_secret.into()));
+        self
+    }
+
+    /// Signs the request with the given nonce and timestamp, in seconds since the Unix epoch, which [RequestSigner::sign] generates.
+    ///
+    /// The signature matches the example of the OAuth Core 1.0 specification RFC 5849 derives from, which also signs the
+    /// `oauth_version` parameter always sent here :
+    ///
+    /// ```
+    /// use api_request_utils::OAuth1Signer;
+    /// use api_request_utils::reqwest::Client;
+    ///
+    /// let signer = OAuth1Signer::new("dpf43f3p2l4k3l03","kd94hf93k423kf44").token("nnch734d00sl2jdk","pfkkdhi9sl3r4s00");
+    /// let mut request = Client::new().get("http://photos.example.net/photos?file=vacation.jpg&size=original").build().unwrap();
+    /// signer.sign_with(&mut request,"kllo9940pd9333jh",1191242096);
+    /// let authorization = request.headers()["authorization"].to_str().unwrap();
+    /// assert!(authorization.contains(r#"oauth_signature="tR3%2BTy81lMeYAr%2FFid0kMTYa%2FWM%3D""#));
+    /// ```
+    pub fn sign_with(&self,request : &mut reqwest::Request,nonce : &str,timestamp : u64) {
+        let mut oauth = vec![
+            ("oauth_consumer_key",self.consumer_key.clone()),
+            ("oauth_nonce",nonce.to_owned()),
+            ("oauth_signature_method","HMAC-SHA1".to_owned()),
+            ("oauth_timestamp",timestamp.to_string()),
+            ("oauth_version","1.0".to_owned()),
+        ];
+        if let Some((token,_)) = &self.token {
+            oauth.push(("oauth_token",token.clone()));
+        }
+
+        let mut parameters : Vec<(String,String)> = request.url().query_pairs().map(|(key,value)| (uri_encode(&key),uri_encode(&value))).collect();
+        let is_form = request.headers().get(CONTENT_TYPE).and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.starts_with("application/x-www-form-urlencoded"));
+        if let Some(body) = request.body().and_then(reqwest::Body::as_bytes).filter(|_| is_form) {
+            let body = String::from_utf8_lossy(body);
+            parameters.extend(body.split('&').filter(|pair| !pair.is_empty()).map(|pair| {
+                let (key,value) = pair.split_once('=').unwrap_or((pair,""));
+                let decode = |value : &str| uri_encode(&percent_decode(&value.replace('+'," ")));
+                (decode(key),decode(value))
+            }));
+        }
+        parameters.extend(oauth.iter().map(|(key,value)| ((*key).to_owned(),uri_encode(value))));
+        parameters.sort();
+        let parameters = parameters.iter().map(|(key,value)| format!("{key}={value}")).collect::<Vec<_>>().join("&");
+
+        let url = request.url();
+        let base_url = match url.port() {
+            Some(port) => format!("{}://{}:{port}{}",url.scheme(),url.host_str().unwrap_or_default(),url.path()),
+            None => format!("{}://{}{}",url.scheme(),url.host_str().unwrap_or_default(),url.path())
+        };
+        let base_string = format!("{}&{}&{}",request.method(),uri_encode(&base_url),uri_encode(&parameters));
+        let key = format!("{}&{}",uri_encode(&self.consumer_secret),self.token.as_ref().map(|(_,secret)| uri_encode(secret)).unwrap_or_default());
+        let signature = BASE64.encode(hash::hmac_sha1(key.as_bytes(),base_string.as_bytes()));
+
+        oauth.push(("oauth_signature",signature));
+        oauth.sort();
+        let authorization = oauth.iter().map(|(key,value)| format!(r#"{key}="{}""#,uri_encode(value))).collect::<Vec<_>>().join(", ");
+        if let Ok(mut authorization) = HeaderValue::try_from(format!("OAuth {authorization}")) {
+            authorization.set_sensitive(true);
+            request.headers_mut().insert(AUTHORIZATION,authorization);
+        }
+    }
+}
+
+impl RequestSigner for OAuth1Signer {
+    fn sign(&self,request : &mut reqwest::Request) {
+        // Without random bytes for the nonce, the request is sent unsigned and rejected by the server
+        if let Ok(nonce) = hash::random_bytes::<16>() {
+            let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+            self.sign_with(request,&hash::hex(&nonce),now.as_secs());
+        }
+    }
+}
@@ -0,0 +1,215 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use reqwest::header::{HeaderValue, AUTHORIZATION};
+use serde::Deserialize;
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+use crate::{AuthRefresh, RawResponse, RequestError};
+#[cfg(feature = "jwt")]
+use crate::JwtAssertion;
+
+/// Fetches, caches and refreshes access tokens with the OAuth2 client credentials grant (RFC 6749 section 4.4), or the JWT
+/// bearer grant with the `jwt` feature.
+///
+/// Return it from [crate::RequestDefaults::token_provider] to send every request with an `Authorization : Bearer` header,
+/// unless the request already carries an `Authorization` header. The token is fetched from the token endpoint with the
+/// client of the request the first time it is needed, then reused until it is about to expire (30 seconds before by
+/// default, to tolerate clock skew and slow requests). Concurrent requests wait for a single refresh.
+///
+/// ```rust ignore
+/// let provider = OAuth2TokenProvider::new("https://auth.example.com/oauth/token","client-id","client-secret")
+///     .scope("read:users");
+/// ```
+///
+/// Not available on `wasm32`.
+pub struct OAuth2TokenProvider {
+    token_url : String,
+    grant : Grant,
+    scopes : Vec<String>,
+    parameters : Vec<(String,String)>,
+    refresh_skew : Duration,
+    token : Mutex<Option<CachedToken>>,
+}
+
+/// How the provider authenticates to the token endpoint.
+#[derive(Debug)]
+enum Grant {
+    ClientCredentials {
+        client_id : String,
+        client_secret : String,
+        in_body : bool,
+    },
+    #[cfg(feature = "jwt")]
+    JwtBearer(JwtAssertion),
+}
+
+struct CachedToken {
+    access_token : String,
+    /// `None` when the server did not tell when the token expires, in which case it is kept until invalidated.
+    expires_at : Option<Instant>,
+}
+
+// The client secret and the token are left out
+impl std::fmt::Debug for OAuth2TokenProvider {
+    fn fmt(&self,f : &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut debug = f.debug_struct("OAuth2TokenProvider");
+        debug.field("token_url",&self.token_url);
+        match &self.grant {
+            Grant::ClientCredentials { client_id, in_body, .. } => debug.field("client_id",client_id).field("credentials_in_body",in_body),
+            #[cfg(feature = "jwt")]
+            Grant::JwtBearer(assertion) => debug.field("assertion",assertion)
+        };
+        debug.field("scopes",&self.scopes).field("refresh_skew",&self.refresh_skew).finish_non_exhaustive()
+    }
+}
+
+/// The successful response of a token endpoint.
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token : String,
+    #[serde(default)]
+    expires_in : Option<u64>,
+}
+
+impl OAuth2TokenProvider {
+    /// Creates a provider requesting tokens from the given token endpoint with the given client credentials.
+    pub fn new(token_url : impl Into<String>,client_id : impl Into<String>,client_secret : impl Into<String>) -> Self {
+        Self::with_grant(token_url.into(),Grant::ClientCredentials { client_id : client_id.into(), client_secret : client_secret.into(), in_body : false })
+    }
+
+    /// Creates a provider exchanging the tokens built by the given assertion for access tokens, with the JWT bearer grant
+    /// (RFC 7523) used by Google service accounts among others. A new assertion is built for every token request.
+    #[cfg(feature = "jwt")]
+    pub fn jwt_bearer(token_url : impl Into<String>,assertion : JwtAssertion) -> Self {
+        Self::with_grant(token_url.into(),Grant::JwtBearer(assertion))
+    }
+
+    fn with_grant(token_url : String,grant : Grant) -> Self {
+        Self {
+            token_url,
+            grant,
+            scopes : Vec::new(),
+            parameters : Vec::new(),
+            refresh_skew : Duration::from_secs(30),
+            token : Mutex::new(None),
+        }
+    }
+
+    /// Adds a scope to request, scopes are sent space-separated in the `scope` parameter.
+    pub fn scope(mut self,scope : impl Into<String>) -> Self {
+        self.scopes.push(scope.into());
+        self
+    }
+
+    /// Adds a parameter to the token request, such as the `audience` some providers require.
+    pub fn parameter(mut self,name : impl Into<String>,value : impl Into<String>) -> Self {
+        self.parameters.push((name.into(),value.into()));
+        self
+    }
+
+    /// Sends the client credentials as `client_id` and `client_secret` parameters of the token request rather than with
+    /// HTTP Basic authentication, for providers which do not support the latter. Does nothing for the JWT bearer grant.
+    pub fn credentials_in_body(mut self) -> Self {
+        // Only refutable with the `jwt` feature
+        #[allow(irrefutable_let_patterns)]
+        if let Grant::ClientCredentials { in_body, .. } = &mut self.grant {
+            *in_body = true;
+        }
+        self
+    }
+
+    /// Sets how long before its expiry a token is refreshed, 30 seconds by default.
+    ///
+    /// A skew too large to be added to the current time, such as `Duration::MAX`, refreshes expiring tokens on every request.
+    pub fn refresh_skew(mut self,skew : Duration) -> Self {
+        self.refresh_skew = skew;
+        self
+    }
+
+    /// Returns the cached access token, fetching a new one with the given client if there is none or it is about to expire.
+    ///
+    /// # Returns
+    ///
+    /// The access token, or the error of the token request. Unsuccessful responses yield [RequestError::TokenRequestFailed]
+    /// and assertions that could not be signed [RequestError::TokenSigningFailed].
+    pub async fn token<E>(&self,client : &reqwest::Client) -> Result<String,RequestError<E>> {
+        let mut cached = self.token.lock().await;
+        if let Some(token) = cached.as_ref().filter(|token| token.expires_at.is_none_or(|expires_at| Instant::now().checked_add(self.refresh_skew).is_some_and(|refresh_at| refresh_at < expires_at))) {
+            return Ok(token.access_token.clone());
+        }
+
+        let token = self.fetch(client).await?;
+        let access_token = token.access_token.clone();
+        *cached = Some(token);
+        Ok(access_token)
+    }
+
+    /// Discards the cached token, so the next request fetches a new one.
+    pub async fn invalidate(&self) {
+        *self.token.lock().await = None;
+    }
+
+    /// Adds the `Authorization` header carrying the access token to the request, unless it already has one.
+    pub(crate) async fn authorize<E>(&self,client : &reqwest::Client,request : &mut reqwest::Request) -> Result<(),RequestError<E>> {
+        if request.headers().contains_key(AUTHORIZATION) {
+            return Ok(());
+        }
+
+        let token = self.token(client).await?;
+        let value = HeaderValue::from_str(&format!("Bearer {token}")).map_err(|_| RequestError::InvalidHeader { name : AUTHORIZATION.to_string() })?;
+        request.headers_mut().insert(AUTHORIZATION,value);
+        Ok(())
+    }
+
+    async fn fetch<E>(&self,client : &reqwest::Client) -> Result<CachedToken,RequestError<E>> {
+        let request = client.post(&self.token_url);
+        #[cfg(feature = "jwt")]
+        let assertion;
+        let (mut form,request) = match &self.grant {
+            Grant::ClientCredentials { client_id, client_secret, in_body : true } => {
+                (vec![("grant_type","client_credentials"),("client_id",client_id.as_str()),("client_secret",client_secret.as_str())],request)
+            }
+            Grant::ClientCredentials { client_id, client_secret, in_body : false } => {
+                (vec![("grant_type","client_credentials")],request.basic_auth(client_id,Some(client_secret)))
+            }
+            #[cfg(feature = "jwt")]
+            Grant::JwtBearer(jwt) => {
+                assertion = jwt.build().map_err(RequestError::TokenSigningFailed)?;
+                (vec![("grant_type","urn:ietf:params:oauth:grant-type:jwt-bearer"),("assertion",assertion.as_str())],request)
+            }
+        };
+        let scope = self.scopes.join(" ");
+        if !scope.is_empty() {
+            form.push(("scope",&scope));
+        }
+        form.extend(self.parameters.iter().map(|(name,value)| (name.as_str(),value.as_str())));
+
+        let requested_at = Instant::now();
+        let response = request.form(&form).send().await?;
+        let status = response.status();
+        let body = response.bytes().await?;
+        if !status.is_success() {
+            return Err(RequestError::TokenRequestFailed { status, body : String::from_utf8_lossy(&body).into_owned() });
+        }
+
+        let token : TokenResponse = serde_json::from_slice(&body)?;
+        Ok(CachedToken {
+            access_token : token.access_token,
+            // Lifetimes beyond what `Instant` represents never expire
+            expires_at : token.expires_in.and_then(|expires_in| requested_at.checked_add(Duration::from_secs(expires_in))),
+        })
+    }
+}
+
+/// Discards the rejected token, so the request is retried with a new one.
+#[async_trait]
+impl AuthRefresh for OAuth2TokenProvider {
+    async fn refresh(&self,request : &mut reqwest::Request,_response : &RawResponse) -> bool {
+        self.invalidate().await;
+        request.headers_mut().remove(AUTHORIZATION);
+        true
+    }
+}
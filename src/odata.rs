@@ -0,0 +1,154 @@
+use std::collections::HashMap;
+
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Serialize, Serializer};
+use serde_json::Value;
+
+/// The system query options of an [OData](https://www.odata.org) request, such as those of Microsoft Graph or Dynamics 365.
+///
+/// It serializes to the query parameters [crate::RequestDefaults::default_get_query_requestor] takes, and
+/// [ODataQuery::parameters] returns them in the form [crate::RequestDefaults::default_get_requestor] takes :
+///
+/// ```
+/// use api_request_utils::ODataQuery;
+///
+/// let query = ODataQuery::new()
+///     .filter("startswith(displayName,'A')")
+///     .filter("accountEnabled eq true")
+///     .select(["id","displayName"])
+///     .order_by("displayName")
+///     .order_by_desc("createdDateTime")
+///     .expand("manager")
+///     .top(10)
+///     .skip(20);
+/// let parameters = query.parameters();
+/// assert_eq!(parameters["$filter"],"(startswith(displayName,'A')) and (accountEnabled eq true)");
+/// assert_eq!(parameters["$select"],"id,displayName");
+/// assert_eq!(parameters["$orderby"],"displayName,createdDateTime desc");
+/// assert_eq!(parameters["$expand"],"manager");
+/// assert_eq!(parameters["$top"],10);
+/// assert_eq!(parameters["$skip"],20);
+/// ```
+///
+/// Filter expressions are sent as they are : string literals within them must be quoted with `'`, and their own `'`
+/// doubled, which [ODataQuery::literal] does.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ODataQuery {
+    filters : Vec<String>,
+    select : Vec<String>,
+    order_by : Vec<String>,
+    expand : Vec<String>,
+    top : Option<u64>,
+    skip : Option<u64>,
+    count : bool,
+}
+
+impl ODataQuery {
+    /// Creates a query without any option.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Quotes a string literal for a filter expression, such as `'O''Neil'` for `O'Neil`.
+    pub fn literal(value : &str) -> String {
+        format!("'{}'",value.replace('\'',"''"))
+    }
+
+    /// Adds a `$filter` expression, such as `price lt 10`. Every expression added must hold.
+    pub fn filter(mut self,expression : impl Into<String>) -> Self {
+        self.filters.push(expression.into());
+        self
+    }
+
+    /// Adds the properties of `$select`, leaving the others out of the response.
+    pub fn select<I : IntoIterator<Item = F>,F : Into<String>>(mut self,properties : I) -> Self {
+        self.select.extend(properties.into_iter().map(Into::into));
+        self
+    }
+
+    /// Sorts by the given property in ascending order. Properties added first take precedence.
+    pub fn order_by(mut self,property : impl Into<String>) -> Self {
+        self.order_by.push(property.into());
+        self
+    }
+
+    /// Sorts by the given property in descending order. Properties added first take precedence.
+    pub fn order_by_desc(mut self,property : impl Into<String>) -> Self {
+        self.order_by.push(format!("{} desc",property.into()));
+        self
+    }
+
+    /// Adds a navigation property to `$expand`, including the related entities inline, such as `manager` or `members($select=id)`.
+    pub fn expand(mut self,property : impl Into<String>) -> Self {
+        self.expand.push(property.into());
+        self
+    }
+
+    /// Sets `$top`, the maximum number of entities returned.
+    pub fn top(mut self,top : u64) -> Self {
+        self.top = Some(top);
+        self
+    }
+
+    /// Sets `$skip`, the number of entities skipped before the first returned.
+    pub fn skip(mut self,skip : u64) -> Self {
+        self.skip = Some(skip);
+        self
+    }
+
+    /// Sets `$count`, asking for the total number of matching entities in [ODataPage::count].
+    pub fn count(mut self,count : bool) -> Self {
+        self.count = count;
+        self
+    }
+
+    /// Returns the set options as query parameters, leaving the others out.
+    pub fn parameters(&self) -> HashMap<&'static str,Value> {
+        self.options().collect()
+    }
+
+    /// Returns the set options, in the order they are serialized.
+    fn options(&self) -> impl Iterator<Item = (&'static str,Value)> + '_ {
+        let filter = match self.filters.as_slice() {
+            [] => None,
+            [filter] => Some(filter.clone()),
+            filters => Some(filters.iter().map(|filter| format!("({filter})")).collect::<Vec<_>>().join(" and "))
+        };
+        let lists = [("$select",&self.select),("$orderby",&self.order_by),("$expand",&self.expand)].into_iter()
+            .filter(|(_,values)| !values.is_empty())
+            .map(|(name,values)| (name,Value::from(values.join(","))));
+
+        filter.map(|filter| ("$filter",Value::from(filter))).into_iter()
+            .chain(lists)
+            .chain(self.top.map(|top| ("$top",Value::from(top))))
+            .chain(self.skip.map(|skip| ("$skip",Value::from(skip))))
+            .chain(self.count.then(|| ("$count",Value::from(true))))
+    }
+}
+
+impl Serialize for ODataQuery {
+    fn serialize<S : Serializer>(&self,serializer : S) -> Result<S::Ok,S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+        for (name,value) in self.options() {
+            map.serialize_entry(name,&value)?;
+        }
+        map.end()
+    }
+}
+
+/// A page of an OData collection, see [crate::PaginatedRequestHandler::paginate_odata].
+///
+/// ```rust ignore
+/// impl RequestHandler<ODataPage<User>,ODataPage<User>,ApiError> for MyAPIClient {}
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ODataPage<T> {
+    /// The entities of the page.
+    pub value : Vec<T>,
+    /// The URL of the following page, `None` for the last page.
+    #[serde(rename = "@odata.nextLink", alias = "odata.nextLink", default, skip_serializing_if = "Option::is_none")]
+    pub next_link : Option<String>,
+    /// The total number of matching entities, when requested with [ODataQuery::count].
+    #[serde(rename = "@odata.count", default, skip_serializing_if = "Option::is_none")]
+    pub count : Option<u64>,
+}
@@ -0,0 +1,314 @@
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard};
+
+use reqwest::{Method, StatusCode, Url};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION, CONTENT_LENGTH, COOKIE, PROXY_AUTHORIZATION};
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+
+use crate::{auth, ApiKeyLocation, RawResponse, RequestDefaults, RequestError};
+
+/// A durable queue of the mutations that could not be sent for lack of connectivity, replayed in order once it returns.
+///
+/// When a `POST`, `PUT`, `PATCH` or `DELETE` request fails to connect, it is added to the queue and the handler fails with
+/// [RequestError::QueuedOffline] instead of the connection error, which is kept when the request cannot be queued, such as
+/// when its body is streamed or the queue fails to be persisted. Calling [OfflineQueue::flush] later, such as when the
+/// device reports it is online again, sends the queued requests one after the other, in the order they were queued.
+///
+/// ```rust ignore
+/// let queue = OfflineQueue::persistent("data/outbox.json")?
+///     .on_conflict(|request,response| match response.meta.status {
+///         StatusCode::CONFLICT => ConflictResolution::Discard,
+///         _ => ConflictResolution::Keep,
+///     });
+/// // Once back online
+/// let sent = queue.flush::<_,ApiError>(&api_client).await?;
+/// ```
+///
+/// Credentials are not queued : the `Authorization`, `Proxy-Authorization` and `Cookie` headers, as well as the headers
+/// marked as sensitive, are dropped, and the [RequestDefaults::api_key_auth], [RequestDefaults::token_provider] and
+/// [RequestDefaults::request_signer] apply again when the requests are replayed. The API key of the
+/// [RequestDefaults::api_key_auth] is also removed from the query string of the requests queued by the handlers. Bodies
+/// are queued as they are, base64 encoded in the file of a persistent queue, so binary bodies such as compressed or
+/// multipart ones replay unchanged.
+///
+/// Return it from [RequestDefaults::offline_queue] to apply it. Not available on `wasm32`.
+pub struct OfflineQueue {
+    path : Option<PathBuf>,
+    state : Mutex<State>,
+    flushing : tokio::sync::Mutex<()>,
+    on_conflict : Option<Box<ConflictCallback>>,
+}
+
+/// The callback of [OfflineQueue::on_conflict].
+type ConflictCallback = dyn Fn(&QueuedRequest,&RawResponse) -> ConflictResolution + Send + Sync;
+
+// The conflict callback is left out
+impl std::fmt::Debug for OfflineQueue {
+    fn fmt(&self,f : &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OfflineQueue")
+            .field("path",&self.path)
+            .field("requests",&self.state().requests)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct State {
+    next_id : u64,
+    requests : Vec<StoredRequest>,
+}
+
+/// A request of an [OfflineQueue].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueuedRequest {
+    /// The ID of the request in the queue, as reported by [RequestError::QueuedOffline].
+    pub id : u64,
+    /// The method of the request.
+    pub method : Method,
+    /// The URL of the request.
+    pub url : Url,
+    /// The headers of the request, without the credentials.
+    pub headers : HeaderMap,
+    /// The body of the request.
+    pub body : Option<Vec<u8>>,
+}
+
+/// What an [OfflineQueue] does with a replayed request answered with an unsuccessful status, see [OfflineQueue::on_conflict].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConflictResolution {
+    /// Removes the request from the queue and goes on with the next one.
+    Discard,
+    /// Keeps the request at the front of the queue and stops flushing, to replay it with the next flush.
+    Keep,
+}
+
+/// The form in which a [QueuedRequest] is persisted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredRequest {
+    id : u64,
+    method : String,
+    url : String,
+    headers : Vec<(String,String)>,
+    /// The body, base64 encoded.
+    body : Option<String>,
+}
+
+impl OfflineQueue {
+    /// Creates a queue held in memory, which is lost when the process exits.
+    pub fn new() -> Self {
+        Self { path : None, state : Mutex::new(State::default()), flushing : tokio::sync::Mutex::new(()), on_conflict : None }
+    }
+
+    /// Creates a queue persisted to the given JSON file, loading the requests it holds from a previous run.
+    ///
+    /// The file is rewritten atomically every time the queue changes, and created along with its directory if needed.
+    pub fn persistent(path : impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        let state = match fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)?,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => State::default(),
+            Err(error) => return Err(error)
+        };
+        Ok(Self { path : Some(path), state : Mutex::new(state), ..Self::new() })
+    }
+
+    /// Decides what happens to replayed requests answered with an unsuccessful status, such as a `409 Conflict` when the
+    /// resource changed while offline.
+    ///
+    /// Without a callback, requests answered with `429 Too Many Requests` or a `5xx` status are kept, and the others discarded
+    /// with a warning.
+    pub fn on_conflict(mut self,callback : impl Fn(&QueuedRequest,&RawResponse) -> ConflictResolution + Send + Sync + 'static) -> Self {
+        self.on_conflict = Some(Box::new(callback));
+        self
+    }
+
+    /// Returns how many requests are queued.
+    pub fn len(&self) -> usize {
+        self.state().requests.len()
+    }
+
+    /// Returns whether no request is queued.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the queued requests, in the order they are replayed. Requests that can no longer be parsed are left out.
+    pub fn pending(&self) -> Vec<QueuedRequest> {
+        self.state().requests.iter().filter_map(StoredRequest::parse).collect()
+    }
+
+    /// Adds a request to the back of the queue.
+    ///
+    /// # Returns
+    ///
+    /// The ID of the request in the queue, or an error of kind [io::ErrorKind::InvalidInput] if its body is streamed, or
+    /// the error the queue failed to be persisted with.
+    pub fn enqueue(&self,request : &reqwest::Request) -> io::Result<u64> {
+        let body = match request.body() {
+            Some(body) => {
+                let bytes = body.as_bytes().ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput,"streamed bodies cannot be queued"))?;
+                Some(BASE64.encode(bytes))
+            },
+            None => None
+        };
+        let headers = request.headers().iter()
+            .filter(|(name,value)| !value.is_sensitive() && ![AUTHORIZATION,PROXY_AUTHORIZATION,COOKIE,CONTENT_LENGTH].contains(name))
+            .filter_map(|(name,value)| value.to_str().ok().map(|value| (name.to_string(),value.to_owned())))
+            .collect();
+
+        let mut state = self.state();
+        state.next_id += 1;
+        let id = state.next_id;
+        state.requests.push(StoredRequest { id, method : request.method().to_string(), url : request.url().to_string(), headers, body });
+        if let Err(error) = self.persist(&state) {
+            state.requests.pop();
+            return Err(error);
+        }
+        Ok(id)
+    }
+
+    /// Removes the request with the given ID from the queue.
+    ///
+    /// # Returns
+    ///
+    /// Whether the request was queued, or the error the queue failed to be persisted with.
+    pub fn remove(&self,id : u64) -> io::Result<bool> {
+        let mut state = self.state();
+        let Some(index) = state.requests.iter().position(|request| request.id == id) else {
+            return Ok(false)
+        };
+        state.requests.remove(index);
+        self.persist(&state).map(|_| true)
+    }
+
+    /// Removes every request from the queue.
+    pub fn clear(&self) -> io::Result<()> {
+        let mut state = self.state();
+        state.requests.clear();
+        self.persist(&state)
+    }
+
+    /// Replays the queued requests in order through the sending hooks of `defaults`, removing the ones answered successfully.
+    ///
+    /// Flushing stops at the first request failing to connect again, or kept by the [OfflineQueue::on_conflict] callback,
+    /// leaving it and the following ones queued. Concurrent flushes wait for each other, so requests are never sent twice.
+    ///
+    /// # Returns
+    ///
+    /// How many requests were sent, be they answered successfully or discarded, or the error the first remaining
+    /// request failed with.
+    pub async fn flush<D : RequestDefaults + ?Sized,E : DeserializeOwned>(&self,defaults : &D) -> Result<usize,RequestError<E>> {
+        let _flushing = self.flushing.lock().await;
+        let mut sent = 0;
+
+        loop {
+            // Not matched inline, which would hold the lock while the request is sent
+            let Some(stored) = self.state().requests.first().cloned() else {
+                break;
+            };
+            let Some(request) = stored.parse() else {
+                log::warn!("Discarding queued request {} which could not be parsed",stored.id);
+                self.remove(stored.id)?;
+                continue;
+            };
+
+            let response = crate::send_refreshing(defaults,replay(defaults,&request),|_,_ : &RequestError<E>,_| {}).await?;
+            let resolution = match (response.meta.status.is_success(),&self.on_conflict) {
+                (true,_) => ConflictResolution::Discard,
+                (false,Some(on_conflict)) => on_conflict(&request,&response),
+                (false,None) if response.meta.status == StatusCode::TOO_MANY_REQUESTS || response.meta.status.is_server_error() => ConflictResolution::Keep,
+                (false,None) => {
+                    log::warn!("Discarding queued request {} {} answered with status code {}",request.method,crate::logging::redact_url(&request.url),response.meta.status);
+                    ConflictResolution::Discard
+                }
+            };
+
+            if resolution == ConflictResolution::Keep {
+                break;
+            }
+            self.remove(request.id)?;
+            sent += 1;
+        }
+        Ok(sent)
+    }
+
+    /// Writes the queue to its file, if it is persistent.
+    fn persist(&self,state : &State) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(())
+        };
+
+        if let Some(directory) = path.parent().filter(|directory| !directory.as_os_str().is_empty()) {
+            fs::create_dir_all(directory)?;
+        }
+        let temporary = path.with_extension(format!("{}.tmp",std::process::id()));
+        fs::write(&temporary,serde_json::to_vec(state)?)?;
+        fs::rename(&temporary,path)
+    }
+
+    fn state(&self) -> MutexGuard<'_,State> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Default for OfflineQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StoredRequest {
+    fn parse(&self) -> Option<QueuedRequest> {
+        let mut headers = HeaderMap::new();
+        for (name,value) in &self.headers {
+            headers.append(HeaderName::from_bytes(name.as_bytes()).ok()?,HeaderValue::from_str(value).ok()?);
+        }
+        Some(QueuedRequest {
+            id : self.id,
+            method : Method::from_bytes(self.method.as_bytes()).ok()?,
+            url : Url::parse(&self.url).ok()?,
+            headers,
+            body : match &self.body {
+                Some(body) => Some(BASE64.decode(body).ok()?),
+                None => None
+            },
+        })
+    }
+}
+
+/// Builds the request replaying a queued one, with the API key applied again.
+fn replay<D : RequestDefaults + ?Sized>(defaults : &D,request : &QueuedRequest) -> reqwest::RequestBuilder {
+    let mut url = request.url.clone();
+    strip_api_key(defaults,&mut url);
+    let builder = defaults.client().request(request.method.clone(),url).headers(request.headers.clone());
+    let builder = match &request.body {
+        Some(body) => builder.body(body.clone()),
+        None => builder
+    };
+    match defaults.api_key_auth() {
+        Some(auth) => auth::apply_api_key(auth,builder),
+        None => builder
+    }
+}
+
+/// Removes the API key of the [RequestDefaults::api_key_auth] from the query string of `url`, if it is sent there.
+pub(crate) fn strip_api_key<D : RequestDefaults + ?Sized>(defaults : &D,url : &mut Url) {
+    let Some(ApiKeyLocation::Query(name)) = defaults.api_key_auth().map(|auth| auth.api_key_location()) else {
+        return
+    };
+    if !url.query_pairs().any(|(key,_)| key == name.as_str()) {
+        return
+    }
+    let pairs : Vec<(String,String)> = url.query_pairs().filter(|(key,_)| key != name.as_str()).map(|(key,value)| (key.into_owned(),value.into_owned())).collect();
+    match pairs.is_empty() {
+        true => url.set_query(None),
+        false => { url.query_pairs_mut().clear().extend_pairs(pairs); }
+    }
+}
@@ -0,0 +1,402 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+
+use futures_util::{Stream, StreamExt};
+
+use reqwest::Method;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::{base_requestor, logging, finish_request, parse_response, prepare_response, MaybeSend, MaybeSync, ODataPage, RequestError, RequestHandler};
+
+/// How long [PaginatedRequestHandler::long_poll] waits before polling again after the connection dropped.
+#[cfg(not(target_arch = "wasm32"))]
+const RECONNECT_DELAY : std::time::Duration = std::time::Duration::from_secs(1);
+
+/// How many pages [PaginatedRequestHandler::get_all_pages] fetches at most, unless set with [PageConfig::max_pages].
+pub const DEFAULT_MAX_PAGES : usize = 1000;
+
+/// How [PaginatedRequestHandler::get_all_pages] requests the pages of an endpoint, and when it stops.
+///
+/// ```rust ignore
+/// // ?page=1&per_page=100, ?page=2&per_page=100, ... until a page has fewer than 100 items
+/// let config = PageConfig::page_number("page").page_size("per_page",100);
+/// // ?offset=0&limit=50, ?offset=50&limit=50, ... until a page has fewer than 50 items
+/// let config = PageConfig::offset("offset").page_size("limit",50);
+/// // ?cursor=... with the cursor of the previous page, until a page has none
+/// let config = PageConfig::cursor("cursor",|page : &UsersPage| page.next_cursor.clone()).max_items(10_000);
+/// ```
+pub struct PageConfig<P> {
+    strategy : PageStrategy<P>,
+    page_size : Option<(String,usize)>,
+    max_pages : usize,
+    max_items : Option<usize>,
+}
+
+/// The callback of [PageConfig::cursor].
+type CursorCallback<P> = dyn Fn(&P) -> Option<String> + Send + Sync;
+
+enum PageStrategy<P> {
+    PageNumber { parameter : String, first : u64 },
+    Offset { parameter : String },
+    Cursor { parameter : String, next : Box<CursorCallback<P>> },
+}
+
+// The cursor callback is left out
+impl<P> std::fmt::Debug for PageConfig<P> {
+    fn fmt(&self,f : &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (strategy,parameter) = match &self.strategy {
+            PageStrategy::PageNumber { parameter, .. } => ("PageNumber",parameter),
+            PageStrategy::Offset { parameter } => ("Offset",parameter),
+            PageStrategy::Cursor { parameter, .. } => ("Cursor",parameter)
+        };
+        f.debug_struct("PageConfig")
+            .field("strategy",&strategy)
+            .field("parameter",parameter)
+            .field("page_size",&self.page_size)
+            .field("max_pages",&self.max_pages)
+            .field("max_items",&self.max_items)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<P> PageConfig<P> {
+    /// Requests the pages by number, in the given parameter, starting from `1`. Paging stops at the first empty page.
+    pub fn page_number(parameter : impl Into<String>) -> Self {
+        Self::new(PageStrategy::PageNumber { parameter : parameter.into(), first : 1 })
+    }
+
+    /// Requests the pages by the number of items to skip, in the given parameter, starting from `0`. Paging stops at the
+    /// first empty page.
+    pub fn offset(parameter : impl Into<String>) -> Self {
+        Self::new(PageStrategy::Offset { parameter : parameter.into() })
+    }
+
+    /// Requests the pages by the cursor `next` reads from the previous page, in the given parameter, the first page being
+    /// requested without it. Paging stops at the first page without a cursor.
+    pub fn cursor(parameter : impl Into<String>,next : impl Fn(&P) -> Option<String> + Send + Sync + 'static) -> Self {
+        Self::new(PageStrategy::Cursor { parameter : parameter.into(), next : Box::new(next) })
+    }
+
+    /// Sets the number of the first page for [PageConfig::page_number], such as `0`.
+    pub fn first_page(mut self,number : u64) -> Self {
+        if let PageStrategy::PageNumber { first, .. } = &mut self.strategy {
+            *first = number;
+        }
+        self
+    }
+
+    /// Sends the page size in the given parameter, such as `per_page` or `limit`. With page numbers and offsets, paging
+    /// then also stops at the first page with fewer items.
+    pub fn page_size(mut self,parameter : impl Into<String>,size : usize) -> Self {
+        self.page_size = Some((parameter.into(),size));
+        self
+    }
+
+    /// Sets how many pages are fetched at most, [DEFAULT_MAX_PAGES] by default, against servers ignoring the paging parameters.
+    pub fn max_pages(mut self,max_pages : usize) -> Self {
+        self.max_pages = max_pages;
+        self
+    }
+
+    /// Sets how many items are collected at most, without limit by default.
+    pub fn max_items(mut self,max_items : usize) -> Self {
+        self.max_items = Some(max_items);
+        self
+    }
+
+    fn new(strategy : PageStrategy<P>) -> Self {
+        Self { strategy, page_size : None, max_pages : DEFAULT_MAX_PAGES, max_items : None }
+    }
+}
+
+/// Streams the items of paginated endpoints, following pages until the last one.
+///
+/// Implemented like the other traits, it is available for every page type `P` the client has a [RequestHandler] for :
+///
+/// ```rust ignore
+/// impl RequestHandler<UsersPage,UsersPage,ApiError> for MyAPIClient {}
+/// impl PaginatedRequestHandler<UsersPage,ApiError> for MyAPIClient {}
+/// ```
+#[cfg_attr(not(feature = "wasm"),async_trait)]
+#[cfg_attr(feature = "wasm",async_trait(?Send))]
+pub trait PaginatedRequestHandler<P : DeserializeOwned,E : DeserializeOwned> : RequestHandler<P,P,E> {
+    /// Sends the given request and then the requests for the following pages, yielding the items of every page as they arrive.
+    ///
+    /// Every page is fetched through [RequestHandler::request_map], so retries, rate limiting and the error payload handling apply
+    /// to each of them. The next page is only requested once every item of the current one has been consumed, and the stream ends
+    /// after the first error.
+    ///
+    /// ```rust ignore
+    /// let users = api_client.paginate(
+    ///     api_client.default_get_query_requestor("users",&query),
+    ///     // Cursor based, page numbers or offsets work the same way
+    ///     |page : &UsersPage| page.next_cursor.as_ref().map(|cursor| api_client.default_get_query_requestor("users",&[("cursor",cursor)])),
+    ///     |page : UsersPage| page.users,
+    /// );
+    /// futures_util::pin_mut!(users);
+    /// while let Some(user) = users.next().await {
+    ///     println!("{:?}",user?);
+    /// }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The `reqwest::RequestBuilder` representing the request for the first page.
+    /// * `next_page` - A closure building the request for the page following the given one, `None` once it is the last page.
+    /// * `items` - A closure extracting the items out of a page.
+    ///
+    /// # Returns
+    ///
+    /// A `Stream` of the items or `RequestError` variants.
+    fn paginate<'a,T,I>(&'a self,request : reqwest::RequestBuilder,next_page : impl FnMut(&P) -> Option<reqwest::RequestBuilder> + 'a,items : impl FnMut(P) -> I + 'a) -> impl Stream<Item = Result<T,RequestError<E>>> + 'a
+    where Self : MaybeSync, I : IntoIterator<Item = T> + 'a, P : 'a, E : 'a, T : 'a {
+        let pages = futures_util::stream::unfold((Some(request),next_page),move |(request,mut next_page)| async move {
+            let page = self.request_map(request?,|page| page).await;
+            let next = page.as_ref().ok().and_then(&mut next_page);
+            Some((page,(next,next_page)))
+        });
+
+        flatten_pages(pages,items)
+    }
+
+    /// Fetches every page of an endpoint, one after the other, and collects their items.
+    ///
+    /// Every page is requested like with [crate::RequestDefaults::default_get_query_requestor], with the paging parameters of
+    /// the [PageConfig] added to the query, and fetched through [RequestHandler::request_map]. Paging stops once the
+    /// [PageConfig] tells the last page was reached, or with a warning once [PageConfig::max_pages] pages or
+    /// [PageConfig::max_items] items were collected, keeping what was collected so far.
+    ///
+    /// The page type must iterate over its items : arrays such as `Vec<User>` do, and wrappers can implement `IntoIterator`.
+    ///
+    /// ```rust ignore
+    /// impl RequestHandler<Vec<Repository>,Vec<Repository>,ApiError> for MyAPIClient {}
+    /// impl PaginatedRequestHandler<Vec<Repository>,ApiError> for MyAPIClient {}
+    ///
+    /// let config = PageConfig::page_number("page").page_size("per_page",100).max_items(5000);
+    /// let repositories = api_client.get_all_pages("user/repos",&[("sort","updated")],&config).await?;
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint` - The endpoint of the pages.
+    /// * `parameters` - The query parameters sent with every page.
+    /// * `config` - How the pages are requested.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the items of every page or the `RequestError` the first failing page was answered with.
+    async fn get_all_pages<T,Q>(&self,endpoint : &str,parameters : &Q,config : &PageConfig<P>) -> Result<Vec<T>,RequestError<E>>
+    where Self : MaybeSync, P : IntoIterator<Item = T> + MaybeSend, T : MaybeSend, Q : Serialize + MaybeSync + ?Sized {
+        let mut collected = Vec::new();
+        let mut received = 0;
+        let mut cursor : Option<String> = None;
+
+        for index in 0..config.max_pages {
+            let mut paging : Vec<(&str,String)> = match &config.strategy {
+                PageStrategy::PageNumber { parameter, first } => vec![(parameter,(first + index as u64).to_string())],
+                PageStrategy::Offset { parameter } => vec![(parameter,received.to_string())],
+                PageStrategy::Cursor { parameter, .. } => cursor.iter().map(|cursor| (parameter.as_str(),cursor.clone())).collect()
+            };
+            paging.extend(config.page_size.iter().map(|(parameter,size)| (parameter.as_str(),size.to_string())));
+            let request = finish_request(self,base_requestor(self,Method::GET,endpoint,&HashMap::new()).query(parameters).query(&paging));
+
+            let page = self.request_map(request,|page| page).await?;
+            cursor = match &config.strategy {
+                PageStrategy::Cursor { next, .. } => next(&page),
+                _ => None
+            };
+            let items : Vec<T> = page.into_iter().collect();
+            let count = items.len();
+            received += count;
+            let last = match &config.strategy {
+                PageStrategy::Cursor { .. } => cursor.is_none(),
+                _ => count == 0 || config.page_size.as_ref().is_some_and(|(_,size)| count < *size)
+            };
+
+            let remaining = config.max_items.map_or(usize::MAX,|max_items| max_items - collected.len());
+            let truncated = count > remaining;
+            collected.extend(items.into_iter().take(remaining));
+            let full = config.max_items.is_some_and(|max_items| collected.len() >= max_items);
+            if full && (truncated || !last) {
+                log::warn!("Stopped collecting the pages of {endpoint} at {} items",collected.len());
+            }
+            if last || full {
+                return Ok(collected);
+            }
+        }
+        log::warn!("Stopped collecting the pages of {endpoint} after {} pages",config.max_pages);
+        Ok(collected)
+    }
+
+    /// Sends the given request and then follows the `rel="next"` links of the `Link` response headers until exhausted, yielding
+    /// the items of every page as they arrive.
+    ///
+    /// This suits APIs paginating through headers rather than the body, such as GitHub. The requests for the following pages use
+    /// the method and headers of the first request, with the URL given by the link (see [crate::ResponseMeta::link]), without the
+    /// credentials headers when the link leads to another origin. Otherwise this behaves like [PaginatedRequestHandler::paginate].
+    ///
+    /// ```rust ignore
+    /// let repositories = api_client.paginate_links(api_client.default_get_query_requestor("user/repos",&query),|page : Vec<Repository>| page);
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The `reqwest::RequestBuilder` representing the request for the first page.
+    /// * `items` - A closure extracting the items out of a page.
+    ///
+    /// # Returns
+    ///
+    /// A `Stream` of the items or `RequestError` variants.
+    fn paginate_links<'a,T,I>(&'a self,request : reqwest::RequestBuilder,items : impl FnMut(P) -> I + 'a) -> impl Stream<Item = Result<T,RequestError<E>>> + 'a
+    where Self : MaybeSync, I : IntoIterator<Item = T> + 'a, P : 'a, E : 'a, T : 'a {
+        let template = request.try_clone().and_then(|request| request.build().ok());
+        let pages = futures_util::stream::unfold((Some(request),template),move |(request,template)| async move {
+            let response = match self.request_raw(request?).await {
+                Ok(response) => response,
+                Err(error) => return Some((Err(error),(None,None)))
+            };
+
+            let next = match response.meta.status.is_success() {
+                true => template.as_ref().zip(response.meta.link("next")).map(|(template,url)| next_page(self.client(),template,url)),
+                false => None
+            };
+            Some((prepare_response(self,response).and_then(|response| parse_response(&response)),(next,template)))
+        });
+
+        flatten_pages(pages,items)
+    }
+
+    /// Sends the given request for an OData collection and then follows the `@odata.nextLink` of every page until the last
+    /// one, yielding the entities of every page as they arrive.
+    ///
+    /// The requests for the following pages use the method and headers of the first request, with the URL given by the
+    /// link, resolved against the URL of the page when relative. The credentials headers are dropped when the link leads to
+    /// another origin. Its query carries the options of the first request, such as
+    /// [crate::ODataQuery::filter], along with the server's skip token. Otherwise this behaves like [PaginatedRequestHandler::paginate].
+    ///
+    /// ```rust ignore
+    /// impl PaginatedRequestHandler<ODataPage<User>,ApiError> for MyAPIClient {}
+    ///
+    /// let query = ODataQuery::new().filter("accountEnabled eq true").select(["id","displayName"]);
+    /// let users = api_client.paginate_odata(api_client.default_get_query_requestor("users",&query));
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The `reqwest::RequestBuilder` representing the request for the first page.
+    ///
+    /// # Returns
+    ///
+    /// A `Stream` of the entities or `RequestError` variants.
+    fn paginate_odata<'a,T>(&'a self,request : reqwest::RequestBuilder) -> impl Stream<Item = Result<T,RequestError<E>>> + 'a
+    where Self : MaybeSync, P : Into<ODataPage<T>> + 'a, E : 'a, T : 'a {
+        let template = request.try_clone().and_then(|request| request.build().ok());
+        let pages = futures_util::stream::unfold((Some(request),template),move |(request,template)| async move {
+            let response = match self.request_raw(request?).await {
+                Ok(response) => response,
+                Err(error) => return Some((Err(error),(None,None)))
+            };
+
+            let url = response.meta.url.clone();
+            let page = prepare_response(self,response).and_then(|response| parse_response::<P,E>(&response)).map(P::into);
+            let next = page.as_ref().ok()
+                .and_then(|page : &ODataPage<T>| page.next_link.as_deref())
+                .and_then(|link| url.join(link).ok())
+                .zip(template.as_ref())
+                .map(|(url,template)| next_page(self.client(),template,url));
+            Some((page,(next,template)))
+        });
+
+        flatten_pages(pages,|page : ODataPage<T>| page.value)
+    }
+
+    /// Long-polls an endpoint holding requests open until updates are available, such as the `getUpdates` method of
+    /// Telegram, yielding the items of every response as they arrive.
+    ///
+    /// Each request is built from the cursor, which `advance` moves past the items of every response, so no update is
+    /// received twice. Requests that end without updates are sent again without surfacing anything : timeouts, `204 No
+    /// Content` responses and connections dropped while idle, after a one second pause for the latter. The client timeout
+    /// must be longer than the timeout the server holds requests for, otherwise every poll times out. Other errors are
+    /// handled like in [RequestHandler::request_map] and end the stream.
+    ///
+    /// ```rust ignore
+    /// let updates = api_client.long_poll(
+    ///     0,
+    ///     |offset| api_client.default_get_query_requestor("getUpdates",&[("offset",*offset),("timeout",50)]),
+    ///     |page : &UpdatesPage,offset| page.result.last().map_or(offset,|update| update.update_id + 1),
+    ///     |page : UpdatesPage| page.result,
+    /// );
+    /// futures_util::pin_mut!(updates);
+    /// while let Some(update) = updates.next().await {
+    ///     println!("{:?}",update?);
+    /// }
+    /// ```
+    ///
+    /// Not available on `wasm32`.
+    ///
+    /// # Arguments
+    ///
+    /// * `cursor` - The cursor the first request is built from, such as an offset or the ID of the last update seen.
+    /// * `request` - A closure building the request for the given cursor.
+    /// * `advance` - A closure returning the cursor following the given response.
+    /// * `items` - A closure extracting the items out of a response.
+    ///
+    /// # Returns
+    ///
+    /// A `Stream` of the items or `RequestError` variants.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn long_poll<'a,C,T,I>(&'a self,cursor : C,request : impl FnMut(&C) -> reqwest::RequestBuilder + 'a,advance : impl FnMut(&P,C) -> C + 'a,items : impl FnMut(P) -> I + 'a) -> impl Stream<Item = Result<T,RequestError<E>>> + 'a
+    where Self : MaybeSync, I : IntoIterator<Item = T> + 'a, C : 'a, P : 'a, E : 'a, T : 'a {
+        let pages = futures_util::stream::unfold(Some((cursor,request,advance)),move |state| async move {
+            let (cursor,mut request,mut advance) = state?;
+            loop {
+                let page = match self.request_raw(request(&cursor)).await {
+                    Ok(response) if response.meta.status == reqwest::StatusCode::NO_CONTENT => continue,
+                    Ok(response) => prepare_response(self,response).and_then(|response| parse_response(&response)),
+                    Err(RequestError::Timeout) => continue,
+                    Err(RequestError::RequestError(error)) if !error.is_connect() && error.status().is_none() => {
+                        tokio::time::sleep(RECONNECT_DELAY).await;
+                        continue;
+                    },
+                    Err(error) => Err(error)
+                };
+
+                return match page {
+                    Ok(page) => {
+                        let cursor = advance(&page,cursor);
+                        Some((Ok(page),Some((cursor,request,advance))))
+                    },
+                    Err(error) => Some((Err(error),None))
+                };
+            }
+        });
+
+        flatten_pages(pages,items)
+    }
+}
+
+/// Builds the request for the page at `url` with the method and headers of the request for the first page.
+///
+/// Like redirects, pages of another origin than the first one are requested without the credentials : the `Authorization`,
+/// `Proxy-Authorization` and `Cookie` headers, the API key headers and the headers marked as sensitive are dropped.
+fn next_page(client : &reqwest::Client,template : &reqwest::Request,url : reqwest::Url) -> reqwest::RequestBuilder {
+    let headers = match url.origin() == template.url().origin() {
+        true => template.headers().clone(),
+        false => template.headers().iter()
+            .filter(|(name,value)| !value.is_sensitive() && !logging::is_redacted(&logging::REDACTED_HEADERS,name.as_str()))
+            .map(|(name,value)| (name.clone(),value.clone()))
+            .collect()
+    };
+    client.request(template.method().clone(),url).headers(headers)
+}
+
+/// Flattens a stream of pages into a stream of their items.
+fn flatten_pages<P,T,I : IntoIterator<Item = T>,E>(pages : impl Stream<Item = Result<P,RequestError<E>>>,mut items : impl FnMut(P) -> I) -> impl Stream<Item = Result<T,RequestError<E>>> {
+    pages.flat_map(move |page| match page {
+        Ok(page) => futures_util::stream::iter(items(page).into_iter().map(Ok)).left_stream(),
+        Err(error) => futures_util::stream::once(std::future::ready(Err(error))).right_stream()
+    })
+}
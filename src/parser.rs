@@ -0,0 +1,222 @@
+use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
+
+use crate::{RawResponse, RequestError};
+
+/// The size in bytes from which [JsonParser] deserializes bodies with `simd-json`, smaller ones gaining nothing from it.
+#[cfg(feature = "simd")]
+pub const SIMD_THRESHOLD : usize = 4 * 1024;
+
+/// The format response bodies are deserialized from.
+///
+/// [crate::RequestHandler::request_map_with] sends requests and checks statuses the same way whatever the format, and
+/// only delegates the deserialization of the success and error bodies to the parser.
+///
+/// JSON and, with their features, XML, MessagePack and CBOR are built in. Together with [crate::RequestDefaults::accept] and
+/// [crate::RequestHandler::encode_body], implementing it is all it takes to talk to an API in another format, such as
+/// YAML with `serde_yaml` :
+///
+/// ```rust ignore
+/// struct YamlParser;
+///
+/// impl ResponseParser for YamlParser {
+///     fn parse_body<T : DeserializeOwned,E>(body : &[u8]) -> Result<T,RequestError<E>> {
+///         serde_yaml::from_slice(body).map_err(|error| RequestError::UnexpectedBody { status : StatusCode::OK, body : error.to_string() })
+///     }
+///
+///     fn parse_error_body<E : DeserializeOwned>(response : &RawResponse) -> RequestError<E> {
+///         match serde_yaml::from_slice(&response.body) {
+///             Ok(payload) => RequestError::ErrorPayload { payload, context : Box::new(response.meta.context()) },
+///             Err(_) => RequestError::UnexpectedBody { status : response.meta.status, body : String::from_utf8_lossy(&response.body).into_owned() }
+///         }
+///     }
+/// }
+///
+/// impl RequestDefaults for MyAPIClient {
+///     fn accept(&self) -> Option<&str> {
+///         Some("application/yaml")
+///     }
+/// }
+///
+/// impl RequestHandler<User,User,ApiError> for MyAPIClient {
+///     fn encode_body<B : Serialize + ?Sized>(&self,body : &B) -> Result<(Vec<u8>,&'static str),RequestError<ApiError>> {
+///         serde_yaml::to_string(body)
+///             .map(|yaml| (yaml.into_bytes(),"application/yaml"))
+///             .map_err(|error| RequestError::InvalidRequestBody(Box::new(error)))
+///     }
+/// }
+///
+/// let user = api_client.request_map_with::<YamlParser>(request,|user| user).await?;
+/// ```
+pub trait ResponseParser {
+    /// Deserializes the body of a successful response.
+    fn parse_body<T : DeserializeOwned,E>(body : &[u8]) -> Result<T,RequestError<E>>;
+
+    /// Deserializes the body of an unsuccessful response into the error payload `E`.
+    fn parse_error_body<E : DeserializeOwned>(response : &RawResponse) -> RequestError<E>;
+}
+
+/// Deserializes error payloads into a type depending on the status code of the response, as done by
+/// [crate::RequestHandler::request_map_status].
+///
+/// This lets one error type `E` hold distinct payloads for distinct statuses, instead of a single shape for every unsuccessful response :
+///
+/// ```
+/// use api_request_utils::StatusErrorMapper;
+/// use api_request_utils::reqwest::StatusCode;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct AuthError { realm : String }
+///
+/// #[derive(Deserialize)]
+/// struct ValidationError { fields : Vec<String> }
+///
+/// #[derive(Deserialize)]
+/// #[serde(untagged)]
+/// enum ApiError {
+///     Auth(AuthError),
+///     NotFound,
+///     Validation(ValidationError),
+///     Other(serde_json::Value),
+/// }
+///
+/// impl StatusErrorMapper for ApiError {
+///     fn from_status(status : StatusCode,body : &[u8]) -> Result<Self,serde_json::Error> {
+///         Ok(match status {
+///             StatusCode::UNAUTHORIZED => Self::Auth(serde_json::from_slice(body)?),
+///             StatusCode::NOT_FOUND => Self::NotFound,
+///             StatusCode::UNPROCESSABLE_ENTITY => Self::Validation(serde_json::from_slice(body)?),
+///             _ => Self::Other(serde_json::from_slice(body)?),
+///         })
+///     }
+/// }
+///
+/// let error = ApiError::from_status(StatusCode::UNPROCESSABLE_ENTITY,br#"{ "fields" : ["name"] }"#).unwrap();
+/// assert!(matches!(error,ApiError::Validation(ValidationError { fields }) if fields == ["name"]));
+/// ```
+pub trait StatusErrorMapper : DeserializeOwned {
+    /// Deserializes the JSON body of an unsuccessful response with the given status.
+    ///
+    /// # Returns
+    ///
+    /// The error payload, by default deserialized from the body whatever the status, or the deserialization error.
+    fn from_status(status : StatusCode,body : &[u8]) -> Result<Self,serde_json::Error> {
+        let _ = status;
+        serde_json::from_slice(body)
+    }
+}
+
+/// Parses JSON bodies, as done by [crate::RequestHandler::request_map].
+///
+/// With the `simd` feature, bodies of at least [SIMD_THRESHOLD] bytes are deserialized with `simd-json`, falling back to
+/// `serde_json` when it fails so the error reported is the same either way.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonParser;
+
+impl JsonParser {
+    /// Deserializes a JSON body, with `simd-json` when it is large enough and the `simd` feature is enabled.
+    fn from_slice<T : DeserializeOwned>(body : &[u8]) -> Result<T,serde_json::Error> {
+        #[cfg(feature = "simd")]
+        if body.len() >= SIMD_THRESHOLD {
+            // `simd-json` parses in place, so it works on a copy of the body
+            if let Ok(value) = simd_json::serde::from_slice(&mut body.to_vec()) {
+                return Ok(value);
+            }
+        }
+        serde_json::from_slice(body)
+    }
+}
+
+impl ResponseParser for JsonParser {
+    /// An empty body, such as the one of a `204 No Content` response, is deserialized as `null`, so it succeeds for `()`,
+    /// `Option` and `serde_json::Value` while other types fail with an explicit `invalid type: null` error.
+    fn parse_body<T : DeserializeOwned,E>(body : &[u8]) -> Result<T,RequestError<E>> {
+        match body.trim_ascii().is_empty() {
+            true => Ok(serde_json::from_value(serde_json::Value::Null)?),
+            false => Ok(Self::from_slice(body)?)
+        }
+    }
+
+    /// A body that does not match `E` (such as an HTML error page) yields [RequestError::ErrorPayloadParseFailed] rather than
+    /// [RequestError::InvalidJsonBody], so it can be told apart from a success body that failed to deserialize.
+    fn parse_error_body<E : DeserializeOwned>(response : &RawResponse) -> RequestError<E> {
+        match Self::from_slice(&response.body) {
+            Ok(payload) => RequestError::ErrorPayload { payload, context : Box::new(response.meta.context()) },
+            Err(source) => RequestError::ErrorPayloadParseFailed {
+                status : response.meta.status,
+                source,
+                body : String::from_utf8_lossy(&response.body).into_owned(),
+            }
+        }
+    }
+}
+
+/// Parses XML bodies, as done by [crate::RequestHandler::request_map_xml].
+#[cfg(feature = "xml")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct XmlParser;
+
+#[cfg(feature = "xml")]
+impl ResponseParser for XmlParser {
+    fn parse_body<T : DeserializeOwned,E>(body : &[u8]) -> Result<T,RequestError<E>> {
+        quick_xml::de::from_reader(body).map_err(RequestError::InvalidXmlBody)
+    }
+
+    /// A body that does not match `E` yields [RequestError::UnexpectedBody], keeping the body for diagnostics.
+    fn parse_error_body<E : DeserializeOwned>(response : &RawResponse) -> RequestError<E> {
+        match quick_xml::de::from_reader(response.body.as_ref()) {
+            Ok(payload) => RequestError::ErrorPayload { payload, context : Box::new(response.meta.context()) },
+            Err(_) => RequestError::UnexpectedBody {
+                status : response.meta.status,
+                body : String::from_utf8_lossy(&response.body).into_owned(),
+            }
+        }
+    }
+}
+
+/// Parses MessagePack bodies, as done by [crate::RequestHandler::request_map_msgpack].
+#[cfg(feature = "msgpack")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MessagePackParser;
+
+#[cfg(feature = "msgpack")]
+impl ResponseParser for MessagePackParser {
+    fn parse_body<T : DeserializeOwned,E>(body : &[u8]) -> Result<T,RequestError<E>> {
+        rmp_serde::from_slice(body).map_err(RequestError::InvalidMessagePackBody)
+    }
+
+    /// A body that does not match `E` yields [RequestError::UnexpectedBody], keeping the body for diagnostics.
+    fn parse_error_body<E : DeserializeOwned>(response : &RawResponse) -> RequestError<E> {
+        match rmp_serde::from_slice(&response.body) {
+            Ok(payload) => RequestError::ErrorPayload { payload, context : Box::new(response.meta.context()) },
+            Err(_) => RequestError::UnexpectedBody {
+                status : response.meta.status,
+                body : String::from_utf8_lossy(&response.body).into_owned(),
+            }
+        }
+    }
+}
+
+/// Parses CBOR bodies, as done by [crate::RequestHandler::request_map_cbor].
+#[cfg(feature = "cbor")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CborParser;
+
+#[cfg(feature = "cbor")]
+impl ResponseParser for CborParser {
+    fn parse_body<T : DeserializeOwned,E>(body : &[u8]) -> Result<T,RequestError<E>> {
+        ciborium::from_reader(body).map_err(RequestError::InvalidCborBody)
+    }
+
+    /// A body that does not match `E` yields [RequestError::UnexpectedBody], keeping the body for diagnostics.
+    fn parse_error_body<E : DeserializeOwned>(response : &RawResponse) -> RequestError<E> {
+        match ciborium::from_reader(response.body.as_ref()) {
+            Ok(payload) => RequestError::ErrorPayload { payload, context : Box::new(response.meta.context()) },
+            Err(_) => RequestError::UnexpectedBody {
+                status : response.meta.status,
+                body : String::from_utf8_lossy(&response.body).into_owned(),
+            }
+        }
+    }
+}
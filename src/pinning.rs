@@ -0,0 +1,215 @@
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use rustls::client::{ServerCertVerified, ServerCertVerifier, WebPkiVerifier};
+use rustls::{Certificate, CertificateError, ClientConfig, OwnedTrustAnchor, RootCertStore, ServerName};
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+
+use crate::hash::sha256;
+
+/// Public key pins the certificates of the API hosts must match, guarding against certificate authorities issuing
+/// certificates for them to someone else.
+///
+/// A pin is the base64 SHA-256 hash of the `SubjectPublicKeyInfo` of a certificate, in the `sha256/...` form of HPKP,
+/// which outlives certificate renewals as long as the key is kept. It can be computed from a certificate with
+/// [CertificatePins::fingerprint], or with `openssl` :
+///
+/// ```text
+/// openssl x509 -in cert.pem -pubkey -noout | openssl pkey -pubin -outform der | openssl dgst -sha256 -binary | base64
+/// ```
+///
+/// The pins are checked during the TLS handshake, after the certificate chain was verified against the built-in
+/// certificate authorities : the chain presented by a pinned host must hold a certificate, usually the leaf or an
+/// intermediate, matching one of the pins, or the connection is closed before anything is sent and the request fails with
+/// [crate::RequestError::PinMismatch]. Add a backup pin for the next key, so rotating it does not lock the client out,
+/// and restrict the pins to the hosts of the API so other hosts, such as token endpoints, are not checked :
+///
+/// ```rust ignore
+/// impl RequestInfo for MyAPIClient {
+///     fn certificate_pins() -> Option<CertificatePins> {
+///         Some(CertificatePins::new(["sha256/r/mIkG3eEpVdm+u/ko/cwxzOMo1bk4TyHIlByibiA5E=","sha256/YLh1dUR9y6Kja30RrAn7JKnbQG/uEtLMkBgFF2Fuihg="]).host("api.example.com"))
+///     }
+/// }
+///
+/// let client = MyAPIClient::default_client(); // Or `pins.apply(builder)` for clients built otherwise
+/// ```
+///
+/// The pins install a `rustls` configuration of their own in the client, which replaces the settings of a
+/// [crate::TlsConfig]. Requests answered by a custom [crate::RequestDefaults::transport] or by mocks are not checked.
+///
+/// Return it from [crate::RequestInfo::certificate_pins] to apply it to [crate::RequestInfo::default_client_builder].
+/// Requires the `pinning` feature, which enables the `rustls` backend of `reqwest`. Not available on `wasm32`, where the
+/// browser verifies certificates.
+#[derive(Debug, Clone)]
+pub struct CertificatePins {
+    pins : Vec<String>,
+    hosts : Vec<String>,
+    roots : Vec<Vec<u8>>,
+}
+
+impl CertificatePins {
+    /// Creates pins from their `sha256/...` form, the prefix being optional, checked for every host until restricted with
+    /// [CertificatePins::host].
+    pub fn new<I : IntoIterator<Item = S>,S : AsRef<str>>(pins : I) -> Self {
+        let pins = pins.into_iter().map(|pin| {
+            let pin = pin.as_ref().trim();
+            pin.strip_prefix("sha256/").unwrap_or(pin).to_owned()
+        }).collect();
+        Self { pins, hosts : Vec::new(), roots : Vec::new() }
+    }
+
+    /// Restricts the pins to the given host, such as `api.example.com`, along with the other hosts given.
+    pub fn host(mut self,host : &str) -> Self {
+        self.hosts.push(host.trim().trim_end_matches('.').to_ascii_lowercase());
+        self
+    }
+
+    /// Trusts the certificate authority of the given DER certificate along with the built-in ones, for private deployments.
+    pub fn root_certificate_der(mut self,der : impl Into<Vec<u8>>) -> Self {
+        self.roots.push(der.into());
+        self
+    }
+
+    /// Returns the pins, without their `sha256/` prefix.
+    pub fn pins(&self) -> &[String] {
+        &self.pins
+    }
+
+    /// Returns the hosts the pins are checked for, every host if empty.
+    pub fn hosts(&self) -> &[String] {
+        &self.hosts
+    }
+
+    /// Computes the pin of the public key of a DER encoded certificate, such as [crate::ResponseMeta::peer_certificate].
+    ///
+    /// # Returns
+    ///
+    /// The pin in its `sha256/...` form, or `None` if the certificate cannot be parsed.
+    pub fn fingerprint(certificate : &[u8]) -> Option<String> {
+        Some(format!("sha256/{}",BASE64.encode(sha256(subject_public_key_info(certificate)?))))
+    }
+
+    /// Makes the clients built by the given builder check the pins during the TLS handshake, trusting the certificate
+    /// authorities of `webpki-roots` and the added ones. Added certificates that cannot be parsed are left out.
+    pub fn apply(&self,builder : reqwest::ClientBuilder) -> reqwest::ClientBuilder {
+        let mut roots = RootCertStore::empty();
+        roots.add_trust_anchors(webpki_roots::TLS_SERVER_ROOTS.iter().map(|anchor| {
+            OwnedTrustAnchor::from_subject_spki_name_constraints(anchor.subject,anchor.spki,anchor.name_constraints)
+        }));
+        roots.add_parsable_certificates(&self.roots);
+        let verifier = PinningVerifier { pins : self.clone(), verifier : WebPkiVerifier::new(roots,None) };
+        let mut config = ClientConfig::builder()
+            .with_safe_defaults()
+            .with_custom_certificate_verifier(Arc::new(verifier))
+            .with_no_client_auth();
+        config.alpn_protocols = vec![b"h2".to_vec(),b"http/1.1".to_vec()];
+        builder.use_preconfigured_tls(config)
+    }
+
+    /// Returns whether the pins are checked for the given host.
+    fn applies_to(&self,host : &str) -> bool {
+        self.hosts.is_empty() || self.hosts.iter().any(|pinned| pinned.eq_ignore_ascii_case(host.trim_end_matches('.')))
+    }
+
+    /// Returns whether a certificate matches one of the pins.
+    fn matches(&self,certificate : &[u8]) -> bool {
+        subject_public_key_info(certificate).is_some_and(|key| {
+            let hash = BASE64.encode(sha256(key));
+            self.pins.contains(&hash)
+        })
+    }
+}
+
+/// The certificate of a pinned host matched none of the pins, found in the source of the connection error by
+/// [crate::RequestError::PinMismatch].
+#[derive(Debug)]
+pub(crate) struct PinMismatch {
+    pub(crate) host : String,
+    pub(crate) fingerprint : Option<String>,
+}
+
+impl std::fmt::Display for PinMismatch {
+    fn fmt(&self,f : &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f,"certificate of {} does not match the pinned public keys",self.host)
+    }
+}
+
+impl std::error::Error for PinMismatch {}
+
+impl PinMismatch {
+    /// Finds the pin mismatch an error of the client was caused by, if any.
+    pub(crate) fn find(error : &reqwest::Error) -> Option<&Self> {
+        std::error::Error::source(error).and_then(Self::find_in)
+    }
+
+    fn find_in<'a>(error : &'a (dyn std::error::Error + 'static)) -> Option<&'a Self> {
+        if let Some(mismatch) = error.downcast_ref::<Self>() {
+            return Some(mismatch);
+        }
+        // The handshake errors of `rustls` hold their cause behind an `Arc`, and `io::Error` sources skip the wrapped error
+        let inner : Option<&(dyn std::error::Error + 'static)> = match (error.downcast_ref::<rustls::Error>(),error.downcast_ref::<std::io::Error>()) {
+            (Some(rustls::Error::InvalidCertificate(CertificateError::Other(other))),_) => Some(other.as_ref()),
+            (_,Some(error)) => error.get_ref().map(|error| error as &(dyn std::error::Error + 'static)),
+            _ => None
+        };
+        inner.and_then(Self::find_in).or_else(|| error.source().and_then(Self::find_in))
+    }
+}
+
+/// Verifies the certificate chain as usual, then that it holds a pinned key.
+struct PinningVerifier {
+    pins : CertificatePins,
+    verifier : WebPkiVerifier,
+}
+
+impl ServerCertVerifier for PinningVerifier {
+    fn verify_server_cert(&self,end_entity : &Certificate,intermediates : &[Certificate],server_name : &ServerName,scts : &mut dyn Iterator<Item = &[u8]>,ocsp_response : &[u8],now : SystemTime) -> Result<ServerCertVerified,rustls::Error> {
+        let verified = self.verifier.verify_server_cert(end_entity,intermediates,server_name,scts,ocsp_response,now)?;
+        let host = match server_name {
+            ServerName::DnsName(name) => name.as_ref().to_owned(),
+            ServerName::IpAddress(address) => address.to_string(),
+            _ => return Ok(verified)
+        };
+        if !self.pins.applies_to(&host) || std::iter::once(end_entity).chain(intermediates).any(|certificate| self.pins.matches(&certificate.0)) {
+            return Ok(verified);
+        }
+        let fingerprint = CertificatePins::fingerprint(&end_entity.0);
+        Err(rustls::Error::InvalidCertificate(CertificateError::Other(Arc::new(PinMismatch { host, fingerprint }))))
+    }
+}
+
+/// Returns the encoded `SubjectPublicKeyInfo` of an X.509 certificate (RFC 5280).
+fn subject_public_key_info(certificate : &[u8]) -> Option<&[u8]> {
+    let (_,certificate,_) = element(certificate,0x30)?;
+    let (_,tbs_certificate,_) = element(certificate,0x30)?;
+    // The version is an optional explicitly tagged field
+    let mut fields = match tbs_certificate.first() {
+        Some(0xa0) => element(tbs_certificate,0xa0)?.2,
+        _ => tbs_certificate
+    };
+    // Serial number, signature algorithm, issuer, validity and subject
+    for _ in 0..5 {
+        fields = element(fields,*fields.first()?)?.2;
+    }
+    element(fields,0x30).map(|(encoded,_,_)| encoded)
+}
+
+/// Splits the DER element of the given tag at the start of `der`, returning it whole, its content and the bytes after it.
+fn element(der : &[u8],tag : u8) -> Option<(&[u8],&[u8],&[u8])> {
+    if *der.first()? != tag {
+        return None;
+    }
+    let (length,header) = match *der.get(1)? {
+        length @ 0..=0x7f => (length as usize,2),
+        0x81..=0x84 => {
+            let size = usize::from(der[1] & 0x7f);
+            let length = der.get(2..2 + size)?.iter().fold(0,|length,byte| length << 8 | usize::from(*byte));
+            (length,2 + size)
+        },
+        _ => return None
+    };
+    let end = header.checked_add(length).filter(|end| *end <= der.len())?;
+    Some((&der[..end],&der[header..end],&der[end..]))
+}
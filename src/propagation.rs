@@ -0,0 +1,92 @@
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+
+use crate::{MaybeSend, MaybeSync};
+
+/// Injects the context of the current distributed trace into outgoing requests, such as the W3C `traceparent` and
+/// `tracestate` headers, so they appear in the traces of the services they reach.
+///
+/// Return it from [crate::RequestDefaults::trace_propagator] to apply it. It is called right before every attempt is sent,
+/// inside the `request` span of the `tracing` feature, so with `tracing-opentelemetry` the injected parent is the client
+/// span of the attempt :
+///
+/// ```rust ignore
+/// use opentelemetry::global;
+/// use opentelemetry_http::HeaderInjector;
+/// use tracing_opentelemetry::OpenTelemetrySpanExt;
+///
+/// struct OpenTelemetryPropagator;
+///
+/// impl TracePropagator for OpenTelemetryPropagator {
+///     fn inject(&self,headers : &mut HeaderMap) {
+///         let context = tracing::Span::current().context();
+///         global::get_text_map_propagator(|propagator| propagator.inject_context(&context,&mut HeaderInjector(headers)));
+///     }
+/// }
+/// ```
+///
+/// [TraceContext] forwards a trace context received by a server instead.
+pub trait TracePropagator : MaybeSend + MaybeSync {
+    /// Adds the headers carrying the trace context to the request, replacing any previous ones.
+    fn inject(&self,headers : &mut HeaderMap);
+}
+
+/// A W3C trace context (https://www.w3.org/TR/trace-context/), as carried by the `traceparent` and `tracestate` headers.
+///
+/// Parse it from the headers of an incoming request with [TraceContext::from_headers] to make the requests sent on its
+/// behalf part of the same trace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceContext {
+    /// The ID of the whole trace.
+    pub trace_id : u128,
+    /// The ID of the span the requests are sent from.
+    pub parent_id : u64,
+    /// Whether the trace is sampled, meaning the caller may have recorded it.
+    pub sampled : bool,
+    /// The vendor specific `tracestate` header, if any.
+    pub trace_state : Option<String>,
+}
+
+impl TraceContext {
+    /// Parses the `traceparent` and `tracestate` headers.
+    ///
+    /// # Returns
+    ///
+    /// The trace context, or `None` if `traceparent` is missing or invalid (including all-zero IDs).
+    pub fn from_headers(headers : &HeaderMap) -> Option<Self> {
+        let traceparent = headers.get("traceparent")?.to_str().ok()?;
+        let mut parts = traceparent.trim().split('-');
+        let (version,trace_id,parent_id,flags) = (parts.next()?,parts.next()?,parts.next()?,parts.next()?);
+        let is_hex = |part : &str,length : usize| part.len() == length && part.bytes().all(|byte| matches!(byte,b'0'..=b'9' | b'a'..=b'f'));
+        // Later versions may append fields, but only the version `ff` is forbidden
+        if !is_hex(version,2) || version == "ff" || (version == "00" && parts.next().is_some()) || !is_hex(trace_id,32) || !is_hex(parent_id,16) || !is_hex(flags,2) {
+            return None;
+        }
+
+        let trace_id = u128::from_str_radix(trace_id,16).ok().filter(|id| *id != 0)?;
+        let parent_id = u64::from_str_radix(parent_id,16).ok().filter(|id| *id != 0)?;
+        let flags = u8::from_str_radix(flags,16).ok()?;
+        let trace_state = headers.get_all("tracestate").iter()
+            .filter_map(|value| value.to_str().ok())
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .collect::<Vec<_>>()
+            .join(",");
+
+        Some(Self { trace_id, parent_id, sampled : flags & 1 == 1, trace_state : Some(trace_state).filter(|state| !state.is_empty()) })
+    }
+
+    /// Formats the `traceparent` header of the context.
+    pub fn traceparent(&self) -> String {
+        format!("00-{:032x}-{:016x}-{:02x}",self.trace_id,self.parent_id,u8::from(self.sampled))
+    }
+}
+
+impl TracePropagator for TraceContext {
+    fn inject(&self,headers : &mut HeaderMap) {
+        headers.insert(HeaderName::from_static("traceparent"),self.traceparent().parse().expect("a traceparent is a valid header value"));
+        match self.trace_state.as_deref().and_then(|state| HeaderValue::from_str(state).ok()) {
+            Some(state) => headers.insert(HeaderName::from_static("tracestate"),state),
+            None => headers.remove("tracestate")
+        };
+    }
+}
@@ -0,0 +1,112 @@
+use reqwest::{ClientBuilder, NoProxy, Proxy};
+
+/// The requests a [ProxyConfig] applies to, decided by the scheme of their URL.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ProxyScope {
+    /// Every request.
+    #[default]
+    All,
+    /// Requests to `http` URLs only.
+    Http,
+    /// Requests to `https` URLs only.
+    Https,
+}
+
+/// A proxy the requests of the client are sent through, such as a corporate HTTP proxy or a SOCKS5 gateway.
+///
+/// The scheme of the proxy URL decides how it is spoken to : `http://` and `https://` for HTTP proxies, `socks5://` for
+/// SOCKS5 proxies resolving host names locally and `socks5h://` for those resolving them remotely. SOCKS5 proxies
+/// require the `socks` feature of `reqwest` to be enabled by the application.
+///
+/// ```
+/// use api_request_utils::{ProxyConfig, ProxyScope};
+///
+/// let proxy = ProxyConfig::new("http://proxy.corp.example:3128")
+///     .basic_auth("user","secret")
+///     .no_proxy(["localhost","127.0.0.1",".internal.example"]);
+/// let client = proxy.apply(reqwest::Client::builder()).unwrap().build().unwrap();
+///
+/// let https_only = ProxyConfig::new("http://proxy.corp.example:3128").scope(ProxyScope::Https);
+/// ```
+///
+/// Return it from [crate::RequestDefaults::proxy] and build the client with [crate::RequestDefaults::proxied_client_builder]
+/// to apply it. Without any, `reqwest` honours the `HTTP_PROXY`, `HTTPS_PROXY` and `NO_PROXY` environment variables.
+/// Not available on `wasm32`, where the browser decides.
+#[derive(Clone)]
+pub struct ProxyConfig {
+    url : String,
+    scope : ProxyScope,
+    credentials : Option<(String,String)>,
+    no_proxy : Vec<String>,
+}
+
+// The password is left out
+impl std::fmt::Debug for ProxyConfig {
+    fn fmt(&self,f : &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProxyConfig")
+            .field("url",&self.url)
+            .field("scope",&self.scope)
+            .field("username",&self.credentials.as_ref().map(|(username,_)| username))
+            .field("no_proxy",&self.no_proxy)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ProxyConfig {
+    /// Creates a configuration sending every request through the proxy at the given URL, such as `http://proxy:3128`
+    /// or `socks5h://127.0.0.1:1080`.
+    pub fn new(url : impl Into<String>) -> Self {
+        Self { url : url.into(), scope : ProxyScope::All, credentials : None, no_proxy : Vec::new() }
+    }
+
+    /// Restricts the proxy to the requests of the given scope, the others being sent directly.
+    pub fn scope(mut self,scope : ProxyScope) -> Self {
+        self.scope = scope;
+        self
+    }
+
+    /// Authenticates with the proxy with the given credentials, sent in a `Proxy-Authorization` header for HTTP proxies.
+    pub fn basic_auth(mut self,username : impl Into<String>,password : impl Into<String>) -> Self {
+        self.credentials = Some((username.into(),password.into()));
+        self
+    }
+
+    /// Sends the requests to the given hosts directly. Entries are host names, matching their subdomains too when they
+    /// start with `.`, IP addresses or CIDR ranges such as `10.0.0.0/8`, or `*` for every host.
+    pub fn no_proxy<I : IntoIterator<Item = S>,S : Into<String>>(mut self,hosts : I) -> Self {
+        self.no_proxy.extend(hosts.into_iter().map(Into::into));
+        self
+    }
+
+    /// Returns the URL of the proxy.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Creates the [reqwest::Proxy] of the configuration.
+    ///
+    /// # Returns
+    ///
+    /// The proxy, or an error if the URL is invalid or uses a scheme `reqwest` was compiled without.
+    pub fn to_proxy(&self) -> reqwest::Result<Proxy> {
+        let proxy = match self.scope {
+            ProxyScope::All => Proxy::all(&self.url),
+            ProxyScope::Http => Proxy::http(&self.url),
+            ProxyScope::Https => Proxy::https(&self.url)
+        }?;
+        let proxy = match &self.credentials {
+            Some((username,password)) => proxy.basic_auth(username,password),
+            None => proxy
+        };
+        Ok(proxy.no_proxy(NoProxy::from_string(&self.no_proxy.join(","))))
+    }
+
+    /// Adds the proxy to the given `ClientBuilder`.
+    ///
+    /// # Returns
+    ///
+    /// The builder, or an error if the proxy is invalid, see [ProxyConfig::to_proxy].
+    pub fn apply(&self,client_builder : ClientBuilder) -> reqwest::Result<ClientBuilder> {
+        Ok(client_builder.proxy(self.to_proxy()?))
+    }
+}
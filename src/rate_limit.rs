@@ -0,0 +1,275 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use reqwest::Url;
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+use crate::RateLimitState;
+
+/// A token-bucket rate limiter shared by every request sent through `request_map`.
+///
+/// The bucket holds up to `requests` tokens and is refilled continuously so that at most `requests` requests are
+/// sent per `per` duration. When the bucket is empty, requests wait (in the order they arrived) until a token becomes
+/// available instead of failing, which keeps clients from being answered with `429 Too Many Requests` in the first place.
+///
+/// A single bucket is shared by the whole API by default, or one bucket per endpoint with [RateLimiter::per_endpoint].
+/// With [RateLimiter::adaptive], the budget the server reports in the rate limit headers of its responses also drains
+/// the bucket, so the limiter follows quotas shared with other clients. Return it from [crate::RequestDefaults::rate_limiter] to apply it.
+#[derive(Debug)]
+pub struct RateLimiter {
+    capacity : f64,
+    refill_interval : Duration,
+    fail_fast_on_deadline : bool,
+    per_endpoint : bool,
+    routes : Vec<String>,
+    adaptive : bool,
+    reported : std::sync::Mutex<Option<RateLimitState>>,
+    pending : std::sync::Mutex<HashMap<String,(RateLimitState,Instant)>>,
+    buckets : std::sync::Mutex<HashMap<String,Arc<Mutex<Bucket>>>>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens : f64,
+    last_refill : Instant,
+    paused_until : Option<Instant>,
+}
+
+/// The permit could not be acquired before the deadline of the request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeadlineExceeded;
+
+impl RateLimiter {
+    /// Creates a rate limiter allowing `requests` requests per `per` duration, starting with a full bucket.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `requests` is `0`.
+    pub fn new(requests : u32,per : Duration) -> Self {
+        assert!(requests > 0,"a rate limiter must allow at least one request");
+        Self {
+            capacity : f64::from(requests),
+            refill_interval : per / requests,
+            fail_fast_on_deadline : false,
+            per_endpoint : false,
+            routes : Vec::new(),
+            adaptive : false,
+            reported : std::sync::Mutex::new(None),
+            pending : std::sync::Mutex::new(HashMap::new()),
+            buckets : std::sync::Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Creates a rate limiter allowing `requests` requests per second.
+    pub fn per_second(requests : u32) -> Self {
+        Self::new(requests,Duration::from_secs(1))
+    }
+
+    /// Creates a rate limiter allowing `requests` requests per minute.
+    pub fn per_minute(requests : u32) -> Self {
+        Self::new(requests,Duration::from_secs(60))
+    }
+
+    /// Makes the limiter aware of per-request deadlines.
+    ///
+    /// When enabled, a request carrying a timeout (see [reqwest::RequestBuilder::timeout]) fails immediately with
+    /// [crate::RequestError::PermitTimeout] if waiting for a permit would exceed that timeout, rather than queuing behind a
+    /// saturated limiter. The time spent waiting for the permit is also deducted from the timeout of the request, so the
+    /// timeout acts as a deadline for the whole operation. Disabled by default.
+    pub fn fail_fast_on_deadline(mut self,enabled : bool) -> Self {
+        self.fail_fast_on_deadline = enabled;
+        self
+    }
+
+    /// Gives every endpoint (URL without its query) its own bucket of `requests` per `per` duration, for APIs whose quotas
+    /// apply per endpoint rather than to the whole API. Disabled by default.
+    ///
+    /// Buckets left untouched until they are full again are forgotten, so the endpoints seen do not pile up.
+    pub fn per_endpoint(mut self,enabled : bool) -> Self {
+        self.per_endpoint = enabled;
+        self
+    }
+
+    /// Gives the endpoints matching a path template, such as `users/{id}`, a single bucket when [RateLimiter::per_endpoint]
+    /// is enabled, instead of one bucket per user. The `{name}` placeholders match any segment, and the template matches
+    /// the paths ending with its segments, so it is relative to the base URL.
+    ///
+    /// ```rust
+    /// # use std::time::Duration;
+    /// # use api_request_utils::reqwest::Url;
+    /// # use api_request_utils::{DeadlineExceeded, RateLimiter};
+    /// #[tokio::main(flavor = "current_thread")]
+    /// async fn main() {
+    ///     let limiter = RateLimiter::new(1,Duration::from_secs(3600)).per_endpoint(true).route("users/{id}");
+    ///     let user = |id : u32| Url::parse(&format!("https://api.example.com/v1/users/{id}")).unwrap();
+    ///
+    ///     assert!(limiter.acquire_for(&user(1),Some(Duration::ZERO)).await.is_ok());
+    ///     assert_eq!(limiter.acquire_for(&user(2),Some(Duration::ZERO)).await,Err(DeadlineExceeded));
+    ///     assert!(limiter.acquire_for(&Url::parse("https://api.example.com/v1/orders").unwrap(),Some(Duration::ZERO)).await.is_ok());
+    /// }
+    /// ```
+    pub fn route(mut self,template : impl Into<String>) -> Self {
+        self.routes.push(template.into());
+        self
+    }
+
+    /// Tunes the limiter to the budget the server reports in the rate limit headers of its responses, see
+    /// [RateLimitState]. Disabled by default.
+    ///
+    /// When enabled, the bucket never holds more tokens than the requests the server says are remaining, and once none
+    /// are, requests wait for the reset of the window, after which the bucket is full again.
+    pub fn adaptive(mut self,enabled : bool) -> Self {
+        self.adaptive = enabled;
+        self
+    }
+
+    /// Returns the budget the server reported in the last response with rate limit headers, received by any request
+    /// sent through the limiter.
+    pub fn rate_limit_state(&self) -> Option<RateLimitState> {
+        *self.reported.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Returns whether the limiter fails fast when a permit cannot be acquired before the deadline of a request.
+    pub fn is_deadline_aware(&self) -> bool {
+        self.fail_fast_on_deadline
+    }
+
+    /// Waits until a permit of the shared bucket is available and consumes it.
+    pub async fn acquire(&self) {
+        // Without a deadline a permit is always acquired.
+        let _ = self.acquire_within(None).await;
+    }
+
+    /// Waits until a permit of the shared bucket is available and consumes it, giving up if that would take longer than `deadline`.
+    ///
+    /// # Arguments
+    ///
+    /// * `deadline` - The maximum time to wait for a permit, `None` to wait as long as needed.
+    ///
+    /// # Returns
+    ///
+    /// The time spent waiting, or [DeadlineExceeded] if no permit could be acquired in time. No permit is consumed in that case.
+    pub async fn acquire_within(&self,deadline : Option<Duration>) -> Result<Duration,DeadlineExceeded> {
+        self.take(String::new(),deadline).await
+    }
+
+    /// Waits until a permit for the given URL is available and consumes it, giving up if that would take longer than `deadline`.
+    ///
+    /// The permit is taken from the bucket of the endpoint when [RateLimiter::per_endpoint] is enabled, otherwise from the shared bucket.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The URL the request is sent to.
+    /// * `deadline` - The maximum time to wait for a permit, `None` to wait as long as needed.
+    ///
+    /// # Returns
+    ///
+    /// The time spent waiting, or [DeadlineExceeded] if no permit could be acquired in time. No permit is consumed in that case.
+    pub async fn acquire_for(&self,url : &Url,deadline : Option<Duration>) -> Result<Duration,DeadlineExceeded> {
+        self.take(self.key(url),deadline).await
+    }
+
+    /// Records the budget reported by a response to a request for the given URL, applied to its bucket by the next
+    /// request when adaptive.
+    pub(crate) fn observe(&self,url : &Url,state : RateLimitState) {
+        *self.reported.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(state);
+        if self.adaptive {
+            // The bucket may be locked by a request waiting for a token, so the budget is not applied right away
+            self.pending.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).insert(self.key(url),(state,Instant::now()));
+        }
+    }
+
+    /// Applies the last budget reported for the bucket stored under `key`, if any.
+    fn apply_reported(&self,key : &str,bucket : &mut Bucket) {
+        let Some((state,reported_at)) = self.pending.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).remove(key) else {
+            return
+        };
+        if let Some(remaining) = state.remaining {
+            bucket.tokens = bucket.tokens.min(remaining as f64);
+            if let (0,Some(reset)) = (remaining,state.reset) {
+                bucket.paused_until = Some(reported_at + reset);
+            }
+        }
+    }
+
+    /// Returns the key of the bucket of the given URL.
+    fn key(&self,url : &Url) -> String {
+        match self.per_endpoint {
+            true => crate::endpoint::endpoint_key(url,&self.routes),
+            false => String::new()
+        }
+    }
+
+    /// Returns the bucket stored under `key`, creating a full one if needed.
+    ///
+    /// Creating a bucket first drops the idle ones, which would be full by now and are neither in use nor paused.
+    fn bucket(&self,key : String) -> Arc<Mutex<Bucket>> {
+        let mut buckets = self.buckets.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if !buckets.contains_key(&key) {
+            let now = Instant::now();
+            let mut pending = self.pending.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            // A reported budget only matters until the window of the server resets
+            let resetting = |(state,reported_at) : &(RateLimitState,Instant)| state.reset.is_some_and(|reset| reported_at.checked_add(reset).is_none_or(|until| now < until));
+            buckets.retain(|key,bucket| Arc::strong_count(bucket) > 1 || pending.get(key).is_some_and(resetting) || match bucket.try_lock() {
+                Ok(bucket) => bucket.paused_until.is_some_and(|until| now < until)
+                    || bucket.tokens + now.duration_since(bucket.last_refill).as_secs_f64() / self.refill_interval.as_secs_f64() < self.capacity,
+                Err(_) => true
+            });
+            pending.retain(|key,reported| buckets.contains_key(key) || resetting(reported));
+        }
+        buckets.entry(key).or_insert_with(|| Arc::new(Mutex::new(Bucket {
+            tokens : self.capacity,
+            last_refill : Instant::now(),
+            paused_until : None,
+        }))).clone()
+    }
+
+    /// Consumes a permit of the bucket stored under `key`, waiting at most `deadline` for it.
+    async fn take(&self,key : String,deadline : Option<Duration>) -> Result<Duration,DeadlineExceeded> {
+        let started = Instant::now();
+        let bucket = self.bucket(key.clone());
+        let mut bucket = match deadline {
+            Some(deadline) => tokio::time::timeout(deadline,bucket.lock()).await.map_err(|_| DeadlineExceeded)?,
+            None => bucket.lock().await
+        };
+
+        // A budget reported while waiting may pause the bucket again
+        loop {
+            self.apply_reported(&key,&mut bucket);
+            let Some(wait) = self.refill(&mut bucket) else {
+                break
+            };
+            if deadline.is_some_and(|deadline| started.elapsed() + wait > deadline) {
+                return Err(DeadlineExceeded);
+            }
+            tokio::time::sleep(wait).await;
+        }
+        bucket.tokens -= 1.0;
+        Ok(started.elapsed())
+    }
+
+    /// Adds the tokens accumulated since the last refill, returning how long to wait for the next token if the bucket is empty.
+    ///
+    /// A bucket paused until the reset of the window of the server is full again once it is reached.
+    fn refill(&self,bucket : &mut Bucket) -> Option<Duration> {
+        let now = Instant::now();
+        if let Some(until) = bucket.paused_until {
+            if now < until {
+                return Some(until - now);
+            }
+            bucket.paused_until = None;
+            bucket.tokens = self.capacity;
+            bucket.last_refill = now;
+        }
+        let accumulated = now.duration_since(bucket.last_refill).as_secs_f64() / self.refill_interval.as_secs_f64();
+        bucket.tokens = (bucket.tokens + accumulated).min(self.capacity);
+        bucket.last_refill = now;
+
+        match bucket.tokens >= 1.0 {
+            true => None,
+            false => Some(self.refill_interval.mul_f64(1.0 - bucket.tokens))
+        }
+    }
+}
@@ -0,0 +1,324 @@
+use std::time::{Duration, SystemTime};
+
+use bytes::Bytes;
+
+use reqwest::{Method, StatusCode, Url, Version};
+use reqwest::header::{HeaderMap, LINK, RETRY_AFTER};
+
+/// Metadata of a received response : everything but its body.
+#[derive(Debug, Clone)]
+pub struct ResponseMeta {
+    /// The method of the request the response answers.
+    pub method : Method,
+    /// The status code of the response.
+    pub status : StatusCode,
+    /// The headers of the response.
+    pub headers : HeaderMap,
+    /// The final URL of the response, after following redirects.
+    pub url : Url,
+    /// The HTTP version of the response.
+    pub version : Version,
+    /// The DER encoded certificate presented by the server, when the client was built with
+    /// [reqwest::ClientBuilder::tls_info] enabled and the connection used TLS. Always `None` on `wasm32`.
+    pub peer_certificate : Option<Vec<u8>>,
+    /// The correlation ID the request was sent with, see [crate::RequestDefaults::correlation_header]. `None` if it had none.
+    pub correlation_id : Option<String>,
+    /// Whether the response was served from the [crate::RequestDefaults::response_cache]. `None` if it was not looked up in one.
+    pub cache : Option<CacheStatus>,
+}
+
+/// How a response relates to the [crate::RequestDefaults::response_cache], see [ResponseMeta::cache].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CacheStatus {
+    /// The response was served from the cache without any network I/O.
+    Hit,
+    /// The response was not in the cache and was received from the network.
+    Miss,
+    /// The cached response expired, and the server confirmed it is still valid by answering a conditional request with
+    /// `304 Not Modified`.
+    Revalidated,
+    /// The cached response expired and was served while it is refreshed in the background, see
+    /// [crate::ResponseCache::stale_while_revalidate].
+    Stale,
+}
+
+impl ResponseMeta {
+    /// Captures the metadata of the given response to a request sent with `method`.
+    pub fn from_response(method : Method,response : &reqwest::Response) -> Self {
+        Self {
+            method,
+            status : response.status(),
+            headers : response.headers().clone(),
+            url : response.url().clone(),
+            #[cfg(not(target_arch = "wasm32"))]
+            version : response.version(),
+            // The fetch API does not expose the negotiated version.
+            #[cfg(target_arch = "wasm32")]
+            version : Version::default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            peer_certificate : response.extensions().get::<reqwest::tls::TlsInfo>().and_then(|info| info.peer_certificate()).map(<[u8]>::to_vec),
+            #[cfg(target_arch = "wasm32")]
+            peer_certificate : None,
+            correlation_id : None,
+            cache : None,
+        }
+    }
+
+    /// Returns the negotiated protocol as its ALPN identifier, such as `http/1.1`, `h2` or `h3`.
+    ///
+    /// reqwest does not expose the negotiated TLS version itself, see [ResponseMeta::peer_certificate] for the TLS details available.
+    ///
+    /// # Returns
+    ///
+    /// The protocol identifier, or `None` if it is unknown, which is always the case on `wasm32`.
+    pub fn protocol(&self) -> Option<&'static str> {
+        if cfg!(target_arch = "wasm32") {
+            return None;
+        }
+
+        match self.version {
+            Version::HTTP_09 => Some("http/0.9"),
+            Version::HTTP_10 => Some("http/1.0"),
+            Version::HTTP_11 => Some("http/1.1"),
+            Version::HTTP_2 => Some("h2"),
+            Version::HTTP_3 => Some("h3"),
+            _ => None
+        }
+    }
+
+    /// Returns the method, URL, status and correlation ID of the exchange, as attached to errors.
+    pub fn context(&self) -> RequestContext {
+        RequestContext {
+            method : self.method.clone(),
+            url : self.url.clone(),
+            status : self.status,
+            correlation_id : self.correlation_id.clone(),
+        }
+    }
+
+    /// Parses the `Retry-After` header, given either as a number of seconds or as an HTTP date.
+    ///
+    /// # Returns
+    ///
+    /// How long to wait before retrying, or `None` if the header is missing or invalid. Dates in the past yield a zero duration.
+    pub fn retry_after(&self) -> Option<Duration> {
+        let value = self.headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+        match value.parse::<u64>() {
+            Ok(seconds) => Some(Duration::from_secs(seconds)),
+            Err(_) => {
+                let date = httpdate::parse_http_date(value).ok()?;
+                Some(date.duration_since(SystemTime::now()).unwrap_or_default())
+            }
+        }
+    }
+
+    /// Parses the rate limit headers of the response, see [RateLimitState::from_headers].
+    ///
+    /// # Returns
+    ///
+    /// The budget the server reported, or `None` if the response has no rate limit headers.
+    pub fn rate_limit_state(&self) -> Option<RateLimitState> {
+        RateLimitState::from_headers(&self.headers)
+    }
+
+    /// Finds the target of the link with the given relation in the `Link` headers (RFC 8288, formerly RFC 5988), such as
+    /// the `next` page of GitHub-style paginated APIs.
+    ///
+    /// ```
+    /// use api_request_utils::ResponseMeta;
+    /// use api_request_utils::reqwest::{Method, StatusCode, Url, Version, header::{HeaderMap, HeaderValue, LINK}};
+    ///
+    /// let mut headers = HeaderMap::new();
+    /// headers.insert(LINK,HeaderValue::from_static(r#"<https://api.example.com/users?page=2>; rel="next", </users?page=5>; rel="last""#));
+    /// let meta = ResponseMeta {
+    ///     method : Method::GET,
+    ///     status : StatusCode::OK,
+    ///     headers,
+    ///     url : Url::parse("https://api.example.com/users").unwrap(),
+    ///     version : Version::HTTP_11,
+    ///     peer_certificate : None,
+    ///     correlation_id : None,
+    ///     cache : None,
+    /// };
+    ///
+    /// assert_eq!(meta.link("next").unwrap().as_str(),"https://api.example.com/users?page=2");
+    /// assert_eq!(meta.link("last").unwrap().as_str(),"https://api.example.com/users?page=5");
+    /// assert!(meta.link("prev").is_none());
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `rel` - The relation type to look for, compared case-insensitively.
+    ///
+    /// # Returns
+    ///
+    /// The target of the first matching link, resolved against the URL of the response, or `None` if there is none.
+    pub fn link(&self,rel : &str) -> Option<Url> {
+        let has_rel = |params : &str| params.split(';').filter_map(|param| param.split_once('=')).any(|(key,value)| {
+            key.trim().eq_ignore_ascii_case("rel") && value.trim().trim_end_matches(',').trim().trim_matches('"').split_whitespace().any(|value| value.eq_ignore_ascii_case(rel))
+        });
+
+        self.headers.get_all(LINK).iter()
+            .filter_map(|value| value.to_str().ok())
+            // '<' cannot appear unencoded in a URL, so it reliably starts every link
+            .flat_map(|value| value.split('<').skip(1))
+            .filter_map(|link| link.split_once('>'))
+            .find(|(_,params)| has_rel(params))
+            .and_then(|(target,_)| self.url.join(target.trim()).ok())
+    }
+}
+
+/// The rate limit budget a server reported in the headers of a response, see [ResponseMeta::rate_limit_state].
+///
+/// ```
+/// use std::time::Duration;
+/// use api_request_utils::RateLimitState;
+/// use api_request_utils::reqwest::header::{HeaderMap, HeaderValue};
+///
+/// let mut headers = HeaderMap::new();
+/// headers.insert("x-ratelimit-limit",HeaderValue::from_static("5000"));
+/// headers.insert("x-ratelimit-remaining",HeaderValue::from_static("4987"));
+/// headers.insert("x-ratelimit-reset",HeaderValue::from_static("60"));
+///
+/// let state = RateLimitState::from_headers(&headers).unwrap();
+/// assert_eq!(state.limit,Some(5000));
+/// assert_eq!(state.remaining,Some(4987));
+/// assert_eq!(state.reset,Some(Duration::from_secs(60)));
+///
+/// let mut headers = HeaderMap::new();
+/// headers.insert("ratelimit",HeaderValue::from_static("limit=100, remaining=0, reset=30"));
+/// assert_eq!(RateLimitState::from_headers(&headers).unwrap().remaining,Some(0));
+///
+/// // A timestamp too large to be a time is no reset
+/// let mut headers = HeaderMap::new();
+/// headers.insert("x-ratelimit-remaining",HeaderValue::from_static("0"));
+/// headers.insert("x-ratelimit-reset",HeaderValue::from_static("18446744073709551615"));
+/// assert_eq!(RateLimitState::from_headers(&headers).unwrap().reset,None);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RateLimitState {
+    /// How many requests the window allows.
+    pub limit : Option<u64>,
+    /// How many requests are left in the current window.
+    pub remaining : Option<u64>,
+    /// How long until the window resets and the budget is restored.
+    pub reset : Option<Duration>,
+}
+
+impl RateLimitState {
+    /// Parses the `X-RateLimit-Limit`, `X-RateLimit-Remaining` and `X-RateLimit-Reset` headers, or the `RateLimit-Limit`,
+    /// `RateLimit-Remaining` and `RateLimit-Reset` headers of the IETF draft, or its combined `RateLimit` header.
+    ///
+    /// The reset is a number of seconds, except for `X-RateLimit-Reset` values large enough to be a Unix timestamp, as
+    /// sent by GitHub, which are turned into the time left until then, the reset being left out for timestamps too large to be a
+    /// time. Only the first value of lists is used, such as the
+    /// `100, 100;w=60` of policies with several windows.
+    ///
+    /// # Returns
+    ///
+    /// The reported budget, or `None` if none of the headers is present and valid.
+    pub fn from_headers(headers : &HeaderMap) -> Option<Self> {
+        let number = |name : &str| headers.get(name)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.split([',',';']).next()?.trim().parse::<u64>().ok());
+
+        let mut state = Self {
+            limit : number("x-ratelimit-limit").or_else(|| number("ratelimit-limit")),
+            remaining : number("x-ratelimit-remaining").or_else(|| number("ratelimit-remaining")),
+            reset : number("x-ratelimit-reset").and_then(reset_from).or_else(|| number("ratelimit-reset").map(Duration::from_secs)),
+        };
+
+        // The combined field of the draft, either `limit=100, remaining=50, reset=30` or `"default";r=50;t=30`, the first
+        // value of every parameter being used
+        if let Some(value) = headers.get("ratelimit").and_then(|value| value.to_str().ok()) {
+            for (key,value) in value.split([',',';']).filter_map(|param| param.split_once('=')) {
+                let Ok(value) = value.trim().trim_matches('"').parse::<u64>() else { continue };
+                match key.trim().to_ascii_lowercase().as_str() {
+                    "limit" => state.limit = state.limit.or(Some(value)),
+                    "remaining" | "r" => state.remaining = state.remaining.or(Some(value)),
+                    "reset" | "t" => state.reset = state.reset.or(Some(Duration::from_secs(value))),
+                    _ => {}
+                }
+            }
+        }
+
+        (state.limit.is_some() || state.remaining.is_some() || state.reset.is_some()).then_some(state)
+    }
+}
+
+/// Turns an `X-RateLimit-Reset` value into the time left, treating values past 2001 as Unix timestamps.
+///
+/// # Returns
+///
+/// The time left, or `None` for a timestamp too large to be a time.
+fn reset_from(value : u64) -> Option<Duration> {
+    match value >= 1_000_000_000 {
+        true => SystemTime::UNIX_EPOCH.checked_add(Duration::from_secs(value)).map(|reset| reset.duration_since(SystemTime::now()).unwrap_or_default()),
+        false => Some(Duration::from_secs(value))
+    }
+}
+
+/// Identifies the exchange an error happened in : the request and the status it was answered with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestContext {
+    /// The method of the request.
+    pub method : Method,
+    /// The final URL of the response, after following redirects.
+    pub url : Url,
+    /// The status code of the response.
+    pub status : StatusCode,
+    /// The correlation ID the request was sent with, if any.
+    pub correlation_id : Option<String>,
+}
+
+impl std::fmt::Display for RequestContext {
+    fn fmt(&self,f : &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f,"{} {} ({})",self.method,self.url,self.status)?;
+        match &self.correlation_id {
+            Some(id) => write!(f," [{id}]"),
+            None => Ok(())
+        }
+    }
+}
+
+/// A fully read response whose body has not been deserialized yet.
+#[derive(Debug, Clone)]
+pub struct RawResponse {
+    /// The metadata of the response.
+    pub meta : ResponseMeta,
+    /// The body of the response.
+    pub body : Bytes,
+}
+
+impl RawResponse {
+    /// Deserializes the JSON body into a type borrowing its strings and byte slices from it, rather than allocating them.
+    ///
+    /// ```
+    /// use std::borrow::Cow;
+    /// # use api_request_utils::{RawResponse, ResponseMeta};
+    /// # use api_request_utils::reqwest::{Method, StatusCode, Url, Version, header::HeaderMap};
+    ///
+    /// #[derive(serde::Deserialize)]
+    /// struct User<'a> {
+    ///     login : &'a str,
+    ///     #[serde(borrow)]
+    ///     bio : Cow<'a,str>,
+    /// }
+    ///
+    /// # let meta = ResponseMeta { method : Method::GET, status : StatusCode::OK, headers : HeaderMap::new(), url : Url::parse("https://api.example.com/users/1").unwrap(), version : Version::HTTP_11, peer_certificate : None, correlation_id : None, cache : None };
+    /// let response = RawResponse { meta, body : r#"{"login":"octocat","bio":"\"Hello\""}"#.into() };
+    /// let user : User = response.json().unwrap();
+    /// assert_eq!(user.login,"octocat");
+    /// // Escaped strings cannot be borrowed, so `Cow` falls back to an owned copy
+    /// assert_eq!(user.bio,"\"Hello\"");
+    /// ```
+    ///
+    /// Fields of type `&str` fail to deserialize from strings containing escape sequences, which `Cow<str>` handles.
+    ///
+    /// # Returns
+    ///
+    /// The deserialized value, borrowing from the response, or the deserialization error.
+    pub fn json<'de,T : serde::Deserialize<'de>>(&'de self) -> Result<T,serde_json::Error> {
+        serde_json::from_slice(&self.body)
+    }
+}
@@ -0,0 +1,172 @@
+use std::time::Duration;
+
+use reqwest::StatusCode;
+
+use crate::{MaybeSend, MaybeSync};
+
+/// How long to wait between retries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackoffStrategy {
+    /// Waits the same delay before every retry.
+    Fixed(Duration),
+    /// Doubles the delay after every retry, starting at `base` and capped at `max`.
+    Exponential {
+        /// The delay before the first retry.
+        base : Duration,
+        /// The maximum delay.
+        max : Duration,
+    },
+    /// Waits a random delay between zero and the [BackoffStrategy::Exponential] delay ("full jitter"), so clients failing
+    /// at the same time do not retry in lockstep. Requires the `jitter` feature.
+    #[cfg(feature = "jitter")]
+    ExponentialJitter {
+        /// The delay before the first retry, before jitter is applied.
+        base : Duration,
+        /// The maximum delay, before jitter is applied.
+        max : Duration,
+    },
+}
+
+impl BackoffStrategy {
+    /// Returns the time to wait before the given retry, starting at `1`.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use api_request_utils::BackoffStrategy;
+    ///
+    /// let backoff = BackoffStrategy::Exponential { base : Duration::from_millis(100), max : Duration::from_secs(1) };
+    /// assert_eq!(backoff.delay(1),Duration::from_millis(100));
+    /// assert_eq!(backoff.delay(3),Duration::from_millis(400));
+    /// assert_eq!(backoff.delay(10),Duration::from_secs(1));
+    /// ```
+    pub fn delay(&self,attempt : u32) -> Duration {
+        let exponential = |base : Duration,max : Duration| {
+            let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+            base.checked_mul(factor).map_or(max,|delay| delay.min(max))
+        };
+
+        match *self {
+            Self::Fixed(delay) => delay,
+            Self::Exponential { base, max } => exponential(base,max),
+            #[cfg(feature = "jitter")]
+            Self::ExponentialJitter { base, max } => {
+                use rand::Rng;
+                rand::thread_rng().gen_range(Duration::ZERO..=exponential(base,max))
+            }
+        }
+    }
+}
+
+/// Decides whether and when requests failing with a transient error are retried.
+///
+/// Return it from [crate::RequestDefaults::retry_policy] to apply it. [RetryConfig] covers the common cases, implement this trait
+/// for finer control, for example to retry only some endpoints or to read the delay from the error.
+pub trait RetryPolicy : MaybeSend + MaybeSync {
+    /// Returns the maximum number of retries made after the first attempt.
+    fn max_retries(&self) -> u32;
+
+    /// Returns the time waited before the given retry, starting at `1`.
+    fn delay(&self,attempt : u32) -> Duration;
+
+    /// Returns whether a response with the given status is worth retrying.
+    ///
+    /// # Returns
+    ///
+    /// By default, `true` for `429 Too Many Requests`, `500`, `502`, `503` and `504`.
+    fn is_retryable_status(&self,status : StatusCode) -> bool {
+        DEFAULT_RETRYABLE_STATUSES.contains(&status)
+    }
+
+    /// Returns whether a request that failed to be sent or received is worth retrying.
+    ///
+    /// # Returns
+    ///
+    /// By default, `true` for connection errors and timeouts.
+    fn is_retryable_error(&self,error : &reqwest::Error) -> bool {
+        error.is_connect() || error.is_timeout()
+    }
+}
+
+const DEFAULT_RETRYABLE_STATUSES : [StatusCode;5] = [
+    StatusCode::TOO_MANY_REQUESTS,
+    StatusCode::INTERNAL_SERVER_ERROR,
+    StatusCode::BAD_GATEWAY,
+    StatusCode::SERVICE_UNAVAILABLE,
+    StatusCode::GATEWAY_TIMEOUT,
+];
+
+/// Settings for retrying requests that failed with a transient error.
+///
+/// Connection errors, timeouts and the `429 Too Many Requests`, `500`, `502`, `503` and `504` statuses are retried up to
+/// `max_retries` times, waiting between attempts as decided by the [BackoffStrategy]. Requests whose body cannot be cloned (such as streamed bodies)
+/// are never retried.
+///
+/// The default configuration retries 3 times with an exponential backoff from 100 milliseconds up to 10 seconds, randomized with
+/// [BackoffStrategy::ExponentialJitter] when the `jitter` feature is enabled.
+///
+/// Return it from [crate::RequestDefaults::retry_policy] to apply it, and override [crate::RequestHandler::on_retry] to
+/// observe retries.
+#[derive(Debug, Clone)]
+pub struct RetryConfig {
+    max_retries : u32,
+    backoff : BackoffStrategy,
+    retryable_statuses : Vec<StatusCode>,
+}
+
+impl RetryConfig {
+    /// Creates a configuration retrying up to `max_retries` times, waiting `delay` between attempts.
+    pub fn new(max_retries : u32,delay : Duration) -> Self {
+        Self::with_backoff(max_retries,BackoffStrategy::Fixed(delay))
+    }
+
+    /// Creates a configuration retrying up to `max_retries` times, waiting between attempts as decided by `backoff`.
+    pub fn with_backoff(max_retries : u32,backoff : BackoffStrategy) -> Self {
+        Self { max_retries, backoff, retryable_statuses : DEFAULT_RETRYABLE_STATUSES.to_vec() }
+    }
+
+    /// Sets the strategy deciding how long to wait between attempts.
+    pub fn backoff(mut self,backoff : BackoffStrategy) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Sets the statuses that are retried, replacing the default ones.
+    ///
+    /// ```
+    /// use api_request_utils::{RetryConfig, RetryPolicy};
+    /// use reqwest::StatusCode;
+    ///
+    /// let retry = RetryConfig::default().retry_statuses([StatusCode::SERVICE_UNAVAILABLE]);
+    /// assert!(retry.is_retryable_status(StatusCode::SERVICE_UNAVAILABLE));
+    /// assert!(!retry.is_retryable_status(StatusCode::TOO_MANY_REQUESTS));
+    /// ```
+    pub fn retry_statuses(mut self,statuses : impl IntoIterator<Item = StatusCode>) -> Self {
+        self.retryable_statuses = statuses.into_iter().collect();
+        self
+    }
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        let (base,max) = (Duration::from_millis(100),Duration::from_secs(10));
+        #[cfg(feature = "jitter")]
+        let backoff = BackoffStrategy::ExponentialJitter { base, max };
+        #[cfg(not(feature = "jitter"))]
+        let backoff = BackoffStrategy::Exponential { base, max };
+        Self::with_backoff(3,backoff)
+    }
+}
+
+impl RetryPolicy for RetryConfig {
+    fn max_retries(&self) -> u32 {
+        self.max_retries
+    }
+
+    fn delay(&self,attempt : u32) -> Duration {
+        self.backoff.delay(attempt)
+    }
+
+    fn is_retryable_status(&self,status : StatusCode) -> bool {
+        self.retryable_statuses.contains(&status)
+    }
+}
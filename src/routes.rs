@@ -0,0 +1,70 @@
+use reqwest::RequestBuilder;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+
+/// Headers and query parameters added to the requests of the endpoints below given path prefixes.
+///
+/// Every route whose prefix the endpoint starts with applies, the longer prefixes last, so their headers replace those
+/// of the shorter ones and of [crate::RequestDefaults::default_headers], like the `Accept` header :
+///
+/// ```rust ignore
+/// let routes = RouteDefaults::new()
+///     .header("v2/",ACCEPT,HeaderValue::from_static("application/vnd.example.v2+json"))
+///     .header("admin/",HeaderName::from_static("x-scope"),HeaderValue::from_static("admin"))
+///     .query("search","api-version","2024-01-01");
+/// ```
+///
+/// Return it from [crate::RequestDefaults::route_defaults] to apply it to the requests built by the default requestors.
+#[derive(Debug, Clone, Default)]
+pub struct RouteDefaults {
+    routes : Vec<Route>,
+}
+
+#[derive(Debug, Clone)]
+struct Route {
+    prefix : String,
+    headers : HeaderMap,
+    query : Vec<(String,String)>,
+}
+
+impl RouteDefaults {
+    /// Creates defaults without any route.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a header sent to the endpoints starting with `prefix`, replacing any previous value for the same name.
+    pub fn header(mut self,prefix : &str,name : HeaderName,value : HeaderValue) -> Self {
+        self.route(prefix).headers.insert(name,value);
+        self
+    }
+
+    /// Adds a query parameter sent to the endpoints starting with `prefix`.
+    pub fn query(mut self,prefix : &str,name : impl Into<String>,value : impl ToString) -> Self {
+        self.route(prefix).query.push((name.into(),value.to_string()));
+        self
+    }
+
+    /// Applies the routes matching the endpoint to the given `RequestBuilder`.
+    pub fn apply(&self,endpoint : &str,request_builder : RequestBuilder) -> RequestBuilder {
+        let endpoint = endpoint.trim_start_matches('/');
+        let mut routes : Vec<&Route> = self.routes.iter().filter(|route| endpoint.starts_with(route.prefix.as_str())).collect();
+        routes.sort_by_key(|route| route.prefix.len());
+
+        routes.into_iter().fold(request_builder,|request_builder,route| match route.query.is_empty() {
+            true => request_builder.headers(route.headers.clone()),
+            false => request_builder.headers(route.headers.clone()).query(&route.query)
+        })
+    }
+
+    fn route(&mut self,prefix : &str) -> &mut Route {
+        let prefix = prefix.trim_start_matches('/');
+        let index = match self.routes.iter().position(|route| route.prefix == prefix) {
+            Some(index) => index,
+            None => {
+                self.routes.push(Route { prefix : prefix.to_owned(), headers : HeaderMap::new(), query : Vec::new() });
+                self.routes.len() - 1
+            }
+        };
+        &mut self.routes[index]
+    }
+}
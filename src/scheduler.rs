@@ -0,0 +1,179 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::future::Future;
+use std::sync::{Mutex, MutexGuard};
+
+use tokio::sync::oneshot;
+
+tokio::task_local! {
+    static PRIORITY : Priority;
+}
+
+/// The priority of requests in a [RequestScheduler].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Priority {
+    /// Background traffic, such as synchronization, sent once nothing else is waiting.
+    Low,
+    /// The priority of requests sent outside of [Priority::scope].
+    #[default]
+    Normal,
+    /// Interactive calls, sent before anything else that is waiting.
+    High,
+}
+
+impl Priority {
+    /// Runs the future with this priority, which applies to every request it sends.
+    ///
+    /// ```rust ignore
+    /// let user = Priority::High.scope(api_client.request_map(request,|user| user)).await?;
+    /// ```
+    ///
+    /// The priority is not inherited by the tasks the future spawns.
+    pub async fn scope<F : Future>(self,future : F) -> F::Output {
+        PRIORITY.scope(self,future).await
+    }
+
+    /// Returns the priority of the current [Priority::scope], [Priority::Normal] outside of any.
+    pub fn current() -> Self {
+        PRIORITY.try_with(|priority| *priority).unwrap_or_default()
+    }
+}
+
+/// Limits how many requests are in flight at once, dispatching the waiting ones by [Priority], then in the order they arrived.
+///
+/// Requests are tagged with [Priority::scope]. A request holds its slot until its response headers are received,
+/// including the time it waits for the [crate::RequestDefaults::rate_limiter], so once the limiter is saturated, the
+/// requests queue here and the higher-priority ones are sent first : with a low concurrency limit, interactive calls
+/// overtake the background traffic competing with them.
+///
+/// Return it from [crate::RequestDefaults::request_scheduler] to apply it. Not available on `wasm32`.
+#[derive(Debug)]
+pub struct RequestScheduler {
+    max_concurrency : usize,
+    state : Mutex<State>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    running : usize,
+    tickets : u64,
+    waiting : BinaryHeap<Waiter>,
+}
+
+#[derive(Debug)]
+struct Waiter {
+    priority : Priority,
+    ticket : u64,
+    wake : oneshot::Sender<()>,
+}
+
+// Higher priorities first, then earlier tickets
+impl Ord for Waiter {
+    fn cmp(&self,other : &Self) -> Ordering {
+        self.priority.cmp(&other.priority).then_with(|| other.ticket.cmp(&self.ticket))
+    }
+}
+
+impl PartialOrd for Waiter {
+    fn partial_cmp(&self,other : &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Waiter {
+    fn eq(&self,other : &Self) -> bool {
+        self.ticket == other.ticket
+    }
+}
+
+impl Eq for Waiter {}
+
+/// A slot of a [RequestScheduler], released when dropped.
+pub(crate) struct Slot<'a> {
+    scheduler : &'a RequestScheduler,
+}
+
+/// A request waiting for a slot, giving back the slot it was handed if it is cancelled before taking it.
+struct Waiting<'a> {
+    scheduler : &'a RequestScheduler,
+    woken : Option<oneshot::Receiver<()>>,
+}
+
+impl RequestScheduler {
+    /// Creates a scheduler letting at most `max_concurrency` requests in flight at once.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_concurrency` is `0`.
+    pub fn new(max_concurrency : usize) -> Self {
+        assert!(max_concurrency > 0,"a scheduler must let at least one request in flight");
+        Self { max_concurrency, state : Mutex::new(State::default()) }
+    }
+
+    /// Returns how many requests are in flight.
+    pub fn in_flight(&self) -> usize {
+        self.state().running
+    }
+
+    /// Returns how many requests are waiting for a slot.
+    pub fn queued(&self) -> usize {
+        self.state().waiting.len()
+    }
+
+    /// Waits for a slot, which the requests of higher priority waiting get first.
+    pub(crate) async fn acquire(&self,priority : Priority) -> Slot<'_> {
+        let woken = {
+            let mut state = self.state();
+            if state.running < self.max_concurrency && state.waiting.is_empty() {
+                state.running += 1;
+                return Slot { scheduler : self };
+            }
+
+            let (wake,woken) = oneshot::channel();
+            state.tickets += 1;
+            let ticket = state.tickets;
+            state.waiting.push(Waiter { priority, ticket, wake });
+            woken
+        };
+
+        let mut waiting = Waiting { scheduler : self, woken : Some(woken) };
+        if let Some(woken) = waiting.woken.as_mut() {
+            // Waiters only leave the queue once they are handed a slot
+            let _ = woken.await;
+        }
+        waiting.woken = None;
+        Slot { scheduler : self }
+    }
+
+    /// Hands the slot of a finished request to the first waiting one, or frees it if none is waiting.
+    fn release(&self) {
+        let mut state = self.state();
+        while let Some(waiter) = state.waiting.pop() {
+            if waiter.wake.send(()).is_ok() {
+                return;
+            }
+        }
+        state.running -= 1;
+    }
+
+    fn state(&self) -> MutexGuard<'_,State> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Drop for Slot<'_> {
+    fn drop(&mut self) {
+        self.scheduler.release();
+    }
+}
+
+impl Drop for Waiting<'_> {
+    fn drop(&mut self) {
+        if let Some(mut woken) = self.woken.take() {
+            woken.close();
+            if woken.try_recv().is_ok() {
+                self.scheduler.release();
+            }
+        }
+    }
+}
@@ -0,0 +1,223 @@
+use std::fmt;
+
+use serde_json::Value;
+
+use crate::{MaybeSend, MaybeSync, ResponseMeta};
+
+/// A place where a response body does not match its schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaViolation {
+    /// The JSON Pointer to the offending value, empty for the whole body.
+    pub path : String,
+    /// The description of the violation.
+    pub message : String,
+}
+
+impl fmt::Display for SchemaViolation {
+    fn fmt(&self,f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.path.is_empty() {
+            true => write!(f,"{}",self.message),
+            false => write!(f,"{} : {}",self.path,self.message)
+        }
+    }
+}
+
+/// Validates the bodies of successful responses before they are deserialized.
+///
+/// Return it from [crate::RequestDefaults::response_validator] to apply it. Responses with violations fail with
+/// [crate::RequestError::SchemaViolation], which catches changes to third-party APIs early, typically in integration tests.
+/// [SchemaValidator] validates against JSON Schemas, implement this trait to plug in another validator instead.
+///
+/// Validators apply to the same handlers as the [crate::Envelope], the whole body being validated before the envelope is
+/// unwrapped, and only to JSON bodies.
+pub trait ResponseValidator : MaybeSend + MaybeSync {
+    /// Returns the violations of the given body, received in the response described by `meta`.
+    fn validate(&self,meta : &ResponseMeta,body : &Value) -> Vec<SchemaViolation>;
+}
+
+/// Validates responses against a JSON Schema per endpoint.
+///
+/// The keywords `type`, `enum`, `const`, `properties`, `required`, `additionalProperties`, `items`, `minItems`, `maxItems`,
+/// `minLength`, `maxLength`, `minimum`, `maximum`, `exclusiveMinimum`, `exclusiveMaximum`, `allOf`, `anyOf`, `oneOf` and `not`
+/// are checked, while the others (such as `$ref`, `format` or `pattern`) are ignored. Responses of endpoints without a schema
+/// are not validated.
+///
+/// ```
+/// use api_request_utils::SchemaValidator;
+/// use serde_json::json;
+///
+/// let validator = SchemaValidator::new().endpoint("users/{id}",json!({
+///     "type" : "object",
+///     "required" : ["id","name"],
+///     "properties" : { "id" : { "type" : "integer" }, "name" : { "type" : "string" } }
+/// }));
+///
+/// let schema = validator.schema_for("/v1/users/42").unwrap();
+/// let violations = SchemaValidator::validate_value(schema,&json!({ "id" : "42" }));
+/// assert_eq!(violations.len(),2);
+/// assert_eq!(violations[0].to_string(),"missing required property `name`");
+/// assert_eq!(violations[1].to_string(),"/id : expected \"integer\", found string");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SchemaValidator {
+    schemas : Vec<(String,Value)>,
+}
+
+impl SchemaValidator {
+    /// Creates a validator without any schema.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Validates the responses of the given endpoint against the given schema.
+    ///
+    /// The endpoint matches the end of the path of the response URL segment by segment, where segments written as `*` or
+    /// `{name}` match any segment. The first matching endpoint is used.
+    pub fn endpoint(mut self,endpoint : impl Into<String>,schema : Value) -> Self {
+        self.schemas.push((endpoint.into(),schema));
+        self
+    }
+
+    /// Returns the schema of the first endpoint matching the given URL path, if any.
+    pub fn schema_for(&self,path : &str) -> Option<&Value> {
+        let path : Vec<&str> = path.split('/').filter(|segment| !segment.is_empty()).collect();
+        self.schemas.iter().find(|(endpoint,_)| {
+            let endpoint : Vec<&str> = endpoint.split('/').filter(|segment| !segment.is_empty()).collect();
+            endpoint.len() <= path.len() && endpoint.iter().rev().zip(path.iter().rev()).all(|(expected,segment)| {
+                *expected == "*" || (expected.starts_with('{') && expected.ends_with('}')) || expected == segment
+            })
+        }).map(|(_,schema)| schema)
+    }
+
+    /// Returns the violations of the given value against the given schema.
+    pub fn validate_value(schema : &Value,value : &Value) -> Vec<SchemaViolation> {
+        let mut violations = Vec::new();
+        check(schema,value,"",&mut violations);
+        violations
+    }
+}
+
+impl ResponseValidator for SchemaValidator {
+    fn validate(&self,meta : &ResponseMeta,body : &Value) -> Vec<SchemaViolation> {
+        match self.schema_for(meta.url.path()) {
+            Some(schema) => Self::validate_value(schema,body),
+            None => Vec::new()
+        }
+    }
+}
+
+fn check(schema : &Value,value : &Value,path : &str,violations : &mut Vec<SchemaViolation>) {
+    let violation = |violations : &mut Vec<SchemaViolation>,message : String| violations.push(SchemaViolation { path : path.to_owned(), message });
+    let schema = match schema {
+        Value::Bool(true) => return,
+        Value::Bool(false) => return violation(violations,"no value is allowed".to_owned()),
+        Value::Object(schema) => schema,
+        _ => return
+    };
+
+    if let Some(expected) = schema.get("type") {
+        let matches = match expected {
+            Value::String(expected) => has_type(value,expected),
+            Value::Array(expected) => expected.iter().filter_map(Value::as_str).any(|expected| has_type(value,expected)),
+            _ => true
+        };
+        // Other keywords are not checked against a value of the wrong type, as they would only repeat the violation
+        if !matches {
+            return violation(violations,format!("expected {expected}, found {}",type_name(value)));
+        }
+    }
+
+    if let Some(allowed) = schema.get("enum").and_then(Value::as_array) {
+        if !allowed.contains(value) {
+            violation(violations,format!("{value} is not one of {}",Value::Array(allowed.clone())));
+        }
+    }
+    if let Some(expected) = schema.get("const") {
+        if expected != value {
+            violation(violations,format!("expected {expected}, found {value}"));
+        }
+    }
+
+    match value {
+        Value::Object(object) => {
+            for name in schema.get("required").and_then(Value::as_array).into_iter().flatten().filter_map(Value::as_str) {
+                if !object.contains_key(name) {
+                    violation(violations,format!("missing required property `{name}`"));
+                }
+            }
+            let properties = schema.get("properties").and_then(Value::as_object);
+            for (name,property) in object {
+                let property_path = format!("{path}/{}",name.replace('~',"~0").replace('/',"~1"));
+                match (properties.and_then(|properties| properties.get(name)),schema.get("additionalProperties")) {
+                    (Some(property_schema),_) | (None,Some(property_schema)) => check(property_schema,property,&property_path,violations),
+                    (None,None) => {}
+                }
+            }
+        }
+        Value::Array(items) => {
+            check_bounds(schema,"minItems","maxItems",items.len(),"items",&mut |message| violation(violations,message));
+            if let Some(item_schema) = schema.get("items") {
+                for (index,item) in items.iter().enumerate() {
+                    check(item_schema,item,&format!("{path}/{index}"),violations);
+                }
+            }
+        }
+        Value::String(string) => check_bounds(schema,"minLength","maxLength",string.chars().count(),"characters",&mut |message| violation(violations,message)),
+        Value::Number(number) => {
+            let number = number.as_f64().unwrap_or_default();
+            let limit = |keyword : &str| schema.get(keyword).and_then(Value::as_f64);
+            if limit("minimum").is_some_and(|minimum| number < minimum) || limit("exclusiveMinimum").is_some_and(|minimum| number <= minimum) {
+                violation(violations,format!("{number} is below the minimum"));
+            }
+            if limit("maximum").is_some_and(|maximum| number > maximum) || limit("exclusiveMaximum").is_some_and(|maximum| number >= maximum) {
+                violation(violations,format!("{number} is above the maximum"));
+            }
+        }
+        _ => {}
+    }
+
+    let subschemas = |keyword : &str| schema.get(keyword).and_then(Value::as_array).map(Vec::as_slice);
+    for subschema in subschemas("allOf").unwrap_or_default() {
+        check(subschema,value,path,violations);
+    }
+    let matching = |subschemas : &[Value]| subschemas.iter().filter(|subschema| SchemaValidator::validate_value(subschema,value).is_empty()).count();
+    if subschemas("anyOf").is_some_and(|subschemas| matching(subschemas) == 0) {
+        violation(violations,"value matches none of the `anyOf` schemas".to_owned());
+    }
+    if subschemas("oneOf").is_some_and(|subschemas| matching(subschemas) != 1) {
+        violation(violations,"value does not match exactly one of the `oneOf` schemas".to_owned());
+    }
+    if schema.get("not").is_some_and(|subschema| SchemaValidator::validate_value(subschema,value).is_empty()) {
+        violation(violations,"value matches the `not` schema".to_owned());
+    }
+}
+
+/// Checks a length against the given minimum and maximum keywords.
+fn check_bounds(schema : &serde_json::Map<String,Value>,min : &str,max : &str,length : usize,unit : &str,violation : &mut impl FnMut(String)) {
+    let bound = |keyword : &str| schema.get(keyword).and_then(Value::as_u64).map(|bound| bound as usize);
+    if let Some(min) = bound(min).filter(|min| length < *min) {
+        violation(format!("{length} {unit}, at least {min} expected"));
+    }
+    if let Some(max) = bound(max).filter(|max| length > *max) {
+        violation(format!("{length} {unit}, at most {max} expected"));
+    }
+}
+
+fn has_type(value : &Value,expected : &str) -> bool {
+    match expected {
+        "integer" => value.as_i64().is_some() || value.as_u64().is_some() || value.as_f64().is_some_and(|number| number.fract() == 0.0),
+        "number" => value.is_number(),
+        expected => type_name(value) == expected
+    }
+}
+
+fn type_name(value : &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
@@ -0,0 +1,252 @@
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, SystemTime};
+
+use reqwest::{Method, Url};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, COOKIE, SET_COOKIE};
+
+/// A cookie stored in a [CookieJar].
+#[derive(Clone, PartialEq, Eq)]
+pub struct Cookie {
+    /// The name of the cookie.
+    pub name : String,
+    /// The value of the cookie.
+    pub value : String,
+    /// The domain the cookie is sent to, without leading `.`.
+    pub domain : String,
+    /// Whether the cookie is only sent to its exact domain, rather than to its subdomains too, as when it was set without
+    /// a `Domain` attribute.
+    pub host_only : bool,
+    /// The path the cookie is sent below.
+    pub path : String,
+    /// Whether the cookie is only sent over HTTPS.
+    pub secure : bool,
+    /// When the cookie expires, `None` for a session cookie, kept as long as the jar.
+    pub expires : Option<SystemTime>,
+}
+
+// The value is left out, as it is usually a secret
+impl std::fmt::Debug for Cookie {
+    fn fmt(&self,f : &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Cookie")
+            .field("name",&self.name)
+            .field("domain",&self.domain)
+            .field("host_only",&self.host_only)
+            .field("path",&self.path)
+            .field("secure",&self.secure)
+            .field("expires",&self.expires)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A cookie store carrying the session of cookie-authenticated APIs across requests.
+///
+/// The cookies set by the `Set-Cookie` headers of every response are stored, then sent with the following requests to
+/// the domains and paths they apply to, until they expire, like a browser would. Optionally, the value of a CSRF cookie
+/// is echoed in a header of every unsafe request, as the double submit pattern of many frameworks expects :
+///
+/// ```rust ignore
+/// let jar = CookieJar::new().csrf("XSRF-TOKEN",HeaderName::from_static("x-xsrf-token"));
+/// // Once returned from `cookie_jar`
+/// api_client.login(api_client.default_post_requestor("login",credentials_json)).await?;
+/// let profile = api_client.request_map(api_client.default_get_query_requestor("me",&()),|profile| profile).await?;
+/// jar.clear(); // Logs out locally
+/// ```
+///
+/// Only the `Set-Cookie` headers of the final response are seen, not those of the redirects `reqwest` follows : login
+/// endpoints redirecting after setting their cookie need a client built with `redirect::Policy::none()`.
+///
+/// Return it from [crate::RequestDefaults::cookie_jar] to apply it. Not available on `wasm32`, where the browser manages
+/// the cookies.
+#[derive(Default)]
+pub struct CookieJar {
+    cookies : Mutex<Vec<Cookie>>,
+    csrf : Option<(String,HeaderName)>,
+}
+
+// The cookie values are left out
+impl std::fmt::Debug for CookieJar {
+    fn fmt(&self,f : &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CookieJar")
+            .field("cookies",&*self.state())
+            .field("csrf",&self.csrf)
+            .finish()
+    }
+}
+
+impl CookieJar {
+    /// Creates an empty jar.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sends the value of the given cookie in the given header with every `POST`, `PUT`, `PATCH` and `DELETE` request.
+    pub fn csrf(mut self,cookie : impl Into<String>,header : HeaderName) -> Self {
+        self.csrf = Some((cookie.into(),header));
+        self
+    }
+
+    /// Returns the cookies that have not expired.
+    pub fn cookies(&self) -> Vec<Cookie> {
+        let mut cookies = self.state();
+        remove_expired(&mut cookies);
+        cookies.clone()
+    }
+
+    /// Returns the cookie that would be sent to the given URL with the given name, if any.
+    pub fn get(&self,url : &Url,name : &str) -> Option<Cookie> {
+        let mut cookies = self.state();
+        remove_expired(&mut cookies);
+        cookies.iter().filter(|cookie| cookie.name == name && cookie.matches(url)).max_by_key(|cookie| cookie.path.len()).cloned()
+    }
+
+    /// Stores the cookie of a `Set-Cookie` header received from the given URL, such as `session=abc; Path=/; HttpOnly`.
+    ///
+    /// Cookies whose `Domain` the URL does not belong to are ignored, and expired ones remove the cookie they replace.
+    ///
+    /// ```
+    /// use api_request_utils::CookieJar;
+    /// use api_request_utils::reqwest::Url;
+    ///
+    /// let jar = CookieJar::new();
+    /// let url = Url::parse("https://api.example.com/login").unwrap();
+    /// jar.set(&url,"session=abc; Max-Age=9223372036854775807");
+    /// assert_eq!(jar.get(&url,"session").unwrap().expires,None);
+    /// jar.set(&url,"session=abc; Max-Age=0");
+    /// assert!(jar.get(&url,"session").is_none());
+    /// ```
+    pub fn set(&self,url : &Url,set_cookie : &str) {
+        let Some(cookie) = Cookie::parse(url,set_cookie) else {
+            return
+        };
+        let mut cookies = self.state();
+        cookies.retain(|stored| (&stored.name,&stored.domain,&stored.path) != (&cookie.name,&cookie.domain,&cookie.path));
+        if cookie.expires.is_none_or(|expires| expires > SystemTime::now()) {
+            cookies.push(cookie);
+        }
+    }
+
+    /// Removes every cookie of the given name.
+    pub fn remove(&self,name : &str) {
+        self.state().retain(|cookie| cookie.name != name);
+    }
+
+    /// Removes every cookie, ending the session.
+    pub fn clear(&self) {
+        self.state().clear();
+    }
+
+    /// Stores the cookies of the `Set-Cookie` headers of a response received from the given URL.
+    pub(crate) fn store(&self,url : &Url,headers : &HeaderMap) {
+        for value in headers.get_all(SET_COOKIE).iter().filter_map(|value| value.to_str().ok()) {
+            self.set(url,value);
+        }
+    }
+
+    /// Adds the cookies matching the request to its `Cookie` header, after the cookies it already carries, and the CSRF
+    /// header to unsafe requests.
+    pub(crate) fn apply(&self,request : &mut reqwest::Request) {
+        let mut cookies = self.state();
+        remove_expired(&mut cookies);
+        let mut matching : Vec<&Cookie> = cookies.iter().filter(|cookie| cookie.matches(request.url())).collect();
+        // Longer paths first, as browsers send them
+        matching.sort_by_key(|cookie| std::cmp::Reverse(cookie.path.len()));
+
+        let csrf = self.csrf.as_ref()
+            .filter(|_| matches!(*request.method(),Method::POST | Method::PUT | Method::PATCH | Method::DELETE))
+            .and_then(|(name,header)| matching.iter().find(|cookie| &cookie.name == name).map(|cookie| (header.clone(),cookie.value.clone())));
+
+        if !matching.is_empty() {
+            let mut header : Vec<String> = request.headers().get(COOKIE).and_then(|value| value.to_str().ok()).map(str::to_owned).into_iter().collect();
+            header.extend(matching.iter().map(|cookie| format!("{}={}",cookie.name,cookie.value)));
+            if let Ok(mut value) = HeaderValue::try_from(header.join("; ")) {
+                value.set_sensitive(true);
+                request.headers_mut().insert(COOKIE,value);
+            }
+        }
+        if let Some((header,value)) = csrf.and_then(|(header,value)| Some((header,HeaderValue::try_from(value).ok()?))) {
+            request.headers_mut().insert(header,value);
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_,Vec<Cookie>> {
+        self.cookies.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Cookie {
+    /// Parses a `Set-Cookie` header received from the given URL (RFC 6265).
+    fn parse(url : &Url,set_cookie : &str) -> Option<Self> {
+        let host = url.host_str()?.to_ascii_lowercase();
+        let mut attributes = set_cookie.split(';');
+        let (name,value) = attributes.next()?.split_once('=')?;
+        let name = name.trim();
+        if name.is_empty() {
+            return None;
+        }
+
+        let mut cookie = Self {
+            name : name.to_owned(),
+            value : value.trim().trim_matches('"').to_owned(),
+            domain : host.clone(),
+            host_only : true,
+            path : default_path(url),
+            secure : false,
+            expires : None,
+        };
+        let mut max_age = None;
+        for attribute in attributes {
+            let (key,value) = attribute.split_once('=').unwrap_or((attribute,""));
+            let value = value.trim();
+            match key.trim().to_ascii_lowercase().as_str() {
+                "domain" if !value.is_empty() => {
+                    let domain = value.trim_start_matches('.').to_ascii_lowercase();
+                    if host != domain && !host.ends_with(&format!(".{domain}")) {
+                        return None;
+                    }
+                    cookie.domain = domain;
+                    cookie.host_only = false;
+                },
+                "path" if value.starts_with('/') => cookie.path = value.to_owned(),
+                "secure" => cookie.secure = true,
+                "max-age" => max_age = value.parse::<i64>().ok(),
+                "expires" => if let Ok(expires) = httpdate::parse_http_date(value) {
+                    cookie.expires.get_or_insert(expires);
+                },
+                _ => {}
+            }
+        }
+        // Max-Age takes precedence over Expires
+        if let Some(max_age) = max_age {
+            // A Max-Age beyond what `SystemTime` represents keeps the cookie for the whole session
+            cookie.expires = match u64::try_from(max_age) {
+                Ok(seconds) if seconds > 0 => SystemTime::now().checked_add(Duration::from_secs(seconds)),
+                _ => Some(SystemTime::UNIX_EPOCH)
+            };
+        }
+        Some(cookie)
+    }
+
+    /// Returns whether the cookie is sent with requests to the given URL.
+    fn matches(&self,url : &Url) -> bool {
+        let Some(host) = url.host_str().map(str::to_ascii_lowercase) else {
+            return false
+        };
+        let domain_matches = host == self.domain || (!self.host_only && host.ends_with(&format!(".{}",self.domain)));
+        let path = url.path();
+        let path_matches = path == self.path || (path.starts_with(&self.path) && (self.path.ends_with('/') || path[self.path.len()..].starts_with('/')));
+        domain_matches && path_matches && (!self.secure || url.scheme() == "https")
+    }
+}
+
+/// Returns the path of the directory of the URL, which cookies set without a `Path` attribute apply to.
+fn default_path(url : &Url) -> String {
+    match url.path().rfind('/') {
+        Some(0) | None => "/".to_owned(),
+        Some(index) => url.path()[..index].to_owned()
+    }
+}
+
+fn remove_expired(cookies : &mut Vec<Cookie>) {
+    let now = SystemTime::now();
+    cookies.retain(|cookie| cookie.expires.is_none_or(|expires| expires > now));
+}
@@ -0,0 +1,144 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use reqwest::header::{HeaderName, HeaderValue};
+
+use crate::hash;
+
+/// Signs every request right before it is sent, after the other hooks modified it.
+///
+/// Return it from [crate::RequestDefaults::request_signer] to apply it. Retried requests are signed again, so timestamps
+/// stay fresh. [HmacSigner] covers the usual HMAC-SHA256 schemes, implement this trait for the others.
+///
+/// Not available on `wasm32`.
+pub trait RequestSigner : Send + Sync {
+    /// Signs the request, typically by adding headers or query parameters.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The request about to be sent, whose body is `None` or buffered unless it was streamed.
+    fn sign(&self,request : &mut reqwest::Request);
+}
+
+/// Signs requests with HMAC-SHA256, as required by exchanges and payment APIs.
+///
+/// The signed message is the method, the path and query of the URL, the timestamp and the body, separated by newlines :
+///
+/// ```text
+/// POST
+/// /api/v3/order?symbol=BTCUSDT
+/// 1700000000000
+/// {"quantity":1}
+/// ```
+///
+/// The lowercase hexadecimal signature is sent in the `X-Signature` header and the timestamp, in milliseconds since the
+/// Unix epoch, in the `X-Timestamp` header by default. Streamed bodies are signed as empty.
+///
+/// ```rust ignore
+/// let signer = HmacSigner::new(secret).signature_header(HeaderName::from_static("x-mbx-signature"));
+/// ```
+pub struct HmacSigner {
+    secret : Vec<u8>,
+    signature_header : HeaderName,
+    timestamp_header : HeaderName,
+    timestamp_seconds : bool,
+}
+
+// The secret is left out
+impl std::fmt::Debug for HmacSigner {
+    fn fmt(&self,f : &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HmacSigner")
+            .field("signature_header",&self.signature_header)
+            .field("timestamp_header",&self.timestamp_header)
+            .field("timestamp_seconds",&self.timestamp_seconds)
+            .finish_non_exhaustive()
+    }
+}
+
+impl HmacSigner {
+    /// Creates a signer with the given secret.
+    pub fn new(secret : impl Into<Vec<u8>>) -> Self {
+        Self {
+            secret : secret.into(),
+            signature_header : HeaderName::from_static("x-signature"),
+            timestamp_header : HeaderName::from_static("x-timestamp"),
+            timestamp_seconds : false,
+        }
+    }
+
+    /// Sets the header carrying the signature, `X-Signature` by default.
+    pub fn signature_header(mut self,name : HeaderName) -> Self {
+        self.signature_header = name;
+        self
+    }
+
+    /// Sets the header carrying the timestamp, `X-Timestamp` by default.
+    pub fn timestamp_header(mut self,name : HeaderName) -> Self {
+        self.timestamp_header = name;
+        self
+    }
+
+    /// Uses timestamps in seconds rather than milliseconds since the Unix epoch.
+    pub fn timestamp_seconds(mut self) -> Self {
+        self.timestamp_seconds = true;
+        self
+    }
+
+    /// Computes the signature of a request with the given timestamp.
+    ///
+    /// # Returns
+    ///
+    /// The lowercase hexadecimal HMAC-SHA256 of the message described above.
+    pub fn signature(&self,request : &reqwest::Request,timestamp : &str) -> String {
+        let url = request.url();
+        let mut message = format!("{}\n{}",request.method(),url.path());
+        if let Some(query) = url.query() {
+            message.push('?');
+            message.push_str(query);
+        }
+        message.push('\n');
+        message.push_str(timestamp);
+        message.push('\n');
+
+        let mut message = message.into_bytes();
+        message.extend_from_slice(request.body().and_then(reqwest::Body::as_bytes).unwrap_or_default());
+        hash::hex(&hash::hmac_sha256(&self.secret,&message))
+    }
+}
+
+impl RequestSigner for HmacSigner {
+    fn sign(&self,request : &mut reqwest::Request) {
+        let elapsed = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        let timestamp = match self.timestamp_seconds {
+            true => elapsed.as_secs().to_string(),
+            false => elapsed.as_millis().to_string()
+        };
+
+        let signature = self.signature(request,&timestamp);
+        let headers = request.headers_mut();
+        // Both values are made of digits and lowercase hexadecimal characters only
+        headers.insert(self.signature_header.clone(),HeaderValue::try_from(signature).expect("hexadecimal is a valid header value"));
+        headers.insert(self.timestamp_header.clone(),HeaderValue::try_from(timestamp).expect("digits are a valid header value"));
+    }
+}
+
+/// Decodes the percent-encoded characters of the value, lossily converting the result to UTF-8.
+#[cfg(any(feature = "aws-sigv4",feature = "oauth1"))]
+pub(crate) fn percent_decode(value : &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let escaped = bytes.get(index + 1..index + 3).and_then(|hex| std::str::from_utf8(hex).ok()).and_then(|hex| u8::from_str_radix(hex,16).ok());
+        match (bytes[index],escaped) {
+            (b'%',Some(byte)) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            (byte,_) => {
+                decoded.push(byte);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
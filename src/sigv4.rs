@@ -0,0 +1,166 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use reqwest::header::{HeaderName, HeaderValue, AUTHORIZATION, CONTENT_TYPE};
+
+use crate::{hash, RequestSigner};
+use crate::endpoint::uri_encode;
+use crate::signing::percent_decode;
+
+/// Signs requests with AWS Signature Version 4, for AWS services and S3-compatible storage such as MinIO or R2.
+///
+/// The `host`, `content-type` and `x-amz-*` headers are signed. Requests to the `s3` service also carry the
+/// `x-amz-content-sha256` header, with `UNSIGNED-PAYLOAD` for streamed bodies.
+///
+/// ```rust ignore
+/// let signer = AwsSigV4Signer::new(access_key,secret_key,"eu-west-1","s3");
+/// ```
+///
+/// The payload hashes are checked against the SHA-256 examples of FIPS 180 :
+///
+/// ```
+/// use std::time::SystemTime;
+///
+/// use api_request_utils::AwsSigV4Signer;
+/// use api_request_utils::reqwest::Client;
+///
+/// let signer = AwsSigV4Signer::new("AKIDEXAMPLE","wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY","us-east-1","s3");
+/// let cases = [
+///     ("".to_owned(),"e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"),
+///     ("abc".to_owned(),"ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"),
+///     // Padded over two blocks
+///     ("abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq".to_owned(),"248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"),
+///     ("a".repeat(1_000_000),"cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0"),
+/// ];
+/// for (body,digest) in cases {
+///     let mut request = Client::new().put("https://examplebucket.s3.amazonaws.com/test.txt").body(body).build().unwrap();
+///     signer.sign_at(&mut request,SystemTime::UNIX_EPOCH);
+///     assert_eq!(request.headers()["x-amz-content-sha256"],digest);
+/// }
+/// ```
+///
+/// Return it from [crate::RequestDefaults::request_signer] to apply it. Not available on `wasm32`.
+pub struct AwsSigV4Signer {
+    access_key : String,
+    secret_key : String,
+    session_token : Option<String>,
+    region : String,
+    service : String,
+}
+
+// The secret key and the session token are left out
+impl std::fmt::Debug for AwsSigV4Signer {
+    fn fmt(&self,f : &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AwsSigV4Signer")
+            .field("access_key",&self.access_key)
+            .field("region",&self.region)
+            .field("service",&self.service)
+            .finish_non_exhaustive()
+    }
+}
+
+impl AwsSigV4Signer {
+    /// Creates a signer with the given credentials, for the given region and service (such as `s3` or `execute-api`).
+    pub fn new(access_key : impl Into<String>,secret_key : impl Into<String>,region : impl Into<String>,service : impl Into<String>) -> Self {
+        Self {
+            access_key : access_key.into(),
+            secret_key : secret_key.into(),
+            session_token : None,
+            region : region.into(),
+            service : service.into(),
+        }
+    }
+
+    /// Sets the session token of temporary credentials, sent in the `x-amz-security-token` header.
+    pub fn session_token(mut self,token : impl Into<String>) -> Self {
+        self.session_token = Some(token.into());
+        self
+    }
+
+    /// Signs the request as if it was sent at the given time, which [RequestSigner::sign] sets to now.
+    pub fn sign_at(&self,request : &mut reqwest::Request,time : SystemTime) {
+        let (date,timestamp) = format_time(time);
+        let is_s3 = self.service == "s3";
+
+        let payload = match request.body().map(reqwest::Body::as_bytes) {
+            Some(Some(body)) => hash::hex(&hash::sha256(body)),
+            Some(None) if is_s3 => "UNSIGNED-PAYLOAD".to_owned(),
+            // Streamed bodies cannot be hashed ahead of time, they are signed as empty outside of S3
+            _ => hash::hex(&hash::sha256(&[]))
+        };
+
+        let headers = request.headers_mut();
+        let header = |value : &str| HeaderValue::try_from(value).ok();
+        headers.extend([
+            (HeaderName::from_static("x-amz-date"),header(&timestamp)),
+            (HeaderName::from_static("x-amz-content-sha256"),header(&payload).filter(|_| is_s3)),
+            (HeaderName::from_static("x-amz-security-token"),self.session_token.as_deref().and_then(header)),
+        ].into_iter().filter_map(|(name,value)| Some((name,value?))));
+
+        let url = request.url();
+        let host = match url.port() {
+            Some(port) => format!("{}:{port}",url.host_str().unwrap_or_default()),
+            None => url.host_str().unwrap_or_default().to_owned()
+        };
+        let mut signed : Vec<(String,String)> = vec![("host".to_owned(),host)];
+        for name in request.headers().keys().filter(|name| *name == CONTENT_TYPE || name.as_str().starts_with("x-amz-")) {
+            let values : Vec<&str> = request.headers().get_all(name).iter().filter_map(|value| value.to_str().ok()).map(str::trim).collect();
+            signed.push((name.as_str().to_owned(),values.join(",")));
+        }
+        signed.sort();
+        let signed_headers = signed.iter().map(|(name,_)| name.as_str()).collect::<Vec<_>>().join(";");
+        let canonical_headers : String = signed.iter().map(|(name,value)| format!("{name}:{value}\n")).collect();
+
+        // Every service but S3 expects the path to be encoded twice
+        let path = url.path().split('/').map(|segment| {
+            let segment = uri_encode(&percent_decode(segment));
+            match is_s3 {
+                true => segment,
+                false => uri_encode(&segment)
+            }
+        }).collect::<Vec<_>>().join("/");
+        let mut query : Vec<(String,String)> = url.query_pairs().map(|(key,value)| (uri_encode(&key),uri_encode(&value))).collect();
+        query.sort();
+        let query = query.iter().map(|(key,value)| format!("{key}={value}")).collect::<Vec<_>>().join("&");
+
+        let canonical_request = format!("{}\n{path}\n{query}\n{canonical_headers}\n{signed_headers}\n{payload}",request.method());
+        let scope = format!("{date}/{}/{}/aws4_request",self.region,self.service);
+        let string_to_sign = format!("AWS4-HMAC-SHA256\n{timestamp}\n{scope}\n{}",hash::hex(&hash::sha256(canonical_request.as_bytes())));
+
+        let key = [date.as_str(),&self.region,&self.service,"aws4_request"].iter()
+            .fold(format!("AWS4{}",self.secret_key).into_bytes(),|key,part| hash::hmac_sha256(&key,part.as_bytes()).to_vec());
+        let signature = hash::hex(&hash::hmac_sha256(&key,string_to_sign.as_bytes()));
+
+        let authorization = format!("AWS4-HMAC-SHA256 Credential={}/{scope}, SignedHeaders={signed_headers}, Signature={signature}",self.access_key);
+        if let Ok(mut authorization) = HeaderValue::try_from(authorization) {
+            authorization.set_sensitive(true);
+            request.headers_mut().insert(AUTHORIZATION,authorization);
+        }
+    }
+}
+
+impl RequestSigner for AwsSigV4Signer {
+    fn sign(&self,request : &mut reqwest::Request) {
+        self.sign_at(request,SystemTime::now());
+    }
+}
+
+/// Formats the time as the `YYYYMMDD` date and `YYYYMMDD'T'HHMMSS'Z'` timestamp of the signature.
+fn format_time(time : SystemTime) -> (String,String) {
+    let seconds = time.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+    let (days,seconds) = (seconds / 86400,seconds % 86400);
+
+    // Converts the days since the epoch to a civil date, see http://howardhinnant.github.io/date_algorithms.html
+    let days = days + 719468;
+    let era = days / 146097;
+    let day_of_era = days % 146097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+
+    let date = format!("{year:04}{month:02}{day:02}");
+    let timestamp = format!("{date}T{:02}{:02}{:02}Z",seconds / 3600,seconds / 60 % 60,seconds % 60);
+    (date,timestamp)
+}
@@ -0,0 +1,217 @@
+use std::pin::Pin;
+use std::time::Duration;
+
+use bytes::Bytes;
+use futures_util::{Stream, StreamExt};
+
+use reqwest::StatusCode;
+use reqwest::header::{HeaderName, HeaderValue, ACCEPT, CACHE_CONTROL};
+
+use serde::de::DeserializeOwned;
+
+use crate::{open_response, RequestDefaults, RequestError};
+
+/// The delay before reconnecting until the server sends another one through the `retry` field, as used by browsers.
+const DEFAULT_RECONNECT_DELAY : Duration = Duration::from_secs(3);
+
+/// An event received from a Server-Sent Events stream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerSentEvent<T> {
+    /// The type of the event, from its `event` field, `message` by default.
+    pub event : String,
+    /// The last event ID sent by the server, through the `id` field of this or a previous event.
+    pub id : Option<String>,
+    /// The `data` of the event, deserialized from JSON.
+    pub data : T,
+}
+
+/// Subscribes to Server-Sent Events (`text/event-stream`) endpoints.
+///
+/// Implemented like the other traits, it is available for every event type `T` :
+///
+/// ```rust ignore
+/// impl EventStreamHandler<PriceUpdate,ApiError> for MyAPIClient {}
+/// ```
+///
+/// Not available on `wasm32`, where the `EventSource` of the browser should be used instead.
+pub trait EventStreamHandler<T : DeserializeOwned,E : DeserializeOwned> : RequestDefaults {
+    /// Sends the given request and yields the events of the stream it opens as they arrive.
+    ///
+    /// The request is usually built by one of the default requestors, so it carries the default headers and authentication;
+    /// the `Accept` header is set to `text/event-stream`. When the connection drops, the request is sent again after the
+    /// delay given by the server (3 seconds by default) with a `Last-Event-ID` header, so the stream resumes where it left
+    /// off. An unsuccessful response yields a single error, handled like in [crate::RequestHandler::request_map], and ends
+    /// the stream, as does a `204 No Content` response. Events whose data is not valid JSON for `T` yield an
+    /// `InvalidJsonBody` error without ending the stream. Note that the [crate::ClientConfig] timeout, if any, also applies
+    /// to each connection.
+    ///
+    /// ```rust ignore
+    /// let updates = api_client.event_stream(api_client.default_get_query_requestor("prices/stream",&query));
+    /// futures_util::pin_mut!(updates);
+    /// while let Some(update) = updates.next().await {
+    ///     let update : ServerSentEvent<PriceUpdate> = update?;
+    ///     println!("{} : {:?}",update.event,update.data);
+    /// }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The `reqwest::RequestBuilder` representing the request opening the stream, which must not have a
+    ///   streamed body.
+    ///
+    /// # Returns
+    ///
+    /// A `Stream` of the events or `RequestError` variants.
+    fn event_stream<'a>(&'a self,request : reqwest::RequestBuilder) -> impl Stream<Item = Result<ServerSentEvent<T>,RequestError<E>>> + 'a
+    where T : 'a, E : 'a {
+        let state = Connection { request, last_event_id : None, delay : DEFAULT_RECONNECT_DELAY, body : None, parser : EventParser::default(), reconnecting : false };
+        futures_util::stream::unfold(Some(state),move |state| async move {
+            let mut state = state?;
+            loop {
+                if state.body.is_none() {
+                    if state.reconnecting {
+                        tokio::time::sleep(state.delay).await;
+                    }
+                    let response = match state.connect() {
+                        Some(Ok(request)) => open_response(self,request).await,
+                        Some(Err(error)) => Err(error.into()),
+                        None => return None
+                    };
+                    match response {
+                        Ok(response) if response.status() == StatusCode::NO_CONTENT => return None,
+                        Ok(response) => {
+                            state.body = Some(Box::pin(response.bytes_stream()));
+                            state.parser = EventParser::default();
+                        }
+                        Err(error) => return Some((Err(error),None))
+                    }
+                }
+
+                if let Some(event) = state.parser.next_event() {
+                    if let Some(id) = event.id {
+                        state.last_event_id = (!id.is_empty()).then_some(id);
+                    }
+                    let Some(data) = event.data else {
+                        continue;
+                    };
+                    let event = serde_json::from_str(&data)
+                        .map(|data| ServerSentEvent { event : event.event, id : state.last_event_id.clone(), data })
+                        .map_err(RequestError::from);
+                    return Some((event,Some(state)));
+                }
+
+                match state.body.as_mut()?.next().await {
+                    Some(Ok(chunk)) => state.parser.feed(&chunk),
+                    // Incomplete events are discarded when the connection drops
+                    Some(Err(_)) | None => {
+                        state.body = None;
+                        state.reconnecting = true;
+                    }
+                }
+                if let Some(delay) = state.parser.retry.take() {
+                    state.delay = delay;
+                }
+            }
+        })
+    }
+}
+
+/// The state of an event stream across reconnections.
+struct Connection {
+    request : reqwest::RequestBuilder,
+    last_event_id : Option<String>,
+    delay : Duration,
+    body : Option<Pin<Box<dyn Stream<Item = reqwest::Result<Bytes>> + Send>>>,
+    parser : EventParser,
+    reconnecting : bool,
+}
+
+impl Connection {
+    /// Builds the request for the next connection, `None` if the request cannot be cloned.
+    fn connect(&self) -> Option<reqwest::Result<reqwest::RequestBuilder>> {
+        let (client,request) = self.request.try_clone()?.build_split();
+        Some(request.map(|mut request| {
+            let headers = request.headers_mut();
+            headers.insert(ACCEPT,HeaderValue::from_static("text/event-stream"));
+            headers.insert(CACHE_CONTROL,HeaderValue::from_static("no-cache"));
+            if let Some(id) = self.last_event_id.as_deref().and_then(|id| HeaderValue::from_str(id).ok()) {
+                headers.insert(HeaderName::from_static("last-event-id"),id);
+            }
+            reqwest::RequestBuilder::from_parts(client,request)
+        }))
+    }
+}
+
+/// An event as parsed from the stream, before its data is deserialized.
+struct RawEvent {
+    event : String,
+    id : Option<String>,
+    /// `None` when the event carried no data, in which case it is not dispatched.
+    data : Option<String>,
+}
+
+/// Incrementally parses the `text/event-stream` format as chunks arrive.
+#[derive(Default)]
+struct EventParser {
+    buffer : Vec<u8>,
+    event : Option<String>,
+    data : Option<String>,
+    id : Option<String>,
+    retry : Option<Duration>,
+}
+
+impl EventParser {
+    fn feed(&mut self,chunk : &[u8]) {
+        self.buffer.extend_from_slice(chunk);
+    }
+
+    /// Consumes lines until a blank one dispatches an event, `None` if more of the body is needed.
+    fn next_event(&mut self) -> Option<RawEvent> {
+        while let Some(line) = self.next_line() {
+            if line.is_empty() {
+                if self.data.is_none() && self.id.is_none() {
+                    self.event = None;
+                    continue;
+                }
+                return Some(RawEvent {
+                    event : self.event.take().unwrap_or_else(|| "message".to_owned()),
+                    id : self.id.take(),
+                    data : self.data.take(),
+                });
+            }
+
+            let (field,value) = match line.split_once(':') {
+                Some((field,value)) => (field,value.strip_prefix(' ').unwrap_or(value)),
+                None => (line.as_str(),"")
+            };
+            match field {
+                "event" => self.event = Some(value.to_owned()),
+                "data" => match &mut self.data {
+                    Some(data) => {
+                        data.push('\n');
+                        data.push_str(value);
+                    }
+                    None => self.data = Some(value.to_owned())
+                },
+                "id" if !value.contains('\0') => self.id = Some(value.to_owned()),
+                "retry" => self.retry = value.parse().ok().map(Duration::from_millis).or(self.retry),
+                // Comments (empty field names) and unknown fields are ignored
+                _ => {}
+            }
+        }
+        None
+    }
+
+    /// Takes the next complete line out of the buffer, which may end with `\n`, `\r\n` or `\r`.
+    fn next_line(&mut self) -> Option<String> {
+        let end = self.buffer.iter().position(|byte| matches!(byte,b'\n' | b'\r'))?;
+        let separator = match (self.buffer[end],self.buffer.get(end + 1)) {
+            (b'\r',Some(b'\n')) => 2,
+            // The `\n` of a `\r\n` may be in the next chunk
+            (b'\r',None) => return None,
+            _ => 1
+        };
+        let line : Vec<u8> = self.buffer.drain(..end + separator).take(end).collect();
+        Some(String::from_utf8_lossy(&line).into_owned())
+    }
+}
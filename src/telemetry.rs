@@ -0,0 +1,116 @@
+//! Instrumentation of the exchanges with the `tracing` crate, see the `tracing` feature.
+
+use std::future::Future;
+
+use reqwest::StatusCode;
+use tracing::{field, Instrument, Span};
+
+use crate::RequestError;
+
+/// Runs the exchange of a request in a `request` span, recording the status and duration it completes with and emitting a
+/// warning event when it fails.
+///
+/// The fields follow the OpenTelemetry semantic conventions for HTTP clients, so `tracing-opentelemetry` exports the span
+/// as a client span.
+///
+/// # Arguments
+///
+/// * `attempt` - The number of the attempt, `0` for the first one and then the number of the retry.
+/// * `status` - Reads the status of a successful exchange.
+/// * `exchange` - The exchange, which fills the fields describing the request through [record_request].
+pub(crate) async fn instrument<T,E>(attempt : u32,status : impl Fn(&T) -> StatusCode,exchange : impl Future<Output = Result<T,RequestError<E>>>) -> Result<T,RequestError<E>> {
+    let span = tracing::info_span!(
+        "request",
+        otel.name = field::Empty,
+        otel.kind = "client",
+        otel.status_code = field::Empty,
+        http.request.method = field::Empty,
+        url.path = field::Empty,
+        server.address = field::Empty,
+        server.port = field::Empty,
+        http.request.resend_count = attempt,
+        http.response.status_code = field::Empty,
+        "error.type" = field::Empty,
+        duration_ms = field::Empty,
+    );
+
+    // `Instant::now` is not supported by `wasm32-unknown-unknown`
+    #[cfg(not(target_arch = "wasm32"))]
+    let start = std::time::Instant::now();
+    let result = exchange.instrument(span.clone()).await;
+    #[cfg(not(target_arch = "wasm32"))]
+    span.record("duration_ms",u64::try_from(start.elapsed().as_millis()).unwrap_or(u64::MAX));
+
+    span.in_scope(|| match &result {
+        Ok(response) => {
+            let status = status(response);
+            span.record("http.response.status_code",status.as_u16());
+            if status.is_client_error() || status.is_server_error() {
+                span.record("otel.status_code","ERROR");
+                span.record("error.type",status.as_str());
+                tracing::warn!("request failed");
+            }
+        }
+        Err(error) => {
+            if let Some(context) = error.context() {
+                span.record("http.response.status_code",context.status.as_u16());
+            }
+            span.record("otel.status_code","ERROR");
+            span.record("error.type",error_type(error));
+            tracing::warn!(error = %describe(error),"request failed");
+        }
+    });
+    result
+}
+
+/// Records the method, path and server of the request in the span of [instrument]. The query is left out as it may carry secrets.
+pub(crate) fn record_request(request : &reqwest::Request) {
+    let span = Span::current();
+    span.record("otel.name",request.method().as_str());
+    span.record("http.request.method",request.method().as_str());
+    span.record("url.path",request.url().path());
+    if let Some(host) = request.url().host_str() {
+        span.record("server.address",host);
+    }
+    if let Some(port) = request.url().port_or_known_default() {
+        span.record("server.port",port);
+    }
+}
+
+/// Classifies the error an exchange failed with as the low-cardinality `error.type` attribute.
+fn error_type<E>(error : &RequestError<E>) -> &str {
+    match error {
+        RequestError::RequestError(error) if error.is_timeout() => "timeout",
+        #[cfg(not(target_arch = "wasm32"))]
+        RequestError::RequestError(error) if error.is_connect() => "connect",
+        RequestError::RequestError(error) if error.is_body() || error.is_decode() => "body",
+        RequestError::Timeout => "timeout",
+        RequestError::PermitTimeout => "permit_timeout",
+        RequestError::Cancelled => "cancelled",
+        RequestError::ErrorPayload { context, .. } => context.status.as_str(),
+        _ => "_OTHER"
+    }
+}
+
+/// Describes the error an exchange failed with, without requiring the error payload to implement `Display`.
+fn describe<E>(error : &RequestError<E>) -> String {
+    match error {
+        RequestError::RequestError(error) => error.to_string(),
+        #[cfg(not(target_arch = "wasm32"))]
+        RequestError::Transport(error) => format!("transport failed due to {error}"),
+        RequestError::Io(error) => error.to_string(),
+        RequestError::ErrorPayload { context, .. } => format!("error payload from {context}"),
+        RequestError::JsonRpc { error, .. } => format!("JSON-RPC error {}",error.code),
+        RequestError::ErrorPayloadParseFailed { status, .. } | RequestError::UnexpectedBody { status, .. } => format!("unexpected body with status code {status}"),
+        RequestError::Timeout => "timed out".to_owned(),
+        RequestError::Cancelled => "cancelled".to_owned(),
+        RequestError::MaintenanceMode { .. } => "service under maintenance".to_owned(),
+        RequestError::CircuitOpen { .. } => "circuit breaker open".to_owned(),
+        RequestError::PermitTimeout => "rate limiter permit timed out".to_owned(),
+        #[cfg(all(feature = "pinning",not(target_arch = "wasm32")))]
+        RequestError::PinMismatch { host, .. } => format!("certificate pin mismatch for {host}"),
+        RequestError::ResponseTooLarge { limit, .. } => format!("response exceeded {limit} bytes"),
+        RequestError::TokenRequestFailed { status, .. } => format!("token request failed with status code {status}"),
+        _ => "request failed".to_owned()
+    }
+}
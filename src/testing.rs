@@ -0,0 +1,722 @@
+//! Canned responses for testing code built on top of this crate, either without any network I/O or from a local server.
+//!
+//! Return a [MockResponses] registry from [crate::RequestDefaults::mock_responses] and every request sent through
+//! `request_map` is answered from it, keyed by method and endpoint. Both the success and the error paths can be exercised :
+//!
+//! ```
+//! use api_request_utils::*;
+//! use api_request_utils::reqwest::{Client, Method, StatusCode};
+//! use api_request_utils::testing::{MockResponse, MockResponses};
+//! use serde::Deserialize;
+//!
+//! #[derive(Deserialize)]
+//! struct User {
+//!     name : String,
+//! }
+//!
+//! #[derive(Deserialize)]
+//! struct ApiError {
+//!     message : String,
+//! }
+//!
+//! struct MyAPIClient {
+//!     client : Client,
+//!     mocks : MockResponses,
+//! }
+//!
+//! impl RequestInfo for MyAPIClient {
+//!     const BASE_URL : &'static str = "https://api.example.com";
+//!     fn client(&self) -> &Client {
+//!         &self.client
+//!     }
+//! }
+//!
+//! impl RequestModifiers for MyAPIClient {}
+//!
+//! impl RequestDefaults for MyAPIClient {
+//!     fn mock_responses(&self) -> Option<&MockResponses> {
+//!         Some(&self.mocks)
+//!     }
+//! }
+//!
+//! impl RequestHandler<User,User,ApiError> for MyAPIClient {}
+//!
+//! #[tokio::main(flavor = "current_thread")]
+//! async fn main() {
+//!     let api_client = MyAPIClient { client : Client::new(), mocks : MockResponses::new() };
+//!
+//!     api_client.mocks.mock(Method::GET,"users/1",MockResponse::json(StatusCode::OK,&serde_json::json!({ "name" : "Ferris" })));
+//!     api_client.mocks.mock(Method::GET,"users/2",MockResponse::json(StatusCode::NOT_FOUND,&serde_json::json!({ "message" : "User not found" })));
+//!
+//!     let user = api_client.request_map(api_client.default_get_query_requestor("users/1",&()),|user| user).await;
+//!     assert!(matches!(user, Ok(User { name }) if name == "Ferris"));
+//!
+//!     let missing = api_client.request_map(api_client.default_get_query_requestor("users/2",&()),|user| user).await;
+//!     assert!(matches!(missing, Err(RequestError::ErrorPayload { payload : ApiError { message }, .. }) if message == "User not found"));
+//! }
+//! ```
+//!
+//! To exercise the whole stack instead, including the network, timeouts and retries, point the client at a [TestServer] :
+//!
+//! ```
+//! use std::collections::HashMap;
+//! use std::time::Duration;
+//!
+//! use api_request_utils::*;
+//! use api_request_utils::reqwest::{Client, Method, StatusCode};
+//! use api_request_utils::testing::{MockResponse, TestServer};
+//! use serde::Deserialize;
+//!
+//! #[derive(Deserialize)]
+//! struct User {
+//!     name : String,
+//! }
+//!
+//! #[derive(Deserialize)]
+//! struct ApiError {
+//!     message : String,
+//! }
+//!
+//! struct MyAPIClient {
+//!     client : Client,
+//!     base_url : String,
+//!     config : ClientConfig,
+//!     retry : RetryConfig,
+//! }
+//!
+//! impl RequestInfo for MyAPIClient {
+//!     // The server listens on a free port, only known at runtime
+//!     fn base_url(&self) -> &str {
+//!         &self.base_url
+//!     }
+//!
+//!     fn client(&self) -> &Client {
+//!         &self.client
+//!     }
+//! }
+//!
+//! impl RequestModifiers for MyAPIClient {}
+//!
+//! impl RequestDefaults for MyAPIClient {
+//!     fn config(&self) -> Option<&ClientConfig> {
+//!         Some(&self.config)
+//!     }
+//!
+//!     fn config_mut(&mut self) -> Option<&mut ClientConfig> {
+//!         Some(&mut self.config)
+//!     }
+//!
+//!     fn retry_policy(&self) -> Option<&dyn RetryPolicy> {
+//!         Some(&self.retry)
+//!     }
+//! }
+//!
+//! impl RequestHandler<User,User,ApiError> for MyAPIClient {}
+//!
+//! #[tokio::main(flavor = "current_thread")]
+//! async fn main() {
+//!     let server = TestServer::start().unwrap();
+//!     let api_client = MyAPIClient {
+//!         client : Client::new(),
+//!         base_url : format!("http://{}",server.addr()),
+//!         config : ClientConfig::new().timeout(Duration::from_millis(200)),
+//!         retry : RetryConfig::new(1,Duration::from_millis(10)),
+//!     };
+//!
+//!     let ferris = serde_json::json!({ "name" : "Ferris" });
+//!     for method in [Method::GET,Method::POST,Method::PUT,Method::PATCH,Method::DELETE] {
+//!         server.mock(method.clone(),"users/1",MockResponse::json(StatusCode::OK,&ferris));
+//!         let user = api_client.request_map(api_client.default_requestor(method,"users/1",&HashMap::new()),|user| user).await;
+//!         assert!(matches!(user, Ok(User { name }) if name == "Ferris"));
+//!     }
+//!
+//!     // Error payloads
+//!     server.mock(Method::GET,"users/2",MockResponse::json(StatusCode::NOT_FOUND,&serde_json::json!({ "message" : "User not found" })));
+//!     let missing = api_client.request_map(api_client.default_get_query_requestor("users/2",&()),|user| user).await;
+//!     assert!(matches!(missing, Err(RequestError::ErrorPayload { payload : ApiError { message }, .. }) if message == "User not found"));
+//!
+//!     // Retries : the first response is served once, the last one from then on
+//!     server.mock(Method::GET,"flaky",MockResponse::new(StatusCode::SERVICE_UNAVAILABLE));
+//!     server.mock(Method::GET,"flaky",MockResponse::json(StatusCode::OK,&ferris));
+//!     let user = api_client.request_map(api_client.default_get_query_requestor("flaky",&()),|user| user).await;
+//!     assert!(user.is_ok());
+//!     assert_eq!(server.received().iter().filter(|request| request.path == "flaky").count(),2);
+//!
+//!     // Timeouts
+//!     server.mock(Method::GET,"slow",MockResponse::json(StatusCode::OK,&ferris).delay(Duration::from_secs(1)));
+//!     let slow = api_client.request_map(api_client.default_get_query_requestor("slow",&()),|user| user).await;
+//!     assert!(matches!(slow, Err(RequestError::Timeout)));
+//! }
+//! ```
+//!
+//! In between, a [MockTransport] returned from [crate::RequestDefaults::transport] answers requests without any network
+//! I/O while still going through every hook, matching them by method, path and query. It can inject latency and failures,
+//! and captures the requests it receives :
+//!
+//! ```
+//! use api_request_utils::*;
+//! use api_request_utils::reqwest::{Client, Method, StatusCode};
+//! use api_request_utils::testing::{MockResponse, MockTransport};
+//! use serde_json::Value;
+//!
+//! struct MyAPIClient {
+//!     client : Client,
+//!     transport : MockTransport,
+//! }
+//!
+//! impl RequestInfo for MyAPIClient {
+//!     const BASE_URL : &'static str = "https://api.example.com";
+//!     fn client(&self) -> &Client {
+//!         &self.client
+//!     }
+//! }
+//!
+//! impl RequestModifiers for MyAPIClient {}
+//!
+//! impl RequestDefaults for MyAPIClient {
+//!     fn transport(&self) -> Option<&dyn HttpTransport> {
+//!         Some(&self.transport)
+//!     }
+//! }
+//!
+//! impl RequestHandler<Value,Value,Value> for MyAPIClient {}
+//!
+//! #[tokio::main(flavor = "current_thread")]
+//! async fn main() {
+//!     let api_client = MyAPIClient { client : Client::new(), transport : MockTransport::new() };
+//!     api_client.transport.mock(Method::GET,"users",MockResponse::json(StatusCode::OK,&serde_json::json!([1,2])));
+//!     api_client.transport.mock_query(Method::GET,"users",&[("page","2")],MockResponse::json(StatusCode::OK,&serde_json::json!([])));
+//!     api_client.transport.fail(Method::DELETE,"users/1","connection reset");
+//!
+//!     let page = api_client.request_map(api_client.default_get_query_requestor("users",&[("page","2")]),|users| users).await;
+//!     assert_eq!(page.unwrap(),serde_json::json!([]));
+//!
+//!     let deleted = api_client.request_map(api_client.default_delete_query_requestor("users/1",&()),|users| users).await;
+//!     assert!(matches!(deleted, Err(RequestError::Transport(_))));
+//!
+//!     let received = api_client.transport.received();
+//!     assert_eq!((received[0].path.as_str(),received[0].query.as_deref()),("users",Some("page=2")));
+//! }
+//! ```
+//!
+//! For offline demos, a [FixtureHandler] returned from [crate::RequestDefaults::fixtures] answers requests from JSON files
+//! on disk, with templates shared by a family of endpoints :
+//!
+//! ```
+//! use api_request_utils::*;
+//! use api_request_utils::reqwest::{Client, Method};
+//! use api_request_utils::testing::FixtureHandler;
+//! use serde_json::Value;
+//!
+//! struct MyAPIClient {
+//!     client : Client,
+//!     fixtures : FixtureHandler,
+//! }
+//!
+//! impl RequestInfo for MyAPIClient {
+//!     const BASE_URL : &'static str = "https://api.example.com";
+//!     fn client(&self) -> &Client {
+//!         &self.client
+//!     }
+//! }
+//!
+//! impl RequestModifiers for MyAPIClient {}
+//!
+//! impl RequestDefaults for MyAPIClient {
+//!     fn fixtures(&self) -> Option<&FixtureHandler> {
+//!         Some(&self.fixtures)
+//!     }
+//! }
+//!
+//! impl RequestHandler<Value,Value,Value> for MyAPIClient {}
+//!
+//! #[tokio::main(flavor = "current_thread")]
+//! async fn main() {
+//!     let root = std::env::temp_dir().join("api-request-utils-fixtures");
+//!     std::fs::create_dir_all(root.join("users")).unwrap();
+//!     std::fs::write(root.join("users.json"),r#"[{ "id" : 1 }]"#).unwrap();
+//!     std::fs::write(root.join("users/user.json"),r#"{ "id" : {{id}}, "name" : "User {{id}}" }"#).unwrap();
+//!
+//!     let fixtures = FixtureHandler::new(&root).route(Method::GET,"users/{id}","users/user.json");
+//!     let api_client = MyAPIClient { client : Client::new(), fixtures };
+//!
+//!     let users = api_client.request_map(api_client.default_get_query_requestor("users",&()),|users| users).await;
+//!     assert_eq!(users.unwrap(),serde_json::json!([{ "id" : 1 }]));
+//!
+//!     let user = api_client.request_map(api_client.default_get_query_requestor("users/42",&()),|user| user).await;
+//!     assert_eq!(user.unwrap()["name"],"User 42");
+//!
+//!     let missing = api_client.request_map(api_client.default_get_query_requestor("teams",&()),|teams| teams).await;
+//!     assert!(matches!(missing, Err(RequestError::ReplayMiss { .. })));
+//! }
+//! ```
+
+use std::collections::{HashMap, VecDeque};
+use std::convert::Infallible;
+use std::net::{SocketAddr, TcpListener};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use bytes::Bytes;
+
+use hyper::Body;
+use hyper::service::{make_service_fn, service_fn};
+
+use reqwest::{Method, Response, StatusCode, Url};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, CONTENT_TYPE};
+
+use serde::Serialize;
+
+use crate::HttpTransport;
+
+/// A canned response returned instead of sending a request.
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    status : StatusCode,
+    headers : HeaderMap,
+    body : Vec<u8>,
+    delay : Duration,
+}
+
+impl MockResponse {
+    /// Creates an empty response with the given status code.
+    pub fn new(status : StatusCode) -> Self {
+        Self {
+            status,
+            headers : HeaderMap::new(),
+            body : Vec::new(),
+            delay : Duration::ZERO,
+        }
+    }
+
+    /// Creates a response with the given status code and a JSON body.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `body` cannot be serialized, as this always indicates a mistake in the test itself.
+    pub fn json(status : StatusCode,body : &impl Serialize) -> Self {
+        let body = serde_json::to_vec(body).expect("mock response body should serialize to JSON");
+        Self::new(status).header(CONTENT_TYPE,HeaderValue::from_static("application/json")).body(body)
+    }
+
+    /// Sets the body of the response.
+    pub fn body(mut self,body : impl Into<Vec<u8>>) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    /// Adds a header to the response.
+    pub fn header(mut self,name : HeaderName,value : HeaderValue) -> Self {
+        self.headers.append(name,value);
+        self
+    }
+
+    /// Delays the response, to exercise timeouts. Only honoured by the [TestServer] and the [MockTransport], canned responses
+    /// returned from [crate::RequestDefaults::mock_responses] are always answered immediately.
+    pub fn delay(mut self,delay : Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Returns the status code of the response.
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// Converts the canned response into a [reqwest::Response] as if it had been received from the network.
+    pub fn to_response(&self) -> Response {
+        Response::from(self.to_http_response(self.body.clone()))
+    }
+
+    /// Converts the canned response into an [http::Response] carrying the given body.
+    fn to_http_response<B>(&self,body : B) -> http::Response<B> {
+        let mut response = http::Response::new(body);
+        *response.status_mut() = self.status;
+        *response.headers_mut() = self.headers.clone();
+        response
+    }
+}
+
+/// A registry of canned responses keyed by method and endpoint.
+///
+/// Endpoints are the same strings passed to the default requestors, relative to the base URL and without the query string.
+#[derive(Debug, Default)]
+pub struct MockResponses {
+    responses : Mutex<HashMap<(Method,String),MockResponse>>,
+}
+
+impl MockResponses {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the response returned for requests with the given method to the given endpoint, replacing any previous one.
+    pub fn mock(&self,method : Method,endpoint : &str,response : MockResponse) -> &Self {
+        self.responses.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).insert((method,endpoint.trim_matches('/').to_owned()),response);
+        self
+    }
+
+    /// Removes all registered responses.
+    pub fn clear(&self) {
+        self.responses.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clear()
+    }
+
+    /// Finds the response registered for the given method and endpoint.
+    pub fn find(&self,method : &Method,endpoint : &str) -> Option<MockResponse> {
+        self.responses.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).get(&(method.clone(),endpoint.trim_matches('/').to_owned())).cloned()
+    }
+}
+
+/// Answers requests from JSON files on disk, for offline demos and doc tests of clients built on top of this crate.
+///
+/// Return it from [crate::RequestDefaults::fixtures] to apply it. By default, a request to an endpoint is answered with the
+/// file of the same path under the root directory, with a `.json` extension, so `users/1` is answered from
+/// `<root>/users/1.json`. Routes map a family of endpoints to a single file instead, their `{name}` segments matching any
+/// segment of the endpoint. The captured segments are substituted for the same placeholders in the path of the file, and
+/// for `{{name}}` in its contents :
+///
+/// ```rust ignore
+/// // `users/42` is answered from `fixtures/user.json`, whose `{{id}}` are replaced with `42`
+/// let fixtures = FixtureHandler::new("fixtures").route(Method::GET,"users/{id}","user.json");
+/// ```
+///
+/// Requests without a fixture fail with [crate::RequestError::ReplayMiss].
+#[derive(Debug, Clone)]
+pub struct FixtureHandler {
+    root : PathBuf,
+    routes : Vec<FixtureRoute>,
+}
+
+#[derive(Debug, Clone)]
+struct FixtureRoute {
+    method : Method,
+    pattern : String,
+    file : String,
+    status : StatusCode,
+}
+
+impl FixtureHandler {
+    /// Creates a handler reading the fixtures from the given directory.
+    pub fn new(root : impl Into<PathBuf>) -> Self {
+        Self { root : root.into(), routes : Vec::new() }
+    }
+
+    /// Answers requests with the given method to the endpoints matching the pattern from the given file, relative to the
+    /// root directory, with a `200 OK` status. Routes are tried in the order they were added, before the default file.
+    pub fn route(self,method : Method,pattern : &str,file : &str) -> Self {
+        self.route_with_status(method,pattern,file,StatusCode::OK)
+    }
+
+    /// Same as [FixtureHandler::route], answering with the given status, to exercise error payloads.
+    pub fn route_with_status(mut self,method : Method,pattern : &str,file : &str,status : StatusCode) -> Self {
+        self.routes.push(FixtureRoute { method, pattern : pattern.trim_matches('/').to_owned(), file : file.to_owned(), status });
+        self
+    }
+
+    /// Finds the fixture answering the given method and endpoint.
+    ///
+    /// # Returns
+    ///
+    /// The response if a fixture was found, `None` if its file does not exist, or the error reading it.
+    pub fn find(&self,method : &Method,endpoint : &str) -> std::io::Result<Option<MockResponse>> {
+        let endpoint = endpoint.trim_matches('/');
+        let (file,captures,status) = self.routes.iter()
+            .filter(|route| route.method == *method)
+            .find_map(|route| captures(&route.pattern,endpoint).map(|captures| (substitute(&route.file,&captures,"{","}"),captures,route.status)))
+            .unwrap_or_else(|| (format!("{endpoint}.json"),Vec::new(),StatusCode::OK));
+
+        let contents = match std::fs::read_to_string(self.root.join(file)) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(error) => return Err(error)
+        };
+        let body = substitute(&contents,&captures,"{{","}}");
+        Ok(Some(MockResponse::new(status).header(CONTENT_TYPE,HeaderValue::from_static("application/json")).body(body)))
+    }
+}
+
+/// Matches the endpoint against the pattern of a route, returning the segments captured by its placeholders.
+fn captures<'a>(pattern : &'a str,endpoint : &'a str) -> Option<Vec<(&'a str,&'a str)>> {
+    let (mut patterns,mut segments) = (pattern.split('/'),endpoint.split('/'));
+    let mut captures = Vec::new();
+    loop {
+        match (patterns.next(),segments.next()) {
+            (None,None) => return Some(captures),
+            (Some(pattern),Some(segment)) => match pattern.strip_prefix('{').and_then(|name| name.strip_suffix('}')) {
+                Some(name) => captures.push((name,segment)),
+                None if pattern == segment => {},
+                None => return None
+            },
+            _ => return None
+        }
+    }
+}
+
+/// Replaces the placeholders delimited by `open` and `close` with the captured segments.
+fn substitute(template : &str,captures : &[(&str,&str)],open : &str,close : &str) -> String {
+    captures.iter().fold(template.to_owned(),|template,(name,value)| template.replace(&format!("{open}{name}{close}"),value))
+}
+
+/// A request received by a [TestServer] or a [MockTransport].
+#[derive(Debug, Clone)]
+pub struct ReceivedRequest {
+    /// The method of the request.
+    pub method : Method,
+    /// The path of the request, without the leading `/` nor the query string.
+    pub path : String,
+    /// The query string of the request, if any.
+    pub query : Option<String>,
+    /// The headers of the request.
+    pub headers : HeaderMap,
+    /// The body of the request.
+    pub body : Vec<u8>,
+}
+
+#[derive(Debug, Default)]
+struct Routes {
+    responses : Mutex<HashMap<(Method,String),VecDeque<MockResponse>>>,
+    received : Mutex<Vec<ReceivedRequest>>,
+}
+
+impl Routes {
+    /// Pops the next response for the route, keeping the last one so it keeps being served.
+    fn next(&self,method : &Method,path : &str) -> Option<MockResponse> {
+        let mut responses = self.responses.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let queue = responses.get_mut(&(method.clone(),path.to_owned()))?;
+        match queue.len() > 1 {
+            true => queue.pop_front(),
+            false => queue.front().cloned()
+        }
+    }
+
+    async fn handle(&self,request : hyper::Request<Body>) -> hyper::Response<Body> {
+        let (parts,body) = request.into_parts();
+        let body = hyper::body::to_bytes(body).await.map(|body| body.to_vec()).unwrap_or_default();
+        let path = parts.uri.path().trim_matches('/').to_owned();
+        let response = self.next(&parts.method,&path);
+
+        self.received.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).push(ReceivedRequest {
+            method : parts.method,
+            path,
+            query : parts.uri.query().map(str::to_owned),
+            headers : parts.headers,
+            body,
+        });
+
+        let Some(response) = response else {
+            return MockResponse::new(StatusCode::NOT_FOUND).to_http_response(Body::from("no response registered"))
+        };
+        tokio::time::sleep(response.delay).await;
+        response.to_http_response(Body::from(response.body.clone()))
+    }
+}
+
+/// A local HTTP server answering with canned responses, for end-to-end tests of clients built on top of this crate.
+///
+/// Unlike [MockResponses], requests go through the network stack, so timeouts (see [MockResponse::delay]), retries and
+/// everything else applied by `reqwest` are exercised as well. Several responses can be registered for the same route to
+/// script a sequence, such as a failure followed by a success. The server runs on the current tokio runtime and is shut
+/// down when dropped.
+#[derive(Debug)]
+pub struct TestServer {
+    addr : SocketAddr,
+    routes : Arc<Routes>,
+    shutdown : Option<tokio::sync::oneshot::Sender<()>>,
+}
+
+impl TestServer {
+    /// Starts a server on a free local port, see [TestServer::url].
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a tokio runtime.
+    pub fn start() -> std::io::Result<Self> {
+        Self::bind(SocketAddr::from(([127,0,0,1],0)))
+    }
+
+    /// Starts a server on the given address, which is useful when the base URL is the constant [crate::RequestInfo::BASE_URL].
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a tokio runtime.
+    pub fn bind(addr : SocketAddr) -> std::io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        listener.set_nonblocking(true)?;
+
+        let routes = Arc::new(Routes::default());
+        let make_service = {
+            let routes = Arc::clone(&routes);
+            make_service_fn(move |_| {
+                let routes = Arc::clone(&routes);
+                async move {
+                    Ok::<_,Infallible>(service_fn(move |request| {
+                        let routes = Arc::clone(&routes);
+                        async move { Ok::<_,Infallible>(routes.handle(request).await) }
+                    }))
+                }
+            })
+        };
+
+        let server = hyper::Server::from_tcp(listener).map_err(std::io::Error::other)?.serve(make_service);
+        let addr = server.local_addr();
+        let (shutdown,shutdown_signal) = tokio::sync::oneshot::channel();
+        tokio::spawn(server.with_graceful_shutdown(async { shutdown_signal.await.ok(); }));
+
+        Ok(Self { addr, routes, shutdown : Some(shutdown) })
+    }
+
+    /// Returns the address the server listens on.
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Returns the URL of the given path on the server.
+    pub fn url(&self,path : &str) -> String {
+        format!("http://{}/{}",self.addr,path.trim_start_matches('/'))
+    }
+
+    /// Registers a response for requests with the given method to the given path.
+    ///
+    /// Responses registered for the same route are served in order, the last one being served for every following request.
+    pub fn mock(&self,method : Method,path : &str,response : MockResponse) -> &Self {
+        self.routes.responses.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+            .entry((method,path.trim_matches('/').to_owned()))
+            .or_default()
+            .push_back(response);
+        self
+    }
+
+    /// Removes all registered responses and received requests.
+    pub fn clear(&self) {
+        self.routes.responses.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clear();
+        self.routes.received.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clear();
+    }
+
+    /// Returns the requests received so far, in order.
+    pub fn received(&self) -> Vec<ReceivedRequest> {
+        self.routes.received.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clone()
+    }
+}
+
+impl Drop for TestServer {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+    }
+}
+
+/// What a [MockTransport] answers a request with.
+#[derive(Debug, Clone)]
+enum Stub {
+    Response(MockResponse),
+    Failure(String),
+}
+
+/// The method, path and sorted query parameters a [Stub] answers, the latter being `None` to answer any query.
+type Route = (Method,String,Option<Vec<(String,String)>>);
+
+/// An [HttpTransport] answering with canned responses, for unit tests going through every hook of the crate without
+/// any network I/O.
+///
+/// Requests are matched by method and path (the path of their URL, without the leading `/`), and optionally by query
+/// parameters : responses registered with [MockTransport::mock_query] take precedence over those of [MockTransport::mock].
+/// Like with the [TestServer], responses registered for the same route are served in order, the last one being served for
+/// every following request. Requests without a registered response are answered with `404 Not Found`.
+#[derive(Debug, Default)]
+pub struct MockTransport {
+    stubs : Mutex<HashMap<Route,VecDeque<Stub>>>,
+    received : Mutex<Vec<ReceivedRequest>>,
+}
+
+impl MockTransport {
+    /// Creates a transport without any registered response.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a response for requests with the given method to the given path, whatever their query.
+    pub fn mock(&self,method : Method,path : &str,response : MockResponse) -> &Self {
+        self.register((method,path.trim_matches('/').to_owned(),None),Stub::Response(response))
+    }
+
+    /// Registers a response for requests with the given method to the given path, with exactly the given query parameters in any order.
+    pub fn mock_query(&self,method : Method,path : &str,query : &[(&str,&str)],response : MockResponse) -> &Self {
+        let mut query : Vec<(String,String)> = query.iter().map(|(key,value)| ((*key).to_owned(),(*value).to_owned())).collect();
+        query.sort();
+        self.register((method,path.trim_matches('/').to_owned(),Some(query)),Stub::Response(response))
+    }
+
+    /// Registers a failure for requests with the given method to the given path, surfaced as [crate::RequestError::Transport]
+    /// with the given message, to exercise connection errors.
+    pub fn fail(&self,method : Method,path : &str,message : impl Into<String>) -> &Self {
+        self.register((method,path.trim_matches('/').to_owned(),None),Stub::Failure(message.into()))
+    }
+
+    /// Removes all registered responses and received requests.
+    pub fn clear(&self) {
+        self.stubs.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clear();
+        self.received.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clear();
+    }
+
+    /// Returns the requests received so far, in order.
+    pub fn received(&self) -> Vec<ReceivedRequest> {
+        self.received.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clone()
+    }
+
+    fn register(&self,route : Route,stub : Stub) -> &Self {
+        self.stubs.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).entry(route).or_default().push_back(stub);
+        self
+    }
+
+    /// Pops the next stub for the request, keeping the last one of its route so it keeps being served.
+    fn next(&self,method : &Method,path : &str,query : Vec<(String,String)>) -> Option<Stub> {
+        let mut stubs = self.stubs.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let route = [Some(query),None].into_iter()
+            .map(|query| (method.clone(),path.to_owned(),query))
+            .find(|route| stubs.contains_key(route))?;
+        let queue = stubs.get_mut(&route)?;
+        match queue.len() > 1 {
+            true => queue.pop_front(),
+            false => queue.front().cloned()
+        }
+    }
+}
+
+#[cfg_attr(not(feature = "wasm"),async_trait)]
+#[cfg_attr(feature = "wasm",async_trait(?Send))]
+impl HttpTransport for MockTransport {
+    async fn execute(&self,request : http::Request<Vec<u8>>) -> Result<http::Response<Bytes>,Box<dyn std::error::Error + Send + Sync>> {
+        let (parts,body) = request.into_parts();
+        let url = Url::parse(&parts.uri.to_string())?;
+        let path = url.path().trim_matches('/').to_owned();
+        let mut query : Vec<(String,String)> = url.query_pairs().map(|(key,value)| (key.into_owned(),value.into_owned())).collect();
+        query.sort();
+        let stub = self.next(&parts.method,&path,query);
+
+        self.received.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).push(ReceivedRequest {
+            method : parts.method,
+            path,
+            query : url.query().map(str::to_owned),
+            headers : parts.headers,
+            body,
+        });
+
+        let (status,headers,body) = match stub {
+            Some(Stub::Response(response)) => {
+                tokio::time::sleep(response.delay).await;
+                (response.status,response.headers,Bytes::from(response.body))
+            }
+            Some(Stub::Failure(message)) => return Err(message.into()),
+            None => (StatusCode::NOT_FOUND,HeaderMap::new(),Bytes::from_static(b"no response registered"))
+        };
+        let mut response = http::Response::new(body);
+        *response.status_mut() = status;
+        *response.headers_mut() = headers;
+        Ok(response)
+    }
+}
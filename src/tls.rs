@@ -0,0 +1,139 @@
+use reqwest::{Certificate, ClientBuilder, Identity};
+use reqwest::tls::Version;
+
+/// TLS settings of a client : the certificate it authenticates with for mutual TLS, and the certificate authorities it
+/// trusts, as many B2B APIs and private deployments require.
+///
+/// The certificates are kept as bytes and only parsed when applied to a `ClientBuilder` with
+/// [ClientBuilderExt::tls_config], which reports invalid ones :
+///
+/// ```rust ignore
+/// let tls = TlsConfig::new()
+///     .identity_pem(std::fs::read("client.crt")?,std::fs::read("client.key")?)
+///     .root_certificate_pem(std::fs::read("partner-ca.pem")?)
+///     .min_version(reqwest::tls::Version::TLS_1_2);
+///
+/// let client = MyAPIClient::default_client_builder().tls_config(&tls)?.build()?;
+/// ```
+///
+/// Requires the `mtls` feature, which enables the native TLS backend of `reqwest`. Not available on `wasm32`, where the
+/// browser manages certificates.
+#[derive(Clone)]
+pub struct TlsConfig {
+    identity : Option<ClientIdentity>,
+    root_certificates : Vec<CertificateData>,
+    built_in_roots : bool,
+    min_version : Option<Version>,
+}
+
+#[derive(Clone)]
+enum ClientIdentity {
+    Pem { certificate : Vec<u8>, key : Vec<u8> },
+    Pkcs12 { der : Vec<u8>, password : String },
+}
+
+#[derive(Clone)]
+enum CertificateData {
+    Pem(Vec<u8>),
+    Der(Vec<u8>),
+}
+
+// The private key and password are left out
+impl std::fmt::Debug for TlsConfig {
+    fn fmt(&self,f : &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TlsConfig")
+            .field("identity",&self.identity.as_ref().map(|identity| match identity {
+                ClientIdentity::Pem { .. } => "PEM",
+                ClientIdentity::Pkcs12 { .. } => "PKCS#12"
+            }))
+            .field("root_certificates",&self.root_certificates.len())
+            .field("built_in_roots",&self.built_in_roots)
+            .field("min_version",&self.min_version)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for TlsConfig {
+    fn default() -> Self {
+        Self { identity : None, root_certificates : Vec::new(), built_in_roots : true, min_version : None }
+    }
+}
+
+impl TlsConfig {
+    /// Creates settings without client certificate, trusting the built-in certificate authorities.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Authenticates the client with the given PEM certificate chain and PKCS#8 PEM private key (`BEGIN PRIVATE KEY`).
+    pub fn identity_pem(mut self,certificate : impl Into<Vec<u8>>,key : impl Into<Vec<u8>>) -> Self {
+        self.identity = Some(ClientIdentity::Pem { certificate : certificate.into(), key : key.into() });
+        self
+    }
+
+    /// Authenticates the client with the certificate and private key of the given PKCS#12 archive (`.p12` or `.pfx`).
+    pub fn identity_pkcs12(mut self,der : impl Into<Vec<u8>>,password : impl Into<String>) -> Self {
+        self.identity = Some(ClientIdentity::Pkcs12 { der : der.into(), password : password.into() });
+        self
+    }
+
+    /// Trusts the certificate authorities of the given PEM file, which may hold several certificates.
+    pub fn root_certificate_pem(mut self,pem : impl Into<Vec<u8>>) -> Self {
+        self.root_certificates.push(CertificateData::Pem(pem.into()));
+        self
+    }
+
+    /// Trusts the certificate authority of the given DER certificate.
+    pub fn root_certificate_der(mut self,der : impl Into<Vec<u8>>) -> Self {
+        self.root_certificates.push(CertificateData::Der(der.into()));
+        self
+    }
+
+    /// Sets whether the certificate authorities built into the system are trusted along with the added ones, `true` by
+    /// default. Disable it to only trust a private certificate authority.
+    pub fn built_in_roots(mut self,enabled : bool) -> Self {
+        self.built_in_roots = enabled;
+        self
+    }
+
+    /// Sets the oldest TLS version the client accepts to negotiate.
+    pub fn min_version(mut self,version : Version) -> Self {
+        self.min_version = Some(version);
+        self
+    }
+}
+
+/// Extension of [reqwest::ClientBuilder] applying the settings of the crate.
+///
+/// Not available on `wasm32`.
+pub trait ClientBuilderExt : Sized {
+    /// Applies the given [TlsConfig].
+    ///
+    /// # Returns
+    ///
+    /// The builder, or an error if a certificate or the private key cannot be parsed.
+    fn tls_config(self,config : &TlsConfig) -> reqwest::Result<Self>;
+}
+
+impl ClientBuilderExt for ClientBuilder {
+    fn tls_config(self,config : &TlsConfig) -> reqwest::Result<Self> {
+        let mut builder = self.tls_built_in_root_certs(config.built_in_roots);
+        for certificate in &config.root_certificates {
+            let certificates = match certificate {
+                CertificateData::Pem(pem) => Certificate::from_pem_bundle(pem)?,
+                CertificateData::Der(der) => vec![Certificate::from_der(der)?]
+            };
+            builder = certificates.into_iter().fold(builder,ClientBuilder::add_root_certificate);
+        }
+        if let Some(identity) = &config.identity {
+            builder = builder.identity(match identity {
+                ClientIdentity::Pem { certificate, key } => Identity::from_pkcs8_pem(certificate,key)?,
+                ClientIdentity::Pkcs12 { der, password } => Identity::from_pkcs12_der(der,password)?
+            });
+        }
+        Ok(match config.min_version {
+            Some(version) => builder.min_tls_version(version),
+            None => builder
+        })
+    }
+}
@@ -0,0 +1,86 @@
+use std::error::Error;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+
+use http::{Request, Response};
+use reqwest::{Client, ResponseBuilderExt, Url};
+
+use crate::{MaybeSend, MaybeSync};
+
+/// Converts a request built with reqwest into the request handed to an [HttpTransport], failing if its body is streamed.
+pub(crate) fn into_http_request(request : reqwest::Request) -> Result<Request<Vec<u8>>,Box<dyn Error + Send + Sync>> {
+    let body = match request.body() {
+        Some(body) => body.as_bytes().ok_or("streamed bodies cannot be sent through a transport")?.to_vec(),
+        None => Vec::new()
+    };
+    let mut converted = Request::builder().method(request.method().clone()).uri(request.url().as_str()).version(request.version()).body(body)?;
+    *converted.headers_mut() = request.headers().clone();
+    Ok(converted)
+}
+
+/// Converts the response of an [HttpTransport] into a `reqwest::Response` answering a request to `url`, so it goes through the same handling as the network ones.
+pub(crate) fn into_response(response : Response<Bytes>,url : Url) -> reqwest::Response {
+    let (parts,body) = response.into_parts();
+    let mut response = http::Response::builder().status(parts.status).version(parts.version).url(url).body(body).expect("a response with a valid status should build");
+    *response.headers_mut() = parts.headers;
+    reqwest::Response::from(response)
+}
+
+/// The HTTP backend requests are sent with.
+///
+/// Requests are built with reqwest and sent with [crate::RequestInfo::client] by default. Return a transport from
+/// [crate::RequestDefaults::transport] to send them with another client instead, such as hyper, ureq or a test double.
+/// Transports exchange the types of the `http` crate, re-exported as [crate::http], so they do not depend on reqwest : the
+/// request is handed over with its whole body, and the response read whole too. The timeout of the request (see
+/// [reqwest::RequestBuilder::timeout]) bounds the call to the transport, and requests with a streamed body fail with
+/// [crate::RequestError::Transport]. The hooks of the crate (rate limiter, circuit breaker, HAR recorder, ...) apply
+/// whatever the transport. Not available on `wasm32`.
+///
+/// ```rust ignore
+/// struct UreqTransport(ureq::Agent);
+///
+/// #[async_trait]
+/// impl HttpTransport for UreqTransport {
+///     async fn execute(&self,request : http::Request<Vec<u8>>) -> Result<http::Response<Bytes>,Box<dyn Error + Send + Sync>> {
+///         let agent = self.0.clone();
+///         tokio::task::spawn_blocking(move || {
+///             let mut call = agent.request(request.method().as_str(),&request.uri().to_string());
+///             for (name,value) in request.headers() {
+///                 call = call.set(name.as_str(),value.to_str()?);
+///             }
+///             let response = call.send_bytes(request.body())?;
+///             /* Convert `response` into an http::Response<Bytes> */
+///         }).await?
+///     }
+/// }
+/// ```
+#[cfg_attr(not(feature = "wasm"),async_trait)]
+#[cfg_attr(feature = "wasm",async_trait(?Send))]
+pub trait HttpTransport : MaybeSend + MaybeSync {
+    /// Sends the request and reads the whole response.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The request to send, with its method, URI, headers and body.
+    ///
+    /// # Returns
+    ///
+    /// The received response with its whole body, or the error that prevented receiving it, surfaced as [crate::RequestError::Transport].
+    async fn execute(&self,request : Request<Vec<u8>>) -> Result<Response<Bytes>,Box<dyn Error + Send + Sync>>;
+}
+
+/// The default transport.
+#[cfg_attr(not(feature = "wasm"),async_trait)]
+#[cfg_attr(feature = "wasm",async_trait(?Send))]
+impl HttpTransport for Client {
+    async fn execute(&self,request : Request<Vec<u8>>) -> Result<Response<Bytes>,Box<dyn Error + Send + Sync>> {
+        let response = Client::execute(self,reqwest::Request::try_from(request)?).await?;
+        let (status,version,headers) = (response.status(),response.version(),response.headers().clone());
+        let mut converted = Response::new(response.bytes().await?);
+        *converted.status_mut() = status;
+        *converted.version_mut() = version;
+        *converted.headers_mut() = headers;
+        Ok(converted)
+    }
+}
@@ -0,0 +1,174 @@
+use std::borrow::Cow;
+
+use reqwest::RequestBuilder;
+use reqwest::header::HeaderName;
+#[cfg(not(target_arch = "wasm32"))]
+use reqwest::header::ACCEPT;
+
+/// Where an API expects its version, see [ApiVersion].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VersionLocation {
+    /// As the first segment of the path, such as `/v2/users`.
+    Path,
+    /// In the given header, such as `X-GitHub-Api-Version: 2022-11-28`.
+    Header(HeaderName),
+    /// In the vendor media type of the `Accept` header, such as `application/vnd.github.v3+json` for the vendor `github`.
+    MediaType(String),
+    /// In the given query parameter, such as `api-version=2024-01-01`.
+    Query(String),
+}
+
+/// The version of an API, declared once rather than repeated in every endpoint.
+///
+/// Return it from [crate::RequestDefaults::api_version] to apply it to every request built by the default requestors :
+///
+/// ```rust ignore
+/// impl RequestDefaults for MyAPIClient {
+///     fn api_version(&self) -> Option<ApiVersion> {
+///         Some(ApiVersion::path("v2")) // `users` is then sent to `https://api.example.com/v2/users`
+///     }
+/// }
+/// ```
+///
+/// A single request can use another version with [crate::RequestDefaults::with_api_version], such as an endpoint only
+/// available in a newer version. A [VersionLocation::MediaType] version replaces the [crate::RequestDefaults::accept]
+/// header, and a [VersionLocation::Path] one is left out of the absolute URLs given to the requestors, such as hypermedia
+/// links, which already carry it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiVersion {
+    version : String,
+    location : VersionLocation,
+}
+
+impl ApiVersion {
+    /// Creates a version sent as the first segment of the path, such as `v2`.
+    pub fn path(version : impl Into<String>) -> Self {
+        Self { version : version.into().trim_matches('/').to_owned(), location : VersionLocation::Path }
+    }
+
+    /// Creates a version sent in the given header.
+    pub fn header(name : HeaderName,version : impl Into<String>) -> Self {
+        Self { version : version.into(), location : VersionLocation::Header(name) }
+    }
+
+    /// Creates a version sent in the `Accept` header as `application/vnd.{vendor}.{version}+json`.
+    pub fn media_type(vendor : impl Into<String>,version : impl Into<String>) -> Self {
+        Self { version : version.into(), location : VersionLocation::MediaType(vendor.into()) }
+    }
+
+    /// Creates a version sent in the given query parameter.
+    pub fn query(name : impl Into<String>,version : impl Into<String>) -> Self {
+        Self { version : version.into(), location : VersionLocation::Query(name.into()) }
+    }
+
+    /// Returns the version.
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    /// Returns where the version is sent.
+    pub fn location(&self) -> &VersionLocation {
+        &self.location
+    }
+
+    /// Returns the `Accept` header of a [VersionLocation::MediaType] version.
+    pub fn accept(&self) -> Option<String> {
+        match &self.location {
+            VersionLocation::MediaType(vendor) => Some(format!("application/vnd.{vendor}.{}+json",self.version)),
+            _ => None
+        }
+    }
+
+    /// Prefixes the endpoint with a [VersionLocation::Path] version.
+    pub(crate) fn endpoint<'a>(&self,endpoint : &'a str) -> Cow<'a,str> {
+        match self.location {
+            VersionLocation::Path => Cow::Owned(format!("{}/{}",self.version,endpoint.trim_start_matches('/'))),
+            _ => Cow::Borrowed(endpoint)
+        }
+    }
+
+    /// Adds a version sent in a header or in the query to the request, the others being applied along with the endpoint
+    /// and the `Accept` header.
+    pub(crate) fn apply(&self,request_builder : RequestBuilder) -> RequestBuilder {
+        match &self.location {
+            VersionLocation::Path | VersionLocation::MediaType(_) => request_builder,
+            VersionLocation::Header(name) => request_builder.header(name.clone(),self.version.as_str()),
+            VersionLocation::Query(name) => request_builder.query(&[(name.as_str(),self.version.as_str())])
+        }
+    }
+
+    /// Replaces the version `current` was sent with, if any, by this one in a built request to the API at `base_url`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn replace(&self,current : Option<&ApiVersion>,base_url : &str,request : &mut reqwest::Request) {
+        if let Some(current) = current {
+            current.remove(base_url,request);
+        }
+        self.insert(base_url,request);
+    }
+
+    /// Removes this version from a built request.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn remove(&self,base_url : &str,request : &mut reqwest::Request) {
+        match &self.location {
+            VersionLocation::Path => {
+                let prefix = format!("{}/",self.version);
+                if let Some(path) = relative(base_url,request.url()).and_then(|path| path.strip_prefix(&prefix)).map(str::to_owned) {
+                    rebase(base_url,request,&path);
+                }
+            },
+            VersionLocation::Header(name) => {
+                request.headers_mut().remove(name);
+            },
+            VersionLocation::MediaType(_) => {
+                request.headers_mut().remove(ACCEPT);
+            },
+            VersionLocation::Query(name) => {
+                let url = request.url_mut();
+                let pairs : Vec<(String,String)> = url.query_pairs().filter(|(key,_)| key != name).map(|(key,value)| (key.into_owned(),value.into_owned())).collect();
+                url.set_query(None);
+                if !pairs.is_empty() {
+                    url.query_pairs_mut().extend_pairs(pairs);
+                }
+            }
+        }
+    }
+
+    /// Adds this version to a built request.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn insert(&self,base_url : &str,request : &mut reqwest::Request) {
+        match &self.location {
+            VersionLocation::Path => {
+                if let Some(path) = relative(base_url,request.url()).map(|path| self.endpoint(path).into_owned()) {
+                    rebase(base_url,request,&path);
+                }
+            },
+            VersionLocation::Header(name) => {
+                if let Ok(value) = self.version.parse() {
+                    request.headers_mut().insert(name.clone(),value);
+                }
+            },
+            VersionLocation::MediaType(_) => {
+                if let Some(value) = self.accept().and_then(|accept| accept.parse().ok()) {
+                    request.headers_mut().insert(ACCEPT,value);
+                }
+            },
+            VersionLocation::Query(name) => {
+                request.url_mut().query_pairs_mut().append_pair(name,&self.version);
+            }
+        }
+    }
+}
+
+/// Returns the part of the URL after the base URL and its slash, including the query, `None` if it is not below it.
+#[cfg(not(target_arch = "wasm32"))]
+fn relative<'a>(base_url : &str,url : &'a reqwest::Url) -> Option<&'a str> {
+    url.as_str().strip_prefix(base_url.trim_end_matches('/'))?.strip_prefix('/')
+}
+
+/// Points the request to the given path below the base URL.
+#[cfg(not(target_arch = "wasm32"))]
+fn rebase(base_url : &str,request : &mut reqwest::Request,path : &str) {
+    if let Ok(url) = reqwest::Url::parse(&format!("{}/{path}",base_url.trim_end_matches('/'))) {
+        *request.url_mut() = url;
+    }
+}
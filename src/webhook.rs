@@ -0,0 +1,235 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+
+use reqwest::header::{HeaderMap, HeaderName};
+
+use serde::de::DeserializeOwned;
+
+use thiserror::Error as ErrorMacro;
+
+use crate::hash;
+
+/// Verifies the HMAC-SHA256 signatures of the webhooks an API sends, before their payload is trusted.
+///
+/// The signature is read from the `X-Signature` header by default, optionally after a prefix such as the `sha256=` of
+/// GitHub, and compared in constant time with the HMAC-SHA256 of the raw body, encoded as lowercase hexadecimal or base64.
+/// The body must be the exact bytes received, before any JSON parsing.
+///
+/// ```
+/// use api_request_utils::WebhookVerifier;
+/// use api_request_utils::reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+///
+/// // The example of the GitHub documentation
+/// let verifier = WebhookVerifier::new("It's a Secret to Everybody")
+///     .signature_header(HeaderName::from_static("x-hub-signature-256"))
+///     .signature_prefix("sha256=");
+/// let mut headers = HeaderMap::new();
+/// headers.insert("x-hub-signature-256",HeaderValue::from_static("sha256=757107ea0eb2509fc211221cce984b8a37570b6d7586c22c46f4379c8b043e17"));
+/// assert!(verifier.verify(&headers,b"Hello, World!").is_ok());
+/// assert!(verifier.verify(&headers,b"Hello, World?").is_err());
+/// ```
+///
+/// The signatures are checked against the test cases of RFC 4231, in both encodings :
+///
+/// ```
+/// use api_request_utils::{SignatureEncoding, WebhookVerifier};
+/// use api_request_utils::reqwest::header::{HeaderMap, HeaderValue};
+///
+/// let cases : [(Vec<u8>,&[u8],&str,&str); 3] = [
+///     (vec![0x0b; 20],b"Hi There","b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7","sDRMYdjbOFNcqK/OrwvxK4gdwgDJgz2nJuk3bC4yz/c="),
+///     (b"Jefe".to_vec(),b"what do ya want for nothing?","5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843","W9zBRr9gdU5qBCQmCJV1x1oAPwidJzmDnexYuWTsOEM="),
+///     // A key longer than the block size, hashed first
+///     (vec![0xaa; 131],b"Test Using Larger Than Block-Size Key - Hash Key First","60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54","YOQxWR7gtn8Niiaqy/W3f44LxiE3KMUUBUYEDw7jf1Q="),
+/// ];
+/// for (key,data,hex,base64) in cases {
+///     for (encoding,signature) in [(SignatureEncoding::Hex,hex),(SignatureEncoding::Base64,base64)] {
+///         let verifier = WebhookVerifier::new(key.clone()).encoding(encoding);
+///         let mut headers = HeaderMap::new();
+///         headers.insert("x-signature",HeaderValue::from_str(signature).unwrap());
+///         assert!(verifier.verify(&headers,data).is_ok());
+///     }
+/// }
+/// ```
+///
+/// For timestamped schemes, set with [WebhookVerifier::timestamp_header], the signed message is the timestamp in seconds
+/// since the Unix epoch, a `.` and the body, and webhooks older (or further in the future) than the tolerance are rejected
+/// to prevent replays. Timestamps which are not a time at all are rejected before any signature is computed :
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use api_request_utils::{WebhookError, WebhookVerifier};
+/// use api_request_utils::reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+///
+/// let verifier = WebhookVerifier::new("secret").timestamp_header(HeaderName::from_static("x-timestamp"),Duration::from_secs(300));
+/// let mut headers = HeaderMap::new();
+/// headers.insert("x-signature",HeaderValue::from_static("00"));
+/// headers.insert("x-timestamp",HeaderValue::from_static("18446744073709551615"));
+/// assert!(matches!(verifier.verify(&headers,b"{}"), Err(WebhookError::InvalidTimestamp { .. })));
+/// ```
+///
+/// Not available on `wasm32`.
+pub struct WebhookVerifier {
+    secret : Vec<u8>,
+    signature_header : HeaderName,
+    signature_prefix : String,
+    encoding : SignatureEncoding,
+    timestamp : Option<(HeaderName,Duration)>,
+}
+
+// The secret is left out
+impl std::fmt::Debug for WebhookVerifier {
+    fn fmt(&self,f : &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WebhookVerifier")
+            .field("signature_header",&self.signature_header)
+            .field("signature_prefix",&self.signature_prefix)
+            .field("encoding",&self.encoding)
+            .field("timestamp",&self.timestamp)
+            .finish_non_exhaustive()
+    }
+}
+
+/// How a [WebhookVerifier] expects signatures to be encoded.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum SignatureEncoding {
+    /// Hexadecimal, in any case.
+    #[default]
+    Hex,
+    /// Padded base64 with the standard alphabet.
+    Base64,
+}
+
+/// Errors that can occur while verifying a webhook with a [WebhookVerifier].
+#[derive(Debug, ErrorMacro)]
+pub enum WebhookError {
+    /// Error indicating a header needed for the verification is missing or is not valid UTF-8.
+    #[error("Missing webhook header {name}")]
+    MissingHeader {
+        /// The name of the header.
+        name : HeaderName,
+    },
+
+    /// Error indicating the signature does not match the body, which was tampered with or signed with another secret.
+    #[error("Invalid webhook signature")]
+    InvalidSignature,
+
+    /// Error indicating the timestamp header is not a number of seconds since the Unix epoch, or one too large to be a time.
+    #[error("Invalid webhook timestamp {timestamp}")]
+    InvalidTimestamp {
+        /// The value of the timestamp header.
+        timestamp : String,
+    },
+
+    /// Error indicating the timestamp is further from the current time than the tolerance allows, as for replayed webhooks.
+    #[error("Webhook timestamp is {difference:?} away from the current time, more than the tolerance of {tolerance:?}")]
+    TimestampOutOfTolerance {
+        /// How far the timestamp is from the current time, be it in the past or in the future.
+        difference : Duration,
+        /// The tolerance of the verifier.
+        tolerance : Duration,
+    },
+
+    /// Error indicating the verified body is not valid JSON for the payload type.
+    #[error("Failed to parse webhook payload due to {0}")]
+    InvalidPayload(#[from] serde_json::Error),
+}
+
+impl WebhookVerifier {
+    /// Creates a verifier checking hexadecimal signatures of the body alone, sent in the `X-Signature` header.
+    pub fn new(secret : impl Into<Vec<u8>>) -> Self {
+        Self {
+            secret : secret.into(),
+            signature_header : HeaderName::from_static("x-signature"),
+            signature_prefix : String::new(),
+            encoding : SignatureEncoding::Hex,
+            timestamp : None,
+        }
+    }
+
+    /// Sets the header carrying the signature, `X-Signature` by default.
+    pub fn signature_header(mut self,name : HeaderName) -> Self {
+        self.signature_header = name;
+        self
+    }
+
+    /// Sets the prefix the signature comes after in its header, such as `sha256=`, none by default.
+    pub fn signature_prefix(mut self,prefix : impl Into<String>) -> Self {
+        self.signature_prefix = prefix.into();
+        self
+    }
+
+    /// Sets how signatures are encoded, [SignatureEncoding::Hex] by default.
+    pub fn encoding(mut self,encoding : SignatureEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Expects the timestamp of the webhooks in the given header, signed along with the body, and rejects those whose
+    /// timestamp is further than `tolerance` from the current time, such as five minutes.
+    pub fn timestamp_header(mut self,name : HeaderName,tolerance : Duration) -> Self {
+        self.timestamp = Some((name,tolerance));
+        self
+    }
+
+    /// Verifies a webhook.
+    ///
+    /// # Arguments
+    ///
+    /// * `headers` - The headers of the webhook request.
+    /// * `body` - The raw body of the webhook request.
+    ///
+    /// # Returns
+    ///
+    /// `Ok` if the webhook is authentic, otherwise the [WebhookError] telling why it is not.
+    pub fn verify(&self,headers : &HeaderMap,body : &[u8]) -> Result<(),WebhookError> {
+        let header = |name : &HeaderName| headers.get(name).and_then(|value| value.to_str().ok()).ok_or_else(|| WebhookError::MissingHeader { name : name.clone() });
+
+        let signature = header(&self.signature_header)?.trim();
+        let signature = signature.strip_prefix(self.signature_prefix.as_str()).ok_or(WebhookError::InvalidSignature)?;
+
+        let mut message = Vec::with_capacity(body.len());
+        if let Some((name,tolerance)) = &self.timestamp {
+            let timestamp = header(name)?.trim();
+            let invalid = || WebhookError::InvalidTimestamp { timestamp : timestamp.to_owned() };
+            let seconds = timestamp.parse::<u64>().map_err(|_| invalid())?;
+            // The header is not authenticated yet, timestamps beyond what `SystemTime` represents must not panic
+            let sent_at = UNIX_EPOCH.checked_add(Duration::from_secs(seconds)).ok_or_else(invalid)?;
+            let now = SystemTime::now();
+            let difference = now.duration_since(sent_at).or_else(|_| sent_at.duration_since(now)).unwrap_or_default();
+            if difference > *tolerance {
+                return Err(WebhookError::TimestampOutOfTolerance { difference, tolerance : *tolerance });
+            }
+
+            message.extend_from_slice(timestamp.as_bytes());
+            message.push(b'.');
+        }
+        message.extend_from_slice(body);
+
+        let digest = hash::hmac_sha256(&self.secret,&message);
+        let matches = match self.encoding {
+            SignatureEncoding::Hex => constant_time_eq(hash::hex(&digest).as_bytes(),signature.to_ascii_lowercase().as_bytes()),
+            SignatureEncoding::Base64 => constant_time_eq(BASE64.encode(digest).as_bytes(),signature.as_bytes())
+        };
+        match matches {
+            true => Ok(()),
+            false => Err(WebhookError::InvalidSignature)
+        }
+    }
+
+    /// Verifies a webhook like [WebhookVerifier::verify], then deserializes its JSON payload.
+    ///
+    /// # Returns
+    ///
+    /// The payload if the webhook is authentic, otherwise the [WebhookError] telling why it is not or could not be parsed.
+    pub fn verify_json<T : DeserializeOwned>(&self,headers : &HeaderMap,body : &[u8]) -> Result<T,WebhookError> {
+        self.verify(headers,body)?;
+        Ok(serde_json::from_slice(body)?)
+    }
+}
+
+/// Compares the bytes in a time independent of where they differ, so signatures cannot be guessed byte by byte.
+fn constant_time_eq(expected : &[u8],actual : &[u8]) -> bool {
+    expected.len() == actual.len() && expected.iter().zip(actual).fold(0,|difference,(expected,actual)| difference | (expected ^ actual)) == 0
+}
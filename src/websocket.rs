@@ -0,0 +1,341 @@
+//! WebSocket connections opened with the defaults of a client, exchanging typed messages.
+//!
+//! Implement [WebSocketHandler] to connect to the WebSocket endpoints of an API, using the same base URL, default headers
+//! and authentication as its other requests :
+//!
+//! ```rust ignore
+//! impl WebSocketHandler<ApiError> for MyAPIClient {}
+//!
+//! let mut socket = api_client.connect_websocket("feed").await?;
+//! socket.send(&Subscribe { channel : "trades" }).await?;
+//! while let Some(trade) = socket.receive::<Trade>().await? {
+//!     println!("{trade:?}");
+//! }
+//! ```
+
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+
+use reqwest::{Method, StatusCode};
+use reqwest::header::{HeaderName, HeaderValue, CONNECTION, UPGRADE};
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use crate::hash::{random_bytes, sha1};
+use crate::{base_requestor, finish_request, parse_error_payload, read_body, RawResponse, RequestDefaults, RequestError, ResponseMeta};
+
+/// The GUID appended to the key of the handshake, see RFC 6455.
+const HANDSHAKE_GUID : &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+const CONTINUATION : u8 = 0x0;
+const TEXT : u8 = 0x1;
+const BINARY : u8 = 0x2;
+const CLOSE : u8 = 0x8;
+const PING : u8 = 0x9;
+const PONG : u8 = 0xA;
+
+/// Opens WebSocket connections to the endpoints of an API.
+#[async_trait]
+pub trait WebSocketHandler<E : DeserializeOwned + Send> : RequestDefaults + Sync {
+    /// Returns how long [WebSocket::receive] waits for a message before pinging the server to keep the connection alive.
+    ///
+    /// # Returns
+    ///
+    /// 30 seconds by default, `None` disabling the pings.
+    fn websocket_keepalive(&self) -> Option<Duration> {
+        Some(Duration::from_secs(30))
+    }
+
+    /// Opens a WebSocket connection to the given endpoint.
+    ///
+    /// The handshake is a GET request built like the other default requestors, carrying the default headers, query and
+    /// authentication, with `ws` and `wss` base URLs being treated as `http` and `https`. A response refusing the upgrade
+    /// is read and deserialized into the error payload like in [crate::RequestHandler::request_map]. Messages are limited
+    /// to [RequestDefaults::max_response_bytes], if set.
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint` - The endpoint to connect to.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the open [WebSocket] or an `RequestError` variant.
+    async fn connect_websocket(&self,endpoint : &str) -> Result<WebSocket<E>,RequestError<E>> {
+        let key = BASE64.encode(random_bytes::<16>()?);
+        let request = base_requestor(self,Method::GET,endpoint,&HashMap::new())
+            .header(CONNECTION,"Upgrade")
+            .header(UPGRADE,"websocket")
+            .header(HeaderName::from_static("sec-websocket-version"),"13")
+            .header(HeaderName::from_static("sec-websocket-key"),key.as_str());
+        let (client,request) = finish_request(self,request).build_split();
+
+        let mut request = request?;
+        let scheme = match request.url().scheme() {
+            "ws" => Some("http"),
+            "wss" => Some("https"),
+            _ => None
+        };
+        if let Some(scheme) = scheme {
+            // Switching between special schemes cannot fail
+            let _ = request.url_mut().set_scheme(scheme);
+        }
+
+        let response = client.execute(request).await?;
+        if response.status() != StatusCode::SWITCHING_PROTOCOLS {
+            let meta = ResponseMeta::from_response(Method::GET,&response);
+            let body = read_body(response,self.max_response_bytes()).await?;
+            return Err(parse_error_payload(&RawResponse { meta, body }));
+        }
+
+        if response.headers().get("sec-websocket-accept").map(HeaderValue::as_bytes) != Some(accept_key(&key).as_bytes()) {
+            return Err(RequestError::Io(std::io::Error::new(std::io::ErrorKind::InvalidData,"invalid Sec-WebSocket-Accept header in the WebSocket handshake")));
+        }
+
+        Ok(WebSocket {
+            stream : response.upgrade().await?,
+            buffer : Vec::new(),
+            keepalive : self.websocket_keepalive(),
+            max_message_bytes : self.max_response_bytes(),
+            closed : false,
+            error : PhantomData,
+        })
+    }
+}
+
+/// An open WebSocket connection, sending and receiving JSON messages.
+///
+/// Protocol errors and I/O failures are reported as [RequestError::Io].
+pub struct WebSocket<E> {
+    stream : reqwest::Upgraded,
+    buffer : Vec<u8>,
+    keepalive : Option<Duration>,
+    max_message_bytes : Option<usize>,
+    closed : bool,
+    error : PhantomData<fn() -> E>,
+}
+
+impl<E> WebSocket<E> {
+    /// Sends a message serialized as JSON in a text frame.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The message to send.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating whether the message was sent, `RequestError::InvalidRequestBody` if serialization fails.
+    pub async fn send<T : Serialize + ?Sized>(&mut self,message : &T) -> Result<(),RequestError<E>> {
+        let message = serde_json::to_vec(message).map_err(|error| RequestError::InvalidRequestBody(Box::new(error)))?;
+        self.write_frame(TEXT,&message).await
+    }
+
+    /// Sends a text message as is.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The text to send.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating whether the message was sent.
+    pub async fn send_text(&mut self,text : &str) -> Result<(),RequestError<E>> {
+        self.write_frame(TEXT,text.as_bytes()).await
+    }
+
+    /// Sends a ping, which the server answers with a pong.
+    ///
+    /// # Arguments
+    ///
+    /// * `payload` - The application data of the ping, at most 125 bytes.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating whether the ping was sent.
+    pub async fn ping(&mut self,payload : &[u8]) -> Result<(),RequestError<E>> {
+        self.write_frame(PING,payload).await
+    }
+
+    /// Waits for the next text or binary message and deserializes it from JSON.
+    ///
+    /// Pings from the server are answered while waiting, and the server is pinged whenever no message arrives within the
+    /// [WebSocketHandler::websocket_keepalive] delay.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the next message, `None` once the connection is closed, or an `RequestError` variant.
+    pub async fn receive<T : DeserializeOwned>(&mut self) -> Result<Option<T>,RequestError<E>> {
+        let Some(message) = self.receive_raw().await? else {
+            return Ok(None);
+        };
+        Ok(Some(serde_json::from_slice(&message)?))
+    }
+
+    /// Waits for the next text or binary message, without deserializing it.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the payload of the next message, `None` once the connection is closed, or an `RequestError` variant.
+    pub async fn receive_raw(&mut self) -> Result<Option<Vec<u8>>,RequestError<E>> {
+        let mut message : Option<Vec<u8>> = None;
+        while !self.closed {
+            let Some(Frame { fin, opcode, payload }) = self.read_frame().await? else {
+                self.closed = true;
+                break;
+            };
+            match opcode {
+                PING => self.write_frame(PONG,&payload).await?,
+                PONG => {}
+                CLOSE => {
+                    // Echo the status code of the server, as required to complete the closing handshake
+                    let _ = self.write_frame(CLOSE,payload.get(..2).unwrap_or_default()).await;
+                    self.closed = true;
+                }
+                TEXT | BINARY | CONTINUATION => {
+                    let message = message.get_or_insert_with(Vec::new);
+                    message.extend_from_slice(&payload);
+                    if let Some(limit) = self.max_message_bytes.filter(|limit| message.len() > *limit) {
+                        return Err(RequestError::ResponseTooLarge { limit, received : message.len() });
+                    }
+                    if fin {
+                        return Ok(Some(std::mem::take(message)));
+                    }
+                }
+                _ => return Err(protocol_error("unknown WebSocket opcode"))
+            }
+        }
+        Ok(None)
+    }
+
+    /// Closes the connection with the normal closure status code.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating whether the close frame was sent.
+    pub async fn close(mut self) -> Result<(),RequestError<E>> {
+        if !self.closed {
+            self.write_frame(CLOSE,&1000u16.to_be_bytes()).await?;
+        }
+        Ok(self.stream.shutdown().await?)
+    }
+
+    /// Sends a single masked frame, as required from clients.
+    async fn write_frame(&mut self,opcode : u8,payload : &[u8]) -> Result<(),RequestError<E>> {
+        let mut frame = vec![0x80 | opcode];
+        match payload.len() {
+            length @ 0..=125 => frame.push(0x80 | length as u8),
+            length @ 126..=0xFFFF => {
+                frame.push(0x80 | 126);
+                frame.extend_from_slice(&(length as u16).to_be_bytes());
+            }
+            length => {
+                frame.push(0x80 | 127);
+                frame.extend_from_slice(&(length as u64).to_be_bytes());
+            }
+        }
+        let mask = random_bytes::<4>()?;
+        frame.extend_from_slice(&mask);
+        frame.extend(payload.iter().enumerate().map(|(index,byte)| byte ^ mask[index % 4]));
+
+        self.stream.write_all(&frame).await?;
+        Ok(self.stream.flush().await?)
+    }
+
+    /// Reads the next frame, pinging the server whenever the keepalive delay elapses, `None` if the connection ended.
+    async fn read_frame(&mut self) -> Result<Option<Frame>,RequestError<E>> {
+        loop {
+            if let Some((frame,length)) = parse_frame(&self.buffer,self.max_message_bytes)? {
+                self.buffer.drain(..length);
+                return Ok(Some(frame));
+            }
+
+            // Reading into the buffer is cancel safe, so the read can be interrupted to ping the server
+            let read = match self.keepalive {
+                Some(keepalive) => match tokio::time::timeout(keepalive,self.stream.read_buf(&mut self.buffer)).await {
+                    Ok(read) => read?,
+                    Err(_) => {
+                        self.write_frame(PING,&[]).await?;
+                        continue;
+                    }
+                },
+                None => self.stream.read_buf(&mut self.buffer).await?
+            };
+            if read == 0 {
+                return Ok(None);
+            }
+        }
+    }
+}
+
+/// A frame received from the server, unmasked.
+struct Frame {
+    fin : bool,
+    opcode : u8,
+    payload : Vec<u8>,
+}
+
+/// Parses a frame at the start of the buffer, returning it along with its length once it was entirely received.
+fn parse_frame<E>(buffer : &[u8],limit : Option<usize>) -> Result<Option<(Frame,usize)>,RequestError<E>> {
+    let [first,second,..] = *buffer else {
+        return Ok(None);
+    };
+    let (length,mut offset) = match second & 0x7F {
+        126 if buffer.len() >= 4 => (u16::from_be_bytes([buffer[2],buffer[3]]) as u64,4),
+        127 if buffer.len() >= 10 => (u64::from_be_bytes(buffer[2..10].try_into().unwrap_or_default()),10),
+        126 | 127 => return Ok(None),
+        length => (length as u64,2)
+    };
+    // The most significant bit of a 64-bit length must be 0 (RFC 6455 section 5.2)
+    if length > i64::MAX as u64 {
+        return Err(protocol_error("WebSocket frame length out of range"));
+    }
+    if let Some(limit) = limit.filter(|limit| length > *limit as u64) {
+        return Err(RequestError::ResponseTooLarge { limit, received : usize::try_from(length).unwrap_or(usize::MAX) });
+    }
+    let length = usize::try_from(length).map_err(|_| protocol_error("WebSocket frame too large"))?;
+
+    // Servers must not mask their frames, but unmasking them costs nothing
+    let mask = match second & 0x80 != 0 {
+        true => {
+            let Some(mask) = buffer.get(offset..offset + 4) else {
+                return Ok(None);
+            };
+            offset += 4;
+            Some([mask[0],mask[1],mask[2],mask[3]])
+        }
+        false => None
+    };
+    let end = offset.checked_add(length).ok_or_else(|| protocol_error("WebSocket frame too large"))?;
+    let Some(payload) = buffer.get(offset..end) else {
+        return Ok(None);
+    };
+    let payload = match mask {
+        Some(mask) => payload.iter().enumerate().map(|(index,byte)| byte ^ mask[index % 4]).collect(),
+        None => payload.to_vec()
+    };
+
+    Ok(Some((Frame { fin : first & 0x80 != 0, opcode : first & 0x0F, payload },end)))
+}
+
+/// Computes the `Sec-WebSocket-Accept` value a server answers the `Sec-WebSocket-Key` of a handshake with (RFC 6455 section
+/// 4.2.2), which [WebSocketHandler::connect_websocket] checks.
+///
+/// ```
+/// // The example of RFC 6455
+/// assert_eq!(api_request_utils::websocket::accept_key("dGhlIHNhbXBsZSBub25jZQ=="),"s3pPLMBiTxaQ9kYGzzhZRbK+xOo=");
+/// ```
+pub fn accept_key(key : &str) -> String {
+    BASE64.encode(sha1(format!("{key}{HANDSHAKE_GUID}").as_bytes()))
+}
+
+fn protocol_error<E>(message : &'static str) -> RequestError<E> {
+    RequestError::Io(std::io::Error::new(std::io::ErrorKind::InvalidData,message))
+}